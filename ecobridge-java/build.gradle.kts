@@ -173,7 +173,8 @@ MemoryLayout.structLayout(
         JAVA_DOUBLE.withName("seasonal_weight"),
         JAVA_DOUBLE.withName("weekend_weight"),
         JAVA_DOUBLE.withName("newbie_weight"),
-        JAVA_DOUBLE.withName("inflation_weight")
+        JAVA_DOUBLE.withName("inflation_weight"),
+        JAVA_DOUBLE.withName("ln_floor")
 )
         """.trimIndent())
 
@@ -181,7 +182,10 @@ MemoryLayout.structLayout(
 MemoryLayout.structLayout(
         JAVA_LONG.withName("final_tax_micros"),
         JAVA_INT.withName("is_blocked"),
-        JAVA_INT.withName("warning_code")
+        JAVA_INT.withName("warning_code"),
+        JAVA_INT.withName("applied_tax_flags"),
+        JAVA_INT.withName("_padding"),
+        JAVA_LONG.withName("limit_headroom_micros")
 )
         """.trimIndent())
 
@@ -210,12 +214,17 @@ MemoryLayout.structLayout(
         JAVA_DOUBLE.withName("wealth_gap_tax_rate"),
         JAVA_LONG.withName("poor_threshold"),
         JAVA_LONG.withName("rich_threshold"),
-        JAVA_DOUBLE.withName("_reserved"),
+        JAVA_INT.withName("tax_combination_mode"),
+        JAVA_INT.withName("exclusive_limit"),
         JAVA_DOUBLE.withName("warning_ratio"),
         JAVA_LONG.withName("warning_min_amount"),
         JAVA_DOUBLE.withName("newbie_hours"),
         JAVA_DOUBLE.withName("veteran_hours"),
-        JAVA_DOUBLE.withName("velocity_threshold")
+        JAVA_DOUBLE.withName("velocity_threshold"),
+        JAVA_DOUBLE.withName("injection_balance_multiple"),
+        JAVA_DOUBLE.withName("max_tax_ratio"),
+        JAVA_DOUBLE.withName("velocity_penalty_coeff"),
+        JAVA_LONG.withName("injection_min_amount")
 )
         """.trimIndent())
 
@@ -230,7 +239,13 @@ MemoryLayout.structLayout(
         JAVA_DOUBLE.withName("filtered_d"),
         JAVA_DOUBLE.withName("integration_limit"),
         JAVA_INT.withName("is_saturated"),
-        JAVA_INT.withName("_padding")
+        JAVA_INT.withName("derivative_mode"),
+        JAVA_DOUBLE.withName("prev_error"),
+        JAVA_DOUBLE.withName("panic_threshold"),
+        JAVA_DOUBLE.withName("panic_damping"),
+        JAVA_DOUBLE.withName("integral_decay"),
+        JAVA_DOUBLE.withName("output_min"),
+        JAVA_DOUBLE.withName("output_max")
 )
         """.trimIndent())
 
@@ -5,6 +5,7 @@ import com.github.benmanes.caffeine.cache.Caffeine;
 import com.github.benmanes.caffeine.cache.RemovalCause;
 import org.bukkit.Bukkit;
 import top.ellan.ecobridge.EcoBridge;
+import top.ellan.ecobridge.infrastructure.ffi.bridge.EconomyFrozenException;
 import top.ellan.ecobridge.infrastructure.ffi.bridge.NativeBridge;
 import top.ellan.ecobridge.infrastructure.persistence.database.TransactionDao;
 import top.ellan.ecobridge.infrastructure.ffi.model.SaleRecord;
@@ -212,6 +213,9 @@ public class PricingManager {
             // computeTierPrice 会根据单次交易量(amount)产生临时滑点
             return NativeBridge.computeTierPrice(basePrice, Math.abs(amount), amount > 0);
             
+        } catch (EconomyFrozenException e) {
+            // 冻结期间中止交易，不回退到安全价
+            throw e;
         } catch (Exception e) {
             LogUtil.warn("计算物品 " + productId + " 动态价格时发生异常，回退至安全值");
             return 100.0;
@@ -33,6 +33,7 @@ import java.util.concurrent.TimeUnit;
 import java.util.concurrent.locks.ReentrantLock;
 
 import static java.lang.foreign.ValueLayout.JAVA_DOUBLE;
+import static java.lang.foreign.ValueLayout.JAVA_INT;
 import static java.lang.foreign.ValueLayout.JAVA_LONG;
 
 /**
@@ -395,7 +396,8 @@ public class TransferManager {
         double richTh = section != null ? section.getDouble("rich-threshold", 1000000.0) : 1000000.0;
         cfg.set(JAVA_LONG, 40, NativeBridge.moneyToMicros(richTh));
         
-        cfg.set(JAVA_DOUBLE, 48, 0.0); 
+        cfg.set(JAVA_INT, 48, 0); // tax_combination_mode: 混合模式
+        cfg.set(JAVA_INT, 52, 0); // exclusive_limit: 0 = 数量恰好等于限额时放行
         cfg.set(JAVA_DOUBLE, 56, section != null ? section.getDouble("warning-ratio", 0.9) : 0.9);
         
         // warning_min_amount (i64)
@@ -404,6 +406,11 @@ public class TransferManager {
         
         cfg.set(JAVA_DOUBLE, 72, section != null ? section.getDouble("newbie-hours", 10.0) : 10.0);
         cfg.set(JAVA_DOUBLE, 80, section != null ? section.getDouble("veteran-hours", 100.0) : 100.0);
+        cfg.set(JAVA_DOUBLE, 96, section != null ? section.getDouble("injection-balance-multiple", 10.0) : 10.0);
+
+        // injection_min_amount (i64)
+        double injectionMin = section != null ? section.getDouble("injection-min-amount", 1000.0) : 1000.0;
+        cfg.set(JAVA_LONG, 120, NativeBridge.moneyToMicros(injectionMin));
         
         VH_RCFG_V_THRESHOLD.set(cfg, 0L, vThreshold);
     }
@@ -416,6 +423,8 @@ public class TransferManager {
             case NativeBridge.CODE_BLOCK_INSUFFICIENT_FUNDS -> "账户余额校验失败 (FFI)";
             case NativeBridge.CODE_BLOCK_VELOCITY_LIMIT -> "资金流量异常 (洗钱嫌疑)";
             case NativeBridge.CODE_BLOCK_QUANTITY_LIMIT -> "触发动态限额 (市场保护)";
+            case NativeBridge.CODE_OVERFLOW -> "金额超出可计算范围 (数值溢出)";
+            case NativeBridge.CODE_FROZEN -> "经济系统已临时冻结 (事故处理中)";
             case NativeBridge.CODE_BLOCK_VELOCITY_LIMIT + 10000 -> "内部算力错误"; // Fallback for some offset codes
             default -> "违反金融合规协议";
         };
@@ -3,6 +3,7 @@ package top.ellan.ecobridge.domain.algorithm;
 import org.bukkit.configuration.ConfigurationSection;
 import org.bukkit.configuration.file.FileConfiguration;
 import org.bukkit.plugin.Plugin;
+import top.ellan.ecobridge.infrastructure.ffi.bridge.EconomyFrozenException;
 import top.ellan.ecobridge.infrastructure.ffi.bridge.NativeBridge;
 import top.ellan.ecobridge.infrastructure.ffi.bridge.NativeBridge.Layouts;
 import top.ellan.ecobridge.infrastructure.ffi.bridge.NativeContextBuilder;
@@ -117,13 +118,18 @@ public class PriceComputeEngine {
                 double histAvg = histAvgMap.getOrDefault(meta.uniqueKey(), meta.basePrice());
                 double neff = NativeBridge.queryNeffForKey(now, configTau, meta.uniqueKey());
                 double epsilon = NativeBridge.calculateEpsilon(ctxSeg, cfgSeg);
+                // 物品硬底价 (0 = 使用全局物理底价)
+                long minPriceMicros = itemConfig != null
+                    ? NativeBridge.moneyToMicros(itemConfig.getDouble("min-price", 0.0))
+                    : 0L;
                 double computedPrice = NativeBridge.computePriceBounded(
                     meta.basePrice(),
                     neff,
                     0.0,
                     meta.lambda(),
                     epsilon,
-                    histAvg
+                    histAvg,
+                    minPriceMicros
                 );
                 computedPrice = applyRecoveryGuard(config, computedPrice, histAvg, neff);
 
@@ -139,6 +145,10 @@ public class PriceComputeEngine {
                     count, activeVolatility, durationMs));
             }
 
+        } catch (EconomyFrozenException e) {
+            // 冻结期间不发布部分快照，保留上一次的价格
+            LogUtil.debug("PriceComputeEngine: 经济冻结中，跳过本轮快照演算");
+            return new HashMap<>();
         } catch (Throwable e) {
             LogUtil.error("PriceComputeEngine: SIMD 批量计算失败", e);
         }
@@ -254,6 +264,7 @@ public class PriceComputeEngine {
         NativeBridge.VH_CFG_S_AMP.set(cfgBase, offset, globalConfig.getDouble("economy.environment.seasonal-amplitude", 0.15));
         NativeBridge.VH_CFG_W_MULT.set(cfgBase, offset, globalConfig.getDouble("economy.environment.weekend-multiplier", 1.2));
         NativeBridge.VH_CFG_N_PROT.set(cfgBase, offset, globalConfig.getDouble("economy.environment.newbie-protection", 0.2));
+        NativeBridge.VH_CFG_LN_FLOOR.set(cfgBase, offset, globalConfig.getDouble("economy.environment.ln-floor", 0.01));
 
         if (itemSec != null) {
             NativeBridge.VH_CFG_W_SEASONAL.set(cfgBase, offset, itemSec.getDouble("weights.seasonal", 0.25));
@@ -11,6 +11,8 @@ public enum EconStatus {
     INVALID_LENGTH(2),
     INVALID_VALUE(3),
     NUMERIC_OVERFLOW(10),
+    TIMEOUT(11),
+    FROZEN(14),
     INTERNAL_ERROR(100),
     PANIC(101),
     FATAL(255);
@@ -0,0 +1,13 @@
+package top.ellan.ecobridge.infrastructure.ffi.bridge;
+
+/**
+ * 全服经济冻结期间 Native 定价返回 {@link EconStatus#FROZEN} 时抛出。
+ * <p>
+ * 定价类调用不得回退到基础价继续成交，调用方须据此中止本次交易。
+ */
+public class EconomyFrozenException extends IllegalStateException {
+
+    public EconomyFrozenException() {
+        super("经济系统已临时冻结 (事故处理中)");
+    }
+}
@@ -33,7 +33,11 @@ public class NativeBridge {
     private enum BridgeState { UNINITIALIZED, RUNNING, SHUTTING_DOWN, CLOSED }
     private static final AtomicReference<BridgeState> STATE = new AtomicReference<>(BridgeState.UNINITIALIZED);
 
-    private static final int EXPECTED_ABI_VERSION = 0x0009_0000;
+    private static final int EXPECTED_ABI_VERSION = 0x0009_000E;
+    /** EconStatus::AlreadyInitialized：数据库已在本进程中绑定 */
+    private static final int STATUS_ALREADY_INITIALIZED = 12;
+    /** EconStatus::Frozen：全服经济冻结，定价类调用必须中止交易 */
+    private static final int STATUS_FROZEN = 14;
     private static volatile Arena sharedArena;
     
     // 生命周期锁
@@ -55,6 +59,8 @@ public class NativeBridge {
     public static final int CODE_BLOCK_INSUFFICIENT_FUNDS = 4;
     public static final int CODE_BLOCK_VELOCITY_LIMIT = 5;
     public static final int CODE_BLOCK_QUANTITY_LIMIT = 6;
+    public static final int CODE_OVERFLOW = 7;
+    public static final int CODE_FROZEN = 8;
 
     // ==================================================================================
     // 2. VarHandles (内部使用)
@@ -79,6 +85,7 @@ public class NativeBridge {
     public static final VarHandle VH_CFG_W_WEEKEND;
     public static final VarHandle VH_CFG_W_NEWBIE;
     public static final VarHandle VH_CFG_W_INFLATION;
+    public static final VarHandle VH_CFG_LN_FLOOR;
 
     public static final VarHandle VH_TCTX_SENDER_BAL;
     public static final VarHandle VH_TCTX_RECEIVER_BAL;
@@ -156,6 +163,7 @@ public class NativeBridge {
             VH_CFG_W_WEEKEND = cfgLayout.varHandle(MemoryLayout.PathElement.groupElement("weekend_weight"));
             VH_CFG_W_NEWBIE = cfgLayout.varHandle(MemoryLayout.PathElement.groupElement("newbie_weight"));
             VH_CFG_W_INFLATION = cfgLayout.varHandle(MemoryLayout.PathElement.groupElement("inflation_weight"));
+            VH_CFG_LN_FLOOR = cfgLayout.varHandle(MemoryLayout.PathElement.groupElement("ln_floor"));
 
             MemoryLayout tCtxLayout = TransferContext.layout();
             VH_TCTX_SENDER_BAL = tCtxLayout.varHandle(MemoryLayout.PathElement.groupElement("sender_balance"));
@@ -216,7 +224,11 @@ public class NativeBridge {
             try (Arena arena = Arena.ofConfined()) {
                 String dataPath = plugin.getDataFolder().getAbsolutePath();
                 int result = (int) initDBMH.invokeExact(arena.allocateFrom(dataPath));
-                if (result != 0 && result != -3) throw new IllegalStateException("DB Init Failed: " + result);
+                if (result == STATUS_ALREADY_INITIALIZED) {
+                    LogUtil.warn("Native 数据库已在本进程中初始化，沿用已有实例 (本次数据目录被忽略)。");
+                } else if (result != 0) {
+                    throw new IllegalStateException("DB Init Failed: " + result);
+                }
             }
 
             sharedArena = Arena.ofShared();
@@ -249,12 +261,12 @@ public class NativeBridge {
         calcStabilityMH = bind(linker, "ecobridge_calc_stability", FunctionDescriptor.of(JAVA_INT, JAVA_LONG, JAVA_LONG, ADDRESS), Linker.Option.critical(true));
         calcDecayMH = bind(linker, "ecobridge_calc_decay", FunctionDescriptor.of(JAVA_INT, JAVA_DOUBLE, JAVA_DOUBLE, ADDRESS), Linker.Option.critical(true));
         computeTierPriceMH = bind(linker, "ecobridge_compute_tier_price", FunctionDescriptor.of(JAVA_INT, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_INT, ADDRESS), Linker.Option.critical(true));
-        computePriceBoundedMH = bind(linker, "ecobridge_compute_price_bounded", FunctionDescriptor.of(JAVA_INT, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, ADDRESS), Linker.Option.critical(true));
+        computePriceBoundedMH = bind(linker, "ecobridge_compute_price_bounded", FunctionDescriptor.of(JAVA_INT, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_LONG, ADDRESS), Linker.Option.critical(true));
         
         checkTransferMH = bind(linker, "ecobridge_compute_transfer_check", FunctionDescriptor.of(JAVA_INT, ADDRESS, ADDRESS, ADDRESS));
         computePidMH = bind(linker, "ecobridge_compute_pid_adjustment", FunctionDescriptor.of(JAVA_INT, ADDRESS, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, ADDRESS));
         resetPidMH = bind(linker, "ecobridge_reset_pid_state", FunctionDescriptor.of(JAVA_INT, ADDRESS));
-        computeBatchPricesMH = bind(linker, "ecobridge_compute_batch_prices", FunctionDescriptor.of(JAVA_INT, JAVA_LONG, JAVA_DOUBLE, ADDRESS, ADDRESS, ADDRESS, ADDRESS, ADDRESS));
+        computeBatchPricesMH = bind(linker, "ecobridge_compute_batch_prices_checked", FunctionDescriptor.of(JAVA_INT, JAVA_LONG, JAVA_DOUBLE, ADDRESS, JAVA_LONG, ADDRESS, JAVA_LONG, ADDRESS, JAVA_LONG, ADDRESS, JAVA_LONG, ADDRESS, JAVA_LONG));
         injectRemoteTradeMH = bind(linker, "inject_remote_trade", FunctionDescriptor.of(JAVA_INT, JAVA_LONG));
         injectRemoteTradeForKeyMH = bind(linker, "inject_remote_trade_for_key", FunctionDescriptor.of(JAVA_INT, ADDRESS, JAVA_LONG));
         getDynamicLimitMH = bind(linker, "ecobridge_get_dynamic_limit", FunctionDescriptor.of(JAVA_INT, JAVA_LONG, JAVA_DOUBLE, JAVA_DOUBLE, JAVA_DOUBLE, ADDRESS));
@@ -317,12 +329,19 @@ public class NativeBridge {
         computeVelocityDecayMH = null; computeFallbackTaxMH = null; computeSettlementMH = null;
     }
 
+    private static void throwIfFrozen(int status) {
+        if (status == STATUS_FROZEN) throw new EconomyFrozenException();
+    }
+
     // --- 安全执行器 ---
     private static <T> T executeSafely(ThrowingSupplier<T> action, T fallback, boolean isCritical) {
         if (!LIFECYCLE_LOCK.readLock().tryLock()) return fallback;
         try {
             if (!isLoaded()) return fallback;
             return action.get();
+        } catch (EconomyFrozenException e) {
+            // 冻结不是故障：不得回退到 fallback 价格继续成交
+            throw e;
         } catch (Throwable t) {
             if (isCritical) {
                 LogUtil.severe("CRITICAL FFI PANIC: " + t.getMessage(), t);
@@ -377,16 +396,18 @@ public class NativeBridge {
             try (Arena arena = Arena.ofConfined()) {
                 MemorySegment out = arena.allocate(JAVA_DOUBLE);
                 int status = (int) computePriceMH.invokeExact(base, nEff, amount, lambda, epsilon, out);
+                throwIfFrozen(status);
                 return status == 0 ? out.get(JAVA_DOUBLE, 0) : base;
             }
         }, base, false);
     }
 
-    public static double computePriceBounded(double base, double neff, double amt, double lambda, double eps, double histAvg) {
+    public static double computePriceBounded(double base, double neff, double amt, double lambda, double eps, double histAvg, long minPriceMicros) {
         return executeSafely(() -> {
             try (Arena arena = Arena.ofConfined()) {
                 MemorySegment out = arena.allocate(JAVA_DOUBLE);
-                int status = (int) computePriceBoundedMH.invokeExact(base, neff, amt, lambda, eps, histAvg, out);
+                int status = (int) computePriceBoundedMH.invokeExact(base, neff, amt, lambda, eps, histAvg, minPriceMicros, out);
+                throwIfFrozen(status);
                 return status == 0 ? out.get(JAVA_DOUBLE, 0) : base;
             }
         }, base, false);
@@ -523,7 +544,17 @@ public class NativeBridge {
 
     public static void computeBatchPrices(long count, double neff, MemorySegment ctxArr, MemorySegment cfgArr, MemorySegment histAvgs, MemorySegment lambdas, MemorySegment results) {
         executeSafely(() -> {
-            computeBatchPricesMH.invokeExact(count, neff, ctxArr, cfgArr, histAvgs, lambdas, results);
+            // 按段大小换算各数组实际元素数，由 Native 侧校验与 count 一致
+            int status = (int) computeBatchPricesMH.invokeExact(
+                count, neff,
+                ctxArr, ctxArr.byteSize() / Layouts.TRADE_CONTEXT.byteSize(),
+                cfgArr, cfgArr.byteSize() / Layouts.MARKET_CONFIG.byteSize(),
+                histAvgs, histAvgs.byteSize() / JAVA_DOUBLE.byteSize(),
+                lambdas, lambdas.byteSize() / JAVA_DOUBLE.byteSize(),
+                results, results.byteSize() / JAVA_DOUBLE.byteSize());
+            if (status != 0) {
+                LogUtil.warnOnce("batch-prices-status", "批量定价被 Native 层拒绝 (status=" + status + ")，请检查数组长度。");
+            }
             return null;
         }, null, false);
     }
@@ -563,6 +594,7 @@ public class NativeBridge {
             try (Arena arena = Arena.ofConfined()) {
                 MemorySegment out = arena.allocate(JAVA_DOUBLE);
                 int status = (int) computeTierPriceMH.invokeExact(base, qty, isSell ? 1 : 0, out);
+                throwIfFrozen(status);
                 return status == 0 ? out.get(JAVA_DOUBLE, 0) : base;
             }
         }, base, false);
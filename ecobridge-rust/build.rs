@@ -1,6 +1,7 @@
 use std::env;
 use std::path::PathBuf;
 
+#[allow(clippy::field_reassign_with_default)] // cbindgen::Config 字段较多，逐项赋值更易读
 fn main() {
     // 获取当前项目的根目录
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
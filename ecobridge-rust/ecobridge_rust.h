@@ -8,6 +8,32 @@
 #include <stdint.h>
 #include <stdbool.h>
 
+/*
+ 精度缩放常量 (1.0 = 1,000,000 Micros)
+ */
+#define MICROS_SCALE 1000000.0
+
+/*
+ PID 微分项来源：对观测值求导 (默认，无设定值突变冲击)
+ */
+#define PID_DERIVATIVE_ON_MEASUREMENT 0
+
+/*
+ PID 微分项来源：对误差求导 (经典形式，设定值突变时产生冲击)
+ */
+#define PID_DERIVATIVE_ON_ERROR 1
+
+/*
+ `MarketConfig::ln_floor` 默认值
+ */
+#define DEFAULT_LN_FLOOR 0.01
+
+#define DEFAULT_LAMBDA 0.01
+
+#define DEFAULT_TAU 7.0
+
+#define MIN_PHYSICAL_PRICE 0.01
+
 #define DEFAULT_INTEGRATION_LIMIT 30.0
 
 #define MAX_SAFE_DT 1.0
@@ -32,6 +58,59 @@
 
 #define HEAT_SENSITIVITY 0.5
 
+/*
+ 权重拟合的最大迭代次数
+ */
+#define SOLVE_WEIGHTS_MAX_ITER 2000
+
+/*
+ 通胀率钳位区间
+ */
+#define INFLATION_MIN -0.15
+
+#define INFLATION_MAX 0.45
+
+/*
+ 稳定性恢复窗口默认值 (ms)：大额波动后 15 分钟恢复完全稳定
+ */
+#define DEFAULT_RECOVERY_WINDOW_MS 900000.0
+
+/*
+ 卖出灵敏度系数默认值：卖出时 lambda 乘以该系数，模拟 "价格下行粘性"
+ */
+#define DEFAULT_SELL_SENSITIVITY_RATIO 0.6
+
+/*
+ 冷启动样本统计使用的衰减常数 (天)，与 Java 侧 `economy.tau` 默认值一致
+ */
+#define WARMUP_TAU_DAYS 7.0
+
+/*
+ 单个元素演算 panic 时写入的哨兵价格 (与定价硬底线一致)
+ */
+#define BATCH_PANIC_SENTINEL 0.01
+
+/*
+ 交易影响预估的恢复容差：价格回到交易前水平 1% 以内即视为恢复
+ */
+#define TRADE_IMPACT_RECOVERY_TOLERANCE 0.01
+
+#define DEFAULT_FUTURE_TOLERANCE_MS 60000
+
+#define DEFAULT_LOOKBACK_MULTIPLIER 10.0
+
+#define DEFAULT_DOWNSAMPLE_AGE_MS (7 * 86400000)
+
+/*
+ 带时间戳的远程流量缓冲区容量，超出后按当前裁剪策略收缩至 80%
+ */
+#define REMOTE_HISTORY_CAP 65536
+
+/*
+ neff 置信度的样本项半饱和点：Kish 有效样本数达到该值时样本项为 0.5
+ */
+#define NEFF_CONFIDENCE_HALF_SAMPLES 10.0
+
 #define CODE_NORMAL 0
 
 #define CODE_WARNING_HIGH_RISK 1
@@ -46,6 +125,194 @@
 
 #define CODE_BLOCK_QUANTITY_LIMIT 6
 
+/*
+ 金额或税额超出 i64 Micros 可表示范围，按拦截处理以免回绕成负税
+ */
+#define CODE_OVERFLOW 7
+
+/*
+ 全服经济冻结期间拦截一切转账
+ */
+#define CODE_FROZEN 8
+
+/*
+ 混合模式 (默认/兼容)：奢侈税与基础税累加，贫富调节税与该和取最大值
+ */
+#define TAX_MODE_MIXED 0
+
+/*
+ 累加模式：基础税 + 奢侈税 + 贫富调节税
+ */
+#define TAX_MODE_ADDITIVE 1
+
+/*
+ 取最大模式：仅征收三者中最高的一项
+ */
+#define TAX_MODE_MAXIMUM 2
+
+/*
+ 顺序模式：依次征收基础税、奢侈税、贫富调节税，后一项以扣除前项后的剩余金额为税基
+ */
+#define TAX_MODE_SEQUENTIAL 3
+
+#define TAX_FLAG_BASE (1 << 0)
+
+#define TAX_FLAG_LUXURY (1 << 1)
+
+#define TAX_FLAG_WEALTH_GAP (1 << 2)
+
+#define TAX_FLAG_BEHAVIORAL (1 << 3)
+
+/*
+ 行为惩罚税指数输入上限默认值：惩罚倍率最高 e^2 ≈ 7.39
+ */
+#define DEFAULT_PENALTY_EXPONENT_CAP 2.0
+
+/*
+ 税费封顶比例默认值 (`RegulatorConfig::max_tax_ratio` 未设置时使用)
+ */
+#define DEFAULT_MAX_TAX_RATIO 0.8
+
+/*
+ 违规注资判定最低金额默认值 (`RegulatorConfig::injection_min_amount` 未设置时使用)：1,000
+ */
+#define DEFAULT_INJECTION_MIN_AMOUNT 1000000000
+
+/*
+ 行为惩罚税指数系数默认值 (`RegulatorConfig::velocity_penalty_coeff` 未设置时使用)
+ */
+#define DEFAULT_VELOCITY_PENALTY_COEFF 0.05
+
+/*
+ 累进税率表最大档数
+ */
+#define MAX_TAX_BRACKETS 32
+
+#define DEFAULT_REVERSE_FLOW_WINDOW_MS (5 * 60000)
+
+/*
+ 低活跃度判定阈值 (与傀儡账户识别的 0.1 保持一致)
+ */
+#define LOW_ACTIVITY_THRESHOLD 0.1
+
+/*
+ 空闲桶回收的最小间隔 (ms)
+ */
+#define GC_INTERVAL_MS 60000
+
+/*
+ 租户名最大长度 (字节)
+ */
+#define MAX_TENANT_NAME_LEN 32
+
+/*
+ 关机等待写入线程排空的默认超时
+ */
+#define DEFAULT_SHUTDOWN_TIMEOUT_MS 5000
+
+#define DEFAULT_PRELOAD_RETRY_ATTEMPTS 3
+
+/*
+ 启动预热窗口的默认天数 (内存历史与分市场热存储共用)
+ */
+#define DEFAULT_PRELOAD_DAYS 30
+
+/*
+ M1 估算结果的默认缓存有效期 (ms)
+ */
+#define DEFAULT_M1_CACHE_TTL_MS 1000
+
+#define DEFAULT_DB_MEMORY_LIMIT_MB 512
+
+#define DEFAULT_DB_THREADS 4
+
+#define DEFAULT_DB_POOL_SIZE 4
+
+#define DEFAULT_WRITE_RETRY_ATTEMPTS 3
+
+#define DEFAULT_WRITE_RETRY_BACKOFF_MS 50
+
+/*
+ `init_economy_db` 的重复初始化返回码：数据库已绑定，本次传入的路径与配置被忽略
+ */
+#define INIT_ALREADY_DONE -3
+
+/*
+ 单次日汇总查询最多返回的天数
+ */
+#define MAX_SUMMARY_DAYS 366
+
+/*
+ 单次排行榜查询最多返回的玩家数
+ */
+#define MAX_TOP_TRADERS 1000
+
+/*
+ 排行榜 FFI 中每个玩家标识占用的定宽槽位 (字节)：36 字符标准 UUID + 结尾 NUL
+ */
+#define UUID_SLOT_LEN 37
+
+/*
+ 结构体布局描述项 (16 bytes)：供 Java 侧启动时与自身 FFM 布局逐字段核对
+
+ `name_hash` 为 `"结构体名.字段名"` 的 FNV-1a 64 哈希；
+ 每个结构体另有一条以 `"结构体名"` 哈希、offset 为 0、size 为结构体总大小的条目。
+ */
+typedef struct {
+  uint64_t name_hash;
+  uint32_t offset;
+  uint32_t size;
+} LayoutEntry;
+
+/*
+ 单日经济汇总 (32 bytes)
+ */
+typedef struct {
+  long long day_start_ms;
+  long long total_abs_delta_micros;
+  uint64_t event_count;
+  uint64_t distinct_players;
+} DaySummary;
+
+/*
+ 审计监管与计税配置 (128 bytes, ABI 0x0009_000E 起)
+ */
+typedef struct {
+  double base_tax_rate;
+  long long luxury_threshold;
+  double luxury_tax_rate;
+  double wealth_gap_tax_rate;
+  long long poor_threshold;
+  long long rich_threshold;
+  int tax_combination_mode;
+  int exclusive_limit;
+  double warning_ratio;
+  long long warning_min_amount;
+  double newbie_hours;
+  double veteran_hours;
+  double velocity_threshold;
+  double injection_balance_multiple;
+  double max_tax_ratio;
+  double velocity_penalty_coeff;
+  long long injection_min_amount;
+} RegulatorConfig;
+
+/*
+ 市场动态定价配置 (80 bytes, ABI 0x0009_0003 起)
+ */
+typedef struct {
+  double base_lambda;
+  double volatility_factor;
+  double seasonal_amplitude;
+  double weekend_multiplier;
+  double newbie_protection_rate;
+  double seasonal_weight;
+  double weekend_weight;
+  double newbie_weight;
+  double inflation_weight;
+  double ln_floor;
+} MarketConfig;
+
 /*
  交易定价演算上下文 (64 bytes)
  */
@@ -62,27 +329,45 @@ typedef struct {
 } TradeContext;
 
 /*
- 市场动态定价配置 (72 bytes)
+ 单条历史交易快照 (16 bytes)
  */
 typedef struct {
-  double base_lambda;
-  double volatility_factor;
-  double seasonal_amplitude;
-  double weekend_multiplier;
-  double newbie_protection_rate;
-  double seasonal_weight;
-  double weekend_weight;
-  double newbie_weight;
-  double inflation_weight;
-} MarketConfig;
+  long long timestamp;
+  long long amount_micros;
+} HistoryRecord;
+
+/*
+ 卖出阶梯折扣配置 (32 bytes)：超出各档数量上限的部分按对应折扣率计价
+ */
+typedef struct {
+  double tier1_limit;
+  double tier2_limit;
+  double tier2_rate;
+  double tier3_rate;
+} TierConfig;
+
+/*
+ 监控采集快照 (48 bytes, ABI 0x0009_0007 起)：一次 FFI 调用汇总日志健康度与全局热存储指标
+ */
+typedef struct {
+  uint64_t total_logs;
+  uint64_t dropped_logs;
+  double local_neff;
+  long long remote_pending_micros;
+  double avg_trade_age_ms;
+  long long writer_last_flush_ms;
+} MonitoringSnapshot;
 
 /*
- 交易演算最终结果 (16 bytes)
+ 交易演算最终结果 (32 bytes, ABI 0x0009_0002 起)
  */
 typedef struct {
   long long final_tax_micros;
   int is_blocked;
   int warning_code;
+  int applied_tax_flags;
+  int _padding;
+  long long limit_headroom_micros;
 } TransferResult;
 
 /*
@@ -104,25 +389,18 @@ typedef struct {
 } TransferContext;
 
 /*
- 审计监管与计税配置 (96 bytes)
+ 购买报价 (32 bytes)：单次 FFI 调用合并定价与交易审计结果
  */
 typedef struct {
-  double base_tax_rate;
-  long long luxury_threshold;
-  double luxury_tax_rate;
-  double wealth_gap_tax_rate;
-  long long poor_threshold;
-  long long rich_threshold;
-  double _reserved;
-  double warning_ratio;
-  long long warning_min_amount;
-  double newbie_hours;
-  double veteran_hours;
-  double velocity_threshold;
-} RegulatorConfig;
+  long long unit_price_micros;
+  long long total_micros;
+  long long tax_micros;
+  int is_blocked;
+  int warning_code;
+} PurchaseQuote;
 
 /*
- 工业级 PID 控制器状态 (72 bytes)
+ 工业级 PID 控制器状态 (120 bytes, ABI 0x0009_000C 起)
  */
 typedef struct {
   double kp;
@@ -134,82 +412,1398 @@ typedef struct {
   double filtered_d;
   double integration_limit;
   int is_saturated;
-  int _padding;
+  int derivative_mode;
+  double prev_error;
+  double panic_threshold;
+  double panic_damping;
+  double integral_decay;
+  double output_min;
+  double output_max;
 } PidState;
 
 int ecobridge_abi_version(void);
 
 const char *ecobridge_version(void);
 
-int ecobridge_init_threading(int num_threads);
+/*
+ 导出运行时结构体布局描述，供 Java 侧启动时与自身 FFM 布局逐字段核对
 
-int ecobridge_init_db(const char *path_ptr);
+ 写入至多 `cap` 条 `LayoutEntry`，描述项总数写入 `out_count`；
+ 可先以 `cap = 0` 查询总数再分配缓冲区。
 
-int ecobridge_shutdown_db(void);
+ # Safety
 
-int ecobridge_log_to_duckdb(long long ts,
-                            const char *uuid_ptr,
-                            long long trade_amount_micros,
-                            long long balance_micros,
-                            const char *meta_ptr);
+ `out_ptr` 须可写入至少 `cap` 个 `LayoutEntry`；`out_count` 须指向可写的 `u64`。
+ */
+int ecobridge_layout_descriptor(LayoutEntry *out_ptr, uint64_t cap, uint64_t *out_count);
 
-int inject_remote_trade(long long amount_micros);
+/*
+ 设置 panic 处理策略：非 0 = 中止进程 (开发调试)，0 = 捕获并返回 Panic (默认，生产环境)
 
-int ecobridge_query_neff_vectorized(long long current_ts, double tau, double *out_result);
+ 中止前 panic 位置已由默认 panic hook 输出；设置 `RUST_BACKTRACE=1` 可附带完整调用栈。
+ */
+int ecobridge_set_panic_abort(int enabled);
 
-int ecobridge_compute_batch_prices(uint64_t count,
-                                   double neff,
-                                   const TradeContext *ctx_ptr,
-                                   const MarketConfig *cfg_ptr,
-                                   const double *hist_avgs_ptr,
-                                   const double *lambdas_ptr,
-                                   double *results_ptr);
+int ecobridge_init_threading(int num_threads);
 
-int ecobridge_compute_price_final(double base,
-                                  double n_eff,
-                                  double lambda,
-                                  double epsilon,
-                                  double *out_result);
+/*
+ 初始化数据库与写入线程
 
-int ecobridge_compute_tier_price(double base, double qty, int is_sell, double *out_result);
+ 每个进程只能绑定一次；重复调用返回 AlreadyInitialized，且本次传入的路径被忽略。
 
-int ecobridge_compute_price_humane(double base,
-                                   double n_eff,
-                                   double trade_amount,
-                                   double lambda,
-                                   double epsilon,
-                                   double *out_result);
+ # Safety
 
-int ecobridge_compute_price_bounded(double base,
-                                    double n_eff,
-                                    double amt,
-                                    double lambda,
-                                    double eps,
-                                    double hist_avg,
-                                    double *out_result);
+ `path_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_init_db(const char *path_ptr);
 
-int ecobridge_calc_inflation(double current_heat, double m1, double *out_result);
+/*
+ 数据库是否已初始化：1 = 已绑定 (后续 init 将返回 AlreadyInitialized)，0 = 未初始化
+ */
+int ecobridge_is_db_initialized(void);
 
-int ecobridge_calc_stability(long long last_ts, long long curr_ts, double *out_result);
+/*
+ 扩展初始化：可配置 DuckDB 内存上限 (MB)、引擎线程数与读连接池大小
 
-int ecobridge_calc_decay(double heat, double rate, double *out_result);
+ 任一参数 <= 0 时使用默认值 (512MB / 4 线程 / 4 连接)；超出合理范围返回 InvalidValue。
+ 重复初始化返回 AlreadyInitialized。
 
-int ecobridge_get_health_stats(uint64_t *out_total, uint64_t *out_dropped);
+ # Safety
 
-int ecobridge_calculate_epsilon(const TradeContext *ctx_ptr,
-                                const MarketConfig *cfg_ptr,
-                                double *out_result);
+ `path_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_init_db_ex(const char *path_ptr,
+                         int mem_limit_mb,
+                         int db_threads,
+                         int pool_size);
 
-int ecobridge_compute_transfer_check(TransferResult *out_result,
-                                     const TransferContext *ctx_ptr,
-                                     const RegulatorConfig *cfg_ptr);
+/*
+ 扩展初始化 (含写入重试)：在 `ecobridge_init_db_ex` 基础上配置写入线程的瞬时错误重试
 
-int ecobridge_get_dynamic_limit(long long play_time_secs,
-                                double base,
-                                double rate,
-                                double max,
+ 批次提交遇到锁冲突等瞬时错误时，按 `retry_backoff_ms` 起始的指数退避重试至多 `retry_attempts` 次，
+ 耗尽或遇到致命错误才丢弃该批次。两者 <= 0 时使用默认值 (3 次 / 50 ms)；
+ 超出范围 (1..=10 次 / 1..=5000 ms) 返回 InvalidValue。
+
+ # Safety
+
+ `path_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_init_db_with_retry(const char *path_ptr,
+                                 int mem_limit_mb,
+                                 int db_threads,
+                                 int pool_size,
+                                 int retry_attempts,
+                                 int retry_backoff_ms);
+
+/*
+ 关闭数据库写入线程，阻塞等待最终批次落盘 (默认超时 5 秒)
+ */
+int ecobridge_shutdown_db(void);
+
+/*
+ 关闭数据库写入线程，最多等待 `timeout_ms` 毫秒让其排空通道并提交
+
+ 完整落盘返回 Ok；写入线程未运行 (未初始化或已关闭) 返回 NotRunning；
+ 超时返回 Timeout，写入线程仍会在后台继续排空，再次调用将继续等待其退出。
+ */
+int ecobridge_shutdown_db_timeout(long long timeout_ms);
+
+/*
+ 重置全部全局状态 (插件重载)
+
+ 清空热存储与内存历史、归零远程流量累加器与本次会话的日志计数，并将所有可调参数 (含 panic 中止策略) 恢复默认值。
+ 跨重启持久化的累计丢弃计数与写入线程心跳保留不变。
+ 与 `ecobridge_shutdown_db` 不同，数据库连接与写入线程保持运行；
+ 若写入线程正在刷盘，本调用会等待该批次提交完成。
+ */
+int ecobridge_reset_all_state(void);
+
+/*
+ 测试专用：在用例之间重置原生核心的全部全局状态
+
+ 在 `ecobridge_reset_all_state` 的基础上同时归零累计丢弃计数与写入线程心跳。
+ 仅在启用 `testing` feature 的构建中生效；生产构建直接返回 Unsupported 且不修改任何状态。
+ */
+int ecobridge_reset_for_testing(void);
+
+/*
+ 设置热存储超限裁剪策略 (0 = DropOldest, 1 = Downsample)
+
+ `downsample_age_ms` 为降采样年龄阈值，仅对早于 (最新记录 - 阈值) 的记录合并。
+ */
+int ecobridge_set_prune_policy(int policy,
+                               long long downsample_age_ms);
+
+/*
+ 兼容保留：热存储现始终按时间戳有序插入 (晚到记录插入正确位置)，参数被忽略
+ */
+int ecobridge_set_ordered_insert(int _enabled);
+
+/*
+ # Safety
+
+ `uuid_ptr`、`meta_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_log_to_duckdb(long long ts,
+                            const char *uuid_ptr,
+                            long long trade_amount_micros,
+                            long long balance_micros,
+                            const char *meta_ptr);
+
+/*
+ 为租户在同一数据库文件中建立独立日志表 `economy_log_<name>` 与时间索引 (幂等)
+
+ 租户名仅允许 ASCII 字母、数字与下划线，长度 1..=32，否则返回 InvalidValue；
+ 数据库未初始化或建表失败返回 InternalError。每次启动后须重新调用以登记租户。
+
+ # Safety
+
+ `name_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_init_tenant(const char *name_ptr);
+
+/*
+ 记录经济事件到指定租户的日志表 (参数语义同 `ecobridge_log_to_duckdb`)
+
+ 命名租户相互隔离，不进入全局热存储与内存历史。租户名不合法或尚未 `ecobridge_init_tenant`
+ 返回 InvalidValue；金额超出 i64 Micros 范围返回 NumericOverflow。
+
+ # Safety
+
+ `tenant_ptr`、`uuid_ptr`、`meta_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_log_to_duckdb_tenant(const char *tenant_ptr,
+                                   long long ts,
+                                   const char *uuid_ptr,
+                                   long long trade_amount_micros,
+                                   long long balance_micros,
+                                   const char *meta_ptr);
+
+/*
+ 从指定租户的日志表查询 neff (衰减加权，回溯 3·tau 天)
+
+ 租户名不合法或尚未 `ecobridge_init_tenant` 返回 InvalidValue；`tau` 须为正的有限值。
+
+ # Safety
+
+ `tenant_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_tenant_neff(const char *tenant_ptr,
+                                long long current_ts,
+                                double tau,
+                                double *out_result);
+
+/*
+ 设置启动预热失败策略 (0 = ContinueEmpty, 1 = RetryN, 2 = Fatal)，须在初始化数据库之前调用
+
+ `retry_attempts` 仅对 RetryN 生效 (默认 3 次，指数退避)；Fatal 策略下预热失败将令 init 返回 Fatal。
+ */
+int ecobridge_set_preload_failure_policy(int policy,
+                                         uint32_t retry_attempts);
+
+/*
+ 设置启动预热窗口 (天，默认 30)，须在初始化数据库之前调用
+
+ 内存历史与分市场热存储共用此窗口。窗口越长启动越慢、常驻内存越多；`days <= 0` 返回 InvalidValue。
+ */
+int ecobridge_set_preload_days(int days);
+
+/*
+ 设置预热窗口使用的 "当前时间" (ms)，用于游戏内时间与主机时钟不一致的服务器及集成测试
+
+ 非 0 时启动预热、按天数加载历史与 M1 估算缓存均以该值为 "现在"；0 恢复系统时钟，负值返回 InvalidValue。
+ */
+int ecobridge_set_clock_override(long long now_ms);
+
+/*
+ 查询日志写入通道占用率 [0, 1]，数据库未初始化时为 0
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_log_queue_utilization(double *out_result);
+
+/*
+ 设置关键事件阻塞发送：非 0 = 影响余额的事件在通道满时短暂阻塞等待 (最多 100ms)，0 = 满则丢弃 (默认)
+
+ 遥测事件 (delta == 0) 不受影响。开启后可避免丢账，但写入线程落后时调用线程会出现停顿。
+ */
+int ecobridge_set_blocking_critical_logs(int enabled);
+
+/*
+ 查询 M1 货币供应量估算值 (每位玩家最后一次记录的余额之和)
+
+ 基于日志中的最后可见余额估算，结果按缓存有效期复用；数据库未初始化时写入 0。
+
+ # Safety
+
+ `out_m1` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_m1(double *out_m1);
+
+/*
+ 基于历史日志建议风控 `velocity_threshold`
+
+ 统计 [from_ts, to_ts) 内每位玩家的事件速率 (次/分钟)，输出其分布在 `percentile` 处的取值
+ (如 0.99 = 仅标记最活跃的 1%)。`percentile` 须位于 [0, 1]；区间内无日志时返回 InvalidValue。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_suggest_velocity_threshold(long long from_ts,
+                                         long long to_ts,
+                                         double percentile,
+                                         double *out_result);
+
+/*
+ 设置 M1 估算缓存有效期 (ms)，0 表示不缓存
+ */
+int ecobridge_set_m1_cache_ttl(uint64_t ttl_ms);
+
+/*
+ 按 UTC 自然日汇总 [start_ts, end_ts) 的经济日志并写入调用方缓冲区
+
+ 按日期升序写入至多 `cap` 行 (单次上限 366 天)，实际行数写入 `out_written`。
+ 区间为空或数据库未初始化时写入 0 行并返回 Ok；查询或行解码失败时返回 InternalError，不写入任何行。
+
+ # Safety
+
+ `out_ptr` 须可写入至少 `cap` 个 `DaySummary`；`out_written` 须指向可写的 `u64`。
+ */
+int ecobridge_query_daily_summary(long long start_ts,
+                                  long long end_ts,
+                                  DaySummary *out_ptr,
+                                  uint64_t cap,
+                                  uint64_t *out_written);
+
+/*
+ 交易额排行榜：按 [start_ts, end_ts) 内资金变动绝对值之和降序写入至多 `cap` 名玩家 (单次上限 1,000)
+
+ 缓冲区约定：`out_uuids` 为 `cap × UUID_SLOT_LEN` 字节，第 i 名的标识写入第 i 个槽位，
+ 以 NUL 结尾并以 0 填充剩余字节 (超过 36 字节的标识按字节截断)；`out_totals[i]` 为对应总额。
+ 实际行数写入 `out_written`。区间为空或数据库未初始化时写入 0 行并返回 Ok；
+ 查询或行解码失败时返回 InternalError，不写入任何行。
+
+ # Safety
+
+ - `out_uuids` 须可写入 `cap × UUID_SLOT_LEN` 字节；
+ - `out_totals` 须可写入至少 `cap` 个 `c_double`；
+ - `out_written` 须指向可写的 `u64`。
+ */
+int ecobridge_query_top_traders(long long start_ts,
+                                long long end_ts,
+                                char *out_uuids,
+                                double *out_totals,
+                                uint64_t cap,
+                                uint64_t *out_written);
+
+/*
+ 将 [start_ts, end_ts) 的经济日志导出为带表头的 CSV 文件，写入行数写入 `out_rows`
+
+ 目标目录不存在时自动创建。区间为空或路径非 UTF-8 返回 InvalidValue；
+ 数据库未初始化、目录创建或 COPY 失败返回 InternalError。
+
+ # Safety
+
+ `path_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_rows` 须指向可写的 `u64`。
+ */
+int ecobridge_export_csv(const char *path_ptr,
+                         long long start_ts,
+                         long long end_ts,
+                         uint64_t *out_rows);
+
+/*
+ 从 Parquet 归档预热全局热存储 (需包含 `ts` 与 `delta` 列)，载入条数写入 `out_rows`
+
+ 记录与现有热存储合并并遵循裁剪上限。路径非 UTF-8 或缺少必需列返回 InvalidValue；
+ 数据库未初始化或读取失败返回 InternalError。
+
+ # Safety
+
+ `path_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_rows` 须指向可写的 `u64`。
+ */
+int ecobridge_hydrate_parquet(const char *path_ptr,
+                              uint64_t *out_rows);
+
+/*
+ 累加远程成交量 (Micros)；累加器在 i64 两端饱和，长期运行的巨额流量不会回绕为反号
+ */
+int inject_remote_trade(long long amount_micros);
+
+/*
+ 注入带成交时间戳的远程成交量 (Micros)，与本地历史一样按时间衰减计入 neff
+
+ 适用于联邦节点重连后回放积压：旧记录只按其年龄贡献供应量，避免 `inject_remote_trade`
+ 将全部积压视为 "当前" 流量导致 neff 突增、价格崩塌。不经过累加器，查询时不被消费。
+ */
+int inject_remote_trade_smoothed(long long amount_micros,
+                                 long long ts);
+
+/*
+ 查询全局远程流量累加器的待消费值 (Micros)，不消费该值
+
+ # Safety
+
+ `out_micros` 须指向可写的 `c_longlong`。
+ */
+int ecobridge_remote_accumulator_value(long long *out_micros);
+
+/*
+ # Safety
+
+ `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int inject_remote_trade_for_key(const char *market_key_ptr, long long amount_micros);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_longlong`。
+ */
+int ecobridge_money_to_micros(double value, long long *out_result);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_micros_to_money(long long value_micros, double *out_result);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_volatility_from_stability(double stability, double *out_result);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_velocity_decay(double velocity,
+                                     long long delta_ms,
+                                     double half_life_ms,
+                                     double *out_result);
+
+/*
+ 税负归宿模拟：以 `cfg` 对 `count` 组 (余额, 转账金额) 批量计税，输出总税额与平均有效税率
+
+ 供运营在上线新税制前评估其对全体玩家的影响；不经过拦截判定，金额 <= 0 的样本被忽略。
+ `count` 上限 1,000,000。
+
+ # Safety
+
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `balances_ptr`、`amounts_ptr` 须指向至少 `count` 个连续的 `c_longlong`；
+ - `out_total_tax` 须指向可写的 `c_longlong`；
+ - `out_avg_rate` 须指向可写的 `c_double`。
+ */
+int ecobridge_simulate_tax_incidence(const RegulatorConfig *cfg_ptr,
+                                     const long long *balances_ptr,
+                                     const long long *amounts_ptr,
+                                     uint64_t count,
+                                     long long *out_total_tax,
+                                     double *out_avg_rate);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_fallback_tax(double amount, double *out_result);
+
+/*
+ # Safety
+
+ `out_tax`、`out_net` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_settlement(double amount,
+                                 double suggested_tax,
+                                 int bypass_tax,
+                                 double *out_tax,
+                                 double *out_net);
+
+/*
+ 全局有效供应量 = 本地 neff + 远程累加器 (读取后清零) + 带时间戳远程流量的衰减量
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_neff_vectorized(long long current_ts,
+                                    double tau,
+                                    double *out_result);
+
+/*
+ 查询全局有效供应量及其置信度 ∈ [0, 1]，供 UI 展示价格可靠度
+
+ 置信度 = 样本项 × 覆盖项：样本项为 `k / (k + NEFF_CONFIDENCE_HALF_SAMPLES)` (k 为 Kish 有效样本数)，
+ 覆盖项为窗口内最早记录年龄 / tau 天 (钳位至 1)。neff 含带时间戳远程流量，但不消费远程流量累加器；
+ 置信度只反映本地样本。
+
+ # Safety
+
+ `out_neff`、`out_confidence` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_neff_with_confidence(long long current_ts,
+                                         double tau,
+                                         double *out_neff,
+                                         double *out_confidence);
+
+/*
+ 一次调用查询多个衰减常数下的全局有效供应量 (如 1 / 7 / 30 天)
+
+ 只遍历热存储一次，`out_ptr[i]` 对应 `taus_ptr[i]`。与 `ecobridge_query_neff_vectorized` 一致地计入
+ 带时间戳远程流量，但不消费远程流量累加器。任一 tau 非正或非有限返回 InvalidValue；`count` 上限 1,024。
+
+ # Safety
+
+ `taus_ptr` 须指向至少 `count` 个连续的 `c_double`；`out_ptr` 须可写入至少 `count` 个 `c_double`。
+ */
+int ecobridge_query_neff_multi(long long current_ts,
+                               const double *taus_ptr,
+                               uint64_t count,
+                               double *out_ptr);
+
+/*
+ # Safety
+
+ `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_neff_for_key(long long current_ts,
+                                 double tau,
+                                 const char *market_key_ptr,
+                                 double *out_result);
+
+/*
+ 设置 neff 衰减窗口：未来时钟偏差容忍 (ms, >= 0) 与回溯长度 (tau 的倍数, >= 1.0)
+
+ 默认值为 60,000 ms 与 10.0。
+ */
+int ecobridge_set_neff_window(long long future_tolerance_ms,
+                              double lookback_multiplier);
+
+/*
+ 按累积模式查询全局有效供应量
+
+ `signed != 0`: 保留买卖方向 (与 `ecobridge_query_neff_vectorized` 的本地部分一致)；
+ `signed == 0`: 取成交规模绝对值累积。不消费远程流量累加器。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_neff_signed(long long current_ts, double tau, int signed_, double *out_result);
+
+/*
+ 估算全局 neff 的浮点累积误差
+
+ `out_neff` 为常规演算结果 (不含远程流量)，`out_error_estimate` 为朴素逐项累加与
+ Neumaier 补偿求和参考值的绝对差，用于判断当前数据集是否需要关注精度。
+
+ # Safety
+
+ `out_neff`、`out_error_estimate` 须指向可写的 `c_double`。
+ */
+int ecobridge_estimate_neff_error(long long current_ts,
+                                  double tau,
+                                  double *out_neff,
+                                  double *out_error_estimate);
+
+/*
+ # Safety
+
+ `out_age_ms` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_avg_trade_age(long long current_ts, double tau, double *out_age_ms);
+
+/*
+ 查询全局热存储的衰减加权中位交易规模 (标准单位，买卖均取绝对值)
+
+ 权重与 neff 衰减一致且不随规模放大，可作为不受巨额交易影响的 "典型交易" 指标。
+ 窗口内无记录时写入 0。
+
+ # Safety
+
+ `out` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_weighted_median(long long current_ts,
+                                    double tau,
+                                    double *out);
+
+/*
+ 查询两个市场成交活跃度的相关系数 (正值: 互补品，负值: 替代品)
+
+ 将 [from_ts, to_ts) 按 `bucket_ms` 分桶后计算 Pearson 相关系数，桶数上限 100,000。
+ 市场不存在于热存储或相关系数无定义 (任一序列无波动) 时返回 InvalidValue。
+
+ # Safety
+
+ `key_a_ptr`、`key_b_ptr` 须为以 NUL 结尾的有效 C 字符串；`out` 须指向可写的 `c_double`。
+ */
+int ecobridge_query_market_neff_correlation(const char *key_a_ptr,
+                                            const char *key_b_ptr,
+                                            long long from_ts,
+                                            long long to_ts,
+                                            long long bucket_ms,
+                                            double *out);
+
+/*
+ 将 `MarketConfig` 的四项环境权重按比例归一化 (和为 1.0)
+
+ `out_adjusted` 写入 1 表示发生了调整、0 表示已归一化。权重为负/非有限或全为 0 时返回 InvalidValue 且不修改配置。
+
+ # Safety
+
+ `cfg_ptr` 须指向有效且可写的 `MarketConfig`；`out_adjusted` 须指向可写的 `c_int`。
+ */
+int ecobridge_normalize_market_weights(MarketConfig *cfg_ptr,
+                                       int *out_adjusted);
+
+/*
+ 按样本 (上下文, 期望 ε) 拟合四项环境权重，结果写入 `out_cfg`
+
+ `out_cfg` 为 `cfg_ptr` 的副本，仅四项权重被替换为拟合值 (非负且和为 1)。
+ 无有效样本 (期望 ε 均非正或非有限) 时权重保持不变；`count` 上限 100,000。
+
+ # Safety
+
+ - `ctxs_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+ - `targets_ptr` 须指向至少 `count` 个连续的 `c_double`；
+ - `cfg_ptr` 须指向有效的 `MarketConfig`；
+ - `out_cfg` 须指向可写的 `MarketConfig`。
+ */
+int ecobridge_solve_epsilon_weights(const TradeContext *ctxs_ptr,
+                                    const double *targets_ptr,
+                                    uint64_t count,
+                                    const MarketConfig *cfg_ptr,
+                                    MarketConfig *out_cfg);
+
+/*
+ 计算单条记录在 `age_ms` 年龄下的衰减权重 `exp(-age_ms / (tau · 86_400_000))`
+
+ 与 neff 累积使用的权重公式一致。要求 `tau > 0` 且 `age_ms >= 0`。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_decay_weight(long long age_ms, double tau, double *out_result);
+
+/*
+ 查询全局热存储在闭区间 [start_ts, end_ts] 内的原始 (不衰减) 成交量，单位为标准货币
+
+ 供报表使用，与衰减加权的 neff 互补；`start_ts > end_ts` 时返回 InvalidValue。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_volume_in_range(long long start_ts,
+                              long long end_ts,
+                              double *out_result);
+
+/*
+ 按分桶边界统计全局热存储的单笔成交量分布 (标准货币，取绝对值)，供看板绘制分布图
+
+ `edge_count` 个严格递增的边界划分 `edge_count - 1` 个桶，`out_counts` 须至少容纳同样数量；
+ 最后一个桶包含右边界，超出边界范围的记录不计入。边界少于 2 个或超过 1,024 个返回 InvalidLength，
+ 含非有限值或未严格递增返回 InvalidValue。
+
+ # Safety
+
+ `edges_ptr` 须指向至少 `edge_count` 个连续的 `c_double`；`out_counts` 须可写入至少 `edge_count - 1` 个 `u64`。
+ */
+int ecobridge_amount_histogram(const double *edges_ptr,
+                               uint64_t edge_count,
+                               uint64_t *out_counts);
+
+/*
+ 由两次 neff 观测 (期间无新成交) 反推衰减常数 tau (天)，用于按实测数据校准模型
+
+ 未观测到衰减 (`neff_t2 >= neff_t1` 或符号不一致) 或 `dt_ms <= 0` 时返回 InvalidValue。
+
+ # Safety
+
+ `out_tau` 须指向可写的 `c_double`。
+ */
+int ecobridge_fit_tau(double neff_t1,
+                      double neff_t2,
+                      long long dt_ms,
+                      double *out_tau);
+
+/*
+ 将全局热存储中最新的至多 `cap` 条记录复制到调用方缓冲区
+
+ 返回的数据为调用时刻的时间点快照，按时间先后排列；容量不足时仅保留最新部分。
+ 实际写入条数写入 `out_written`。
+
+ # Safety
+
+ `out_ptr` 须可写入至少 `cap` 个 `HistoryRecord`；`out_written` 须指向可写的 `u64`。
+ */
+int ecobridge_snapshot_history(HistoryRecord *out_ptr,
+                               uint64_t cap,
+                               uint64_t *out_written);
+
+/*
+ 批量注入历史交易到全局热存储 (回测重放)，不写入 DuckDB
+
+ 整批只加一次写锁；乱序记录自动按时间戳排序，超出容量时按当前裁剪策略处理。
+ `count` 为 0 时直接返回 Ok，上限 1,000,000 条 (更大的数据集请分批注入)。
+
+ # Safety
+
+ `ptr` 须指向至少 `count` 个连续的 `HistoryRecord`。
+ */
+int ecobridge_bulk_inject(const HistoryRecord *ptr,
+                          uint64_t count);
+
+/*
+ 将联邦节点的历史批次按时间戳归并进全局热存储 (乱序批次自动排序)，不写入 DuckDB
+
+ 与本地记录原地归并以维持热存储的有序不变量，同一时间戳下本地记录在前。
+ `count` 为 0 时直接返回 Ok，上限 1,000,000 条。
+
+ # Safety
+
+ `ptr` 须指向至少 `count` 个连续的 `HistoryRecord`。
+ */
+int ecobridge_merge_peer_history(const HistoryRecord *ptr,
+                                 uint64_t count);
+
+/*
+ 批量定价 (全部物品共享同一 neff)
+
+ 单个元素演算 panic 时该元素写入哨兵价格 0.01，其余元素照常完成；
+ 首个失败元素的下标可在同一线程经 `ecobridge_last_batch_error` 查询。
+
+ # Safety
+
+ - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+ - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+ - `hist_avgs_ptr`、`lambdas_ptr` 须指向至少 `count` 个连续的 `f64`；
+ - `results_ptr` 须可写入至少 `count` 个 `f64`。
+ */
+int ecobridge_compute_batch_prices(uint64_t count,
+                                   double neff,
+                                   const TradeContext *ctx_ptr,
+                                   const MarketConfig *cfg_ptr,
+                                   const double *hist_avgs_ptr,
+                                   const double *lambdas_ptr,
+                                   double *results_ptr);
+
+/*
+ 逐物品 neff 的批量定价：第 i 个物品使用 `neffs_ptr[i]`，其余参数与 `ecobridge_compute_batch_prices` 相同
+
+ 适用于按物品独立统计供应量的市场；全部物品共享同一 neff 时请使用原函数。
+ `neffs_len` 为 `neffs_ptr` 数组的实际元素个数，不等于 `count` 时返回 InvalidLength 且不读写任何元素；
+ 任一 neff 非有限值返回 InvalidValue 且不写入结果。
+
+ # Safety
+
+ - `neffs_ptr` 须指向至少 `neffs_len` 个连续的 `f64`；
+ - `hist_avgs_ptr`、`lambdas_ptr` 须指向至少 `count` 个连续的 `f64`；
+ - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+ - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+ - `results_ptr` 须可写入至少 `count` 个 `f64`。
+ */
+int ecobridge_compute_batch_prices_per_neff(uint64_t count,
+                                            const double *neffs_ptr,
+                                            uint64_t neffs_len,
+                                            const TradeContext *ctx_ptr,
+                                            const MarketConfig *cfg_ptr,
+                                            const double *hist_avgs_ptr,
+                                            const double *lambdas_ptr,
+                                            double *results_ptr);
+
+/*
+ 按物品 ID 查表取 lambda 的批量定价：`item_ids_ptr[i]` 对应 `ecobridge_set_item_lambda` 登记的值
+
+ 未登记的物品取默认 lambda (0.01)；其余参数与 `ecobridge_compute_batch_prices` 相同。
+ 查表在原生侧完成，调用方无需每次编组完整的 lambda 数组。
+
+ # Safety
+
+ - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+ - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+ - `hist_avgs_ptr` 须指向至少 `count` 个连续的 `f64`；
+ - `item_ids_ptr` 须指向至少 `count` 个连续的 `u32`；
+ - `results_ptr` 须可写入至少 `count` 个 `f64`。
+ */
+int ecobridge_compute_batch_prices_by_item(uint64_t count,
+                                           double neff,
+                                           const TradeContext *ctx_ptr,
+                                           const MarketConfig *cfg_ptr,
+                                           const double *hist_avgs_ptr,
+                                           const uint32_t *item_ids_ptr,
+                                           double *results_ptr);
+
+/*
+ 登记物品的 lambda (价格敏感度)，供 `ecobridge_compute_batch_prices_by_item` 查表
+
+ `lambda` 须为正有限值，否则返回 InvalidValue。
+ */
+int ecobridge_set_item_lambda(uint32_t item_id, double lambda);
+
+/*
+ 查询物品的 lambda，未登记时写入默认值 0.01
+
+ # Safety
+
+ `out_lambda` 须指向可写的 `c_double`。
+ */
+int ecobridge_get_item_lambda(uint32_t item_id, double *out_lambda);
+
+/*
+ 带长度校验的批量定价
+
+ 跨 FFI 无法感知指针背后的真实数组长度，因此要求调用方为每个数组显式传入其元素个数：
+ 输入数组 (`ctx`/`cfg`/`hist_avgs`/`lambdas`) 与输出数组 `results` 的长度均须等于 `count`，
+ 任一不一致即返回 InvalidLength 且不读写任何元素，避免越界读取导致的未定义行为。
+
+ # Safety
+
+ - `ctx_ptr` 须指向至少 `ctx_len` 个连续的 `TradeContext`；
+ - `cfg_ptr` 须指向至少 `cfg_len` 个连续的 `MarketConfig`；
+ - `hist_avgs_ptr` 须指向至少 `hist_avgs_len` 个连续的 `f64`；
+ - `lambdas_ptr` 须指向至少 `lambdas_len` 个连续的 `f64`；
+ - `results_ptr` 须可写入至少 `results_len` 个 `f64`。
+ */
+int ecobridge_compute_batch_prices_checked(uint64_t count,
+                                           double neff,
+                                           const TradeContext *ctx_ptr,
+                                           uint64_t ctx_len,
+                                           const MarketConfig *cfg_ptr,
+                                           uint64_t cfg_len,
+                                           const double *hist_avgs_ptr,
+                                           uint64_t hist_avgs_len,
+                                           const double *lambdas_ptr,
+                                           uint64_t lambdas_len,
+                                           double *results_ptr,
+                                           uint64_t results_len);
+
+/*
+ 查询本线程最近一次批量定价中首个 panic 元素的下标，无失败时写入 -1
+
+ 批量定价对单个元素的 panic 做隔离：该元素输出哨兵价格 0.01，其余元素照常完成。
+ 记录按调用线程区分，须在发起批量定价的同一线程查询；批量接口因参数校验提前返回时同样记为 -1。
+
+ # Safety
+
+ `out_index` 须指向可写的 `c_longlong`。
+ */
+int ecobridge_last_batch_error(long long *out_index);
+
+/*
+ 登记新市场的上线爬坡：`[launch_ts, launch_ts + ramp_ms)` 内价格从首发价线性过渡到演算价
+
+ `ramp_ms <= 0` 移除该市场的爬坡设置；启用爬坡时 `launch_price_micros` 须为正。
+
+ # Safety
+
+ `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_set_market_launch(const char *market_key_ptr,
+                                long long launch_ts,
+                                long long ramp_ms,
+                                long long launch_price_micros);
+
+/*
+ 按市场成熟度混合价格：爬坡期内在首发价与 `computed_price` 之间按已上线时长插值
+
+ 未登记的市场或爬坡期结束后原样输出 `computed_price`。
+
+ # Safety
+
+ `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_apply_market_launch(const char *market_key_ptr,
+                                  long long now_ms,
+                                  double computed_price,
+                                  double *out_result);
+
+/*
+ 设置冷启动最少样本数 (0 = 关闭，默认)
+
+ 定价时若对应市场热存储窗口内样本数低于该值，价格按 样本数/最少样本数 的置信度向基础价线性回归，
+ 避免重置或新开服后 neff 过小导致价格剧烈跳变。单体定价按调用方传入的 tau 统计样本，
+ 批量定价接口不携带 tau，按 `WARMUP_TAU_DAYS` (7 天) 统计。
+ */
+int ecobridge_set_pricing_min_samples(uint64_t n);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_final(double base,
+                                  double n_eff,
+                                  double lambda,
+                                  double epsilon,
+                                  double *out_result);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_tier_price(double base, double qty, int is_sell, double *out_result);
+
+/*
+ 预估一次性卖出 `quantity` 件物品的总收益 (Micros)
+
+ 逐件计入有效供应量冲击与 `tier_cfg_ptr` 指定的阶梯折扣，而非简单的 数量 × 单价。
+ `quantity` 上限为 1,000,000 件；阶梯配置非法 (见 `TierConfig::is_valid`) 时返回 InvalidValue。
+
+ # Safety
+
+ - `tier_cfg_ptr` 须指向有效的 `TierConfig`；
+ - `out_total_micros` 须指向可写的 `c_longlong`。
+ */
+int ecobridge_compute_sell_all_value(long long base_micros,
+                                     double n_eff,
+                                     long long quantity,
+                                     double lambda,
+                                     double eps,
+                                     const TierConfig *tier_cfg_ptr,
+                                     long long *out_total_micros);
+
+/*
+ 计算参考物品在 [from_ts, to_ts] 间的购买力比值 (then 价格 / now 价格)
+
+ 两端价格使用全局热存储在各自时点的 neff，不消费远程流量累加器。
+ 结果 < 1 表示单位货币现在能买到的更少。
+
+ # Safety
+
+ `out_ratio` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_purchasing_power(long long base_micros,
+                                       long long from_ts,
+                                       long long to_ts,
+                                       double lambda,
+                                       double tau,
+                                       double *out_ratio);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_humane(double base,
+                                   double n_eff,
+                                   double trade_amount,
+                                   double lambda,
+                                   double epsilon,
+                                   double *out_result);
+
+/*
+ 锚定价均值回归定价：在 `ecobridge_compute_price_humane` 的结果上按 `reversion_strength` 拉向 `anchor_micros`
+
+ `reversion_strength` 须位于 [0, 1] (0 时与 humane 结果完全一致)，`anchor_micros` 须为正，否则返回 InvalidValue。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_anchored(double base,
+                                     double n_eff,
+                                     double trade_amount,
+                                     double lambda,
+                                     double epsilon,
+                                     long long anchor_micros,
+                                     double reversion_strength,
+                                     double *out_result);
+
+/*
+ 带底价保护的价格演算：底价取 历史均价 × 20% 与物品硬底价 `min_price_micros` 的较大者
+
+ `min_price_micros <= 0` 时硬底价退回全局 0.01。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_bounded(double base,
+                                    double n_eff,
+                                    double amt,
+                                    double lambda,
+                                    double eps,
+                                    double hist_avg,
+                                    long long min_price_micros,
+                                    double *out_result);
+
+/*
+ 一站式定价：由 `ctx` 与 `cfg` 直接得出带底价保护的价格
+
+ 内部以 `ctx.current_timestamp` 与 `tau` 查询全局 neff (含带时间戳远程流量，不消费远程流量累加器)，
+ 由 `cfg` 计算环境因子，再按 `ctx.current_amount` 演算，硬底价取全局 0.01；
+ 全局样本不足时按同一 `tau` 的预热置信度向基础价回归 (见 `ecobridge_set_pricing_min_samples`)；
+ 卖出时按 `ctx.play_time_seconds` 叠加新手卖出加成 (见 `ecobridge_set_newbie_sell_bonus`)，不读取 `ctx.newbie_mask`。
+ tau 非正或非有限返回 InvalidValue。
+
+ # Safety
+
+ - `ctx_ptr` 须指向有效的 `TradeContext`；
+ - `cfg_ptr` 须指向有效的 `MarketConfig`；
+ - `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_price_from_context(const TradeContext *ctx_ptr,
+                                 const MarketConfig *cfg_ptr,
+                                 double tau,
+                                 double lambda,
+                                 double hist_avg,
+                                 double *out_result);
+
+/*
+ 设置新手卖出加成：游戏时长低于 `threshold_secs` 的玩家经 `ecobridge_price_from_context` 卖出时，
+ 价格乘以随时长线性衰减的倍率 (0 时长为 `factor`，达到阈值时为 1.0)
+
+ 新手身份与环境因子的新手保护一致，仅由 `play_time_seconds` 判定；`newbie_mask` 的 bit1 为节庆标记，不参与判定。
+ `threshold_secs` 须非负 (0 关闭)，`factor` 须位于 [1.0, 2.0]，否则返回 InvalidValue。
+ */
+int ecobridge_set_newbie_sell_bonus(long long threshold_secs,
+                                    double factor);
+
+/*
+ 同 `ecobridge_compute_price_humane`，卖出时 lambda 的缩放系数由调用方指定 (默认 0.6)
+
+ `sell_sensitivity_ratio` 须位于 (0, 2]，否则返回 InvalidValue。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_humane_ex(double base,
+                                      double n_eff,
+                                      double trade_amount,
+                                      double lambda,
+                                      double epsilon,
+                                      double sell_sensitivity_ratio,
+                                      double *out_result);
+
+/*
+ 同 `ecobridge_compute_price_bounded`，卖出时 lambda 的缩放系数由调用方指定 (默认 0.6)
+
+ `sell_sensitivity_ratio` 须位于 (0, 2]，否则返回 InvalidValue。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_bounded_ex(double base,
+                                       double n_eff,
+                                       double amt,
+                                       double lambda,
+                                       double eps,
+                                       double hist_avg,
+                                       long long min_price_micros,
+                                       double sell_sensitivity_ratio,
+                                       double *out_result);
+
+/*
+ 影子定价 A/B 对照：同一输入下分别按线上与候选 lambda 计算带底价保护的价格
+
+ 不影响实际价格，供运营记录 "live vs shadow" 偏差以决定是否采纳新配置。
+
+ # Safety
+
+ `out_live`、`out_shadow` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_ab(long long base_micros,
+                               double n_eff,
+                               long long amt_micros,
+                               double live_cfg_lambda,
+                               double shadow_cfg_lambda,
+                               double eps,
+                               double hist_avg,
+                               double *out_live,
+                               double *out_shadow);
+
+/*
+ 以数据库演算的权威 neff 定价 (不含本次交易冲击，带动态底价保护)
+
+ 供不维护热存储的只读副本 / 分析节点使用：neff 按 `tau` (天) 在数据库日志上衰减累积。
+ 数据库未初始化返回 InternalError；`tau` 非正或非有限返回 InvalidValue。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_price_db_neff(long long base_micros,
+                                    long long current_ts,
+                                    double tau,
+                                    double lambda,
+                                    double eps,
+                                    double hist_avg,
+                                    double *out_result);
+
+/*
+ 预估假想交易的市场冲击：成交后即时价格与价格恢复到交易前水平 (1% 以内) 所需时长 (ms)
+
+ `amount` 为物品件数 (正数为卖出)，`tau` 为 neff 衰减常数 (天)。
+ 参数非有限值、`tau <= 0` 或 `lambda < 0` 时返回 InvalidValue；全服冻结时返回 Frozen 且不写入结果。
+
+ # Safety
+
+ `out_new_price` 须指向可写的 `c_double`；`out_recovery_ms` 须指向可写的 `c_longlong`。
+ */
+int ecobridge_project_trade_impact(double current_neff,
+                                   double amount,
+                                   double lambda,
+                                   double tau,
+                                   double *out_new_price,
+                                   long long *out_recovery_ms,
+                                   long long base_micros,
+                                   double eps);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calc_inflation(double current_heat, double m1, double *out_result);
+
+/*
+ 反解目标通胀率所需的 M1 供应量 (`m1 = heat / target_inflation`)
+
+ 目标须非 0 且位于 [-0.15, 0.45]；不存在有效 (> 1.0) 的 M1 时同样返回 InvalidValue。
+
+ # Safety
+
+ `out_m1` 须指向可写的 `c_double`。
+ */
+int ecobridge_required_m1(double current_heat,
+                          double target_inflation,
+                          double *out_m1);
+
+/*
+ 平滑通胀率：以 tanh 软饱和渐近逼近 [-0.15, 0.45]，`smoothing` 须为正的有限值
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calc_inflation_smooth(double current_heat,
+                                    double m1,
+                                    double smoothing,
+                                    double *out_result);
+
+/*
+ 市场稳定性因子，使用默认恢复窗口 `DEFAULT_RECOVERY_WINDOW_MS`
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calc_stability(long long last_ts, long long curr_ts, double *out_result);
+
+/*
+ 市场稳定性因子，自定义恢复窗口 `recovery_window_ms` (须为正的有限值)
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calc_stability_with_window(long long last_ts,
+                                         long long curr_ts,
+                                         double recovery_window_ms,
+                                         double *out_result);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calc_decay(double heat, double rate, double *out_result);
+
+/*
+ 按模式计算热度衰减量 (0 = 线性, 1 = 指数复利)
+
+ `rate` 须位于 [0, 1]，`cycles_per_day` 须为正的有限值。
+
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calc_decay_ex(double heat,
+                            double rate,
+                            double cycles_per_day,
+                            int mode,
+                            double *out_result);
+
+/*
+ # Safety
+
+ `out_total`、`out_dropped` 须指向可写的 `u64`。
+ */
+int ecobridge_get_health_stats(uint64_t *out_total, uint64_t *out_dropped);
+
+/*
+ 写入线程心跳：最近一次批次刷盘完成的 Unix 时间戳 (ms)，尚未刷盘时返回 0
+
+ 写入线程仅在有日志时刷盘；若 `total_logs` 持续增长而该值长时间未推进，
+ 说明写入线程已停滞或崩溃，队列将被填满并开始丢弃日志。
+ */
+long long ecobridge_writer_last_flush_ms(void);
+
+/*
+ 监控采集：单次调用汇总日志健康度、全局 neff 与平均交易年龄
+
+ 只读快照，不消费远程流量累加器 (待消费量通过 `remote_pending_micros` 单独上报)。
+
+ # Safety
+
+ `out` 须指向可写的 `MonitoringSnapshot`。
+ */
+int ecobridge_monitoring_poll(long long current_ts,
+                              double tau,
+                              MonitoringSnapshot *out);
+
+/*
+ 设置按月季节振幅表 (12 个 f64，索引 0 = 一月)
+
+ 月份由 `TradeContext::current_timestamp` 结合时区偏移推算。
+ 传入空指针清除该表，回退到 `MarketConfig::seasonal_amplitude` 常量。
+
+ # Safety
+
+ `ptr` 为空或指向 12 个连续的 `c_double`。
+ */
+int ecobridge_set_monthly_amplitude(const double *ptr);
+
+/*
+ 设置季节复合波的日/周/月周期 (秒) 与权重，适配自定义游戏内日历 (如 20 分钟一天)
+
+ 默认为现实世界的 86400 / 604800 / 2592000 秒与 0.6 / 0.3 / 0.1 权重；周末与月份判定不受影响。
+ 任一周期非正或非有限、任一权重非有限返回 InvalidValue。
+ */
+int ecobridge_set_seasonal_config(double day_secs,
+                                  double week_secs,
+                                  double month_secs,
+                                  double day_w,
+                                  double week_w,
+                                  double month_w);
+
+/*
+ 添加节假日区间 [start_day, end_day] (含两端，单位为自 Unix 纪元起的天数)
+
+ 区间内的本地自然日按周末定价 (`MarketConfig::weekend_multiplier`)；重叠区间自动合并。
+ `start_day > end_day` 返回 InvalidValue。
+ */
+int ecobridge_add_holiday(long long start_day,
+                          long long end_day);
+
+/*
+ 清空节假日表，周末因子回退到纯星期几判定
+ */
+int ecobridge_clear_holidays(void);
+
+/*
+ # Safety
+
+ - `ctx_ptr` 须指向有效的 `TradeContext`；
+ - `cfg_ptr` 须指向有效的 `MarketConfig`；
+ - `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_calculate_epsilon(const TradeContext *ctx_ptr,
+                                const MarketConfig *cfg_ptr,
+                                double *out_result);
+
+/*
+ 全服经济冻结开关 (事故应急)：非 0 = 冻结，0 = 解除
+
+ 冻结期间交易审计、拦截预校验与购物车计税一律以 `CODE_FROZEN` 拦截，定价类接口返回 `EconStatus::Frozen`；
+ 日志写入与只读查询照常进行，便于事后审计。
+ */
+int ecobridge_set_frozen(int frozen);
+
+/*
+ 查询当前是否处于冻结状态：1 = 冻结，0 = 正常
+ */
+int ecobridge_is_frozen(void);
+
+/*
+ # Safety
+
+ - `out_result` 须指向可写的 `TransferResult`；
+ - `ctx_ptr` 须指向有效的 `TransferContext`；
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`。
+ */
+int ecobridge_compute_transfer_check(TransferResult *out_result,
+                                     const TransferContext *ctx_ptr,
+                                     const RegulatorConfig *cfg_ptr);
+
+/*
+ 购物车合并计税：`count` 件商品按总额统一计税，输出单一放行/拦截结论
+
+ 拦截与预警逐行判定 (同一物品须由调用方合并为一行)，任一物品被拦截则整单拦截；余额不足、违规注资与奢侈税、贫富调节税阈值作用于总额。
+ 行为速率取各件最大值 (一次结账视为单次行为)，余额与游戏时长取自首件。`count` 上限 1,024，0 时放行且税额为 0。
+
+ # Safety
+
+ - `items_ptr` 须指向至少 `count` 个连续的 `TransferContext`；
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `out_result` 须指向可写的 `TransferResult`。
+ */
+int ecobridge_compute_cart_tax(const TransferContext *items_ptr,
+                               uint64_t count,
+                               const RegulatorConfig *cfg_ptr,
+                               TransferResult *out_result);
+
+/*
+ 税费预览：仅输出常规审计的 `final_tax_micros` (被拦截时为 0)
+
+ 不修改任何全局状态 (不记录流转、不更新计数器)，可供 UI 滑块等场景反复调用。
+
+ # Safety
+
+ - `ctx_ptr` 须指向有效的 `TransferContext`；
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `out_ptr` 须指向可写的 `c_longlong`。
+ */
+int ecobridge_preview_transfer_tax(const TransferContext *ctx_ptr,
+                                   const RegulatorConfig *cfg_ptr,
+                                   long long *out_ptr);
+
+/*
+ 购买报价：一次调用完成定价与交易审计，减少结账流程的 FFI 往返
+
+ 单价由 `compute_price_bounded_internal` 演算 (交易数量取 `ctx.current_amount`，
+ 环境因子由 `mcfg` 计算)，总价 = 单价 × |数量|。审计沿用 `tctx`，
+ 但 `amount_micros` 以本次总价覆盖，调用方无需预先填写。
+
+ # Safety
+
+ - `ctx_ptr` 须指向有效的 `TradeContext`；
+ - `tctx_ptr` 须指向有效的 `TransferContext`；
+ - `mcfg_ptr` 须指向有效的 `MarketConfig`；
+ - `rcfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `out_ptr` 须指向可写的 `PurchaseQuote`。
+ */
+int ecobridge_quote_purchase(const TradeContext *ctx_ptr,
+                             const TransferContext *tctx_ptr,
+                             const MarketConfig *mcfg_ptr,
+                             const RegulatorConfig *rcfg_ptr,
+                             double neff,
+                             double lambda,
+                             double hist_avg,
+                             PurchaseQuote *out_ptr);
+
+/*
+ 带账户身份的交易审计 (含逆向流转检测)
+
+ 在 `ecobridge_compute_transfer_check` 的基础上，拦截低活跃度账户在检测窗口内
+ 向原发送者回转资金的行为 (`CODE_BLOCK_REVERSE_FLOW`)。放行的转账会被记录。
+
+ # Safety
+
+ - `out_result` 须指向可写的 `TransferResult`；
+ - `ctx_ptr` 须指向有效的 `TransferContext`；
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `sender_uuid_ptr`、`receiver_uuid_ptr` 须为以 NUL 结尾的有效 C 字符串。
+ */
+int ecobridge_compute_transfer_check_tracked(TransferResult *out_result,
+                                             const TransferContext *ctx_ptr,
+                                             const RegulatorConfig *cfg_ptr,
+                                             const char *sender_uuid_ptr,
+                                             const char *receiver_uuid_ptr,
+                                             long long now_ms);
+
+/*
+ 设置行为惩罚税的指数输入上限 (`sender_velocity * 0.05` 的最大值，默认 2.0)
+ */
+int ecobridge_set_penalty_exponent_cap(double cap);
+
+/*
+ 设置累进税率表 (平行数组：起征点 Micros 与边际税率)，设置后以累进税替代单一阈值的奢侈税
+
+ 起征点须非负且严格递增，边际税率须位于 [0, 1]，否则返回 InvalidValue；
+ `count` 为 0 时清空税率表 (恢复奢侈税阈值)，超过 `MAX_TAX_BRACKETS` 返回 InvalidLength。
+
+ # Safety
+
+ `thresholds_micros` 须指向至少 `count` 个连续的 `c_longlong`；`marginal_rates` 须指向至少 `count` 个连续的 `c_double`。
+ */
+int ecobridge_set_tax_brackets(const long long *thresholds_micros,
+                               const double *marginal_rates,
+                               uint64_t count);
+
+/*
+ 设置逆向流转检测窗口 (ms，须为正数，默认 300,000)
+ */
+int ecobridge_set_reverse_flow_window(long long window_ms);
+
+/*
+ 令牌桶限流：为玩家 `uuid_ptr` 获取 1 个转账令牌
+
+ 桶容量 `burst` (>= 1)，以 `rate_per_min` (>= 0) 个/分钟补充。`out_allowed` 写入 1 表示放行、
+ 0 表示应拦截本次转账。参数非法或 uuid 为空返回 InvalidValue。
+
+ # Safety
+
+ `uuid_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_allowed` 须指向可写的 `c_int`。
+ */
+int ecobridge_ratelimit_try_acquire(const char *uuid_ptr,
+                                    long long now_ms,
+                                    double rate_per_min,
+                                    double burst,
+                                    int *out_allowed);
+
+/*
+ 仅执行拦截判定的轻量预校验 (不计算税额)
+
+ `out_block_code` 写入首个命中的拦截码，放行时为 0；结果与 `ecobridge_compute_transfer_check` 一致。
+
+ # Safety
+
+ - `ctx_ptr` 须指向有效的 `TransferContext`；
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `out_block_code` 须指向可写的 `c_int`。
+ */
+int ecobridge_would_block(const TransferContext *ctx_ptr,
+                          const RegulatorConfig *cfg_ptr,
+                          int *out_block_code);
+
+/*
+ 带账户身份的拦截预校验 (含逆向流转检测，不计算税额)
+
+ 结果与 `ecobridge_compute_transfer_check_tracked` 的拦截码一致，但不记录流转，可供 UI 反复调用。
+
+ # Safety
+
+ - `ctx_ptr` 须指向有效的 `TransferContext`；
+ - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+ - `sender_uuid_ptr`、`receiver_uuid_ptr` 须为以 NUL 结尾的有效 C 字符串；
+ - `out_block_code` 须指向可写的 `c_int`。
+ */
+int ecobridge_would_block_tracked(const TransferContext *ctx_ptr,
+                                  const RegulatorConfig *cfg_ptr,
+                                  const char *sender_uuid_ptr,
+                                  const char *receiver_uuid_ptr,
+                                  long long now_ms,
+                                  int *out_block_code);
+
+/*
+ # Safety
+
+ `out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_get_dynamic_limit(long long play_time_secs,
+                                double base,
+                                double rate,
+                                double max,
                                 double *out_result);
 
+/*
+ # Safety
+
+ `pid_ptr` 须指向有效且可写的 `PidState`；`out_result` 须指向可写的 `c_double`。
+ */
 int ecobridge_compute_pid_adjustment(PidState *pid_ptr,
                                      double target,
                                      double current,
@@ -218,6 +1812,67 @@ int ecobridge_compute_pid_adjustment(PidState *pid_ptr,
                                      double market_heat,
                                      double *out_result);
 
+/*
+ 带前馈补偿的 PID 调节：`feedforward` 在限幅前叠加到输出，用于提前抵消已知扰动
+
+ `feedforward = 0` 时与 `ecobridge_compute_pid_adjustment` 等价；非有限值时输出基准值 1.0。
+
+ # Safety
+
+ `pid_ptr` 须指向有效且可写的 `PidState`；`out_result` 须指向可写的 `c_double`。
+ */
+int ecobridge_compute_pid_adjustment_ff(PidState *pid_ptr,
+                                        double target,
+                                        double current,
+                                        double dt,
+                                        double inflation,
+                                        double market_heat,
+                                        double feedforward,
+                                        double *out_result);
+
+/*
+ 设置 PID 恐慌抑制参数：微分超过 `threshold` 时 D 项放大 `damping` 倍 (0 表示使用默认值 50.0 / 1.8)
+
+ 任一参数为负或非有限值时返回 InvalidValue 且不修改状态。
+
+ # Safety
+
+ `pid_ptr` 须指向有效且可写的 `PidState`。
+ */
+int ecobridge_set_pid_panic_params(PidState *pid_ptr,
+                                   double threshold,
+                                   double damping);
+
+/*
+ 设置 PID 每个控制周期的积分保留系数 `decay` ∈ [0, 1] (0 表示使用默认值 0.99999)
+
+ 超出范围或非有限值时返回 InvalidValue 且不修改状态。
+
+ # Safety
+
+ `pid_ptr` 须指向有效且可写的 `PidState`。
+ */
+int ecobridge_set_pid_integral_decay(PidState *pid_ptr,
+                                     double decay);
+
+/*
+ 设置 PID 输出倍率限幅区间 [min, max] (两端同为 0 表示使用默认值 [0.5, 5.0])
+
+ 区间非法 (非有限、为负或下限不小于上限) 时返回 InvalidValue 且不修改状态。
+
+ # Safety
+
+ `pid_ptr` 须指向有效且可写的 `PidState`。
+ */
+int ecobridge_set_pid_output_clamp(PidState *pid_ptr,
+                                   double min,
+                                   double max);
+
+/*
+ # Safety
+
+ `pid_ptr` 须指向有效且可写的 `PidState`。
+ */
 int ecobridge_reset_pid_state(PidState *pid_ptr);
 
 #endif  /* ECOBRIDGE_RUST_H */
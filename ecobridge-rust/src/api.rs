@@ -0,0 +1,111 @@
+// ==================================================
+// FILE: ecobridge-rust/src/api.rs
+// ==================================================
+
+//! Safe Rust API
+//!
+//! 面向 Rust 调用方 (集成测试、模拟器) 的安全封装：直接调用内部演算函数，
+//! 不经过 FFI 的裸指针与状态码编组。价格类参数以标准单位 (f64) 传入，内部转换为 Micros。
+
+use crate::economy::{environment, pricing, summation};
+use crate::models::{to_micros_saturating, MarketConfig, RegulatorConfig, TradeContext, TransferContext, TransferResult};
+use crate::security::regulator;
+
+/// 最终价格演算 (不含本次交易数量冲击与底价保护)
+pub fn compute_price(base: f64, neff: f64, lambda: f64, eps: f64) -> f64 {
+    pricing::compute_price_final_internal(to_micros_saturating(base), neff, lambda, eps)
+}
+
+/// 带底价保护的价格演算
+/// @param amount 本次交易的物品件数：正数为卖出，负数为买入
+/// @param hist_avg 物品历史均价，用于计算动态地板价 (0 关闭)
+/// @param min_price 物品硬底价；<= 0 时使用全局 `MIN_PHYSICAL_PRICE`
+pub fn compute_price_bounded(
+    base: f64, neff: f64, amount: f64, lambda: f64, eps: f64, hist_avg: f64, min_price: f64,
+) -> f64 {
+    pricing::compute_price_bounded_internal(
+        to_micros_saturating(base), neff, to_micros_saturating(amount), lambda, eps, hist_avg,
+        to_micros_saturating(min_price),
+    )
+}
+
+/// 环境因子 (ε) 演算
+pub fn calculate_epsilon(ctx: &TradeContext, cfg: &MarketConfig) -> f64 {
+    environment::calculate_epsilon_internal(ctx, cfg)
+}
+
+/// 批量定价 (共享 neff)，与 `ecobridge_compute_batch_prices` 结果一致
+/// @return 各切片长度不一致时返回 None
+pub fn compute_batch_prices(
+    neff: f64,
+    ctxs: &[TradeContext],
+    cfgs: &[MarketConfig],
+    hist_avgs: &[f64],
+    lambdas: &[f64],
+) -> Option<Vec<f64>> {
+    let count = ctxs.len();
+    if cfgs.len() != count || hist_avgs.len() != count || lambdas.len() != count {
+        return None;
+    }
+    let mut out = vec![0.0; count];
+    // SAFETY: 各指针均来自长度为 count 的切片
+    unsafe {
+        pricing::compute_batch_prices_internal(
+            count, neff, ctxs.as_ptr(), cfgs.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), out.as_mut_ptr(),
+        );
+    }
+    Some(out)
+}
+
+/// 交易审计 (不含逆向流转检测，无副作用)
+pub fn check_transfer(ctx: &TransferContext, cfg: &RegulatorConfig) -> TransferResult {
+    regulator::compute_transfer_check_internal(ctx, cfg)
+}
+
+/// 记录一笔成交到 `market_key` 对应的热存储，并同步计入全局聚合
+/// @return 数量超出 i64 Micros 可表示范围时返回 false 且不写入
+pub fn record_trade(ts: i64, amount: f64, market_key: &str) -> bool {
+    summation::append_trade_to_memory(ts, amount, market_key)
+}
+
+/// 全局有效供应量 (含带时间戳远程流量，不含、也不消费远程流量累加器)
+pub fn query_neff(current_ts: i64, tau: f64) -> f64 {
+    summation::query_neff_with_remote_internal(current_ts, tau)
+}
+
+// ==================== 单元测试 ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_matches_internal_functions() {
+        let base_micros = 10_000_000;
+        assert_eq!(compute_price(10.0, 50.0, 0.01, 1.2), pricing::compute_price_final_internal(base_micros, 50.0, 0.01, 1.2));
+        assert_eq!(
+            compute_price_bounded(10.0, 50.0, -2.0, 0.01, 1.2, 8.0, 3.0),
+            pricing::compute_price_bounded_internal(base_micros, 50.0, -2_000_000, 0.01, 1.2, 8.0, 3_000_000)
+        );
+
+        let tctx = TransferContext { amount_micros: 200_000_000_000, sender_activity_score: 1.0, ..Default::default() };
+        let rcfg = RegulatorConfig::default();
+        assert_eq!(check_transfer(&tctx, &rcfg).final_tax_micros, regulator::compute_transfer_check_internal(&tctx, &rcfg).final_tax_micros);
+    }
+
+    #[test]
+    fn test_batch_prices_rejects_length_mismatch() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let ctxs = [TradeContext { base_price_micros: 10_000_000, ..Default::default() }; 3];
+        let cfgs = [MarketConfig::default(); 3];
+
+        assert_eq!(compute_batch_prices(0.0, &ctxs, &cfgs, &[10.0; 3], &[0.01; 2]), None);
+        assert_eq!(compute_batch_prices(0.0, &[], &[], &[], &[]), Some(Vec::new()));
+
+        let prices = compute_batch_prices(0.0, &ctxs, &cfgs, &[10.0; 3], &[0.01; 3]).unwrap();
+        let eps = calculate_epsilon(&ctxs[0], &cfgs[0]);
+        for price in prices {
+            assert!((price - compute_price_bounded(10.0, 0.0, 0.0, 0.01, eps, 10.0, 0.0)).abs() < 1e-9);
+        }
+    }
+}
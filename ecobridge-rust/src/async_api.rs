@@ -0,0 +1,68 @@
+// ==================================================
+// FILE: ecobridge-rust/src/async_api.rs
+// ==================================================
+
+//! Async Rust API (`async` feature)
+//!
+//! 面向 Tokio 运行时中的 Rust 调用方：DuckDB 读连接池的查询会阻塞线程 (含等待空闲连接)，
+//! 此处经 `tokio::task::spawn_blocking` 转移到阻塞线程池执行，避免占用运行时工作线程。
+//! 同步 API 与 FFI 路径不受影响。
+//!
+//! 查询任务 panic 时返回 `JoinError`，由调用方决定重试或上报。
+
+use crate::models::DaySummary;
+use crate::storage;
+use tokio::task::{self, JoinError};
+
+/// 从数据库日志查询全局有效供应量 (`storage::query_neff_from_db` 的异步版本)
+/// @return 数据库未初始化时为 0
+pub async fn query_neff_async(current_ts: i64, tau: f64) -> Result<f64, JoinError> {
+    task::spawn_blocking(move || storage::query_neff_from_db(current_ts, tau)).await
+}
+
+/// 按 UTC 自然日汇总 [start_ts, end_ts) 的经济日志 (`storage::daily_summary` 的异步版本)
+/// @return 区间为空或数据库未初始化时为空列表；内层 Err 为查询或行解码失败
+pub async fn daily_summary_async(
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Result<Vec<DaySummary>, duckdb::Error>, JoinError> {
+    task::spawn_blocking(move || storage::daily_summary(start_ts, end_ts)).await
+}
+
+// ==================== 单元测试 ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_async_queries_match_sync() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let conn = storage::test_read_pool_conn();
+        let day = 86_400_000;
+        let now = 1_000 * day;
+        let rows: [(i64, &str, f64); 4] = [
+            (now - 2 * day, "a", 30.0),
+            (now - 2 * day + 1_000, "b", -12.5),
+            (now - day, "a", 40.0),
+            (now - 1_000, "c", 5.0),
+        ];
+        for (ts, uuid, delta) in rows {
+            conn.execute("INSERT INTO economy_log VALUES (?, ?, ?, 0.0, '')", duckdb::params![ts, uuid, delta]).unwrap();
+        }
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        let neff = runtime.block_on(query_neff_async(now, 7.0)).unwrap();
+        assert!(neff > 0.0, "neff={}", neff);
+        assert_eq!(neff, storage::query_neff_from_db(now, 7.0));
+
+        let days = runtime.block_on(daily_summary_async(now - 3 * day, now)).unwrap().unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!((days[0].event_count, days[0].distinct_players), (2, 2));
+        assert_eq!(days[0].total_abs_delta_micros, 42_500_000);
+        assert_eq!(days, storage::daily_summary(now - 3 * day, now).unwrap());
+        assert!(runtime.block_on(daily_summary_async(now, now)).unwrap().unwrap().is_empty());
+
+        conn.execute_batch("DELETE FROM economy_log").unwrap();
+    }
+}
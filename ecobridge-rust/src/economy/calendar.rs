@@ -0,0 +1,116 @@
+// ==================================================
+// FILE: ecobridge-rust/src/economy/calendar.rs
+// ==================================================
+
+//! Holiday Calendar
+//!
+//! 区域性节假日表：落在节假日区间内的本地自然日按周末定价 (与周六、周日等同)。
+//! 未配置节假日时周末因子仅由星期几决定。
+//!
+//! 日期以自 Unix 纪元起的天数 (`floor(ts_local_ms / 86_400_000)`) 表示，区间为闭区间。
+
+use std::sync::RwLock;
+
+/// 节假日区间表：按起始日升序排列，插入时合并重叠与相邻区间，保证区间互不相交
+static HOLIDAYS: RwLock<Vec<(i64, i64)>> = RwLock::new(Vec::new());
+
+/// 添加节假日区间 [start_day, end_day] (含两端)
+/// @return `start_day > end_day` 时返回 false 且不修改当前配置
+pub fn add_holiday(start_day: i64, end_day: i64) -> bool {
+    if start_day > end_day {
+        return false;
+    }
+    let mut holidays = HOLIDAYS.write().unwrap_or_else(|e| e.into_inner());
+    insert_merged(&mut holidays, (start_day, end_day));
+    true
+}
+
+/// 清空节假日表，回退到纯星期几判定
+pub fn clear_holidays() {
+    HOLIDAYS.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// 本地自然日是否落在任一节假日区间内
+pub fn is_holiday(day_index: i64) -> bool {
+    let holidays = HOLIDAYS.read().unwrap_or_else(|e| e.into_inner());
+    contains_day(&holidays, day_index)
+}
+
+/// 恢复默认状态 (清空节假日表)
+pub fn reset_state() {
+    clear_holidays();
+}
+
+fn insert_merged(ranges: &mut Vec<(i64, i64)>, (mut start, mut end): (i64, i64)) {
+    // 与新区间重叠或相邻的已有区间为 [lo, hi)
+    let lo = ranges.partition_point(|&(_, e)| e.saturating_add(1) < start);
+    let hi = ranges.partition_point(|&(s, _)| s <= end.saturating_add(1));
+    if lo < hi {
+        start = start.min(ranges[lo].0);
+        end = end.max(ranges[hi - 1].1);
+    }
+    ranges.splice(lo..hi, std::iter::once((start, end)));
+}
+
+fn contains_day(ranges: &[(i64, i64)], day_index: i64) -> bool {
+    let idx = ranges.partition_point(|&(s, _)| s <= day_index);
+    idx > 0 && ranges[idx - 1].1 >= day_index
+}
+
+// ==================== 单元测试 ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::environment::calculate_epsilon_internal;
+    use crate::models::{MarketConfig, TradeContext};
+
+    #[test]
+    fn test_insert_merges_overlapping_and_adjacent_ranges() {
+        let mut ranges = Vec::new();
+        insert_merged(&mut ranges, (10, 12));
+        insert_merged(&mut ranges, (20, 22));
+        insert_merged(&mut ranges, (1, 2));
+        assert_eq!(ranges, vec![(1, 2), (10, 12), (20, 22)]);
+
+        // 与 (10, 12) 相邻、与 (20, 22) 重叠
+        insert_merged(&mut ranges, (13, 20));
+        assert_eq!(ranges, vec![(1, 2), (10, 22)]);
+
+        assert!(contains_day(&ranges, 10));
+        assert!(contains_day(&ranges, 22));
+        assert!(!contains_day(&ranges, 5));
+        assert!(!contains_day(&ranges, 23));
+        assert!(!contains_day(&ranges, 0));
+    }
+
+    #[test]
+    fn test_holiday_triggers_weekend_factor() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let cfg = MarketConfig {
+            weekend_multiplier: 1.5,
+            weekend_weight: 1.0,
+            // 隔离其它变量
+            seasonal_weight: 0.0, newbie_weight: 0.0, inflation_weight: 0.0,
+            volatility_factor: 1.0,
+            ..Default::default()
+        };
+        let wednesday = 1_736_899_200_000; // 2025-01-15 (周三) 00:00 UTC
+        let day = wednesday / 86_400_000;
+        let ctx = TradeContext { current_timestamp: wednesday + 12 * 3_600_000, ..Default::default() };
+
+        assert!((calculate_epsilon_internal(&ctx, &cfg) - 1.0).abs() < 1e-12);
+
+        assert!(!add_holiday(day + 1, day));
+        assert!(add_holiday(day, day));
+        assert!((calculate_epsilon_internal(&ctx, &cfg) - 1.5).abs() < 1e-12);
+
+        // 节假日按本地日期判定：UTC-13h 时本地仍为前一天
+        let shifted = TradeContext { timezone_offset: -13 * 3600, ..ctx };
+        assert!((calculate_epsilon_internal(&shifted, &cfg) - 1.0).abs() < 1e-12);
+
+        reset_state();
+        assert!((calculate_epsilon_internal(&ctx, &cfg) - 1.0).abs() < 1e-12);
+    }
+}
@@ -16,27 +16,27 @@
 //! 2. **Anti-Windup**: 采用 Back-calculation 算法防止积分饱和。
 //! 3. **Panic Damping**: 监测二阶导数（加速度），在市场恐慌时强制阻尼。
 
-use crate::models::PidState;
+use crate::models::{PidState, PID_DERIVATIVE_ON_ERROR};
 
 // ==================== 基础物理常量 ====================
 
 pub const DEFAULT_INTEGRATION_LIMIT: f64 = 30.0;
 pub const MAX_SAFE_DT: f64 = 1.0;
 pub const MIN_TIME_STEP: f64 = 1e-6;
-pub const OUTPUT_MIN_CLAMP: f64 = 0.5;
-pub const OUTPUT_MAX_CLAMP: f64 = 5.0;
+pub const OUTPUT_MIN_CLAMP: f64 = 0.5;    // 输出倍率下限 (PidState::output_min/output_max 同为 0 时使用)
+pub const OUTPUT_MAX_CLAMP: f64 = 5.0;    // 输出倍率上限 (同上)
 pub const OUTPUT_BASELINE: f64 = 1.0;
 
 // ==================== 稳定性与滤波常量 ====================
 
-pub const INTEGRAL_DECAY: f64 = 0.99999;
+pub const INTEGRAL_DECAY: f64 = 0.99999;  // 每周期积分保留系数 (PidState::integral_decay 为 0 时使用)
 pub const BACK_CALC_GAIN: f64 = 0.2;
 pub const DERIVATIVE_FILTER_ALPHA: f64 = 0.3;
 
 // ==================== 行为经济学：宏观调控常量 ====================
 
-pub const PANIC_THRESHOLD: f64 = 50.0;     // 触发恐慌抑制的加速度阈值
-pub const PANIC_DAMPING: f64 = 1.8;       // 恐慌状态下的微分项放大倍数
+pub const PANIC_THRESHOLD: f64 = 50.0;     // 触发恐慌抑制的加速度阈值 (PidState::panic_threshold 为 0 时使用)
+pub const PANIC_DAMPING: f64 = 1.8;       // 恐慌状态下的微分项放大倍数 (PidState::panic_damping 为 0 时使用)
 pub const HEAT_SENSITIVITY: f64 = 0.5;    // 财富流速感应灵敏度
 
 #[inline]
@@ -73,11 +73,28 @@ pub fn compute_pid_adjustment_internal(
     dt: f64,
     inflation: f64,
     market_heat: f64,
+) -> f64 {
+    compute_pid_adjustment_ff_internal(pid, target_vel, current_vel, dt, inflation, market_heat, 0.0)
+}
+
+/// 带前馈补偿的宏观调控步进计算
+///
+/// `feedforward` 为已知扰动 (如预定的双倍经验周末) 的补偿量，在限幅前直接叠加到输出上，
+/// 使控制器无需等待误差积累即可提前响应。限幅与饱和判定均包含前馈贡献。
+pub fn compute_pid_adjustment_ff_internal(
+    pid: &mut PidState,
+    target_vel: f64,
+    current_vel: f64,
+    dt: f64,
+    inflation: f64,
+    market_heat: f64,
+    feedforward: f64,
 ) -> f64 {
     // 1. 输入参数严格校验
     if !target_vel.is_finite() || !current_vel.is_finite() 
        || !dt.is_finite() || dt < 0.0 
-       || !inflation.is_finite() || !market_heat.is_finite() {
+       || !inflation.is_finite() || !market_heat.is_finite()
+       || !feedforward.is_finite() {
         return OUTPUT_BASELINE;
     }
 
@@ -94,22 +111,31 @@ pub fn compute_pid_adjustment_internal(
     let active_ki = base_ki * schedule_gamma;
     
     // 4. 积分项处理 (Anti-windup & Leakage)
-    let combined_leakage = (1.0 - pid.lambda.clamp(0.0, 1.0)) * INTEGRAL_DECAY;
-    
-    if pid.is_saturated != 0 {
-        // 饱和状态引入反向回算 (Back-calculation)，加速退出锁定区
-        let back_calc = error * BACK_CALC_GAIN;
-        pid.integral = pid.integral.mul_add(combined_leakage, back_calc * dt_safe);
-    } else {
-        pid.integral = pid.integral.mul_add(combined_leakage, error * dt_safe);
+    // dt == 0 表示控制器暂停 (无真实时间流逝)：积分既不累积也不泄漏
+    if dt_safe > 0.0 {
+        let combined_leakage = (1.0 - pid.lambda.clamp(0.0, 1.0)) * effective_integral_decay(pid);
+
+        if pid.is_saturated != 0 {
+            // 饱和状态引入反向回算 (Back-calculation)，加速退出锁定区
+            let back_calc = error * BACK_CALC_GAIN;
+            pid.integral = pid.integral.mul_add(combined_leakage, back_calc * dt_safe);
+        } else {
+            pid.integral = pid.integral.mul_add(combined_leakage, error * dt_safe);
+        }
+
+        let limit = if pid.integration_limit > 0.0 { pid.integration_limit } else { DEFAULT_INTEGRATION_LIMIT };
+        pid.integral = pid.integral.clamp(-limit, limit);
     }
     
-    let limit = if pid.integration_limit > 0.0 { pid.integration_limit } else { DEFAULT_INTEGRATION_LIMIT };
-    pid.integral = pid.integral.clamp(-limit, limit);
-    
     // 5. 微分项处理 (滤波与加速度捕捉)
-    let delta_pv = current_vel - pid.prev_pv;
-    let raw_derivative = if dt_safe > MIN_TIME_STEP { delta_pv / dt_safe } else { 0.0 };
+    // 观测值求导避免设定值突变冲击；误差求导取 -Δerror，设定值不变时两者一致
+    // 误差求导的首步尚无上一次误差 (NaN)，以本次误差为起点，避免初始误差本身造成微分冲击
+    let delta = if pid.derivative_mode == PID_DERIVATIVE_ON_ERROR {
+        if pid.prev_error.is_nan() { 0.0 } else { -(error - pid.prev_error) }
+    } else {
+        current_vel - pid.prev_pv
+    };
+    let raw_derivative = if dt_safe > MIN_TIME_STEP { delta / dt_safe } else { 0.0 };
     
     // 低通滤波滤除瞬时噪声
     pid.filtered_d = DERIVATIVE_FILTER_ALPHA.mul_add(
@@ -117,10 +143,12 @@ pub fn compute_pid_adjustment_internal(
         (1.0 - DERIVATIVE_FILTER_ALPHA) * pid.filtered_d
     );
     pid.prev_pv = current_vel;
+    pid.prev_error = error;
 
     // 6. 恐慌抑制逻辑 (Panic Suppression)
-    let d_multiplier = if pid.filtered_d.abs() > PANIC_THRESHOLD {
-        PANIC_DAMPING
+    let (panic_threshold, panic_damping) = effective_panic_params(pid);
+    let d_multiplier = if pid.filtered_d.abs() > panic_threshold {
+        panic_damping
     } else {
         1.0
     };
@@ -130,8 +158,9 @@ pub fn compute_pid_adjustment_internal(
     let i_term = active_ki * pid.integral;
     let d_term = pid.kd * pid.filtered_d * d_multiplier; 
     
-    let raw_output = OUTPUT_BASELINE + p_term + i_term - d_term;
-    let final_output = raw_output.clamp(OUTPUT_MIN_CLAMP, OUTPUT_MAX_CLAMP);
+    let raw_output = OUTPUT_BASELINE + p_term + i_term - d_term + feedforward;
+    let (output_min, output_max) = effective_output_clamp(pid);
+    let final_output = raw_output.clamp(output_min, output_max);
     
     // 更新饱和状态标志 (对齐 models.rs 中的 c_int 类型)
     pid.is_saturated = if (raw_output - final_output).abs() > 1e-6 { 1 } else { 0 };
@@ -139,12 +168,83 @@ pub fn compute_pid_adjustment_internal(
     if final_output.is_finite() { final_output } else { OUTPUT_BASELINE }
 }
 
+/// 生效的恐慌阈值与阻尼倍数：字段为 0 (未配置) 时回退到编译期默认值
+fn effective_panic_params(pid: &PidState) -> (f64, f64) {
+    let threshold = if pid.panic_threshold > 0.0 { pid.panic_threshold } else { PANIC_THRESHOLD };
+    let damping = if pid.panic_damping > 0.0 { pid.panic_damping } else { PANIC_DAMPING };
+    (threshold, damping)
+}
+
+/// 生效的输出限幅区间：两端同为 0 (未配置) 或区间非法时回退到编译期默认值
+fn effective_output_clamp(pid: &PidState) -> (f64, f64) {
+    if is_valid_output_clamp(pid.output_min, pid.output_max) {
+        (pid.output_min, pid.output_max)
+    } else {
+        (OUTPUT_MIN_CLAMP, OUTPUT_MAX_CLAMP)
+    }
+}
+
+/// 合法的自定义限幅区间：有限、非负且下限严格小于上限
+fn is_valid_output_clamp(min: f64, max: f64) -> bool {
+    min.is_finite() && max.is_finite() && min >= 0.0 && min < max
+}
+
+/// 可写入 `PidState` 的限幅区间：未配置 (两端同为 0) 或合法的自定义区间
+fn is_acceptable_output_clamp(min: f64, max: f64) -> bool {
+    (min == 0.0 && max == 0.0) || is_valid_output_clamp(min, max)
+}
+
+/// 设置输出倍率限幅区间 [min, max] (两端同为 0 表示使用默认值 [0.5, 5.0])
+///
+/// 饱和判定 (`is_saturated`) 同样以该区间为准。
+/// @return 区间非法 (非有限、为负或下限不小于上限) 时返回 false 且不修改状态
+pub fn set_output_clamp(pid: &mut PidState, min: f64, max: f64) -> bool {
+    if !is_acceptable_output_clamp(min, max) {
+        return false;
+    }
+    pid.output_min = min;
+    pid.output_max = max;
+    true
+}
+
+/// 生效的积分保留系数：字段为 0 (未配置) 或超出 (0, 1] 时回退到编译期默认值
+fn effective_integral_decay(pid: &PidState) -> f64 {
+    if pid.integral_decay > 0.0 && pid.integral_decay <= 1.0 { pid.integral_decay } else { INTEGRAL_DECAY }
+}
+
+/// 设置每个控制周期的积分保留系数 (0 表示使用默认值 0.99999)
+///
+/// 快节拍服务器应取较小值以免积分几乎不泄漏，慢节拍服务器取较接近 1 的值。
+/// @return 超出 [0, 1] 或非有限值时返回 false 且不修改状态
+pub fn set_integral_decay(pid: &mut PidState, decay: f64) -> bool {
+    if !(0.0..=1.0).contains(&decay) {
+        return false;
+    }
+    pid.integral_decay = decay;
+    true
+}
+
+/// 设置恐慌抑制参数 (0 表示使用默认值)
+/// @return 任一参数为负或非有限值时返回 false 且不修改状态
+pub fn set_panic_params(pid: &mut PidState, threshold: f64, damping: f64) -> bool {
+    if !threshold.is_finite() || threshold < 0.0 || !damping.is_finite() || damping < 0.0 {
+        return false;
+    }
+    pid.panic_threshold = threshold;
+    pid.panic_damping = damping;
+    true
+}
+
 /// 验证 PID 配置参数的合法性
 pub fn validate_pid_params(pid: &PidState) -> bool {
     pid.kp.is_finite() && pid.kp >= 0.0
         && pid.ki.is_finite() && pid.ki >= 0.0
         && pid.kd.is_finite() && pid.kd >= 0.0
         && pid.lambda.is_finite() && (0.0..=1.0).contains(&pid.lambda)
+        && pid.panic_threshold.is_finite() && pid.panic_threshold >= 0.0
+        && pid.panic_damping.is_finite() && pid.panic_damping >= 0.0
+        && (0.0..=1.0).contains(&pid.integral_decay)
+        && is_acceptable_output_clamp(pid.output_min, pid.output_max)
 }
 
 // ==================== 自动化回归测试 ====================
@@ -162,6 +262,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_anti_windup_mechanism() {
         let mut pid = PidState::default();
         pid.ki = 10.0;
@@ -173,6 +274,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_panic_damping_response() {
         let mut pid = PidState::default();
         pid.kd = 1.0;
@@ -180,5 +282,159 @@ mod tests {
         compute_pid_adjustment_internal(&mut pid, 10.0, 0.0, 0.1, 0.0, 1.0);
         let out = compute_pid_adjustment_internal(&mut pid, 10.0, 80.0, 0.1, 0.0, 1.0);
         assert!(out < OUTPUT_BASELINE, "恐慌状态下 D项应产生强力反向压制输出");
+
+        // 仅保留 D 项：输出 = 1 - kd · filtered_d · 倍数，其中 filtered_d = 0.3 · 80 / 0.1 = 240
+        let run = |threshold: f64, damping: f64| {
+            let mut pid = PidState { kp: 0.0, ki: 0.0, kd: 0.001, ..Default::default() };
+            assert!(set_panic_params(&mut pid, threshold, damping));
+            compute_pid_adjustment_internal(&mut pid, 10.0, 0.0, 0.1, 0.0, 0.0);
+            compute_pid_adjustment_internal(&mut pid, 10.0, 80.0, 0.1, 0.0, 0.0)
+        };
+        let undamped = 1.0 - 0.24;
+
+        // 未配置 (0) 时沿用默认阈值 50 与倍数 1.8
+        assert!((run(0.0, 0.0) - (1.0 - 0.24 * PANIC_DAMPING)).abs() < 1e-9);
+        // 阈值提高到 300：240 的微分不再触发阻尼
+        assert!((run(300.0, 0.0) - undamped).abs() < 1e-9);
+        // 阈值 200 触发，并使用自定义倍数
+        assert!((run(200.0, 2.0) - (1.0 - 0.24 * 2.0)).abs() < 1e-9);
+
+        let mut pid = PidState::default();
+        assert!(!set_panic_params(&mut pid, -1.0, 1.8));
+        assert!(!set_panic_params(&mut pid, 50.0, f64::NAN));
+        assert_eq!((pid.panic_threshold, pid.panic_damping), (PANIC_THRESHOLD, PANIC_DAMPING));
+    }
+
+    #[test]
+    fn test_zero_dt_preserves_integral() {
+        let mut pid = PidState { lambda: 0.1, integral: 5.0, ..Default::default() };
+        for _ in 0..100 {
+            compute_pid_adjustment_internal(&mut pid, 10.0, 5.0, 0.0, 0.0, 1.0);
+        }
+        assert_eq!(pid.integral, 5.0, "dt=0 不应对积分产生泄漏");
+
+        // 对照：真实时间流逝时泄漏照常生效
+        compute_pid_adjustment_internal(&mut pid, 5.0, 5.0, 0.1, 0.0, 1.0);
+        assert!(pid.integral < 5.0);
+    }
+
+    #[test]
+    fn test_integral_decay_leaks_over_known_cycles() {
+        // 误差恒为 0 且关闭 lambda 泄漏：每周期积分仅乘以保留系数
+        let run = |decay: f64, cycles: usize| {
+            let mut pid = PidState { lambda: 0.0, integral: 10.0, ..Default::default() };
+            assert!(set_integral_decay(&mut pid, decay));
+            for _ in 0..cycles {
+                compute_pid_adjustment_internal(&mut pid, 5.0, 5.0, 0.1, 0.0, 1.0);
+            }
+            pid.integral
+        };
+
+        // 0.9^100 ≈ 2.7e-5
+        assert!((run(0.9, 100) - 10.0 * 0.9f64.powi(100)).abs() < 1e-12);
+        assert!(run(0.9, 100) < 1e-3);
+        // 默认值 (0 = 0.99999) 下 100 个周期几乎不泄漏
+        assert!(run(0.0, 100) > 9.9);
+
+        let mut pid = PidState::default();
+        assert!(!set_integral_decay(&mut pid, 1.5));
+        assert!(!set_integral_decay(&mut pid, f64::NAN));
+        assert_eq!(pid.integral_decay, INTEGRAL_DECAY);
+    }
+
+    #[test]
+    fn test_custom_output_clamp_band() {
+        let mut pid = PidState { ki: 0.0, kd: 0.0, ..Default::default() };
+        assert!(set_output_clamp(&mut pid, 0.9, 1.1));
+
+        // 大正误差：默认区间内不饱和的输出被新上限钳位并标记饱和
+        let high = compute_pid_adjustment_internal(&mut pid, 10.0, 9.0, 0.1, 0.0, 0.0);
+        assert_eq!(high, 1.1);
+        assert_eq!(pid.is_saturated, 1);
+
+        let low = compute_pid_adjustment_internal(&mut pid, 9.0, 10.0, 0.1, 0.0, 0.0);
+        assert_eq!(low, 0.9);
+        assert_eq!(pid.is_saturated, 1);
+
+        // 区间内的输出不受影响
+        let inside = compute_pid_adjustment_internal(&mut pid, 10.0, 9.9, 0.1, 0.0, 0.0);
+        assert!(inside > 1.0 && inside < 1.1);
+        assert_eq!(pid.is_saturated, 0);
+
+        // 两端同为 0 时回退到默认区间
+        let mut unset = PidState { ki: 0.0, kd: 0.0, output_min: 0.0, output_max: 0.0, ..Default::default() };
+        let mut default = PidState { ki: 0.0, kd: 0.0, ..Default::default() };
+        assert_eq!(
+            compute_pid_adjustment_internal(&mut unset, 100.0, 0.0, 0.1, 0.0, 0.0),
+            compute_pid_adjustment_internal(&mut default, 100.0, 0.0, 0.1, 0.0, 0.0)
+        );
+        assert_eq!(unset.is_saturated, 1);
+        assert!(validate_pid_params(&unset));
+
+        assert!(!set_output_clamp(&mut pid, 1.1, 0.9));
+        assert!(!set_output_clamp(&mut pid, -1.0, 1.0));
+        assert!(!set_output_clamp(&mut pid, 0.5, f64::INFINITY));
+        assert_eq!((pid.output_min, pid.output_max), (0.9, 1.1));
+        assert!(set_output_clamp(&mut pid, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_setpoint_kick_only_in_error_mode() {
+        let base = PidState { kp: 0.0, ki: 0.0, kd: 0.05, lambda: 0.0, prev_pv: 5.0, ..Default::default() };
+        let step = |mode| {
+            let mut pid = PidState { derivative_mode: mode, ..base };
+            // 稳态：设定值与观测值均为 5.0
+            for _ in 0..20 {
+                compute_pid_adjustment_internal(&mut pid, 5.0, 5.0, 0.1, 0.0, 0.0);
+            }
+            // 设定值突变至 15.0，观测值不变
+            compute_pid_adjustment_internal(&mut pid, 15.0, 5.0, 0.1, 0.0, 0.0);
+            pid.filtered_d
+        };
+
+        assert_eq!(step(crate::models::PID_DERIVATIVE_ON_MEASUREMENT), 0.0);
+        assert!(step(PID_DERIVATIVE_ON_ERROR).abs() > 1.0);
+    }
+
+    #[test]
+    fn test_error_mode_first_step_has_no_derivative_kick() {
+        let mut pid = PidState { kp: 0.0, ki: 0.0, kd: 0.05, lambda: 0.0, derivative_mode: PID_DERIVATIVE_ON_ERROR, ..Default::default() };
+        assert!(pid.prev_error.is_nan());
+
+        // 首步即存在较大误差：微分项以其为起点，不产生冲击
+        compute_pid_adjustment_internal(&mut pid, 15.0, 5.0, 0.1, 0.0, 0.0);
+        assert_eq!(pid.filtered_d, 0.0);
+        assert_eq!(pid.prev_error, 10.0);
+
+        // 此后误差变化照常求导
+        compute_pid_adjustment_internal(&mut pid, 15.0, 10.0, 0.1, 0.0, 0.0);
+        assert!(pid.filtered_d > 0.0);
+    }
+
+    #[test]
+    fn test_feedforward_shifts_output_and_saturation() {
+        let base = PidState { kp: 0.1, ki: 0.01, kd: 0.0, lambda: 0.1, ..Default::default() };
+
+        // feedforward = 0 与原函数完全一致
+        let (mut a, mut b) = (base, base);
+        let plain = compute_pid_adjustment_internal(&mut a, 10.0, 9.0, 0.5, 0.02, 0.5);
+        let zero_ff = compute_pid_adjustment_ff_internal(&mut b, 10.0, 9.0, 0.5, 0.02, 0.5, 0.0);
+        assert_eq!(plain, zero_ff);
+        assert_eq!(a.integral, b.integral);
+
+        // 前馈量直接叠加在限幅前的输出上
+        let mut c = base;
+        let shifted = compute_pid_adjustment_ff_internal(&mut c, 10.0, 9.0, 0.5, 0.02, 0.5, 0.5);
+        assert!((shifted - (plain + 0.5)).abs() < 1e-12);
+        assert_eq!(c.is_saturated, 0);
+
+        // 前馈推动输出越过上限时仍被钳位，并计入饱和判定
+        let mut d = base;
+        let clamped = compute_pid_adjustment_ff_internal(&mut d, 10.0, 9.0, 0.5, 0.02, 0.5, 10.0);
+        assert_eq!(clamped, OUTPUT_MAX_CLAMP);
+        assert_eq!(d.is_saturated, 1);
+
+        let mut e = base;
+        assert_eq!(compute_pid_adjustment_ff_internal(&mut e, 10.0, 9.0, 0.5, 0.02, 0.5, f64::NAN), OUTPUT_BASELINE);
     }
 }
\ No newline at end of file
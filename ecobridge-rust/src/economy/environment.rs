@@ -11,15 +11,119 @@
 //! - [v1.6.0] 语义化对齐：适配 i64 定点数协议上下文，确保与 models.rs 兼容。
 //! - [v1.0.0] 引入渐进式新手保护模型（100小时线性衰减）。
 
-use crate::models::{TradeContext, MarketConfig};
+use crate::models::{TradeContext, MarketConfig, DEFAULT_LN_FLOOR};
+use crate::economy::calendar;
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
 
 // ==================== 时间常量 ====================
 const SECONDS_PER_DAY: f64 = 86400.0;
 const SECONDS_PER_WEEK: f64 = 604800.0;
 const SECONDS_PER_MONTH: f64 = 2592000.0;
 
+// ==================== 可调参数 ====================
+
+/// 季节复合波的周期 (秒) 与权重，默认取现实世界的日/周/月
+///
+/// 仅影响正弦波本身；周末判定与按月振幅表仍按现实日历计算。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeasonalConfig {
+    pub day_secs: f64,
+    pub week_secs: f64,
+    pub month_secs: f64,
+    pub day_w: f64,
+    pub week_w: f64,
+    pub month_w: f64,
+}
+
+impl Default for SeasonalConfig {
+    fn default() -> Self {
+        Self {
+            day_secs: SECONDS_PER_DAY,
+            week_secs: SECONDS_PER_WEEK,
+            month_secs: SECONDS_PER_MONTH,
+            day_w: 0.6,
+            week_w: 0.3,
+            month_w: 0.1,
+        }
+    }
+}
+
+impl SeasonalConfig {
+    /// 周期须为正有限值，权重须为有限值
+    pub fn is_valid(&self) -> bool {
+        [self.day_secs, self.week_secs, self.month_secs].iter().all(|p| p.is_finite() && *p > 0.0)
+            && [self.day_w, self.week_w, self.month_w].iter().all(|w| w.is_finite())
+    }
+}
+
+lazy_static! {
+    /// 按月季节振幅表 (索引 0 = 一月)；为 None 时回退到 `MarketConfig::seasonal_amplitude`
+    static ref MONTHLY_AMPLITUDE: RwLock<Option<[f64; 12]>> = RwLock::new(None);
+    /// 季节复合波参数
+    static ref SEASONAL_CONFIG: RwLock<SeasonalConfig> = RwLock::new(SeasonalConfig::default());
+}
+
+/// 设置按月季节振幅表；传入 None 清除并回退到常量振幅
+/// @return 任一元素非有限值或为负数时返回 false 且不修改当前配置
+pub fn set_monthly_amplitude(schedule: Option<[f64; 12]>) -> bool {
+    if let Some(values) = &schedule {
+        if values.iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return false;
+        }
+    }
+    *MONTHLY_AMPLITUDE.write().unwrap_or_else(|e| e.into_inner()) = schedule;
+    true
+}
+
+/// 设置季节复合波参数
+/// @return 参数非法时返回 false 且不修改当前配置
+pub fn set_seasonal_config(config: SeasonalConfig) -> bool {
+    if !config.is_valid() {
+        return false;
+    }
+    *SEASONAL_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+    true
+}
+
+/// 当前生效的季节复合波参数
+pub fn seasonal_config() -> SeasonalConfig {
+    *SEASONAL_CONFIG.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 恢复默认参数 (清除按月振幅表，季节复合波回到现实日/周/月)
+pub fn reset_state() {
+    set_monthly_amplitude(None);
+    set_seasonal_config(SeasonalConfig::default());
+}
+
 // ==================== 辅助数学函数 ====================
 
+/// 由本地时间戳 (秒) 推算公历月份索引 (0 = 一月, 11 = 十二月)
+#[inline]
+fn month_index(ts_sec_local: f64) -> usize {
+    // Howard Hinnant civil_from_days 算法 (proleptic Gregorian)
+    let z = (ts_sec_local / SECONDS_PER_DAY).floor() as i64 + 719_468;
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (month - 1) as usize
+}
+
+/// 当前时刻生效的季节振幅：优先取按月振幅表，未设置时使用配置常量
+#[inline]
+fn seasonal_amplitude_at(cfg: &MarketConfig, ts_sec_local: f64) -> f64 {
+    match *MONTHLY_AMPLITUDE.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(schedule) => schedule[month_index(ts_sec_local)],
+        None => cfg.seasonal_amplitude,
+    }
+}
+
 /// Sigmoid 函数：用于在特定阈值附近平滑触发反馈逻辑
 #[inline]
 fn sigmoid(x: f64) -> f64 {
@@ -35,31 +139,60 @@ pub fn calculate_epsilon_internal(
     ctx: &TradeContext,
     cfg: &MarketConfig,
 ) -> f64 {
-    // 1. 时间轴对齐 (UTC -> Local)
+    let ts_sec_local = local_seconds(ctx);
+    let log_eps = log_epsilon(ctx, cfg, ts_sec_local, seasonal_wave(ts_sec_local, &seasonal_config()));
+    finish_epsilon(cfg, log_eps.exp())
+}
+
+/// 时间轴对齐 (UTC -> Local)，单位为秒
+#[inline]
+fn local_seconds(ctx: &TradeContext) -> f64 {
     let ts_sec_utc = (ctx.current_timestamp as f64) / 1000.0;
     let offset_sec = ctx.timezone_offset as f64;
-    let ts_sec_local = ts_sec_utc + offset_sec;
+    ts_sec_utc + offset_sec
+}
+
+/// 季节复合波：使用复合正弦波模拟日、周、月的周期性波动
+#[inline]
+fn seasonal_wave(ts_sec_local: f64, sc: &SeasonalConfig) -> f64 {
+    let day_wave = (ts_sec_local * 2.0 * std::f64::consts::PI / sc.day_secs).sin();
+    let week_wave = (ts_sec_local * 2.0 * std::f64::consts::PI / sc.week_secs).sin();
+    let month_wave = (ts_sec_local * 2.0 * std::f64::consts::PI / sc.month_secs).sin();
     
-    let safe_ln = |factor: f64| factor.max(0.01).ln();
+    sc.day_w * day_wave + sc.week_w * week_wave + sc.month_w * month_wave
+}
+
+/// 对数加权合成 ln(ε)：季节复合波由调用方给出，便于批量路径向量化正弦计算
+fn log_epsilon(ctx: &TradeContext, cfg: &MarketConfig, ts_sec_local: f64, seasonal_factor: f64) -> f64 {
+    let [l_sea, l_wk, l_nb, l_inf] = log_factors(ctx, cfg, ts_sec_local, seasonal_factor);
+
+    // 6. 对数加权合成最终 Epsilon (Geometric Mean Approximation)
+    cfg.seasonal_weight   * l_sea
+        + cfg.weekend_weight    * l_wk
+        + cfg.newbie_weight     * l_nb
+        + cfg.inflation_weight  * l_inf
+}
+
+/// 四项环境因子取对数后的值 [季节, 周末, 萌新优待, 通胀]，与权重无关
+#[inline]
+fn log_factors(ctx: &TradeContext, cfg: &MarketConfig, ts_sec_local: f64, seasonal_factor: f64) -> [f64; 4] {
+    // 取对数前的下限：非正或非有限配置回退默认值，防止 ln(0) 发散
+    let ln_floor = if cfg.ln_floor.is_finite() && cfg.ln_floor > 0.0 { cfg.ln_floor } else { DEFAULT_LN_FLOOR };
+    let safe_ln = |factor: f64| factor.max(ln_floor).ln();
 
     // 2. 季节性因子 (Seasonal Factor)
-    // 使用复合正弦波模拟日、周、月的周期性波动
-    let day_wave = (ts_sec_local * 2.0 * std::f64::consts::PI / SECONDS_PER_DAY).sin();
-    let week_wave = (ts_sec_local * 2.0 * std::f64::consts::PI / SECONDS_PER_WEEK).sin();
-    let month_wave = (ts_sec_local * 2.0 * std::f64::consts::PI / SECONDS_PER_MONTH).sin();
-    
-    let seasonal_factor = 0.6 * day_wave + 0.3 * week_wave + 0.1 * month_wave;
-    let mut f_sea = 1.0 + cfg.seasonal_amplitude * seasonal_factor;
+    let mut f_sea = 1.0 + seasonal_amplitude_at(cfg, ts_sec_local) * seasonal_factor;
     
     // 节庆模式 (Festival Mode)：检查位掩码 bit1
     if (ctx.newbie_mask >> 1) & 1 == 1 {
         f_sea *= 1.15; 
     }
 
-    // 3. 周末因子 (Weekend Factor)
+    // 3. 周末因子 (Weekend Factor)：周末或节假日表中的本地自然日
     let day_index = (ts_sec_local / SECONDS_PER_DAY).floor() as i64;
     let day_of_week = (day_index + 4).rem_euclid(7); // 0=周一, 6=周日
-    let f_wk = if day_of_week >= 5 { cfg.weekend_multiplier } else { 1.0 };
+    let is_weekend = day_of_week >= 5 || calendar::is_holiday(day_index);
+    let f_wk = if is_weekend { cfg.weekend_multiplier } else { 1.0 };
 
     // 4. 渐进式优待因子 (Dynamic Protection Factor)
     // 逻辑：优待随 play_time_seconds 增加而线性衰减，100小时后完全消失
@@ -74,15 +207,12 @@ pub fn calculate_epsilon_internal(
     let sigmoid_trigger = sigmoid(ctx.inflation_rate - 0.05);
     let f_inf = 1.0 + (ctx.inflation_rate * 0.2 * sigmoid_trigger);
 
-    // 6. 对数加权合成最终 Epsilon (Geometric Mean Approximation)
-    let log_eps = 
-          cfg.seasonal_weight   * safe_ln(f_sea)
-        + cfg.weekend_weight    * safe_ln(f_wk)
-        + cfg.newbie_weight     * safe_ln(f_nb)
-        + cfg.inflation_weight  * safe_ln(f_inf);
-
-    let mut epsilon = log_eps.exp();
+    [safe_ln(f_sea), safe_ln(f_wk), safe_ln(f_nb), safe_ln(f_inf)]
+}
 
+/// 市场波动率增强与安全阀
+#[inline]
+fn finish_epsilon(cfg: &MarketConfig, mut epsilon: f64) -> f64 {
     // 7. 市场波动率非线性增强
     if cfg.volatility_factor > 1.001 {
         epsilon = 1.0 + (epsilon - 1.0) * cfg.volatility_factor;
@@ -92,6 +222,310 @@ pub fn calculate_epsilon_internal(
     epsilon.clamp(0.1, 10.0)
 }
 
+// ==================== 批量演算 (AVX2) ====================
+
+/// 向量化正弦的输入上限 (秒)：保证日周期相位 < 2^20 · π/2，使 Cody-Waite 约简保持精确
+/// (以现实日长为基准，最短周期缩短时按比例收紧)
+const SIMD_MAX_LOCAL_SECONDS: f64 = 1.3e10;
+/// 向量化 exp 的输入上限：保证 2^n 的指数位不溢出，超出时该组回退标量 exp
+const SIMD_MAX_EXP_INPUT: f64 = 700.0;
+
+// π/2 的三段拆分 (fdlibm)，前两段仅 33 位有效位，与整数象限相乘无舍入误差
+const PIO2_1: f64 = 1.5707963267341256;
+const PIO2_2: f64 = 6.077100506303966e-11;
+const PIO2_3: f64 = 2.0222662487111665e-21;
+// [-π/4, π/4] 上的 sin/cos 极小极大多项式系数 (fdlibm k_sin.c / k_cos.c)
+const SIN_COEFFS: [f64; 6] = [
+    -0.16666666666666632, 0.00833333333332249, -0.0001984126982985795,
+    2.7557313707070068e-06, -2.5050760253406863e-08, 1.58969099521155e-10,
+];
+const COS_COEFFS: [f64; 6] = [
+    0.0416666666666666, -0.001388888888887411, 2.480158728947673e-05,
+    -2.7557314351390663e-07, 2.087572321298175e-09, -1.1359647557788195e-11,
+];
+// ln2 的两段拆分与 [-ln2/2, ln2/2] 上 exp 的 12 阶 Taylor 系数 (1/k!)
+const LN2_HI: f64 = 0.6931471803691238;
+const LN2_LO: f64 = 1.9082149292705877e-10;
+const EXP_COEFFS: [f64; 13] = [
+    1.0, 1.0, 0.5, 0.16666666666666666, 0.041666666666666664, 0.008333333333333333,
+    0.001388888888888889, 0.0001984126984126984, 2.48015873015873e-05, 2.7557319223985893e-06,
+    2.755731922398589e-07, 2.505210838544172e-08, 2.08767569878681e-09,
+];
+
+/// 批量环境因子演算：`out[i] = calculate_epsilon_internal(&ctxs[i], &cfgs[i])`
+///
+/// CPU 支持 AVX2 时每 4 个元素一组，向量化计算季节正弦波与最终 exp (与标量版本相对误差 < 1e-14)，
+/// 其余环节 (对数、节假日表查询) 仍逐元素计算；否则回退到标量循环。
+/// 三个切片长度必须一致。
+pub fn calculate_epsilon_batch(ctxs: &[TradeContext], cfgs: &[MarketConfig], out: &mut [f64]) {
+    assert!(ctxs.len() == cfgs.len() && cfgs.len() == out.len(), "epsilon batch slices differ in length");
+
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        unsafe { calculate_epsilon_batch_avx2(ctxs, cfgs, out) };
+        return;
+    }
+
+    calculate_epsilon_batch_scalar(ctxs, cfgs, out);
+}
+
+fn calculate_epsilon_batch_scalar(ctxs: &[TradeContext], cfgs: &[MarketConfig], out: &mut [f64]) {
+    for ((ctx, cfg), eps) in ctxs.iter().zip(cfgs).zip(out.iter_mut()) {
+        *eps = calculate_epsilon_internal(ctx, cfg);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn calculate_epsilon_batch_avx2(ctxs: &[TradeContext], cfgs: &[MarketConfig], out: &mut [f64]) {
+    let vectorized = ctxs.len() / 4 * 4;
+    let sc = seasonal_config();
+    let min_period = sc.day_secs.min(sc.week_secs).min(sc.month_secs);
+    let max_local_seconds = SIMD_MAX_LOCAL_SECONDS * (min_period / SECONDS_PER_DAY);
+
+    for base in (0..vectorized).step_by(4) {
+        let (ctx, cfg, eps) = (&ctxs[base..base + 4], &cfgs[base..base + 4], &mut out[base..base + 4]);
+        let ts = [local_seconds(&ctx[0]), local_seconds(&ctx[1]), local_seconds(&ctx[2]), local_seconds(&ctx[3])];
+        if !ts.iter().all(|t| t.abs() <= max_local_seconds) {
+            calculate_epsilon_batch_scalar(ctx, cfg, eps);
+            continue;
+        }
+
+        let v_ts = _mm256_loadu_pd(ts.as_ptr());
+        let v_seasonal = _mm256_add_pd(
+            _mm256_add_pd(
+                _mm256_mul_pd(_mm256_set1_pd(sc.day_w), sin_pd(phase_pd(v_ts, sc.day_secs))),
+                _mm256_mul_pd(_mm256_set1_pd(sc.week_w), sin_pd(phase_pd(v_ts, sc.week_secs))),
+            ),
+            _mm256_mul_pd(_mm256_set1_pd(sc.month_w), sin_pd(phase_pd(v_ts, sc.month_secs))),
+        );
+        let mut seasonal = [0.0f64; 4];
+        _mm256_storeu_pd(seasonal.as_mut_ptr(), v_seasonal);
+
+        let log_eps = [
+            log_epsilon(&ctx[0], &cfg[0], ts[0], seasonal[0]),
+            log_epsilon(&ctx[1], &cfg[1], ts[1], seasonal[1]),
+            log_epsilon(&ctx[2], &cfg[2], ts[2], seasonal[2]),
+            log_epsilon(&ctx[3], &cfg[3], ts[3], seasonal[3]),
+        ];
+        let mut raw = [0.0f64; 4];
+        if log_eps.iter().all(|x| x.abs() <= SIMD_MAX_EXP_INPUT) {
+            _mm256_storeu_pd(raw.as_mut_ptr(), exp_pd(_mm256_loadu_pd(log_eps.as_ptr())));
+        } else {
+            // 溢出或 NaN：交由标量 exp 保持原有语义
+            for (r, x) in raw.iter_mut().zip(log_eps) {
+                *r = x.exp();
+            }
+        }
+        for ((e, c), r) in eps.iter_mut().zip(cfg).zip(raw) {
+            *e = finish_epsilon(c, r);
+        }
+    }
+
+    calculate_epsilon_batch_scalar(&ctxs[vectorized..], &cfgs[vectorized..], &mut out[vectorized..]);
+}
+
+/// 周期相位 `ts · 2 · π / period`，运算顺序与标量版本一致，保证相位逐位相同
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn phase_pd(ts: __m256d, period: f64) -> __m256d {
+    let scaled = _mm256_mul_pd(_mm256_mul_pd(ts, _mm256_set1_pd(2.0)), _mm256_set1_pd(std::f64::consts::PI));
+    _mm256_div_pd(scaled, _mm256_set1_pd(period))
+}
+
+/// Horner 多项式求值，系数按升幂排列
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn horner_pd(x: __m256d, coeffs: &[f64]) -> __m256d {
+    let mut acc = _mm256_set1_pd(coeffs[coeffs.len() - 1]);
+    for &c in coeffs.iter().rev().skip(1) {
+        acc = _mm256_add_pd(_mm256_mul_pd(acc, x), _mm256_set1_pd(c));
+    }
+    acc
+}
+
+/// 向量化 sin：按 π/2 象限约简后选取 sin/cos 多项式 (|x| < 2^20 · π/2)
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn sin_pd(x: __m256d) -> __m256d {
+    let q = _mm256_round_pd::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(
+        _mm256_mul_pd(x, _mm256_set1_pd(std::f64::consts::FRAC_2_PI)),
+    );
+    let mut r = _mm256_sub_pd(x, _mm256_mul_pd(q, _mm256_set1_pd(PIO2_1)));
+    r = _mm256_sub_pd(r, _mm256_mul_pd(q, _mm256_set1_pd(PIO2_2)));
+    r = _mm256_sub_pd(r, _mm256_mul_pd(q, _mm256_set1_pd(PIO2_3)));
+    let z = _mm256_mul_pd(r, r);
+
+    // sin(r) = r + r·z·P(z), cos(r) = 1 - z/2 + z²·Q(z)
+    let sin_r = _mm256_add_pd(r, _mm256_mul_pd(_mm256_mul_pd(r, z), horner_pd(z, &SIN_COEFFS)));
+    let cos_r = _mm256_add_pd(
+        _mm256_sub_pd(_mm256_set1_pd(1.0), _mm256_mul_pd(_mm256_set1_pd(0.5), z)),
+        _mm256_mul_pd(_mm256_mul_pd(z, z), horner_pd(z, &COS_COEFFS)),
+    );
+
+    // 象限 q mod 4：0 → sin, 1 → cos, 2 → -sin, 3 → -cos
+    let qi = _mm256_cvtpd_epi32(q);
+    let value = _mm256_blendv_pd(sin_r, cos_r, lane_mask(qi, 1));
+    _mm256_xor_pd(value, _mm256_and_pd(lane_mask(qi, 2), _mm256_set1_pd(-0.0)))
+}
+
+/// 整数象限含指定位的通道置为全 1 掩码
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn lane_mask(qi: __m128i, bit: i32) -> __m256d {
+    let hit = _mm_cmpeq_epi32(_mm_and_si128(qi, _mm_set1_epi32(bit)), _mm_set1_epi32(bit));
+    _mm256_castsi256_pd(_mm256_cvtepi32_epi64(hit))
+}
+
+/// 向量化 exp：x = n·ln2 + r，exp(x) = 2^n · P(r) (|x| <= `SIMD_MAX_EXP_INPUT`)
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn exp_pd(x: __m256d) -> __m256d {
+    let n = _mm256_round_pd::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(
+        _mm256_mul_pd(x, _mm256_set1_pd(std::f64::consts::LOG2_E)),
+    );
+    let mut r = _mm256_sub_pd(x, _mm256_mul_pd(n, _mm256_set1_pd(LN2_HI)));
+    r = _mm256_sub_pd(r, _mm256_mul_pd(n, _mm256_set1_pd(LN2_LO)));
+
+    // 2^n：直接拼装 IEEE 754 指数位
+    let biased = _mm256_add_epi64(_mm256_cvtepi32_epi64(_mm256_cvtpd_epi32(n)), _mm256_set1_epi64x(1023));
+    let scale = _mm256_castsi256_pd(_mm256_slli_epi64::<52>(biased));
+    _mm256_mul_pd(horner_pd(r, &EXP_COEFFS), scale)
+}
+
+/// 将四项环境权重按比例缩放至和为 1.0，保证对数加权合成仍是几何平均
+///
+/// 返回 `Some(true)` 表示发生了调整，`Some(false)` 表示已归一化；
+/// 任一权重为负或非有限、或权重和为 0 时无法归一化，返回 `None` 且不修改配置。
+pub fn normalize_weights(cfg: &mut MarketConfig) -> Option<bool> {
+    let weights = [cfg.seasonal_weight, cfg.weekend_weight, cfg.newbie_weight, cfg.inflation_weight];
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return None;
+    }
+    let sum: f64 = weights.iter().sum();
+    if sum <= 0.0 {
+        return None;
+    }
+    if (sum - 1.0).abs() <= 1e-9 {
+        return Some(false);
+    }
+    cfg.seasonal_weight /= sum;
+    cfg.weekend_weight /= sum;
+    cfg.newbie_weight /= sum;
+    cfg.inflation_weight /= sum;
+    Some(true)
+}
+
+// ==================== 权重拟合 ====================
+
+/// 权重拟合的最大迭代次数
+pub const SOLVE_WEIGHTS_MAX_ITER: usize = 2_000;
+
+/// 拟合四项环境权重，使 `calculate_epsilon_internal` 在样本 (上下文, 期望 ε) 上的对数误差平方和最小
+///
+/// ln(ε) 对权重是线性的，因此先将目标 ε 逆变换 (撤销波动率增强) 后取对数，
+/// 再在 "权重非负且和为 1" 的约束下做投影梯度下降，至多 `SOLVE_WEIGHTS_MAX_ITER` 次。
+/// 目标非有限、非正或逆变换后非正的样本被跳过；无有效样本时原样返回 `cfg`。
+/// 其余配置字段保持不变。
+pub fn solve_weights(targets: &[(TradeContext, f64)], cfg: &MarketConfig) -> MarketConfig {
+    // 正规方程 A = XᵀX / n, b = Xᵀy / n
+    let mut a = [[0.0; 4]; 4];
+    let mut b = [0.0; 4];
+    let mut samples = 0usize;
+    let sc = seasonal_config();
+    for (ctx, target) in targets {
+        let pre = if cfg.volatility_factor > 1.001 { 1.0 + (target - 1.0) / cfg.volatility_factor } else { *target };
+        if !target.is_finite() || *target <= 0.0 || !(pre > 0.0 && pre.is_finite()) {
+            continue;
+        }
+        let ts_sec_local = local_seconds(ctx);
+        let x = log_factors(ctx, cfg, ts_sec_local, seasonal_wave(ts_sec_local, &sc));
+        let y = pre.ln();
+        for i in 0..4 {
+            for j in 0..4 {
+                a[i][j] += x[i] * x[j];
+            }
+            b[i] += x[i] * y;
+        }
+        samples += 1;
+    }
+    if samples == 0 {
+        return *cfg;
+    }
+    let n = samples as f64;
+    a.iter_mut().flatten().for_each(|v| *v /= n);
+    b.iter_mut().for_each(|v| *v /= n);
+
+    // 步长 1/L，L = 2·λmax(A) 由幂迭代估计
+    let lipschitz = 2.0 * max_eigenvalue(&a);
+    if !(lipschitz > 0.0 && lipschitz.is_finite()) {
+        return *cfg;
+    }
+    let step = 1.0 / lipschitz;
+
+    let mut start = *cfg;
+    let mut w = match normalize_weights(&mut start) {
+        Some(_) => [start.seasonal_weight, start.weekend_weight, start.newbie_weight, start.inflation_weight],
+        None => [0.25; 4],
+    };
+    for _ in 0..SOLVE_WEIGHTS_MAX_ITER {
+        let mut next = w;
+        for i in 0..4 {
+            let grad = 2.0 * ((0..4).map(|j| a[i][j] * w[j]).sum::<f64>() - b[i]);
+            next[i] -= step * grad;
+        }
+        let next = project_to_simplex(next);
+        let delta: f64 = next.iter().zip(&w).map(|(p, q)| (p - q).abs()).sum();
+        w = next;
+        if delta < 1e-12 {
+            break;
+        }
+    }
+
+    MarketConfig {
+        seasonal_weight: w[0],
+        weekend_weight: w[1],
+        newbie_weight: w[2],
+        inflation_weight: w[3],
+        ..*cfg
+    }
+}
+
+/// 对称半正定 4×4 矩阵的最大特征值 (幂迭代)
+fn max_eigenvalue(a: &[[f64; 4]; 4]) -> f64 {
+    let mut v = [0.5; 4];
+    let mut lambda = 0.0;
+    for _ in 0..64 {
+        let mut av = [0.0; 4];
+        for i in 0..4 {
+            av[i] = (0..4).map(|j| a[i][j] * v[j]).sum();
+        }
+        let norm = av.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return 0.0;
+        }
+        lambda = norm;
+        v = av.map(|x| x / norm);
+    }
+    lambda
+}
+
+/// 欧氏投影到单纯形 {w >= 0, Σw = 1}
+fn project_to_simplex(w: [f64; 4]) -> [f64; 4] {
+    let mut sorted = w;
+    sorted.sort_unstable_by(|x, y| y.total_cmp(x));
+    let mut cumulative = 0.0;
+    let mut theta = 0.0;
+    for (k, &u) in sorted.iter().enumerate() {
+        cumulative += u;
+        let t = (cumulative - 1.0) / (k + 1) as f64;
+        if u - t > 0.0 {
+            theta = t;
+        }
+    }
+    w.map(|x| (x - theta).max(0.0))
+}
+
 // ==================== 单元测试 ====================
 
 #[cfg(test)]
@@ -99,6 +533,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_v1_6_progressive_protection() {
         let mut cfg = MarketConfig::default();
         cfg.newbie_protection_rate = 0.2; // 20% 满额优待
@@ -122,4 +557,265 @@ mod tests {
         let eps_pro = calculate_epsilon_internal(&ctx_pro, &cfg);
         assert!((eps_pro - 1.0).abs() < 1e-4);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_configurable_ln_floor() {
+        // 99.5% 优待使新手因子降至 0.005，低于默认下限 0.01
+        let cfg = MarketConfig {
+            newbie_protection_rate: 0.995,
+            newbie_weight: 0.25,
+            seasonal_weight: 0.0, weekend_weight: 0.0, inflation_weight: 0.0,
+            volatility_factor: 1.0,
+            ..Default::default()
+        };
+        let ctx = TradeContext::default();
+
+        let eps_default = calculate_epsilon_internal(&ctx, &cfg);
+        assert!((eps_default - 0.01f64.powf(0.25)).abs() < 1e-9);
+
+        // 调低下限后按真实因子加权
+        let deep = MarketConfig { ln_floor: 0.001, ..cfg };
+        let eps_deep = calculate_epsilon_internal(&ctx, &deep);
+        assert!((eps_deep - 0.005f64.powf(0.25)).abs() < 1e-9);
+        assert!(eps_deep < eps_default);
+
+        // 非正下限回退默认值
+        let invalid = MarketConfig { ln_floor: 0.0, ..cfg };
+        assert_eq!(calculate_epsilon_internal(&ctx, &invalid), eps_default);
+    }
+
+    #[test]
+    fn test_month_index() {
+        // 2025-01-01T00:00:00Z / 2025-06-15 / 2024-12-31T23:59:59Z
+        assert_eq!(month_index(1_735_689_600.0), 0);
+        assert_eq!(month_index(1_749_945_600.0), 5);
+        assert_eq!(month_index(1_735_689_599.0), 11);
+        // 闰年 2 月 29 日
+        assert_eq!(month_index(1_709_164_800.0), 1);
+    }
+
+    #[test]
+    fn test_monthly_amplitude_schedule() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let cfg = MarketConfig {
+            seasonal_amplitude: 0.15,
+            seasonal_weight: 1.0,
+            // 隔离其它变量
+            weekend_weight: 0.0, newbie_weight: 0.0, inflation_weight: 0.0,
+            volatility_factor: 1.0,
+            ..Default::default()
+        };
+        // 在给定起点后的一整天内逐小时采样，取 epsilon 偏离 1.0 的最大幅度
+        let max_swing = |start_ms: i64| {
+            (0..24)
+                .map(|h| {
+                    let ctx = TradeContext { current_timestamp: start_ms + h * 3_600_000, ..Default::default() };
+                    (calculate_epsilon_internal(&ctx, &cfg) - 1.0).abs()
+                })
+                .fold(0.0, f64::max)
+        };
+        let december = 1_733_011_200_000; // 2024-12-01
+        let june = 1_717_200_000_000;     // 2024-06-01
+
+        let mut schedule = [0.05; 12];
+        schedule[11] = 0.6;
+        assert!(set_monthly_amplitude(Some(schedule)));
+        assert!(max_swing(december) > 2.0 * max_swing(june));
+
+        // 非法值被拒绝，原配置保持不变
+        let mut bad = schedule;
+        bad[3] = f64::NAN;
+        assert!(!set_monthly_amplitude(Some(bad)));
+        assert!(max_swing(december) > 2.0 * max_swing(june));
+
+        // 清除后回退到常量振幅：12 月与 6 月量级相同
+        reset_state();
+        let (dec, jun) = (max_swing(december), max_swing(june));
+        assert!((dec - jun).abs() < 0.5 * dec.max(jun));
+    }
+
+    /// 覆盖各时区、玩家阶段、通胀与配置组合的确定性样本 (线性同余生成器)
+    fn epsilon_samples(count: usize) -> (Vec<TradeContext>, Vec<MarketConfig>) {
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..count)
+            .map(|_| {
+                let ctx = TradeContext {
+                    current_timestamp: (next() * 4_000_000_000_000.0) as i64,
+                    timezone_offset: ((next() - 0.5) * 86_400.0) as i32,
+                    play_time_seconds: (next() * 720_000.0) as i64,
+                    inflation_rate: next() * 0.4 - 0.1,
+                    newbie_mask: (next() * 4.0) as i32,
+                    ..Default::default()
+                };
+                let cfg = MarketConfig {
+                    seasonal_amplitude: next() * 0.3,
+                    weekend_multiplier: 1.0 + next() * 0.5,
+                    newbie_protection_rate: next() * 0.3,
+                    volatility_factor: 0.5 + next() * 2.0,
+                    ..Default::default()
+                };
+                (ctx, cfg)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn test_epsilon_batch_matches_scalar() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        // 非 4 的倍数，覆盖尾部标量路径
+        let (mut ctxs, mut cfgs) = epsilon_samples(1_003);
+        // 超出向量化范围的时间戳与 exp 溢出的权重，应回退标量计算
+        ctxs[5].current_timestamp = i64::MAX / 2;
+        cfgs[9].inflation_weight = 1e6;
+
+        let mut batch = vec![0.0; ctxs.len()];
+        calculate_epsilon_batch(&ctxs, &cfgs, &mut batch);
+        for (i, (ctx, cfg)) in ctxs.iter().zip(&cfgs).enumerate() {
+            let scalar = calculate_epsilon_internal(ctx, cfg);
+            assert!((batch[i] - scalar).abs() < 1e-10, "i={} batch={} scalar={}", i, batch[i], scalar);
+        }
+
+        calculate_epsilon_batch(&[], &[], &mut []);
+    }
+
+    #[test]
+    fn test_compressed_seasonal_day() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let cfg = MarketConfig {
+            seasonal_amplitude: 0.2,
+            seasonal_weight: 1.0,
+            weekend_weight: 0.0, newbie_weight: 0.0, inflation_weight: 0.0,
+            volatility_factor: 1.0,
+            ..Default::default()
+        };
+        let eps_at = |secs: i64| calculate_epsilon_internal(&TradeContext { current_timestamp: secs * 1000, ..Default::default() }, &cfg);
+
+        // 游戏内一天 20 分钟，仅保留日波
+        let compressed = SeasonalConfig { day_secs: 1_200.0, day_w: 1.0, week_w: 0.0, month_w: 0.0, ..Default::default() };
+        assert!(set_seasonal_config(compressed));
+        let start = 1_200 * 1_000_000; // 日波相位为 0
+        assert!((eps_at(start) - 1.0).abs() < 1e-9);
+        assert!((eps_at(start + 300) - 1.2).abs() < 1e-9); // 四分之一周期达到峰值
+        assert!((eps_at(start + 900) - 0.8).abs() < 1e-9);
+        assert!((eps_at(start + 300) - eps_at(start + 300 + 1_200)).abs() < 1e-9);
+
+        // 批量路径读取同一配置
+        let ctxs: Vec<_> = (0..8).map(|i| TradeContext { current_timestamp: (start + i * 150) * 1000, ..Default::default() }).collect();
+        let mut batch = vec![0.0; ctxs.len()];
+        calculate_epsilon_batch(&ctxs, &vec![cfg; ctxs.len()], &mut batch);
+        for (ctx, b) in ctxs.iter().zip(&batch) {
+            assert!((b - calculate_epsilon_internal(ctx, &cfg)).abs() < 1e-10);
+        }
+
+        // 非法参数被拒绝，原配置保持不变
+        assert!(!set_seasonal_config(SeasonalConfig { week_w: f64::NAN, ..compressed }));
+        assert!(!set_seasonal_config(SeasonalConfig { month_secs: 0.0, ..compressed }));
+        assert_eq!(seasonal_config(), compressed);
+
+        // 恢复默认后为现实日长：20 分钟后的波动远未回到原位
+        reset_state();
+        assert_eq!(seasonal_config(), SeasonalConfig::default());
+        assert!((eps_at(start + 300) - 1.2).abs() > 0.1);
+    }
+
+    /// 批量与逐元素演算的耗时对比：`cargo test --release -- --ignored --nocapture bench_epsilon_batch`
+    #[test]
+    #[ignore]
+    fn bench_epsilon_batch() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let (ctxs, cfgs) = epsilon_samples(10_000);
+        let mut out = vec![0.0; ctxs.len()];
+        let rounds = 200;
+
+        let start = std::time::Instant::now();
+        for _ in 0..rounds {
+            for (o, (ctx, cfg)) in out.iter_mut().zip(ctxs.iter().zip(&cfgs)) {
+                *o = calculate_epsilon_internal(std::hint::black_box(ctx), cfg);
+            }
+        }
+        let scalar = start.elapsed();
+
+        let start = std::time::Instant::now();
+        for _ in 0..rounds {
+            calculate_epsilon_batch(std::hint::black_box(&ctxs), &cfgs, &mut out);
+        }
+        let batch = start.elapsed();
+
+        println!(
+            "epsilon x{}: scalar {:?}/round, batch {:?}/round, speedup {:.2}x",
+            ctxs.len(),
+            scalar / rounds,
+            batch / rounds,
+            scalar.as_secs_f64() / batch.as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn test_solve_weights_recovers_known_weights() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let truth = MarketConfig {
+            seasonal_weight: 0.4, weekend_weight: 0.1,
+            newbie_weight: 0.3, inflation_weight: 0.2,
+            ..Default::default()
+        };
+        let samples: Vec<(TradeContext, f64)> = (0..200)
+            .map(|i| {
+                let ctx = TradeContext {
+                    current_timestamp: 1_700_000_000_000 + i * 7_919_000,
+                    play_time_seconds: (i % 50) * 14_400,
+                    inflation_rate: (i % 13) as f64 * 0.04 - 0.1,
+                    newbie_mask: ((i % 7 == 0) as i32) << 1,
+                    ..Default::default()
+                };
+                (ctx, calculate_epsilon_internal(&ctx, &truth))
+            })
+            .collect();
+
+        let fitted = solve_weights(&samples, &MarketConfig::default());
+        let got = [fitted.seasonal_weight, fitted.weekend_weight, fitted.newbie_weight, fitted.inflation_weight];
+        for (g, t) in got.iter().zip([0.4, 0.1, 0.3, 0.2]) {
+            assert!((g - t).abs() < 1e-3, "got={:?}", got);
+        }
+        assert!((got.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(fitted.weekend_multiplier, truth.weekend_multiplier);
+
+        // 无有效样本时原样返回
+        let cfg = MarketConfig::default();
+        let unchanged = solve_weights(&[(TradeContext::default(), f64::NAN)], &cfg);
+        assert_eq!(unchanged.seasonal_weight, cfg.seasonal_weight);
+    }
+
+    #[test]
+    fn test_normalize_weights_preserves_ratios() {
+        let mut cfg = MarketConfig {
+            seasonal_weight: 0.8, weekend_weight: 0.4,
+            newbie_weight: 0.6, inflation_weight: 0.2,
+            ..Default::default()
+        };
+        assert_eq!(normalize_weights(&mut cfg), Some(true));
+        let sum = cfg.seasonal_weight + cfg.weekend_weight + cfg.newbie_weight + cfg.inflation_weight;
+        assert!((sum - 1.0).abs() < 1e-12);
+        assert!((cfg.seasonal_weight - 0.4).abs() < 1e-12);
+        assert!((cfg.weekend_weight - 0.2).abs() < 1e-12);
+        assert!((cfg.newbie_weight - 0.3).abs() < 1e-12);
+        assert!((cfg.inflation_weight - 0.1).abs() < 1e-12);
+
+        // 已归一化的配置不调整；非法权重拒绝且保持原样
+        assert_eq!(normalize_weights(&mut cfg), Some(false));
+        let mut bad = MarketConfig { weekend_weight: -0.5, ..Default::default() };
+        assert_eq!(normalize_weights(&mut bad), None);
+        assert_eq!(bad.weekend_weight, -0.5);
+        let mut zero = MarketConfig {
+            seasonal_weight: 0.0, weekend_weight: 0.0, newbie_weight: 0.0, inflation_weight: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(normalize_weights(&mut zero), None);
+    }
+}
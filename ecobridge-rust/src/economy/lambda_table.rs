@@ -0,0 +1,81 @@
+// ==================================================
+// FILE: ecobridge-rust/src/economy/lambda_table.rs
+// ==================================================
+
+//! Per-Item Lambda Table (物品价格敏感度常驻表)
+//!
+//! Java 侧维护的逐物品 lambda 表一次性写入原生核心，批量定价时按物品 ID 查表，
+//! 无需每次打开菜单都重新编组完整的 lambda 数组。
+//!
+//! # 并发
+//! 分片并发哈希表：定价查表只锁定所在分片，配置热重载写入不会阻塞其余分片的查询。
+
+use crate::economy::DEFAULT_LAMBDA;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// item_id -> lambda
+    static ref LAMBDA_TABLE: DashMap<u32, f64> = DashMap::new();
+}
+
+/// 设置物品的 lambda，须为正有限值
+/// @return 非法值返回 false 且不修改当前配置
+pub fn set_lambda(item_id: u32, lambda: f64) -> bool {
+    if !lambda.is_finite() || lambda <= 0.0 {
+        return false;
+    }
+    LAMBDA_TABLE.insert(item_id, lambda);
+    true
+}
+
+/// 查询物品的 lambda，未登记时返回 `DEFAULT_LAMBDA`
+pub fn get_lambda(item_id: u32) -> f64 {
+    LAMBDA_TABLE.get(&item_id).map_or(DEFAULT_LAMBDA, |v| *v)
+}
+
+/// 移除物品的 lambda 登记，返回此前是否存在
+pub fn remove_lambda(item_id: u32) -> bool {
+    LAMBDA_TABLE.remove(&item_id).is_some()
+}
+
+/// 按物品 ID 批量查表，未登记的物品取 `DEFAULT_LAMBDA`
+pub fn lookup_many(item_ids: &[u32]) -> Vec<f64> {
+    item_ids.iter().map(|&id| get_lambda(id)).collect()
+}
+
+/// 清空 lambda 表
+pub fn reset_state() {
+    LAMBDA_TABLE.clear();
+}
+
+// ==================== 单元测试 ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lambda_table_lookup() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+
+        assert_eq!(get_lambda(7), DEFAULT_LAMBDA);
+        assert!(set_lambda(7, 0.05));
+        assert!(set_lambda(9, 0.002));
+        assert_eq!(get_lambda(7), 0.05);
+
+        // 非法值被拒绝，原值保持不变
+        assert!(!set_lambda(7, 0.0));
+        assert!(!set_lambda(7, f64::NAN));
+        assert_eq!(get_lambda(7), 0.05);
+
+        assert_eq!(lookup_many(&[9, 1, 7, 9]), vec![0.002, DEFAULT_LAMBDA, 0.05, 0.002]);
+
+        assert!(remove_lambda(9));
+        assert!(!remove_lambda(9));
+        assert_eq!(get_lambda(9), DEFAULT_LAMBDA);
+        reset_state();
+        assert_eq!(get_lambda(7), DEFAULT_LAMBDA);
+    }
+}
@@ -10,6 +10,10 @@
 //! 虽然本模块内部使用 `f64` 进行非线性运算，但其输入参数（如 `m1_supply`）
 //! 必须是经过标准化处理的（即：原始金额 / 1,000,000.0）。
 //! 这种“中间高精度浮点，两端定点整数”的架构确保了宏观趋势计算的平滑性。
+//!
+//! # 非有限输入
+//! 任一浮点输入为 NaN/Inf 时返回安全默认值 (通胀率 0、稳定性 1.0、衰减量 0)，
+//! 与 `control.rs` 的处理方式一致，防止 NaN 污染下游演算。
 
 /// 计算通货膨胀率 (Inflation Rate)
 /// 
@@ -21,6 +25,9 @@
 /// * `m1_supply` - 标准化 M1 供应量 (已由 Micros 缩放)
 #[inline(always)]
 pub fn calculate_inflation_rate(current_heat: f64, m1_supply: f64) -> f64 {
+    if !current_heat.is_finite() || !m1_supply.is_finite() {
+        return 0.0;
+    }
     // 防御性编程: 防止由于极小货币总量导致的数值爆炸
     if m1_supply <= 1.0 { 
         return 0.0; 
@@ -29,9 +36,50 @@ pub fn calculate_inflation_rate(current_heat: f64, m1_supply: f64) -> f64 {
     let raw_rate = current_heat / m1_supply;
     
     // 硬约束: 通胀率上限 45% (高税收/高价格), 通缩率下限 -15% (补贴/底价保护)
-    raw_rate.clamp(-0.15, 0.45)
+    raw_rate.clamp(INFLATION_MIN, INFLATION_MAX)
+}
+
+/// 通胀率钳位区间
+pub const INFLATION_MIN: f64 = -0.15;
+pub const INFLATION_MAX: f64 = 0.45;
+
+/// 反解通胀公式：在当前流通热度下达到目标通胀率所需的 M1 供应量 (`m1 = heat / target`)
+///
+/// 目标为 0、超出 [-0.15, 0.45] 钳位区间、或反解出的 M1 不大于 1.0
+/// (即 `calculate_inflation_rate` 的有效下限) 时返回 `None`。
+pub fn required_m1_for_inflation(current_heat: f64, target_inflation: f64) -> Option<f64> {
+    if !current_heat.is_finite() || !target_inflation.is_finite() || target_inflation == 0.0
+        || !(INFLATION_MIN..=INFLATION_MAX).contains(&target_inflation) {
+        return None;
+    }
+    let m1 = current_heat / target_inflation;
+    if m1.is_finite() && m1 > 1.0 { Some(m1) } else { None }
+}
+
+/// 计算平滑通货膨胀率 (Soft Saturation)
+///
+/// 与 `calculate_inflation_rate` 相同的原始比率，但以 `tanh` 软饱和代替硬钳位：
+/// 正负两侧分别以 `bound * tanh(raw / (bound * smoothing))` 渐近逼近上下限，
+/// 原点处斜率为 `1 / smoothing`，避免 M1 剧烈波动时的阶跃跳变。
+///
+/// `smoothing` 须为正的有限值，否则退化为硬钳位版本。
+#[inline(always)]
+pub fn calculate_inflation_rate_smooth(current_heat: f64, m1_supply: f64, smoothing: f64) -> f64 {
+    if !smoothing.is_finite() || smoothing <= 0.0 {
+        return calculate_inflation_rate(current_heat, m1_supply);
+    }
+    if !current_heat.is_finite() || !m1_supply.is_finite() || m1_supply <= 1.0 {
+        return 0.0;
+    }
+
+    let raw_rate = current_heat / m1_supply;
+    let bound = if raw_rate >= 0.0 { INFLATION_MAX } else { -INFLATION_MIN };
+    bound * (raw_rate / (bound * smoothing)).tanh()
 }
 
+/// 稳定性恢复窗口默认值 (ms)：大额波动后 15 分钟恢复完全稳定
+pub const DEFAULT_RECOVERY_WINDOW_MS: f64 = 900_000.0;
+
 /// 计算市场稳定性因子 (Stability Factor)
 /// 
 /// 逻辑: 这是一个线性恢复函数。距离上一次大额波动（Volatile Event）越久，
@@ -40,15 +88,15 @@ pub fn calculate_inflation_rate(current_heat: f64, m1_supply: f64) -> f64 {
 /// # Arguments
 /// * `last_volatile_ts` - 上一次大额波动的时间戳 (ms, i64)
 /// * `current_ts` - 当前时间戳 (ms, i64)
-/// * `recovery_window_ms` - 恢复 100% 稳定性所需的毫秒数 (默认 900,000ms)
+/// * `recovery_window_ms` - 恢复 100% 稳定性所需的毫秒数 (默认 `DEFAULT_RECOVERY_WINDOW_MS`)
 #[inline(always)]
 pub fn calculate_stability(
     last_volatile_ts: i64, 
     current_ts: i64, 
     recovery_window_ms: f64
 ) -> f64 {
-    // 如果从未发生过波动 (0), 市场处于完美稳定状态
-    if last_volatile_ts <= 0 { 
+    // 如果从未发生过波动 (0) 或恢复窗口非法, 市场视为完美稳定状态
+    if last_volatile_ts <= 0 || !recovery_window_ms.is_finite() { 
         return 1.0; 
     }
     
@@ -75,6 +123,9 @@ pub fn calculate_stability(
 /// * `cycles_per_day` - 每日任务频率
 #[inline(always)]
 pub fn calculate_decay(current_heat: f64, daily_decay_rate: f64, cycles_per_day: f64) -> f64 {
+    if !current_heat.is_finite() || !daily_decay_rate.is_finite() || !cycles_per_day.is_finite() {
+        return 0.0;
+    }
     // 归零逻辑：如果热度绝对值小于 1.0 标准单位，则直接返回当前值进行全量扣除
     if current_heat.abs() < 1.0 { 
         return current_heat; 
@@ -86,6 +137,52 @@ pub fn calculate_decay(current_heat: f64, daily_decay_rate: f64, cycles_per_day:
     current_heat * per_cycle_rate
 }
 
+/// 热度衰减模式
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecayMode {
+    /// 线性：每周期扣减 `daily_decay_rate / cycles_per_day` 比例 (与 `calculate_decay` 一致)
+    Linear = 0,
+    /// 指数复利：每周期保留 `(1 - daily_decay_rate)^(1 / cycles_per_day)`，
+    /// 一整天后恰好保留 `1 - daily_decay_rate`
+    Exponential = 1,
+}
+
+impl DecayMode {
+    pub fn from_raw(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Linear),
+            1 => Some(Self::Exponential),
+            _ => None,
+        }
+    }
+}
+
+/// 按指定模式计算热度衰减量 (Decay Amount)
+///
+/// 归零阈值逻辑与 `calculate_decay` 相同。指数模式下 `daily_decay_rate` 被钳位至 [0, 1]。
+#[inline(always)]
+pub fn calculate_decay_ex(
+    current_heat: f64,
+    daily_decay_rate: f64,
+    cycles_per_day: f64,
+    mode: DecayMode,
+) -> f64 {
+    match mode {
+        DecayMode::Linear => calculate_decay(current_heat, daily_decay_rate, cycles_per_day),
+        DecayMode::Exponential => {
+            if !current_heat.is_finite() || !daily_decay_rate.is_finite() || !cycles_per_day.is_finite() {
+                return 0.0;
+            }
+            if current_heat.abs() < 1.0 {
+                return current_heat;
+            }
+            let retained = (1.0 - daily_decay_rate.clamp(0.0, 1.0)).powf(1.0 / cycles_per_day);
+            current_heat * (1.0 - retained)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,6 +194,42 @@ mod tests {
         assert_eq!(calculate_inflation_rate(5000.0, 1000.0), 0.45);
     }
 
+    #[test]
+    fn test_smooth_inflation_saturates_asymptotically() {
+        let m1 = 1000.0;
+        // 恰在钳位边界：硬钳位已触顶，平滑版本仍留有余量
+        let hard = calculate_inflation_rate(450.0, m1);
+        let smooth = calculate_inflation_rate_smooth(450.0, m1, 1.0);
+        assert_eq!(hard, INFLATION_MAX);
+        assert!(smooth > 0.3 && smooth < hard, "smooth={}", smooth);
+
+        // 远超边界：渐近逼近但不越过上下限
+        let far = calculate_inflation_rate_smooth(5_000.0, m1, 1.0);
+        assert!(far > smooth && far < INFLATION_MAX);
+        let deflation = calculate_inflation_rate_smooth(-500.0, m1, 1.0);
+        assert!(deflation < 0.0 && deflation > INFLATION_MIN);
+        assert_eq!(calculate_inflation_rate(-500.0, m1), INFLATION_MIN);
+
+        // 原点附近与线性版本基本一致；非法 smoothing 退化为硬钳位
+        let small = calculate_inflation_rate_smooth(1.0, m1, 1.0);
+        assert!((small - calculate_inflation_rate(1.0, m1)).abs() < 1e-6);
+        assert_eq!(calculate_inflation_rate_smooth(450.0, m1, 0.0), hard);
+    }
+
+    #[test]
+    fn test_required_m1_round_trips_through_inflation() {
+        for (heat, target) in [(100.0, 0.10), (450.0, 0.45), (-30.0, -0.15), (2_000.0, 0.02)] {
+            let m1 = required_m1_for_inflation(heat, target).unwrap();
+            assert!((calculate_inflation_rate(heat, m1) - target).abs() < 1e-12, "heat={} target={}", heat, target);
+        }
+
+        assert_eq!(required_m1_for_inflation(100.0, 0.0), None);
+        assert_eq!(required_m1_for_inflation(100.0, 0.5), None);
+        assert_eq!(required_m1_for_inflation(100.0, -0.2), None);
+        // 热度与目标方向相反时不存在正的 M1
+        assert_eq!(required_m1_for_inflation(100.0, -0.1), None);
+    }
+
     #[test]
     fn test_stability_monotonicity() {
         let window = 1000.0;
@@ -105,6 +238,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::float_equality_without_abs)]
     fn test_decay_zeroing_threshold() {
         // 验证归零逻辑
         let small_heat = 0.5; // 低于 1.0
@@ -114,4 +248,41 @@ mod tests {
         let large_heat = 1000.0;
         assert!(calculate_decay(large_heat, 0.48, 48.0) - 10.0 < f64::EPSILON);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_exponential_decay_half_life() {
+        // 日衰减率 50%：一天 (48 个周期) 后热度恰好减半，两天后剩 1/4
+        let cycles = 48.0;
+        let mut heat = 1024.0;
+        for day in 1..=2 {
+            for _ in 0..48 {
+                heat -= calculate_decay_ex(heat, 0.5, cycles, DecayMode::Exponential);
+            }
+            let expected = 1024.0 * 0.5f64.powi(day);
+            assert!((heat - expected).abs() < 1e-9, "day={} heat={}", day, heat);
+        }
+
+        // 线性模式与原函数一致；指数模式同样保留归零阈值
+        assert_eq!(calculate_decay_ex(1000.0, 0.48, cycles, DecayMode::Linear), calculate_decay(1000.0, 0.48, cycles));
+        assert_eq!(calculate_decay_ex(0.5, 0.5, cycles, DecayMode::Exponential), 0.5);
+        assert_eq!(DecayMode::from_raw(2), None);
+    }
+
+    #[test]
+    fn test_non_finite_inputs_fall_back_to_safe_defaults() {
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(calculate_inflation_rate(bad, 1000.0), 0.0);
+            assert_eq!(calculate_inflation_rate(100.0, bad), 0.0);
+
+            assert_eq!(calculate_stability(1000, 1500, bad), 1.0);
+
+            assert_eq!(calculate_decay(bad, 0.05, 48.0), 0.0);
+            assert_eq!(calculate_decay(1000.0, bad, 48.0), 0.0);
+            assert_eq!(calculate_decay(1000.0, 0.05, bad), 0.0);
+            for mode in [DecayMode::Linear, DecayMode::Exponential] {
+                assert_eq!(calculate_decay_ex(bad, 0.05, 48.0, mode), 0.0);
+                assert_eq!(calculate_decay_ex(1000.0, bad, 48.0, mode), 0.0);
+            }
+        }
+    }
+}
@@ -2,8 +2,11 @@
 // FILE: ecobridge-rust/src/economy/mod.rs
 // ==================================================
 
+pub mod calendar;
 pub mod control;
 pub mod environment;
+pub mod lambda_table;
+pub mod macro_eco;
 pub mod pricing;
 pub mod summation;
 
@@ -116,4 +119,28 @@ mod tests {
         // 验证软限幅逻辑：即使供应量无限，价格也不应跌破硬底线
         assert!(price >= MIN_PHYSICAL_PRICE);
     }
+
+    #[test]
+    fn test_per_item_hard_floor() {
+        let base_price_micros = 100 * MICROS;
+        let (crash_vol, lambda, eps) = (1e6, 0.5, 1.0);
+
+        // 未设硬底价 (0) 时退回全局物理底价
+        let default_floor = compute_price_bounded_internal(base_price_micros, crash_vol, 0, lambda, eps, 0.0, 0);
+        assert_eq!(default_floor, MIN_PHYSICAL_PRICE);
+
+        // 高价值物品的硬底价生效
+        let item_floor = compute_price_bounded_internal(base_price_micros, crash_vol, 0, lambda, eps, 0.0, 25 * MICROS);
+        assert_eq!(item_floor, 25.0);
+
+        // 动态底价 (历史均价 20%) 与硬底价取较大者
+        let dynamic_wins = compute_price_bounded_internal(base_price_micros, crash_vol, 0, lambda, eps, 200.0, 25 * MICROS);
+        assert_eq!(dynamic_wins, 40.0);
+        let hard_wins = compute_price_bounded_internal(base_price_micros, crash_vol, 0, lambda, eps, 50.0, 25 * MICROS);
+        assert_eq!(hard_wins, 25.0);
+
+        // 正常行情下底价不干预
+        let normal = compute_price_bounded_internal(base_price_micros, 0.0, 0, lambda, eps, 100.0, 25 * MICROS);
+        assert_eq!(normal, compute_price_final_internal(base_price_micros, 0.0, lambda, eps));
+    }
 }
\ No newline at end of file
@@ -2,13 +2,199 @@
 // FILE: ecobridge-rust/src/economy/pricing.rs
 // ==================================================
 
-use crate::models::{TradeContext, MarketConfig};
+use crate::models::{TradeContext, MarketConfig, TierConfig};
 use rayon::prelude::*;
-use crate::economy::environment;
+use crate::economy::{environment, summation, MIN_PHYSICAL_PRICE};
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// 精度缩放常量 (1.0 = 1,000,000 Micros)
 const MICROS_SCALE: f64 = 1_000_000.0;
 
+/// 阶梯定价分界 (件) 与折扣率
+const TIER1_LIMIT: f64 = 500.0;
+const TIER2_LIMIT: f64 = 2000.0;
+const TIER2_RATE: f64 = 0.85;
+const TIER3_RATE: f64 = 0.60;
+
+impl Default for TierConfig {
+    /// 与 `compute_tier_price_internal` 的内置阶梯一致
+    fn default() -> Self {
+        Self { tier1_limit: TIER1_LIMIT, tier2_limit: TIER2_LIMIT, tier2_rate: TIER2_RATE, tier3_rate: TIER3_RATE }
+    }
+}
+
+/// 卖出灵敏度系数默认值：卖出时 lambda 乘以该系数，模拟 "价格下行粘性"
+pub const DEFAULT_SELL_SENSITIVITY_RATIO: f64 = 0.6;
+
+/// 卖出灵敏度系数须为有限值且位于 (0, 2]
+#[inline]
+pub fn validate_sell_sensitivity_ratio(ratio: f64) -> bool {
+    ratio.is_finite() && ratio > 0.0 && ratio <= 2.0
+}
+
+/// 冷启动样本统计使用的衰减常数 (天)，与 Java 侧 `economy.tau` 默认值一致
+pub const WARMUP_TAU_DAYS: f64 = 7.0;
+
+/// 冷启动最少样本数：窗口内样本不足时价格按置信度线性回归基础价 (0 = 关闭)
+static PRICING_MIN_SAMPLES: AtomicU64 = AtomicU64::new(0);
+
+/// 新市场上线参数：爬坡期内价格从固定首发价线性过渡到演算价
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketLaunch {
+    pub launch_ts: i64,
+    pub ramp_ms: i64,
+    pub launch_price_micros: i64,
+}
+
+lazy_static! {
+    /// market_key -> 上线参数
+    static ref MARKET_LAUNCHES: RwLock<HashMap<String, MarketLaunch>> = RwLock::new(HashMap::new());
+}
+
+/// 登记 (或覆盖) 市场的上线爬坡参数；`ramp_ms <= 0` 表示移除该市场的爬坡设置
+pub fn set_market_launch(market_key: &str, launch: MarketLaunch) -> bool {
+    if launch.launch_price_micros <= 0 && launch.ramp_ms > 0 {
+        return false;
+    }
+    let mut lock = MARKET_LAUNCHES.write().unwrap_or_else(|e| e.into_inner());
+    if launch.ramp_ms <= 0 {
+        lock.remove(market_key);
+    } else {
+        lock.insert(market_key.to_string(), launch);
+    }
+    true
+}
+
+impl MarketLaunch {
+    /// 按成熟度混合价格：权重 = (now - launch_ts) / ramp_ms，钳位至 [0, 1]
+    fn ramp(&self, now_ms: i64, computed_price: f64) -> f64 {
+        let elapsed = now_ms.saturating_sub(self.launch_ts);
+        if elapsed >= self.ramp_ms {
+            return computed_price;
+        }
+        let maturity = elapsed.max(0) as f64 / self.ramp_ms as f64;
+        blend_toward_base(computed_price, self.launch_price_micros, maturity)
+    }
+}
+
+fn market_launch(market_key: &str) -> Option<MarketLaunch> {
+    MARKET_LAUNCHES.read().unwrap_or_else(|e| e.into_inner()).get(market_key).copied()
+}
+
+/// 按市场成熟度混合价格：权重 = (now - launch_ts) / ramp_ms，钳位至 [0, 1]
+///
+/// 未登记的市场或爬坡期已结束时原样返回 `computed_price`。
+pub fn apply_launch_ramp(market_key: &str, now_ms: i64, computed_price: f64) -> f64 {
+    match market_launch(market_key) {
+        Some(launch) => launch.ramp(now_ms, computed_price),
+        None => computed_price,
+    }
+}
+
+/// 新手卖出加成：游戏时长低于 `threshold_secs` 的玩家卖出时价格乘以加成倍率
+///
+/// 倍率随游戏时长线性衰减：0 时长为 `factor`，达到阈值时回到 1.0。与环境因子中的新手买价折扣相互独立。
+/// 新手身份与环境因子的新手保护一致，仅由游戏时长判定，不读取 `TradeContext::newbie_mask`
+/// (该掩码的 bit1 为节庆标记，不表示新手身份)。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewbieSellBonus {
+    pub threshold_secs: i64,
+    pub factor: f64,
+}
+
+impl NewbieSellBonus {
+    /// 关闭状态 (默认)：阈值为 0，倍率恒为 1.0
+    pub(crate) const DISABLED: Self = Self { threshold_secs: 0, factor: 1.0 };
+
+    /// 倍率上限：新手卖出价至多翻倍，防止误配置放大套利空间
+    pub const MAX_FACTOR: f64 = 2.0;
+
+    /// 阈值须非负，倍率须位于 [1.0, `MAX_FACTOR`]
+    pub fn is_valid(&self) -> bool {
+        self.threshold_secs >= 0 && (1.0..=Self::MAX_FACTOR).contains(&self.factor)
+    }
+
+    /// 给定游戏时长 (秒) 下的卖出价倍率
+    pub fn multiplier(&self, play_time_secs: i64) -> f64 {
+        if self.threshold_secs <= 0 {
+            return 1.0;
+        }
+        let remaining = (1.0 - play_time_secs.max(0) as f64 / self.threshold_secs as f64).clamp(0.0, 1.0);
+        1.0 + (self.factor - 1.0) * remaining
+    }
+}
+
+static NEWBIE_SELL_BONUS: RwLock<NewbieSellBonus> = RwLock::new(NewbieSellBonus::DISABLED);
+
+/// 设置新手卖出加成，非法参数返回 false 且不修改当前配置
+pub fn set_newbie_sell_bonus(bonus: NewbieSellBonus) -> bool {
+    if !bonus.is_valid() {
+        return false;
+    }
+    *NEWBIE_SELL_BONUS.write().unwrap_or_else(|e| e.into_inner()) = bonus;
+    true
+}
+
+/// 当前生效的新手卖出加成
+pub fn newbie_sell_bonus() -> NewbieSellBonus {
+    *NEWBIE_SELL_BONUS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 单个元素演算 panic 时写入的哨兵价格 (与定价硬底线一致)
+pub const BATCH_PANIC_SENTINEL: f64 = 0.01;
+
+#[cfg(test)]
+pub(crate) static TEST_PANIC_AT_INDEX: std::sync::atomic::AtomicI64 = std::sync::atomic::AtomicI64::new(-1);
+
+#[cfg(test)]
+static TEST_EPSILON_PANIC_AT_CHUNK: std::sync::atomic::AtomicI64 = std::sync::atomic::AtomicI64::new(-1);
+
+/// 设置冷启动最少样本数 (0 关闭预热混合)
+pub fn set_min_samples(n: u64) {
+    PRICING_MIN_SAMPLES.store(n, Ordering::Relaxed);
+}
+
+/// 恢复默认参数 (关闭预热混合与新手卖出加成，清空市场上线设置)
+pub fn reset_state() {
+    set_min_samples(0);
+    *NEWBIE_SELL_BONUS.write().unwrap_or_else(|e| e.into_inner()) = NewbieSellBonus::DISABLED;
+    MARKET_LAUNCHES.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// 预热置信度：样本数 / 最少样本数，钳位至 [0, 1]；未启用时恒为 1
+pub fn warmup_confidence(sample_count: usize) -> f64 {
+    confidence_from_samples(sample_count, PRICING_MIN_SAMPLES.load(Ordering::Relaxed))
+}
+
+fn confidence_from_samples(sample_count: usize, min_samples: u64) -> f64 {
+    if min_samples == 0 {
+        return 1.0;
+    }
+    (sample_count as f64 / min_samples as f64).min(1.0)
+}
+
+/// 指定市场在调用方 tau 下的预热置信度 (样本取自该市场的热存储分桶，与其 neff 同源)
+///
+/// 未启用预热时直接返回 1，不访问热存储。
+pub fn market_warmup_confidence(market_key: &str, current_ts: i64, tau: f64) -> f64 {
+    let min_samples = PRICING_MIN_SAMPLES.load(Ordering::Relaxed);
+    if min_samples == 0 {
+        return 1.0;
+    }
+    confidence_from_samples(summation::sample_count_in_window(market_key, current_ts, tau), min_samples)
+}
+
+/// 按置信度在演算价与基础价之间线性混合
+#[inline]
+pub fn blend_toward_base(price: f64, base_price_micros: i64, confidence: f64) -> f64 {
+    let base_price = (base_price_micros as f64) / MICROS_SCALE;
+    base_price + (price - base_price) * confidence.clamp(0.0, 1.0)
+}
+
 // -----------------------------------------------------------------------------
 // 1. 内部定价核心逻辑 (Core Engine)
 // -----------------------------------------------------------------------------
@@ -18,12 +204,32 @@ const MICROS_SCALE: f64 = 1_000_000.0;
 /// @param base_price_micros 物品基础定价 (i64 Micros)
 /// @param n_eff 有效物品供应累积量 (来自 SIMD 演算，已缩放为标准 f64)
 /// @param trade_amount_micros 本次交易的物品件数 (i64 Micros)：正数为卖出，负数为买入
+/// @param sell_sensitivity_ratio 卖出时 lambda 的缩放系数 (默认 0.6)
 fn compute_price_behavioral_core(
     base_price_micros: i64,
     n_eff: f64,
     trade_amount_micros: i64, 
     lambda: f64,
     epsilon: f64,
+    sell_sensitivity_ratio: f64,
+) -> f64 {
+    compute_price_anchored_core(base_price_micros, n_eff, trade_amount_micros, lambda, epsilon, sell_sensitivity_ratio, 0, 0.0)
+}
+
+/// 定价引擎本体：在供需演算价之后可选地向参考锚定价均值回归
+///
+/// @param anchor_micros 参考锚定价 (i64 Micros)
+/// @param reversion_strength 回归强度 ∈ [0, 1]：0 为纯供需定价，1 为直接取锚定价；<= 0 或非有限时不回归
+#[allow(clippy::too_many_arguments)]
+fn compute_price_anchored_core(
+    base_price_micros: i64,
+    n_eff: f64,
+    trade_amount_micros: i64,
+    lambda: f64,
+    epsilon: f64,
+    sell_sensitivity_ratio: f64,
+    anchor_micros: i64,
+    reversion_strength: f64,
 ) -> f64 {
     // 1. 数据转换与安全性检查
     let base_price_f64 = (base_price_micros as f64) / MICROS_SCALE;
@@ -35,9 +241,9 @@ fn compute_price_behavioral_core(
     }
 
     // 2. 非对称灵敏度 (Asymmetric Sensitivity)
-    // 逻辑：卖出物品时灵敏度降低(0.6x)，模拟“价格下行粘性”
+    // 逻辑：卖出物品时灵敏度按系数缩放 (默认 0.6x)，模拟“价格下行粘性”
     let adj_lambda = if trade_amount_micros > 0 {
-        lambda * 0.6 
+        lambda * sell_sensitivity_ratio
     } else {
         lambda
     };
@@ -51,9 +257,15 @@ fn compute_price_behavioral_core(
     // 使用 tanh 确保价格曲线在极端工业产出下平滑逼近底价，不会突变为 0
     let clamped_exponent = 10.0 * (raw_exponent / 10.0).tanh();
     
-    let final_price = base_price_f64 * epsilon * clamped_exponent.exp();
-    
-    // 5. 绝对硬底线 (0.01 货币单位)
+    let mut final_price = base_price_f64 * epsilon * clamped_exponent.exp();
+
+    // 5. 锚定价均值回归：按强度线性拉向参考价，抑制纯供需驱动的长期漂移
+    if reversion_strength > 0.0 && reversion_strength.is_finite() {
+        let anchor_f64 = (anchor_micros as f64) / MICROS_SCALE;
+        final_price += (anchor_f64 - final_price) * reversion_strength.min(1.0);
+    }
+
+    // 6. 绝对硬底线 (0.01 货币单位)
     final_price.max(0.01)
 }
 
@@ -69,7 +281,7 @@ pub fn compute_tier_price_internal(
     is_sell: bool
 ) -> f64 {
     // 只有卖出且物品数量超过 500 件时触发阶梯折扣
-    if !is_sell || quantity_f64 <= 500.0 || quantity_f64 <= 0.0 {
+    if !is_sell || quantity_f64 <= TIER1_LIMIT || quantity_f64 <= 0.0 {
         return base_price;
     }
 
@@ -77,35 +289,85 @@ pub fn compute_tier_price_internal(
     let mut remaining = quantity_f64;
 
     // Tier 1: 0 - 500 件 (100% 原始演算价)
-    let t1 = remaining.min(500.0);
+    let t1 = remaining.min(TIER1_LIMIT);
     total_value += t1 * base_price;
     remaining -= t1;
 
     // Tier 2: 501 - 2000 件 (85% 折扣价)
     if remaining > 0.0 {
-        let t2 = remaining.min(1500.0);
-        total_value += t2 * (base_price * 0.85);
+        let t2 = remaining.min(TIER2_LIMIT - TIER1_LIMIT);
+        total_value += t2 * (base_price * TIER2_RATE);
         remaining -= t2;
     }
 
     // Tier 3: 2000 件以上 (60% 深度折扣)
     if remaining > 0.0 {
-        total_value += remaining * (base_price * 0.60);
+        total_value += remaining * (base_price * TIER3_RATE);
     }
 
     total_value / quantity_f64
 }
 
+/// 第 `unit_index` 件 (从 0 计) 卖出物品在 `tiers` 下所处阶梯的折扣率
+#[inline]
+fn tier_rate_for_unit(unit_index: f64, tiers: &TierConfig) -> f64 {
+    if unit_index < tiers.tier1_limit {
+        1.0
+    } else if unit_index < tiers.tier2_limit {
+        tiers.tier2_rate
+    } else {
+        tiers.tier3_rate
+    }
+}
+
+/// 计算一次性卖出全部库存的总收益 (标准 f64 货币单位)
+///
+/// 逐件累加成交价：第 k 件卖出时有效供应量已被前 k 件推高，因此单价随数量递减 (滑点)，
+/// 同时按 `tiers` 叠加阶梯折扣。结果恒不高于 `quantity × 当前单价`。
+pub fn compute_sell_all_value_internal(
+    base_micros: i64,
+    n_eff: f64,
+    quantity: u64,
+    lambda: f64,
+    eps: f64,
+    tiers: &TierConfig,
+) -> f64 {
+    (0..quantity)
+        .map(|k| {
+            let sold_micros = ((k + 1) as f64 * MICROS_SCALE) as i64;
+            compute_price_behavioral_core(base_micros, n_eff, sold_micros, lambda, eps, DEFAULT_SELL_SENSITIVITY_RATIO)
+                * tier_rate_for_unit(k as f64, tiers)
+        })
+        .sum()
+}
+
 /// 包含动态底价保护的最终价格演算
 /// @param hist_avg 物品历史均价 (标准 f64)，用于计算动态地板价
+/// @param min_price_micros 物品硬底价 (i64 Micros)；<= 0 时使用全局 `MIN_PHYSICAL_PRICE`
 pub fn compute_price_bounded_internal(
     base_micros: i64, n_eff: f64, amt_micros: i64, lambda: f64, eps: f64, 
-    hist_avg: f64
+    hist_avg: f64, min_price_micros: i64
+) -> f64 {
+    compute_price_bounded_with_ratio_internal(
+        base_micros, n_eff, amt_micros, lambda, eps, hist_avg, min_price_micros, DEFAULT_SELL_SENSITIVITY_RATIO,
+    )
+}
+
+/// 同 `compute_price_bounded_internal`，卖出灵敏度系数可按市场配置
+#[allow(clippy::too_many_arguments)]
+pub fn compute_price_bounded_with_ratio_internal(
+    base_micros: i64, n_eff: f64, amt_micros: i64, lambda: f64, eps: f64,
+    hist_avg: f64, min_price_micros: i64, sell_sensitivity_ratio: f64,
 ) -> f64 {
-    let raw_price = compute_price_behavioral_core(base_micros, n_eff, amt_micros, lambda, eps);
+    let raw_price = compute_price_behavioral_core(base_micros, n_eff, amt_micros, lambda, eps, sell_sensitivity_ratio);
     
-    // 动态地板价逻辑: 价格不得低于历史均价的 20%，防止市场彻底崩溃
-    let floor = (hist_avg * 0.2).max(0.01);
+    let hard_floor = if min_price_micros > 0 {
+        (min_price_micros as f64 / MICROS_SCALE).max(MIN_PHYSICAL_PRICE)
+    } else {
+        MIN_PHYSICAL_PRICE
+    };
+    // 动态地板价逻辑: 价格不得低于历史均价的 20%，防止市场彻底崩溃；与物品硬底价取较大者
+    let floor = (hist_avg * 0.2).max(hard_floor);
     
     if raw_price < floor {
         floor
@@ -114,21 +376,161 @@ pub fn compute_price_bounded_internal(
     }
 }
 
+/// 影子定价 A/B 对照：以相同市场输入分别按线上 (live) 与候选 (shadow) lambda 计算带底价保护的价格
+///
+/// 仅用于输出对比，不写入任何状态，不影响实际成交价。
+/// @return (live_price, shadow_price)
+pub fn compute_price_ab_internal(
+    base_micros: i64, n_eff: f64, amt_micros: i64,
+    live_lambda: f64, shadow_lambda: f64, eps: f64, hist_avg: f64,
+) -> (f64, f64) {
+    let price = |lambda| compute_price_bounded_internal(base_micros, n_eff, amt_micros, lambda, eps, hist_avg, 0);
+    (price(live_lambda), price(shadow_lambda))
+}
+
+/// 以给定 neff 计算不含本次交易冲击、带动态底价保护的价格 (无物品硬底价)
+#[inline]
+pub fn price_against_neff(base_micros: i64, n_eff: f64, lambda: f64, eps: f64, hist_avg: f64) -> f64 {
+    compute_price_bounded_internal(base_micros, n_eff, 0, lambda, eps, hist_avg, 0)
+}
+
+/// 以数据库演算的权威 neff 定价，适用于不维护热存储的只读副本 / 分析节点
+///
+/// neff 由 `storage::query_neff_from_db` 在读连接池上计算；数据库未初始化时 neff 读数为 0。
+pub fn compute_price_db_neff_internal(
+    base_micros: i64, current_ts: i64, tau: f64, lambda: f64, eps: f64, hist_avg: f64,
+) -> f64 {
+    let n_eff = crate::storage::query_neff_from_db(current_ts, tau);
+    price_against_neff(base_micros, n_eff, lambda, eps, hist_avg)
+}
+
+/// 交易影响预估的恢复容差：价格回到交易前水平 1% 以内即视为恢复
+pub const TRADE_IMPACT_RECOVERY_TOLERANCE: f64 = 0.01;
+
+/// 预估一笔假想交易的市场冲击：成交后的即时价格，以及价格恢复到交易前水平所需时长 (ms)
+///
+/// 交易对 neff 的贡献按 `exp(-t / tau)` 衰减 (`tau` 单位为天)，当其对价格指数的偏移
+/// 不再超过 `TRADE_IMPACT_RECOVERY_TOLERANCE` 时视为恢复。`amount` 为物品件数：正数为卖出。
+/// 参数非有限值、`tau <= 0` 或 `lambda < 0` 时返回 `None`。
+/// @return (new_price, recovery_ms)
+pub fn project_trade_impact_internal(
+    base_micros: i64, current_neff: f64, amount: f64, lambda: f64, tau: f64, eps: f64,
+) -> Option<(f64, i64)> {
+    if !current_neff.is_finite() || !amount.is_finite() || !lambda.is_finite() || !tau.is_finite() || !eps.is_finite() {
+        return None;
+    }
+    if tau <= 0.0 || lambda < 0.0 {
+        return None;
+    }
+
+    let new_price = compute_price_final_internal(base_micros, current_neff + amount, lambda, eps);
+
+    // 价格比 = exp(-lambda * amount * e^(-t/tau))，解出偏移降至容差所需的 t
+    let shift = lambda * amount.abs();
+    let threshold = -(1.0 - TRADE_IMPACT_RECOVERY_TOLERANCE).ln();
+    let recovery_days = if shift <= threshold { 0.0 } else { tau * (shift / threshold).ln() };
+    let recovery_ms = (recovery_days * 86_400_000.0).min(i64::MAX as f64) as i64;
+
+    Some((new_price, recovery_ms))
+}
+
+/// 购买力比值：同一参考物品在 `from_ts` 与 `to_ts` 两个时点的价格之比 (then / now)
+///
+/// 两端价格均基于该时点的全局方向性 neff (含带时间戳远程流量)。比值 < 1 表示单位货币
+/// 现在能买到的更少 (通胀)，> 1 表示购买力上升。
+pub fn compute_purchasing_power_internal(
+    base_micros: i64, from_ts: i64, to_ts: i64, lambda: f64, tau: f64
+) -> f64 {
+    let price_then = compute_price_final_internal(
+        base_micros, summation::query_neff_with_remote_internal(from_ts, tau), lambda, 1.0
+    );
+    let price_now = compute_price_final_internal(
+        base_micros, summation::query_neff_with_remote_internal(to_ts, tau), lambda, 1.0
+    );
+    price_then / price_now
+}
+
 // -----------------------------------------------------------------------------
 // 3. 转发逻辑层 (API Layer)
 // -----------------------------------------------------------------------------
 
 /// 获取单体实时价格 (不包含本次交易的预测冲击)
 pub fn compute_price_final_internal(base_micros: i64, n_eff: f64, lambda: f64, eps: f64) -> f64 {
-    compute_price_behavioral_core(base_micros, n_eff, 0, lambda, eps)
+    compute_price_behavioral_core(base_micros, n_eff, 0, lambda, eps, DEFAULT_SELL_SENSITIVITY_RATIO)
 }
 
 /// 获取单体成交价格 (包含本次物品数量冲击)
 pub fn compute_price_humane_internal(base_micros: i64, n_eff: f64, amt_micros: i64, lambda: f64, eps: f64) -> f64 {
-    compute_price_behavioral_core(base_micros, n_eff, amt_micros, lambda, eps)
+    compute_price_behavioral_core(base_micros, n_eff, amt_micros, lambda, eps, DEFAULT_SELL_SENSITIVITY_RATIO)
+}
+
+/// 市场定价共享内核：带动态底价保护的演算价 (无物品硬底价)，再按预热置信度向基础价混合
+///
+/// 批量定价的逐件演算与单体上下文定价共用此内核，保证同一输入下两者价格一致。
+#[inline]
+fn market_price_core(
+    base_micros: i64, n_eff: f64, amt_micros: i64, lambda: f64, eps: f64, hist_avg: f64, confidence: f64,
+) -> f64 {
+    let price = compute_price_bounded_internal(base_micros, n_eff, amt_micros, lambda, eps, hist_avg, 0);
+    blend_toward_base(price, base_micros, confidence)
+}
+
+/// 由交易上下文定价：环境因子、本次数量冲击、动态底价保护与冷启动预热，卖出时叠加新手卖出加成
+///
+/// `ctx.current_amount` 为正表示卖出；加成按 `ctx.play_time_seconds` 与当前 `NewbieSellBonus` 计算。
+/// `n_eff` 须取自 `market_key` 的热存储分桶，预热样本按同一分桶与 `tau` 统计；
+/// 该市场登记了上线爬坡时，市场价在叠加卖出加成前按 `ctx.current_timestamp` 过渡自首发价。
+pub fn compute_price_from_context_internal(
+    ctx: &TradeContext, cfg: &MarketConfig, market_key: &str, tau: f64, n_eff: f64, lambda: f64, hist_avg: f64,
+) -> f64 {
+    let confidence = market_warmup_confidence(market_key, ctx.current_timestamp, tau);
+    price_from_context(ctx, cfg, n_eff, lambda, hist_avg, confidence, market_launch(market_key), &newbie_sell_bonus())
 }
 
-/// 批量价格演算内核 - 适配 v1.6.0 高精度上下文
+#[allow(clippy::too_many_arguments)]
+fn price_from_context(
+    ctx: &TradeContext, cfg: &MarketConfig, n_eff: f64, lambda: f64, hist_avg: f64, confidence: f64,
+    launch: Option<MarketLaunch>, bonus: &NewbieSellBonus,
+) -> f64 {
+    let epsilon = environment::calculate_epsilon_internal(ctx, cfg);
+    let mut price = market_price_core(ctx.base_price_micros, n_eff, ctx.current_amount, lambda, epsilon, hist_avg, confidence);
+    if let Some(launch) = launch {
+        price = launch.ramp(ctx.current_timestamp, price);
+    }
+    if ctx.current_amount > 0 {
+        price * bonus.multiplier(ctx.play_time_seconds)
+    } else {
+        price
+    }
+}
+
+/// 获取单体成交价格 (包含本次物品数量冲击)，并按 `reversion_strength` 向锚定价 `anchor_micros` 回归
+///
+/// 强度为 0 时与 `compute_price_humane_internal` 完全一致。
+pub fn compute_price_anchored_internal(
+    base_micros: i64, n_eff: f64, amt_micros: i64, lambda: f64, eps: f64, anchor_micros: i64, reversion_strength: f64,
+) -> f64 {
+    compute_price_anchored_core(
+        base_micros, n_eff, amt_micros, lambda, eps, DEFAULT_SELL_SENSITIVITY_RATIO, anchor_micros, reversion_strength,
+    )
+}
+
+/// 获取单体成交价格 (包含本次物品数量冲击)，卖出灵敏度系数可按市场配置
+pub fn compute_price_humane_with_ratio_internal(
+    base_micros: i64, n_eff: f64, amt_micros: i64, lambda: f64, eps: f64, sell_sensitivity_ratio: f64,
+) -> f64 {
+    compute_price_behavioral_core(base_micros, n_eff, amt_micros, lambda, eps, sell_sensitivity_ratio)
+}
+
+/// 批量价格演算内核 - 适配 v1.6.0 高精度上下文 (全部物品共享同一 neff)
+/// @return 本批次中首个 panic 元素的下标 (该元素已写入 `BATCH_PANIC_SENTINEL`)，无失败时为 None
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+/// - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+/// - `hist_avgs_ptr`、`lambdas_ptr` 须指向至少 `count` 个连续的 `f64`；
+/// - `output_ptr` 须可写入至少 `count` 个 `f64`。
 pub unsafe fn compute_batch_prices_internal(
     count: usize,
     neff: f64,
@@ -137,31 +539,464 @@ pub unsafe fn compute_batch_prices_internal(
     hist_avgs_ptr: *const f64,
     lambdas_ptr: *const f64,
     output_ptr: *mut f64,
-) {
+) -> Option<usize> {
+    compute_batch_prices_core(count, |_| neff, ctx_ptr, cfg_ptr, hist_avgs_ptr, lambdas_ptr, output_ptr)
+}
+
+/// 批量价格演算内核 (逐物品 neff)：第 i 个物品使用 `neffs_ptr[i]`，适用于按物品独立统计供应量的市场
+/// @return 本批次中首个 panic 元素的下标，无失败时为 None
+///
+/// # Safety
+///
+/// - `neffs_ptr`、`hist_avgs_ptr`、`lambdas_ptr` 须指向至少 `count` 个连续的 `f64`；
+/// - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+/// - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+/// - `output_ptr` 须可写入至少 `count` 个 `f64`。
+pub unsafe fn compute_batch_prices_per_neff_internal(
+    count: usize,
+    neffs_ptr: *const f64,
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    hist_avgs_ptr: *const f64,
+    lambdas_ptr: *const f64,
+    output_ptr: *mut f64,
+) -> Option<usize> {
+    let neffs = std::slice::from_raw_parts(neffs_ptr, count);
+    compute_batch_prices_core(count, |i| neffs[i], ctx_ptr, cfg_ptr, hist_avgs_ptr, lambdas_ptr, output_ptr)
+}
+
+/// 批量定价中环境因子演算的分块大小 (每块单线程向量化，块间并行)
+const EPSILON_BATCH_CHUNK: usize = 256;
+
+unsafe fn compute_batch_prices_core(
+    count: usize,
+    neff_at: impl Fn(usize) -> f64 + Sync,
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    hist_avgs_ptr: *const f64,
+    lambdas_ptr: *const f64,
+    output_ptr: *mut f64,
+) -> Option<usize> {
     let ctx_slice = std::slice::from_raw_parts(ctx_ptr, count);
     let cfg_slice = std::slice::from_raw_parts(cfg_ptr, count);
     let hist_avgs = std::slice::from_raw_parts(hist_avgs_ptr, count);
     let lambdas = std::slice::from_raw_parts(lambdas_ptr, count);
     let output = std::slice::from_raw_parts_mut(output_ptr, count);
 
+    // 冷启动预热：全局热存储样本不足时价格向基础价回归 (同一批次共享时间戳，仅统计一次)
+    let confidence = match ctx_slice.first() {
+        Some(ctx) => market_warmup_confidence(summation::GLOBAL_MARKET_KEY, ctx.current_timestamp, WARMUP_TAU_DAYS),
+        None => 1.0,
+    };
+
+    // 失败下标仅在本次调用内汇总，并发批次互不覆盖
+    let first_failed = AtomicU64::new(u64::MAX);
+
+    // 环境因子按块批量演算 (AVX2 向量化正弦波与 exp)，块间并行
+    // 块内任一元素 panic 时整块作废，改由下方逐元素隔离区标量演算，坏元素在那里单独记为失败
+    let mut epsilons = vec![0.0; count];
+    let chunk_ok: Vec<bool> = epsilons.par_chunks_mut(EPSILON_BATCH_CHUNK)
+        .zip(ctx_slice.par_chunks(EPSILON_BATCH_CHUNK))
+        .zip(cfg_slice.par_chunks(EPSILON_BATCH_CHUNK))
+        .enumerate()
+        .map(|(_chunk, ((eps_out, ctxs), cfgs))| {
+            panic::catch_unwind(AssertUnwindSafe(|| {
+                #[cfg(test)]
+                if TEST_EPSILON_PANIC_AT_CHUNK.load(Ordering::Relaxed) == _chunk as i64 {
+                    panic!("injected epsilon batch panic");
+                }
+                environment::calculate_epsilon_batch(ctxs, cfgs, eps_out)
+            })).is_ok()
+        })
+        .collect();
+
     // 并行演算，确保在打开商店大菜单时零延迟
+    // 单个元素 panic 时写入哨兵价格并记录下标，其余元素照常完成
     output.par_iter_mut()
         .enumerate()
         .for_each(|(i, price_out)| {
-            let ctx = &ctx_slice[i];
-            let cfg = &cfg_slice[i];
-            let lambda = lambdas[i];
-            let hist_avg = hist_avgs[i];
-
-            let epsilon = environment::calculate_epsilon_internal(ctx, cfg);
-
-            *price_out = compute_price_bounded_internal(
-                ctx.base_price_micros, // 使用适配后的字段名
-                neff, 
-                0, 
-                lambda, 
-                epsilon, 
-                hist_avg
-            );
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                #[cfg(test)]
+                if TEST_PANIC_AT_INDEX.load(Ordering::Relaxed) == i as i64 {
+                    panic!("injected batch element panic");
+                }
+
+                let ctx = &ctx_slice[i];
+                let lambda = lambdas[i];
+                let hist_avg = hist_avgs[i];
+                let epsilon = if chunk_ok[i / EPSILON_BATCH_CHUNK] {
+                    epsilons[i]
+                } else {
+                    environment::calculate_epsilon_internal(ctx, &cfg_slice[i])
+                };
+
+                market_price_core(
+                    ctx.base_price_micros, // 使用适配后的字段名
+                    neff_at(i), 
+                    0, 
+                    lambda, 
+                    epsilon, 
+                    hist_avg,
+                    confidence
+                )
+            }));
+            *price_out = match result {
+                Ok(price) => price,
+                Err(_) => {
+                    if crate::panic_abort_enabled() {
+                        std::process::abort();
+                    }
+                    first_failed.fetch_min(i as u64, Ordering::Relaxed);
+                    BATCH_PANIC_SENTINEL
+                }
+            };
         });
-}
\ No newline at end of file
+
+    match first_failed.into_inner() {
+        u64::MAX => None,
+        idx => Some(idx as usize),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// 单元测试
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newbie_sell_bonus_decays_with_play_time() {
+        let bonus = NewbieSellBonus { threshold_secs: 10 * 3600, factor: 1.2 };
+        assert!((bonus.multiplier(0) - 1.2).abs() < 1e-12);
+        assert!((bonus.multiplier(5 * 3600) - 1.1).abs() < 1e-12);
+        assert_eq!(bonus.multiplier(10 * 3600), 1.0);
+        assert_eq!(bonus.multiplier(200 * 3600), 1.0);
+        assert_eq!(NewbieSellBonus::DISABLED.multiplier(0), 1.0);
+
+        // 随游戏时长单调衰减至 1.0
+        let curve: Vec<f64> = (0..=12).map(|h| bonus.multiplier(h * 3600)).collect();
+        assert!(curve.windows(2).all(|w| w[0] >= w[1]));
+
+        // 仅卖出 (数量为正) 享受加成，环境因子 (含新手买价折扣) 照常生效
+        let cfg = MarketConfig::default();
+        let sell = TradeContext { base_price_micros: 10_000_000, current_amount: 2_000_000, ..Default::default() };
+        let buy = TradeContext { current_amount: -2_000_000, ..sell };
+        let plain = |ctx: &TradeContext| price_from_context(ctx, &cfg, 50.0, 0.01, 0.0, 1.0, None, &NewbieSellBonus::DISABLED);
+        assert!((price_from_context(&sell, &cfg, 50.0, 0.01, 0.0, 1.0, None, &bonus) - plain(&sell) * 1.2).abs() < 1e-12);
+        assert_eq!(price_from_context(&buy, &cfg, 50.0, 0.01, 0.0, 1.0, None, &bonus), plain(&buy));
+        let veteran = TradeContext { play_time_seconds: 20 * 3600, ..sell };
+        assert_eq!(price_from_context(&veteran, &cfg, 50.0, 0.01, 0.0, 1.0, None, &bonus), plain(&veteran));
+
+        assert!(!set_newbie_sell_bonus(NewbieSellBonus { threshold_secs: 3600, factor: 0.9 }));
+        assert!(!set_newbie_sell_bonus(NewbieSellBonus { threshold_secs: -1, factor: 1.2 }));
+        assert!(!set_newbie_sell_bonus(NewbieSellBonus { threshold_secs: 3600, factor: f64::NAN }));
+        assert!(!set_newbie_sell_bonus(NewbieSellBonus { threshold_secs: 3600, factor: f64::INFINITY }));
+        assert!(!NewbieSellBonus { threshold_secs: 3600, factor: NewbieSellBonus::MAX_FACTOR + 1e-9 }.is_valid());
+        assert!(NewbieSellBonus { threshold_secs: 3600, factor: NewbieSellBonus::MAX_FACTOR }.is_valid());
+
+        // 新手身份仅由游戏时长判定，掩码 (bit1 为节庆标记) 不影响加成倍率
+        let festival = TradeContext { newbie_mask: 0b11, ..sell };
+        let plain_festival = price_from_context(&festival, &cfg, 50.0, 0.01, 0.0, 1.0, None, &NewbieSellBonus::DISABLED);
+        assert!((price_from_context(&festival, &cfg, 50.0, 0.01, 0.0, 1.0, None, &bonus) - plain_festival * 1.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_anchor_reversion_pulls_toward_anchor() {
+        let (base, neff, amt, lambda, eps) = (10_000_000, 200.0, 5_000_000, 0.01, 1.0);
+        let anchor = 8_000_000;
+        let raw = compute_price_humane_internal(base, neff, amt, lambda, eps);
+        assert!(raw < 8.0);
+
+        // 强度为 0 时与原行为逐位一致
+        assert_eq!(compute_price_anchored_internal(base, neff, amt, lambda, eps, anchor, 0.0), raw);
+
+        let mut prev_gap = (raw - 8.0).abs();
+        for strength in [0.25, 0.5, 0.75] {
+            let price = compute_price_anchored_internal(base, neff, amt, lambda, eps, anchor, strength);
+            let gap = (price - 8.0).abs();
+            assert!(gap < prev_gap, "strength={} price={}", strength, price);
+            assert!((price - (raw + (8.0 - raw) * strength)).abs() < 1e-12);
+            prev_gap = gap;
+        }
+        assert!((compute_price_anchored_internal(base, neff, amt, lambda, eps, anchor, 1.0) - 8.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sell_all_value_includes_slippage() {
+        let base_micros = 10_000_000; // 10.0
+        let (n_eff, lambda, eps) = (50.0, 0.002, 1.0);
+        let tiers = TierConfig::default();
+        let unit_price = compute_price_final_internal(base_micros, n_eff, lambda, eps);
+
+        // 数量未触发阶梯折扣时，差额完全来自供应冲击带来的滑点
+        let qty = 400;
+        let total = compute_sell_all_value_internal(base_micros, n_eff, qty, lambda, eps, &tiers);
+        let naive = qty as f64 * unit_price;
+        assert!(total < naive, "total={} naive={}", total, naive);
+        assert!(total > 0.0);
+
+        // 跨越阶梯后叠加折扣，总收益仍随数量单调递增
+        let larger = compute_sell_all_value_internal(base_micros, n_eff, 2_500, lambda, eps, &tiers);
+        assert!(larger > total);
+        assert!(larger < 2_500.0 * unit_price * compute_tier_price_internal(1.0, 2_500.0, true));
+
+        // 调用方自定义阶梯：不打折时收益更高，但滑点仍使其低于朴素估算
+        let flat = TierConfig { tier2_rate: 1.0, tier3_rate: 1.0, ..tiers };
+        let undiscounted = compute_sell_all_value_internal(base_micros, n_eff, 2_500, lambda, eps, &flat);
+        assert!(undiscounted > larger);
+        assert!(undiscounted < 2_500.0 * unit_price);
+        let early = TierConfig { tier1_limit: 100.0, ..tiers };
+        assert!(compute_sell_all_value_internal(base_micros, n_eff, qty, lambda, eps, &early) < total);
+
+        assert_eq!(compute_sell_all_value_internal(base_micros, n_eff, 0, lambda, eps, &tiers), 0.0);
+
+        assert!(tiers.is_valid());
+        assert!(!TierConfig { tier2_limit: 100.0, ..tiers }.is_valid());
+        assert!(!TierConfig { tier2_rate: 0.0, ..tiers }.is_valid());
+        assert!(!TierConfig { tier3_rate: 1.5, ..tiers }.is_valid());
+        assert!(!TierConfig { tier1_limit: f64::NAN, ..tiers }.is_valid());
+    }
+
+    #[test]
+    fn test_price_ab_diverges_only_with_different_lambdas() {
+        let (base_micros, n_eff, amt_micros, eps, hist_avg) = (10_000_000, 200.0, 5_000_000, 1.0, 0.0);
+
+        let (live, shadow) = compute_price_ab_internal(base_micros, n_eff, amt_micros, 0.002, 0.004, eps, hist_avg);
+        assert!((live - shadow).abs() > 1e-9, "live={} shadow={}", live, shadow);
+        assert_eq!(live, compute_price_bounded_internal(base_micros, n_eff, amt_micros, 0.002, eps, hist_avg, 0));
+
+        let (live, shadow) = compute_price_ab_internal(base_micros, n_eff, amt_micros, 0.002, 0.002, eps, hist_avg);
+        assert_eq!(live, shadow);
+    }
+
+    #[test]
+    fn test_larger_trade_drops_price_more_and_recovers_slower() {
+        let (base_micros, n_eff, lambda, tau, eps) = (10_000_000, 100.0, 0.002, 7.0, 1.0);
+        let pre_price = compute_price_final_internal(base_micros, n_eff, lambda, eps);
+
+        let (small_price, small_ms) = project_trade_impact_internal(base_micros, n_eff, 100.0, lambda, tau, eps).unwrap();
+        let (large_price, large_ms) = project_trade_impact_internal(base_micros, n_eff, 1_000.0, lambda, tau, eps).unwrap();
+        assert!(small_price < pre_price, "pre={} small={}", pre_price, small_price);
+        assert!(large_price < small_price, "small={} large={}", small_price, large_price);
+        assert!(large_ms > small_ms && small_ms > 0, "small={}ms large={}ms", small_ms, large_ms);
+
+        // 冲击低于容差时视为即时恢复
+        assert_eq!(project_trade_impact_internal(base_micros, n_eff, 1.0, lambda, tau, eps).unwrap().1, 0);
+        assert!(project_trade_impact_internal(base_micros, n_eff, 100.0, lambda, 0.0, eps).is_none());
+    }
+
+    #[test]
+    fn test_purchasing_power_drops_in_inflating_market() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        summation::reset_state();
+        let day = 86_400_000;
+        let (from, to) = (300 * day, 310 * day);
+        let (lambda, tau) = (0.002, 7.0);
+
+        // 持续净买入：供应被抽干，参考物品价格随时间上涨
+        for d in 0..10 {
+            assert!(summation::append_trade_to_memory(from + d * day + 1, -50.0, "inflate:item"));
+        }
+        let ratio = compute_purchasing_power_internal(10_000_000, from, to, lambda, tau);
+        assert!(ratio < 1.0, "ratio={}", ratio);
+
+        // 同一时点比值恒为 1
+        let same = compute_purchasing_power_internal(10_000_000, to, to, lambda, tau);
+        assert!((same - 1.0).abs() < 1e-12);
+        summation::reset_state();
+    }
+
+    #[test]
+    fn test_batch_isolates_panicking_element() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let ctx = [TradeContext { base_price_micros: 10_000_000, ..Default::default() }; 4];
+        let cfg = [MarketConfig::default(); 4];
+        let (hist_avgs, lambdas) = ([10.0; 4], [0.01; 4]);
+        let mut out = [-1.0; 4];
+
+        TEST_PANIC_AT_INDEX.store(2, Ordering::Relaxed);
+        let failed = unsafe {
+            compute_batch_prices_internal(4, 0.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), out.as_mut_ptr())
+        };
+        TEST_PANIC_AT_INDEX.store(-1, Ordering::Relaxed);
+
+        assert_eq!(out[2], BATCH_PANIC_SENTINEL);
+        assert_eq!(failed, Some(2));
+        for i in [0, 1, 3] {
+            assert!(out[i] > BATCH_PANIC_SENTINEL, "out[{}]={}", i, out[i]);
+        }
+
+        // 无失败的批次不报告下标
+        let failed = unsafe {
+            compute_batch_prices_internal(4, 0.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), out.as_mut_ptr())
+        };
+        assert_eq!(failed, None);
+        assert!(out[2] > BATCH_PANIC_SENTINEL);
+    }
+
+    #[test]
+    fn test_batch_recovers_from_epsilon_chunk_panic() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let count = EPSILON_BATCH_CHUNK + 4;
+        let ctx: Vec<_> = (0..count)
+            .map(|i| TradeContext { base_price_micros: 10_000_000, current_timestamp: 1_700_000_000_000 + i as i64 * 3_600_000, ..Default::default() })
+            .collect();
+        let cfg = vec![MarketConfig::default(); count];
+        let (hist_avgs, lambdas) = (vec![10.0; count], vec![0.01; count]);
+        let (mut clean, mut out) = (vec![0.0; count], vec![-1.0; count]);
+
+        let run = |out: &mut Vec<f64>| unsafe {
+            compute_batch_prices_internal(count, 5.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), out.as_mut_ptr())
+        };
+        assert_eq!(run(&mut clean), None);
+
+        // 首块的批量环境因子演算 panic：该块回退标量演算，整批照常完成且无失败元素
+        TEST_EPSILON_PANIC_AT_CHUNK.store(0, Ordering::Relaxed);
+        let failed = run(&mut out);
+        TEST_EPSILON_PANIC_AT_CHUNK.store(-1, Ordering::Relaxed);
+
+        assert_eq!(failed, None);
+        for i in 0..count {
+            assert!((out[i] - clean[i]).abs() <= 1e-9 * clean[i].abs(), "i={} out={} clean={}", i, out[i], clean[i]);
+        }
+    }
+
+    #[test]
+    fn test_sell_sensitivity_ratio_scales_sell_impact_only() {
+        let (base_micros, n_eff, lambda, eps) = (10_000_000, 50.0, 0.01, 1.0);
+        let sell = 100_000_000;
+
+        // 默认系数与原接口一致
+        assert_eq!(
+            compute_price_humane_with_ratio_internal(base_micros, n_eff, sell, lambda, eps, DEFAULT_SELL_SENSITIVITY_RATIO),
+            compute_price_humane_internal(base_micros, n_eff, sell, lambda, eps)
+        );
+
+        // 系数越高，卖出冲击越大 (粘性越弱)
+        let sticky = compute_price_humane_with_ratio_internal(base_micros, n_eff, sell, lambda, eps, 0.3);
+        let elastic = compute_price_humane_with_ratio_internal(base_micros, n_eff, sell, lambda, eps, 1.5);
+        assert!(elastic < sticky, "sticky={} elastic={}", sticky, elastic);
+
+        // 买入不受系数影响
+        let buy_a = compute_price_humane_with_ratio_internal(base_micros, n_eff, -sell, lambda, eps, 0.3);
+        let buy_b = compute_price_humane_with_ratio_internal(base_micros, n_eff, -sell, lambda, eps, 1.5);
+        assert_eq!(buy_a, buy_b);
+
+        assert!(validate_sell_sensitivity_ratio(2.0));
+        assert!(!validate_sell_sensitivity_ratio(0.0));
+        assert!(!validate_sell_sensitivity_ratio(2.01));
+        assert!(!validate_sell_sensitivity_ratio(f64::NAN));
+    }
+
+    #[test]
+    fn test_per_neff_batch_matches_shared_neff() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let ctx: Vec<TradeContext> = (1..=5)
+            .map(|i| TradeContext { base_price_micros: i * 10_000_000, current_timestamp: 1_736_851_200_000, ..Default::default() })
+            .collect();
+        let cfg = [MarketConfig::default(); 5];
+        let (hist_avgs, lambdas) = ([10.0, 0.0, 30.0, 0.0, 50.0], [0.01, 0.02, 0.005, 0.01, 0.03]);
+        let (mut shared, mut per_item) = ([0.0; 5], [0.0; 5]);
+
+        unsafe {
+            compute_batch_prices_internal(5, 42.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), shared.as_mut_ptr());
+            compute_batch_prices_per_neff_internal(5, [42.0; 5].as_ptr(), ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), per_item.as_mut_ptr());
+        }
+        assert_eq!(shared, per_item);
+
+        // 各物品 neff 不同时仅影响对应物品
+        let mut neffs = [42.0; 5];
+        neffs[3] = 400.0;
+        unsafe {
+            compute_batch_prices_per_neff_internal(5, neffs.as_ptr(), ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), per_item.as_mut_ptr());
+        }
+        assert!(per_item[3] < shared[3]);
+        for i in [0, 1, 2, 4] {
+            assert_eq!(per_item[i], shared[i]);
+        }
+    }
+
+    #[test]
+    fn test_launch_ramp_interpolates_toward_computed_price() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let key = "launch:test_item";
+        let launch = MarketLaunch { launch_ts: 1_000_000, ramp_ms: 10_000, launch_price_micros: 50_000_000 };
+        assert!(set_market_launch(key, launch));
+
+        let computed = 10.0;
+        assert_eq!(apply_launch_ramp(key, 1_000_000, computed), 50.0);
+        assert_eq!(apply_launch_ramp(key, 1_002_500, computed), 40.0);
+        assert_eq!(apply_launch_ramp(key, 1_005_000, computed), 30.0);
+        assert_eq!(apply_launch_ramp(key, 1_010_000, computed), computed);
+        // 上线前按首发价；未登记市场不受影响
+        assert_eq!(apply_launch_ramp(key, 900_000, computed), 50.0);
+        assert_eq!(apply_launch_ramp("launch:other", 1_000_000, computed), computed);
+
+        // ramp_ms <= 0 移除设置
+        assert!(set_market_launch(key, MarketLaunch { ramp_ms: 0, ..launch }));
+        assert_eq!(apply_launch_ramp(key, 1_000_000, computed), computed);
+        assert!(!set_market_launch(key, MarketLaunch { launch_price_micros: 0, ..launch }));
+    }
+
+    #[test]
+    fn test_warmup_blends_toward_base() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        summation::reset_state();
+        let now = 500 * 86_400_000;
+        let ctx = TradeContext { base_price_micros: 10_000_000, current_timestamp: now, ..Default::default() };
+        let cfg = MarketConfig { volatility_factor: 3.0, ..Default::default() };
+        let price_with = |samples: usize| {
+            summation::reset_state();
+            for i in 0..samples {
+                assert!(summation::append_trade_to_memory(now - i as i64, 1.0, "warmup:item"));
+            }
+            let mut out = 0.0;
+            unsafe { compute_batch_prices_internal(1, 0.0, &ctx, &cfg, &0.0, &0.01, &mut out) };
+            out
+        };
+
+        // 未启用时保持原演算价
+        let raw = price_with(0);
+        assert!((raw - 10.0).abs() > 1e-6, "测试前提: 演算价需偏离基础价");
+
+        // 最少 10 个样本：0 样本完全回归基础价，5 个样本位于中点，10 个样本恢复演算价
+        set_min_samples(10);
+        assert!((price_with(0) - 10.0).abs() < 1e-9);
+        assert!((price_with(5) - (10.0 + (raw - 10.0) * 0.5)).abs() < 1e-9);
+        assert!((price_with(10) - raw).abs() < 1e-9);
+
+        reset_state();
+        summation::reset_state();
+    }
+
+    #[test]
+    fn test_context_warmup_scoped_to_market_and_tau() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        summation::reset_state();
+        let day = 86_400_000_i64;
+        let now = 500 * day;
+        // 样本均在 30 天前：tau = 7 的回溯窗口可见，tau = 1 的窗口不可见
+        for i in 0..10 {
+            assert!(summation::append_trade_to_memory(now - 30 * day - i, 1.0, "warmup:a"));
+        }
+        let ctx = TradeContext { base_price_micros: 10_000_000, current_timestamp: now, ..Default::default() };
+        let cfg = MarketConfig { volatility_factor: 3.0, ..Default::default() };
+        let price = |key: &str, tau: f64| compute_price_from_context_internal(&ctx, &cfg, key, tau, 0.0, 0.01, 0.0);
+
+        let raw = price("warmup:a", 7.0);
+        assert!((raw - 10.0).abs() > 1e-6, "测试前提: 演算价需偏离基础价");
+
+        set_min_samples(10);
+        assert!((market_warmup_confidence("warmup:a", now, 7.0) - 1.0).abs() < 1e-12);
+        assert!((price("warmup:a", 7.0) - raw).abs() < 1e-9);
+        // 其它市场没有样本，完全回归基础价
+        assert!((price("warmup:b", 7.0) - 10.0).abs() < 1e-9);
+        // 调用方 tau 更短时旧样本不计入
+        assert!((price("warmup:a", 1.0) - 10.0).abs() < 1e-9);
+
+        reset_state();
+        summation::reset_state();
+    }
+}
@@ -10,10 +10,11 @@
 //! - [v1.6.0] 适配 i64 Micros 定点数协议，消除浮点累积误差。
 //! - [v1.1] 优化: 使用二分查找降至 O(logN + M)。
 
-use crate::models::HistoryRecord;
+use crate::models::{to_micros_checked, HistoryRecord};
 use crate::storage;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicI32, AtomicI64, AtomicU64, Ordering};
 use lazy_static::lazy_static;
 
 #[cfg(target_arch = "x86_64")]
@@ -26,23 +27,66 @@ use rayon::prelude::*;
 
 const PARALLEL_THRESHOLD: usize = 750;
 const MS_PER_DAY: f64 = 86_400_000.0;
-const MAX_FUTURE_TOLERANCE: i64 = 60_000;
+pub const DEFAULT_FUTURE_TOLERANCE_MS: i64 = 60_000;
+pub const DEFAULT_LOOKBACK_MULTIPLIER: f64 = 10.0;
 const MICROS_SCALE: f64 = 1_000_000.0; // [v1.6.0] 精度缩放因子
-const GLOBAL_MARKET_KEY: &str = "__global__";
+pub const GLOBAL_MARKET_KEY: &str = "__global__";
 
 // 内存管理阈值
 const MAX_HISTORY_SIZE: usize = 500_000;
 const PRUNE_TO_SIZE: usize = 400_000;
+pub const DEFAULT_DOWNSAMPLE_AGE_MS: i64 = 7 * 86_400_000;
+
+/// 带时间戳的远程流量缓冲区容量，超出后按当前裁剪策略收缩至 80%
+pub const REMOTE_HISTORY_CAP: usize = 65_536;
+
+/// neff 置信度的样本项半饱和点：Kish 有效样本数达到该值时样本项为 0.5
+pub const NEFF_CONFIDENCE_HALF_SAMPLES: f64 = 10.0;
+
+/// 热存储超限时的裁剪策略
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PrunePolicy {
+    /// 直接丢弃最旧记录 (默认)
+    DropOldest = 0,
+    /// 对超过年龄阈值的旧记录两两合并 (数量相加)，以更低分辨率保留更长历史
+    Downsample = 1,
+}
+
+impl PrunePolicy {
+    pub fn from_raw(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::DropOldest),
+            1 => Some(Self::Downsample),
+            _ => None,
+        }
+    }
+}
+
+static PRUNE_POLICY: AtomicI32 = AtomicI32::new(PrunePolicy::DropOldest as i32);
+static DOWNSAMPLE_AGE_MS: AtomicI64 = AtomicI64::new(DEFAULT_DOWNSAMPLE_AGE_MS);
+
+// 衰减窗口参数：允许的未来时钟偏差 (ms) 与回溯长度 (tau 的倍数，以 f64 位模式存储)
+static FUTURE_TOLERANCE_MS: AtomicI64 = AtomicI64::new(DEFAULT_FUTURE_TOLERANCE_MS);
+static LOOKBACK_MULTIPLIER_BITS: AtomicU64 = AtomicU64::new(DEFAULT_LOOKBACK_MULTIPLIER.to_bits());
 
 // ==================== 全局内存态 (Hot Memory Layer) ====================
 
 lazy_static! {
+    /// 按市场键分桶的热存储
+    ///
+    /// 不变量：每个桶均按时间戳非降序排列 (同一时间戳保持写入顺序)。
+    /// `calculate_volume_in_memory` 以 `partition_point` 二分定位衰减窗口，乱序记录会使窗口外的记录被计入、
+    /// 窗口内的记录被漏算。所有写入路径必须维持该顺序：批量写入经 `merge_sorted_history` 归并，
+    /// 逐条追加时晚到的记录按时间戳二分插入。
     static ref HOT_HISTORY_BY_KEY: RwLock<HashMap<String, Vec<HistoryRecord>>> = RwLock::new(HashMap::new());
+    /// 带时间戳的远程流量 (联邦节点重连后的积压回放)，与本地历史同样按时间衰减
+    static ref REMOTE_HISTORY: RwLock<Vec<HistoryRecord>> = RwLock::new(Vec::new());
 }
 
 /// 初始化加载逻辑 (服务器启动时调用)
 pub fn hydrate_hot_store() {
-    let records_by_key = storage::load_recent_market_history_by_key(30);
+    let records_by_key = storage::load_recent_market_history_by_key(storage::preload_days());
     let total: usize = records_by_key.values().map(Vec::len).sum();
     let markets = records_by_key.len();
 
@@ -56,19 +100,35 @@ pub fn hydrate_hot_store() {
 }
 
 /// 实时双写逻辑
+///
+/// 按时间戳单调到达时直接追加到桶尾；晚到的较早记录以二分查找插入到正确位置
+/// (同一时间戳保持到达顺序)，以维持热存储的有序不变量，代价是移动其后的记录 (O(N))。
 /// @param amount 这里的 amount 为原始 double，内部转换为 i64 Micros 存储
-pub fn append_trade_to_memory(ts: i64, amount: f64, market_key: &str) {
+/// @return 金额超出 i64 Micros 可表示范围时返回 false 且不写入
+pub fn append_trade_to_memory(ts: i64, amount: f64, market_key: &str) -> bool {
+    let amount_micros = match to_micros_checked(amount) {
+        Some(v) => v,
+        None => return false,
+    };
+    let policy = PrunePolicy::from_raw(PRUNE_POLICY.load(Ordering::Relaxed)).unwrap_or(PrunePolicy::DropOldest);
+    let downsample_age_ms = DOWNSAMPLE_AGE_MS.load(Ordering::Relaxed);
     let mut lock = HOT_HISTORY_BY_KEY.write().unwrap();
 
-    let amount_micros = (amount * MICROS_SCALE) as i64;
     let push_record = |bucket: &mut Vec<HistoryRecord>| {
-        bucket.push(HistoryRecord {
+        let record = HistoryRecord {
             timestamp: ts,
             amount_micros,
-        });
+        };
+        match bucket.last() {
+            // 同一时间戳的记录保持到达顺序，插入到相等区段之后
+            Some(last) if last.timestamp > ts => {
+                let idx = bucket.partition_point(|r| r.timestamp <= ts);
+                bucket.insert(idx, record);
+            }
+            _ => bucket.push(record),
+        }
         if bucket.len() > MAX_HISTORY_SIZE {
-            let remove_count = bucket.len() - PRUNE_TO_SIZE;
-            bucket.drain(0..remove_count);
+            prune_history(bucket, PRUNE_TO_SIZE, policy, downsample_age_ms);
         }
     };
 
@@ -78,6 +138,152 @@ pub fn append_trade_to_memory(ts: i64, amount: f64, market_key: &str) {
     // Keep a global aggregate key for compatibility and diagnostics.
     let global_bucket = lock.entry(GLOBAL_MARKET_KEY.to_string()).or_insert_with(|| Vec::with_capacity(4096));
     push_record(global_bucket);
+    true
+}
+
+/// 将批量历史记录 (如归档导入) 合并进全局热存储，归并规则同 `merge_sorted_history`
+pub fn merge_global_history(records: Vec<HistoryRecord>) {
+    merge_sorted_history(&records);
+}
+
+/// 批量注入历史交易 (如回测重放) 到全局热存储，不经过 DuckDB
+///
+/// 整批只获取一次写锁，避免逐条调用 `append_trade_to_memory` 反复加锁。
+/// 乱序批次先按时间戳稳定排序；批次早于已有记录时与之归并 (同一时间戳下已有记录在前)。
+/// 注入后超出容量时按当前裁剪策略处理。返回注入条数。
+pub fn bulk_inject(records: &[HistoryRecord]) -> usize {
+    merge_sorted_history(records)
+}
+
+/// 将联邦节点的历史批次归并进全局热存储，保持按时间戳排序的不变量
+///
+/// 单次写锁内完成：乱序批次先稳定排序，再与已有记录自尾部原地归并 (O(N + M))，
+/// 同一时间戳下已有记录在前。归并后超出容量时按当前裁剪策略处理。返回归并条数。
+pub fn merge_sorted_history(peer: &[HistoryRecord]) -> usize {
+    if peer.is_empty() {
+        return 0;
+    }
+    let mut batch = peer.to_vec();
+    if !batch.is_sorted_by_key(|r| r.timestamp) {
+        batch.sort_by_key(|r| r.timestamp);
+    }
+    let policy = PrunePolicy::from_raw(PRUNE_POLICY.load(Ordering::Relaxed)).unwrap_or(PrunePolicy::DropOldest);
+    let downsample_age_ms = DOWNSAMPLE_AGE_MS.load(Ordering::Relaxed);
+
+    let mut lock = HOT_HISTORY_BY_KEY.write().unwrap();
+    let bucket = lock.entry(GLOBAL_MARKET_KEY.to_string()).or_insert_with(|| Vec::with_capacity(batch.len()));
+    if bucket.last().is_none_or(|last| last.timestamp <= batch[0].timestamp) {
+        bucket.extend(batch);
+    } else {
+        merge_from_back(bucket, &batch);
+    }
+    if bucket.len() > MAX_HISTORY_SIZE {
+        prune_history(bucket, PRUNE_TO_SIZE, policy, downsample_age_ms);
+    }
+    peer.len()
+}
+
+/// 将有序批次原地归并进有序历史：扩容后自尾部双指针回填，相等时间戳下已有记录在前
+fn merge_from_back(history: &mut Vec<HistoryRecord>, batch: &[HistoryRecord]) {
+    let mut i = history.len();
+    let mut j = batch.len();
+    history.resize(i + j, HistoryRecord::default());
+    let mut k = history.len();
+    while j > 0 {
+        k -= 1;
+        if i > 0 && history[i - 1].timestamp > batch[j - 1].timestamp {
+            i -= 1;
+            history[k] = history[i];
+        } else {
+            j -= 1;
+            history[k] = batch[j];
+        }
+    }
+}
+
+/// 设置热存储裁剪策略；`downsample_age_ms` 为降采样的年龄阈值 (相对最新记录)，须为正数
+pub fn set_prune_policy(policy: PrunePolicy, downsample_age_ms: i64) -> bool {
+    if downsample_age_ms <= 0 {
+        return false;
+    }
+    PRUNE_POLICY.store(policy as i32, Ordering::Relaxed);
+    DOWNSAMPLE_AGE_MS.store(downsample_age_ms, Ordering::Relaxed);
+    true
+}
+
+/// 设置 neff 衰减窗口参数
+///
+/// - `future_tolerance_ms`: 晚于 `current_ts` 多少毫秒的记录仍计入 (时钟漂移容忍)，须 >= 0。
+/// - `lookback_multiplier`: 回溯窗口为 `tau` 天的多少倍，须为有限值且 >= 1.0。
+pub fn set_window_params(future_tolerance_ms: i64, lookback_multiplier: f64) -> bool {
+    if future_tolerance_ms < 0 || !lookback_multiplier.is_finite() || lookback_multiplier < 1.0 {
+        return false;
+    }
+    FUTURE_TOLERANCE_MS.store(future_tolerance_ms, Ordering::Relaxed);
+    LOOKBACK_MULTIPLIER_BITS.store(lookback_multiplier.to_bits(), Ordering::Relaxed);
+    true
+}
+
+/// 计算当前窗口参数下的 (future_limit, past_limit)
+#[inline]
+fn window_limits(current_time: i64, tau: f64) -> (i64, i64) {
+    let future_tolerance = FUTURE_TOLERANCE_MS.load(Ordering::Relaxed);
+    let lookback = f64::from_bits(LOOKBACK_MULTIPLIER_BITS.load(Ordering::Relaxed));
+    (
+        current_time.saturating_add(future_tolerance),
+        current_time.saturating_sub((tau * MS_PER_DAY * lookback) as i64),
+    )
+}
+
+/// 将历史裁剪至 `target_len` 条以内
+///
+/// - `DropOldest`: 丢弃最旧的记录。
+/// - `Downsample`: 对早于 (最新时间戳 - `downsample_age_ms`) 的旧记录反复两两合并，
+///   合并后保留较早的时间戳、数量相加，总供应量不变；旧区间无法再合并时回退为丢弃最旧记录。
+pub fn prune_history(
+    history: &mut Vec<HistoryRecord>,
+    target_len: usize,
+    policy: PrunePolicy,
+    downsample_age_ms: i64,
+) {
+    if history.len() <= target_len {
+        return;
+    }
+
+    if policy == PrunePolicy::Downsample {
+        let newest_ts = history.last().map(|r| r.timestamp).unwrap_or(0);
+        let age_cutoff = newest_ts.saturating_sub(downsample_age_ms);
+
+        while history.len() > target_len {
+            let old_len = history.partition_point(|r| r.timestamp < age_cutoff);
+            if old_len < 2 {
+                break;
+            }
+            let merged: Vec<HistoryRecord> = history[..old_len]
+                .chunks(2)
+                .map(|pair| HistoryRecord {
+                    timestamp: pair[0].timestamp,
+                    amount_micros: pair.iter().fold(0i64, |acc, r| acc.saturating_add(r.amount_micros)),
+                })
+                .collect();
+            history.splice(..old_len, merged);
+        }
+    }
+
+    if history.len() > target_len {
+        let remove_count = history.len() - target_len;
+        history.drain(0..remove_count);
+    }
+}
+
+/// 清空热存储并恢复默认裁剪策略与窗口参数 (插件重载时由 `ecobridge_reset_all_state` 调用)
+pub fn reset_state() {
+    HOT_HISTORY_BY_KEY.write().unwrap_or_else(|e| e.into_inner()).clear();
+    REMOTE_HISTORY.write().unwrap_or_else(|e| e.into_inner()).clear();
+    PRUNE_POLICY.store(PrunePolicy::DropOldest as i32, Ordering::Relaxed);
+    DOWNSAMPLE_AGE_MS.store(DEFAULT_DOWNSAMPLE_AGE_MS, Ordering::Relaxed);
+    FUTURE_TOLERANCE_MS.store(DEFAULT_FUTURE_TOLERANCE_MS, Ordering::Relaxed);
+    LOOKBACK_MULTIPLIER_BITS.store(DEFAULT_LOOKBACK_MULTIPLIER.to_bits(), Ordering::Relaxed);
 }
 
 // ==================== 核心接口 ====================
@@ -98,63 +304,633 @@ pub fn query_neff_global_internal(current_ts: i64, tau: f64) -> f64 {
     query_neff_internal(current_ts, tau, GLOBAL_MARKET_KEY)
 }
 
+/// 定价口径的全局有效供应量：本地热存储 + 带时间戳远程流量的衰减量 (不消费远程流量累加器)
+///
+/// 全局 neff 查询、置信度查询与一站式定价共用此口径，保证价格与上报的 neff 一致。
+pub fn query_neff_with_remote_internal(current_ts: i64, tau: f64) -> f64 {
+    query_neff_global_internal(current_ts, tau) + query_remote_neff(current_ts, tau)
+}
+
+/// 记录一笔带成交时间戳的远程流量 (Micros)
+///
+/// 与直接累加不同，远程流量按 `ts` 参与衰减：重连后一次性回放的旧积压只按其年龄计入 neff。
+/// 乱序到达的记录按时间戳插入；缓冲区超过 `REMOTE_HISTORY_CAP` 时按当前裁剪策略收缩。
+pub fn append_remote_trade(ts: i64, amount_micros: i64) {
+    let policy = PrunePolicy::from_raw(PRUNE_POLICY.load(Ordering::Relaxed)).unwrap_or(PrunePolicy::DropOldest);
+    let downsample_age_ms = DOWNSAMPLE_AGE_MS.load(Ordering::Relaxed);
+    let mut history = REMOTE_HISTORY.write().unwrap_or_else(|e| e.into_inner());
+    let idx = history.partition_point(|r| r.timestamp <= ts);
+    history.insert(idx, HistoryRecord { timestamp: ts, amount_micros });
+    if history.len() > REMOTE_HISTORY_CAP {
+        prune_history(&mut history, REMOTE_HISTORY_CAP / 5 * 4, policy, downsample_age_ms);
+    }
+}
+
+/// 带时间戳远程流量的有效供应量 (方向累积，与本地历史使用相同的衰减窗口)
+pub fn query_remote_neff(current_ts: i64, tau: f64) -> f64 {
+    let history = REMOTE_HISTORY.read().unwrap_or_else(|e| e.into_inner());
+    calculate_volume_in_memory(&history, current_ts, tau)
+}
+
+/// 统计指定市场热存储分桶在当前衰减窗口 (回溯与未来容差) 内的记录条数
+pub fn sample_count_in_window(market_key: &str, current_ts: i64, tau: f64) -> usize {
+    if tau <= 0.0 || !tau.is_finite() {
+        return 0;
+    }
+    let (future_limit, past_limit) = window_limits(current_ts, tau);
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    match lock.get(market_key) {
+        Some(history) => {
+            let start = history.partition_point(|r| r.timestamp < past_limit);
+            history[start..].iter().filter(|r| r.timestamp <= future_limit).count()
+        }
+        None => 0,
+    }
+}
+
+/// 统计全局热存储在闭区间 [start_ts, end_ts] 内的原始 (不衰减) 成交量，单位为标准货币
+///
+/// 成交量按绝对值累计，与默认 neff 口径一致；区间两端均以二分查找定位。
+pub fn volume_in_range(start_ts: i64, end_ts: i64) -> f64 {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    match lock.get(GLOBAL_MARKET_KEY) {
+        Some(history) => raw_volume_in_range(history, start_ts, end_ts),
+        None => 0.0,
+    }
+}
+
+fn raw_volume_in_range(history: &[HistoryRecord], start_ts: i64, end_ts: i64) -> f64 {
+    if start_ts > end_ts {
+        return 0.0;
+    }
+    let start = history.partition_point(|r| r.timestamp < start_ts);
+    let end = history.partition_point(|r| r.timestamp <= end_ts);
+    let total = neumaier_sum(
+        history[start..end].iter().map(|r| record_amount(r, NeffMode::Absolute)),
+    );
+    total / MICROS_SCALE
+}
+
+/// 按调用方给定的分桶边界统计全局热存储的单笔成交量分布 (标准货币，取绝对值)
+///
+/// `n + 1` 个边界划分 `n` 个桶 `[e_i, e_{i+1})`，最后一个桶包含右边界；超出 `[e_0, e_n]` 的记录不计入。
+/// 边界少于 2 个、含非有限值或未严格递增时返回 None。
+pub fn amount_histogram(bucket_edges: &[f64]) -> Option<Vec<u64>> {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    let history = lock.get(GLOBAL_MARKET_KEY).map_or(&[][..], |h| h.as_slice());
+    bin_amounts(history, bucket_edges)
+}
+
+fn bin_amounts(history: &[HistoryRecord], edges: &[f64]) -> Option<Vec<u64>> {
+    if edges.len() < 2 || edges.iter().any(|e| !e.is_finite()) || edges.windows(2).any(|w| w[0] >= w[1]) {
+        return None;
+    }
+    let buckets = edges.len() - 1;
+    let (lo, hi) = (edges[0], edges[buckets]);
+    let mut counts = vec![0u64; buckets];
+    for rec in history {
+        let amount = record_amount(rec, NeffMode::Absolute) / MICROS_SCALE;
+        if amount < lo || amount > hi {
+            continue;
+        }
+        let idx = edges.partition_point(|&e| e <= amount).saturating_sub(1).min(buckets - 1);
+        counts[idx] += 1;
+    }
+    Some(counts)
+}
+
+/// 单条记录在给定年龄下的衰减权重：`exp(-age_ms / (tau · MS_PER_DAY))`
+///
+/// 与 neff 累积使用的权重完全一致，供调用方在外部复现聚合逻辑。
+#[inline]
+pub fn decay_weight(age_ms: i64, tau: f64) -> f64 {
+    (-(age_ms as f64) / (tau * MS_PER_DAY)).exp()
+}
+
+/// 由两次 neff 观测反推衰减常数 tau (天)
+///
+/// 求解 `neff_t2 = neff_t1 · exp(-dt / (tau · MS_PER_DAY))`，要求两次观测之间没有新成交。
+/// 两次读数须同号且 |neff_t2| < |neff_t1| (观测到衰减)，`dt_ms` 须为正，否则返回 None。
+pub fn fit_tau(neff_t1: f64, neff_t2: f64, dt_ms: i64) -> Option<f64> {
+    if dt_ms <= 0 || !neff_t1.is_finite() || !neff_t2.is_finite() {
+        return None;
+    }
+    let ratio = neff_t2 / neff_t1;
+    if !(ratio > 0.0 && ratio < 1.0) {
+        return None;
+    }
+    let tau = -(dt_ms as f64) / (MS_PER_DAY * ratio.ln());
+    if tau.is_finite() && tau > 0.0 { Some(tau) } else { None }
+}
+
+/// 查询全局热存储的衰减加权中位交易规模 (标准单位，取绝对值)
+pub fn query_weighted_median_internal(current_ts: i64, tau: f64) -> f64 {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    match lock.get(GLOBAL_MARKET_KEY) {
+        Some(history) => calculate_weighted_median_in_memory(history, current_ts, tau),
+        None => 0.0,
+    }
+}
+
+/// 查询全局 neff (含带时间戳远程流量) 及其置信度
+///
+/// 本地部分与置信度在单次读锁内基于同一快照计算；置信度只反映本地样本。
+pub fn query_neff_with_confidence_internal(current_ts: i64, tau: f64) -> (f64, f64) {
+    let (local, confidence) = {
+        let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+        match lock.get(GLOBAL_MARKET_KEY) {
+            Some(history) => (
+                calculate_volume_in_memory(history, current_ts, tau),
+                calculate_neff_confidence(history, current_ts, tau),
+            ),
+            None => (0.0, 0.0),
+        }
+    };
+    (local + query_remote_neff(current_ts, tau), confidence)
+}
+
+/// 查询全局热存储的衰减加权平均交易年龄 (ms)
+pub fn query_avg_trade_age_internal(current_ts: i64, tau: f64) -> f64 {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    if let Some(history) = lock.get(GLOBAL_MARKET_KEY) {
+        return calculate_avg_age_in_memory(history, current_ts, tau);
+    }
+    0.0
+}
+
+/// 将全局热存储中最新的至多 `out.len()` 条记录按时间顺序复制到 `out`
+///
+/// 结果为调用时刻的时间点快照；读锁仅覆盖一次连续内存拷贝。
+/// @return 实际写入的记录数
+pub fn snapshot_recent_history(out: &mut [HistoryRecord]) -> usize {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    let history = match lock.get(GLOBAL_MARKET_KEY) {
+        Some(h) => h,
+        None => return 0,
+    };
+    let n = history.len().min(out.len());
+    out[..n].copy_from_slice(&history[history.len() - n..]);
+    n
+}
+
+/// 计算两个市场在 [from_ts, to_ts) 区间内按 `bucket_ms` 分桶的成交活跃度 (绝对成交量) 的 Pearson 相关系数
+///
+/// 正相关提示互补品，负相关提示替代品。
+/// 任一市场不存在、桶数少于 2 或任一序列方差为零 (相关系数无定义) 时返回 None。
+pub fn query_market_correlation(
+    key_a: &str,
+    key_b: &str,
+    from_ts: i64,
+    to_ts: i64,
+    bucket_ms: i64,
+) -> Option<f64> {
+    if bucket_ms <= 0 || to_ts <= from_ts {
+        return None;
+    }
+    let buckets = (to_ts.checked_sub(from_ts)? as u64).div_ceil(bucket_ms as u64) as usize;
+    if buckets < 2 {
+        return None;
+    }
+
+    let (series_a, series_b) = {
+        let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+        let history_a = lock.get(key_a)?;
+        let history_b = lock.get(key_b)?;
+        (
+            bucket_activity(history_a, from_ts, to_ts, bucket_ms, buckets),
+            bucket_activity(history_b, from_ts, to_ts, bucket_ms, buckets),
+        )
+    };
+    pearson_correlation(&series_a, &series_b)
+}
+
+/// 将 [from_ts, to_ts) 内的记录按时间分桶，累加各桶绝对成交量 (标准单位)
+fn bucket_activity(history: &[HistoryRecord], from_ts: i64, to_ts: i64, bucket_ms: i64, buckets: usize) -> Vec<f64> {
+    let mut series = vec![0.0; buckets];
+    let start = history.partition_point(|r| r.timestamp < from_ts);
+    for rec in history[start..].iter().take_while(|r| r.timestamp < to_ts) {
+        let idx = ((rec.timestamp - from_ts) / bucket_ms) as usize;
+        series[idx] += (rec.amount_micros as f64).abs() / MICROS_SCALE;
+    }
+    series
+}
+
+/// 两个等长序列的 Pearson 相关系数；任一序列方差为零时返回 None
+fn pearson_correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len().min(b.len()) as f64;
+    if n < 2.0 {
+        return None;
+    }
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        cov += dx * dy;
+        var_a += dx * dx;
+        var_b += dy * dy;
+    }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return None;
+    }
+    Some((cov / (var_a.sqrt() * var_b.sqrt())).clamp(-1.0, 1.0))
+}
+
+/// 按指定累积模式查询某市场的有效供应量
+pub fn query_neff_with_mode(current_ts: i64, tau: f64, market_key: &str, mode: NeffMode) -> f64 {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    if let Some(history) = lock.get(market_key) {
+        return calculate_volume_with_mode(history, current_ts, tau, mode);
+    }
+    0.0
+}
+
+/// 一次遍历全局热存储，计算多个衰减常数下的有效供应量 (方向累积，含带时间戳远程流量)
+///
+/// `out[i]` 对应 `taus[i]`；长度不一致时只处理较短的部分。
+pub fn query_neff_multi_internal(current_ts: i64, taus: &[f64], out: &mut [f64]) {
+    {
+        let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+        match lock.get(GLOBAL_MARKET_KEY) {
+            Some(history) => calculate_volume_multi(history, current_ts, taus, out),
+            None => out.iter_mut().for_each(|v| *v = 0.0),
+        }
+    }
+    let mut remote = vec![0.0; taus.len().min(out.len())];
+    let history = REMOTE_HISTORY.read().unwrap_or_else(|e| e.into_inner());
+    calculate_volume_multi(&history, current_ts, taus, &mut remote);
+    out.iter_mut().zip(remote).for_each(|(v, r)| *v += r);
+}
+
+/// 保留买卖方向的全局有效供应量 (卖出为正、买入为负，相互抵消)
+pub fn query_neff_signed(current_ts: i64, tau: f64) -> f64 {
+    query_neff_with_mode(current_ts, tau, GLOBAL_MARKET_KEY, NeffMode::Signed)
+}
+
 // ==================== 内存计算实现 (Binary Search + SIMD) ====================
 
+/// 有效供应量的累积模式
+///
+/// 热存储中的 `amount_micros` 始终保留符号 (正数 = 卖出，负数 = 买入)。
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NeffMode {
+    /// 方向累积：买入抵消卖出，反映净供应压力 (默认定价口径)
+    Signed,
+    /// 规模累积：取 |amount|，买卖同向计入，反映成交活跃度
+    Absolute,
+}
+
+#[inline(always)]
+fn record_amount(rec: &HistoryRecord, mode: NeffMode) -> f64 {
+    match mode {
+        NeffMode::Signed => rec.amount_micros as f64,
+        NeffMode::Absolute => rec.amount_micros.unsigned_abs() as f64,
+    }
+}
+
 pub fn calculate_volume_in_memory(
     history: &[HistoryRecord],
     current_time: i64,
     tau: f64,
 ) -> f64 {
-    if history.is_empty() || tau <= 0.0 {
-        return 0.0;
-    }
-
-    let valid_future_limit = current_time + MAX_FUTURE_TOLERANCE;
-    let valid_past_limit = current_time - (tau * MS_PER_DAY * 10.0) as i64;
-
-    let start_idx = history.partition_point(|r| r.timestamp < valid_past_limit);
-    let relevant_slice = &history[start_idx..];
+    calculate_volume_with_mode(history, current_time, tau, NeffMode::Signed)
+}
 
-    if relevant_slice.is_empty() {
+/// 有效供应量核心演算 (二分定位窗口 + AVX2/标量求和)
+///
+/// SIMD 路径对符号无要求：数量以 f64 通道载入，负数与正数一样参与乘加；
+/// `Absolute` 模式在载入通道前取绝对值。需注意 `Signed` 模式下买卖相互抵消，
+/// 净值可能远小于各项量级，此时浮点求和的相对误差会被放大。
+pub fn calculate_volume_with_mode(
+    history: &[HistoryRecord],
+    current_time: i64,
+    tau: f64,
+    mode: NeffMode,
+) -> f64 {
+    let Some(window) = decay_window(history, current_time, tau) else {
         return 0.0;
-    }
-
-    let t_min = relevant_slice[0].timestamp;
-    let lambda = 1.0 / (tau * MS_PER_DAY);
-    let base_multiplier = (-(current_time - t_min) as f64 * lambda).exp();
+    };
 
     #[cfg(target_arch = "x86_64")]
     if is_x86_feature_detected!("avx2") {
         let sum_partial = unsafe { 
-            compute_partial_simd(relevant_slice, t_min, lambda, valid_future_limit, valid_past_limit) 
+            compute_partial_simd(window.slice, window.t_min, window.lambda, window.future_limit, window.past_limit, mode) 
         };
         // 最终求和时缩放回标准单位
-        let result = (sum_partial / MICROS_SCALE) * base_multiplier;
+        let result = (sum_partial / MICROS_SCALE) * window.base_multiplier;
         return if result.is_finite() { result } else { 0.0 };
     }
 
     // Fallback: 标量实现 (针对 slice)
-    let compute_partial = |rec: &HistoryRecord| -> f64 {
-        if rec.timestamp > valid_future_limit {
-            return 0.0; 
+    let sum_partial = sum_partial_scalar(&window, mode);
+    let result = (sum_partial / MICROS_SCALE) * window.base_multiplier;
+    if result.is_finite() { result } else { 0.0 }
+}
+
+/// 标量部分和 (Neumaier 补偿求和)
+///
+/// 40 万条量级的窗口上朴素 `.sum()` 会重新引入浮点累积误差，抵消 i64 Micros 存储的精度收益。
+/// 并行模式下每个分块独立补偿求和，分块结果再做一次补偿合并。
+fn sum_partial_scalar(window: &DecayWindow<'_>, mode: NeffMode) -> f64 {
+    let compute_partial = |rec: &HistoryRecord| window.term(rec, mode);
+
+    if window.slice.len() >= PARALLEL_THRESHOLD {
+        #[cfg(feature = "parallel")]
+        {
+            return window.slice
+                .par_chunks(PARALLEL_THRESHOLD)
+                .map(|chunk| {
+                    let mut acc = NeumaierAccumulator::default();
+                    chunk.iter().for_each(|rec| acc.add(compute_partial(rec)));
+                    acc
+                })
+                .reduce(NeumaierAccumulator::default, NeumaierAccumulator::merge)
+                .total();
         }
-        let dt_rel = rec.timestamp.saturating_sub(t_min) as f64;
-        (rec.amount_micros as f64) * (dt_rel * lambda).exp()
+    }
+    neumaier_sum(window.slice.iter().map(compute_partial))
+}
+
+/// 多 tau 有效供应量 (方向累积)：与逐个调用 `calculate_volume_in_memory` 等价，但只遍历历史一次
+///
+/// 以最大 tau 的回溯下限二分定位起点，逐条记录按各 tau 自身的回溯下限决定是否计入。
+/// 非正或非有限的 tau 对应输出为 0。
+pub fn calculate_volume_multi(history: &[HistoryRecord], current_time: i64, taus: &[f64], out: &mut [f64]) {
+    let n = taus.len().min(out.len());
+    let (taus, out) = (&taus[..n], &mut out[..n]);
+    out.iter_mut().for_each(|v| *v = 0.0);
+
+    let valid = |tau: f64| tau.is_finite() && tau > 0.0;
+    let Some(max_tau) = taus.iter().copied().filter(|&t| valid(t)).reduce(f64::max) else {
+        return;
     };
 
-    let sum_partial: f64 = if relevant_slice.len() >= PARALLEL_THRESHOLD {
-        #[cfg(feature = "parallel")]
-        { relevant_slice.par_iter().map(compute_partial).sum() }
-        #[cfg(not(feature = "parallel"))]
-        { relevant_slice.iter().map(compute_partial).sum() }
-    } else {
-        relevant_slice.iter().map(compute_partial).sum()
+    let (future_limit, widest_past_limit) = window_limits(current_time, max_tau);
+    let past_limits: Vec<i64> = taus.iter().map(|&t| if valid(t) { window_limits(current_time, t).1 } else { i64::MAX }).collect();
+    let lambdas: Vec<f64> = taus.iter().map(|&t| 1.0 / (t * MS_PER_DAY)).collect();
+    let mut accs = vec![NeumaierAccumulator::default(); n];
+
+    let start_idx = history.partition_point(|r| r.timestamp < widest_past_limit);
+    for rec in &history[start_idx..] {
+        if rec.timestamp > future_limit {
+            continue;
+        }
+        let age_ms = current_time.saturating_sub(rec.timestamp) as f64;
+        let amount = record_amount(rec, NeffMode::Signed);
+        for i in 0..n {
+            if rec.timestamp >= past_limits[i] {
+                accs[i].add(amount * (-age_ms * lambdas[i]).exp());
+            }
+        }
+    }
+
+    for (v, acc) in out.iter_mut().zip(accs) {
+        let result = acc.total() / MICROS_SCALE;
+        *v = if result.is_finite() { result } else { 0.0 };
+    }
+}
+
+/// 衰减求和窗口：历史切片及其相对 `t_min` 的指数参数
+///
+/// 各项以 exp((ts - t_min) · lambda) 累加后统一乘以 `base_multiplier`，避免逐项计算大负指数。
+struct DecayWindow<'a> {
+    slice: &'a [HistoryRecord],
+    t_min: i64,
+    lambda: f64,
+    future_limit: i64,
+    past_limit: i64,
+    base_multiplier: f64,
+}
+
+impl DecayWindow<'_> {
+    /// 单条记录相对 `t_min` 的衰减项 (超出未来容差的记录为 0)
+    #[inline]
+    fn term(&self, rec: &HistoryRecord, mode: NeffMode) -> f64 {
+        if rec.timestamp > self.future_limit {
+            return 0.0;
+        }
+        let dt_rel = rec.timestamp.saturating_sub(self.t_min) as f64;
+        record_amount(rec, mode) * (dt_rel * self.lambda).exp()
+    }
+}
+
+fn decay_window(history: &[HistoryRecord], current_time: i64, tau: f64) -> Option<DecayWindow<'_>> {
+    if history.is_empty() || tau <= 0.0 {
+        return None;
+    }
+
+    // 二分定位要求按时间戳升序；乱序会静默漏算窗口内的记录
+    debug_assert!(history.is_sorted_by_key(|r| r.timestamp), "history must be sorted by timestamp");
+    let (future_limit, past_limit) = window_limits(current_time, tau);
+
+    let start_idx = history.partition_point(|r| r.timestamp < past_limit);
+    let slice = &history[start_idx..];
+    if slice.is_empty() {
+        return None;
+    }
+
+    let t_min = slice[0].timestamp;
+    let lambda = 1.0 / (tau * MS_PER_DAY);
+    let base_multiplier = (-(current_time - t_min) as f64 * lambda).exp();
+    Some(DecayWindow { slice, t_min, lambda, future_limit, past_limit, base_multiplier })
+}
+
+/// Neumaier (改进 Kahan) 补偿累加器
+///
+/// 额外维护一个补偿项累积每步加法丢失的低位，即使加数量级悬殊也能保持精度。
+/// 分块并行时各块保留独立的 (sum, compensation)，合并后再折叠，避免块结果提前舍入。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NeumaierAccumulator {
+    sum: f64,
+    compensation: f64,
+}
+
+impl NeumaierAccumulator {
+    #[inline]
+    pub fn add(&mut self, value: f64) {
+        let t = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - t) + value;
+        } else {
+            self.compensation += (value - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    #[inline]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.add(other.sum);
+        self.add(other.compensation);
+        self
+    }
+
+    #[inline]
+    pub fn total(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// Neumaier 补偿求和
+pub fn neumaier_sum<I: IntoIterator<Item = f64>>(values: I) -> f64 {
+    let mut acc = NeumaierAccumulator::default();
+    for value in values {
+        acc.add(value);
+    }
+    acc.total()
+}
+
+/// 以 Neumaier 补偿求和计算有效供应量 (标量高精度参考值，不走 SIMD 路径)
+pub fn calculate_volume_compensated(history: &[HistoryRecord], current_time: i64, tau: f64) -> f64 {
+    let Some(window) = decay_window(history, current_time, tau) else {
+        return 0.0;
     };
+    let sum_partial = sum_partial_scalar(&window, NeffMode::Signed);
+    let result = (sum_partial / MICROS_SCALE) * window.base_multiplier;
+    if result.is_finite() { result } else { 0.0 }
+}
+
+/// 以朴素逐项累加 (`iter().sum()`) 计算有效供应量，仅作为误差估算的对照组
+fn calculate_volume_naive(history: &[HistoryRecord], current_time: i64, tau: f64) -> f64 {
+    let Some(window) = decay_window(history, current_time, tau) else {
+        return 0.0;
+    };
+    let sum_partial: f64 = window.slice.iter().map(|rec| window.term(rec, NeffMode::Signed)).sum();
+    let result = (sum_partial / MICROS_SCALE) * window.base_multiplier;
+    if result.is_finite() { result } else { 0.0 }
+}
+
+/// 估算全局 neff 的浮点累积误差
+///
+/// 返回 (常规演算结果, 朴素逐项累加与补偿求和参考值的绝对差)。
+pub fn estimate_neff_error_internal(current_ts: i64, tau: f64) -> (f64, f64) {
+    let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+    match lock.get(GLOBAL_MARKET_KEY) {
+        Some(history) => neff_error(history, current_ts, tau),
+        None => (0.0, 0.0),
+    }
+}
+
+fn neff_error(history: &[HistoryRecord], current_ts: i64, tau: f64) -> (f64, f64) {
+    let neff = calculate_volume_in_memory(history, current_ts, tau);
+    let naive = calculate_volume_naive(history, current_ts, tau);
+    let precise = calculate_volume_compensated(history, current_ts, tau);
+    (neff, (naive - precise).abs())
+}
+
+/// 计算供应加权的平均交易年龄 (市场新鲜度指标)
+///
+/// 公式: Σ(age · w) / Σ(w)，其中 w = |amount| · exp(-age / (tau · MS_PER_DAY))，
+/// 与 `calculate_volume_in_memory` 使用相同的衰减权重与时间窗口。
+/// 平均年龄越低说明近期交易主导市场；越高说明市场依赖陈旧交易“滑行”。
+/// 未来容差内的记录年龄按 0 处理。无有效记录时返回 0.0。
+pub fn calculate_avg_age_in_memory(
+    history: &[HistoryRecord],
+    current_time: i64,
+    tau: f64,
+) -> f64 {
+    if history.is_empty() || tau <= 0.0 || !tau.is_finite() {
+        return 0.0;
+    }
+
+    let (valid_future_limit, valid_past_limit) = window_limits(current_time, tau);
+
+    let start_idx = history.partition_point(|r| r.timestamp < valid_past_limit);
+    let lambda = 1.0 / (tau * MS_PER_DAY);
+
+    let mut weighted_age = 0.0;
+    let mut total_weight = 0.0;
+    for rec in &history[start_idx..] {
+        if rec.timestamp > valid_future_limit {
+            continue;
+        }
+        let age_ms = current_time.saturating_sub(rec.timestamp).max(0) as f64;
+        let weight = (rec.amount_micros.unsigned_abs() as f64 / MICROS_SCALE) * (-age_ms * lambda).exp();
+        weighted_age += age_ms * weight;
+        total_weight += weight;
+    }
 
-    let result = (sum_partial / MICROS_SCALE) * base_multiplier;
+    if total_weight <= 0.0 {
+        return 0.0;
+    }
+    let result = weighted_age / total_weight;
     if result.is_finite() { result } else { 0.0 }
 }
 
+/// 衰减加权中位交易规模：窗口内记录按 |amount| 排序后累计衰减权重，取累计权重首次达到半数处的规模
+///
+/// 权重仅取决于交易年龄 (与 neff 衰减一致)，不随规模放大，因此单笔巨额交易不会拖动结果。
+/// 窗口为空或参数非法时返回 0。
+pub fn calculate_weighted_median_in_memory(
+    history: &[HistoryRecord],
+    current_time: i64,
+    tau: f64,
+) -> f64 {
+    if history.is_empty() || tau <= 0.0 || !tau.is_finite() {
+        return 0.0;
+    }
+
+    let (valid_future_limit, valid_past_limit) = window_limits(current_time, tau);
+    let start_idx = history.partition_point(|r| r.timestamp < valid_past_limit);
+
+    let mut samples: Vec<(u64, f64)> = history[start_idx..]
+        .iter()
+        .filter(|rec| rec.timestamp <= valid_future_limit)
+        .map(|rec| {
+            let age_ms = current_time.saturating_sub(rec.timestamp).max(0);
+            (rec.amount_micros.unsigned_abs(), decay_weight(age_ms, tau))
+        })
+        .collect();
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.sort_unstable_by_key(|&(amount, _)| amount);
+
+    let half = neumaier_sum(samples.iter().map(|&(_, w)| w)) / 2.0;
+    let mut cumulative = 0.0;
+    for &(amount, weight) in &samples {
+        cumulative += weight;
+        if cumulative >= half {
+            return amount as f64 / MICROS_SCALE;
+        }
+    }
+    samples.last().map_or(0.0, |&(amount, _)| amount as f64 / MICROS_SCALE)
+}
+
+/// neff 置信度 ∈ [0, 1] = 样本项 × 覆盖项
+///
+/// - 样本项：`k / (k + NEFF_CONFIDENCE_HALF_SAMPLES)`，k 为 Kish 有效样本数 `(Σw)² / Σw²`，
+///   w = |amount| · 衰减权重。n 笔等权交易时 k = n；少数巨额交易主导时 k 趋近 1。
+/// - 覆盖项：窗口内最早记录的年龄 / 一个衰减周期 (tau 天)，钳位至 [0, 1]。
+///   历史不足一个周期时窗口前段缺少数据，按已覆盖比例打折。
+///
+/// 窗口内无记录或数量全为 0 时返回 0。
+pub fn calculate_neff_confidence(history: &[HistoryRecord], current_time: i64, tau: f64) -> f64 {
+    if history.is_empty() || tau <= 0.0 || !tau.is_finite() {
+        return 0.0;
+    }
+
+    let (valid_future_limit, valid_past_limit) = window_limits(current_time, tau);
+    let start_idx = history.partition_point(|r| r.timestamp < valid_past_limit);
+
+    let mut oldest_age_ms = None;
+    let (mut sum_w, mut sum_w2) = (0.0, 0.0);
+    for rec in history[start_idx..].iter().filter(|r| r.timestamp <= valid_future_limit) {
+        let age_ms = current_time.saturating_sub(rec.timestamp).max(0);
+        let w = rec.amount_micros.unsigned_abs() as f64 * decay_weight(age_ms, tau);
+        sum_w += w;
+        sum_w2 += w * w;
+        oldest_age_ms.get_or_insert(age_ms);
+    }
+    if !(sum_w2 > 0.0 && sum_w2.is_finite()) {
+        return 0.0;
+    }
+
+    let effective_samples = sum_w * sum_w / sum_w2;
+    let sample_term = effective_samples / (effective_samples + NEFF_CONFIDENCE_HALF_SAMPLES);
+    let coverage = (oldest_age_ms.unwrap_or(0) as f64 / (tau * MS_PER_DAY)).clamp(0.0, 1.0);
+    sample_term * coverage
+}
+
 /// AVX2 优化的部分和计算
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
@@ -163,7 +939,8 @@ unsafe fn compute_partial_simd(
     t_min: i64, 
     lambda: f64,
     valid_future: i64,
-    valid_past: i64
+    valid_past: i64,
+    mode: NeffMode,
 ) -> f64 {
     let mut sum_vec = _mm256_setzero_pd();
     
@@ -181,7 +958,7 @@ unsafe fn compute_partial_simd(
             for r in chunk {
                 if r.timestamp <= valid_future && r.timestamp >= valid_past {
                      let dt = (r.timestamp - t_min) as f64;
-                     let val = (r.amount_micros as f64) * (dt * lambda).exp();
+                     let val = record_amount(r, mode) * (dt * lambda).exp();
                      let v_val = _mm256_set_pd(0.0, 0.0, 0.0, val);
                      sum_vec = _mm256_add_pd(sum_vec, v_val);
                 }
@@ -197,10 +974,10 @@ unsafe fn compute_partial_simd(
             chunk[0].timestamp as f64,
         );
         let v_amount = _mm256_set_pd(
-            chunk[3].amount_micros as f64, // [Fix] 访问 amount_micros
-            chunk[2].amount_micros as f64,
-            chunk[1].amount_micros as f64,
-            chunk[0].amount_micros as f64,
+            record_amount(&chunk[3], mode), // [Fix] 访问 amount_micros
+            record_amount(&chunk[2], mode),
+            record_amount(&chunk[1], mode),
+            record_amount(&chunk[0], mode),
         );
 
         let v_dt = _mm256_sub_pd(v_ts, v_tmin);
@@ -225,9 +1002,554 @@ unsafe fn compute_partial_simd(
     for rec in remainder {
         if rec.timestamp <= valid_future && rec.timestamp >= valid_past {
             let dt = (rec.timestamp - t_min) as f64;
-            total += (rec.amount_micros as f64) * (dt * lambda).exp();
+            total += record_amount(rec, mode) * (dt * lambda).exp();
         }
     }
 
     total
 }
+
+// ==================== 单元测试 ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS_PER_DAY_I64: i64 = 86_400_000;
+
+    #[test]
+    fn test_raw_volume_in_range_inclusive_bounds() {
+        let history: Vec<HistoryRecord> = (0..5)
+            .map(|i| HistoryRecord { timestamp: 1_000 * i, amount_micros: if i % 2 == 0 { 1_000_000 } else { -2_000_000 } })
+            .collect();
+
+        // [1000, 3000] 覆盖 1、2、3 号记录：2 + 1 + 2
+        assert!((raw_volume_in_range(&history, 1_000, 3_000) - 5.0).abs() < 1e-9);
+        assert!((raw_volume_in_range(&history, i64::MIN, i64::MAX) - 7.0).abs() < 1e-9);
+        assert_eq!(raw_volume_in_range(&history, 1_500, 1_900), 0.0);
+        assert_eq!(raw_volume_in_range(&history, 3_000, 1_000), 0.0);
+    }
+
+    #[test]
+    fn test_bin_amounts_buckets_and_validation() {
+        let history: Vec<HistoryRecord> = [0.5, 1.0, -3.0, 9.99, 10.0, 250.0, -1_000.0, 5_000.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &amt)| HistoryRecord { timestamp: i as i64, amount_micros: (amt * 1_000_000.0) as i64 })
+            .collect();
+
+        // [1, 10) [10, 100) [100, 1000]：0.5 与 5000 落在边界外，-1000 取绝对值后计入最后一个闭区间桶
+        assert_eq!(bin_amounts(&history, &[1.0, 10.0, 100.0, 1_000.0]), Some(vec![3, 1, 2]));
+        assert_eq!(bin_amounts(&[], &[0.0, 1.0]), Some(vec![0]));
+
+        assert_eq!(bin_amounts(&history, &[1.0]), None);
+        assert_eq!(bin_amounts(&history, &[1.0, 10.0, 10.0]), None);
+        assert_eq!(bin_amounts(&history, &[10.0, 1.0]), None);
+        assert_eq!(bin_amounts(&history, &[0.0, f64::INFINITY]), None);
+    }
+
+    #[test]
+    fn test_avg_trade_age_weighting() {
+        let now = 100 * MS_PER_DAY_I64;
+        let tau = 1.0;
+        // 两笔等量交易：一笔刚刚发生 (age=0)，一笔发生在 1 个 tau 之前 (age=1 天)
+        let history = vec![
+            HistoryRecord { timestamp: now - MS_PER_DAY_I64, amount_micros: 1_000_000 },
+            HistoryRecord { timestamp: now, amount_micros: 1_000_000 },
+        ];
+
+        // 权重: w0 = 1, w1 = e^-1 => avg = (0 * 1 + 1d * e^-1) / (1 + e^-1)
+        let w_old = (-1.0f64).exp();
+        let expected = (MS_PER_DAY * w_old) / (1.0 + w_old);
+        let avg = calculate_avg_age_in_memory(&history, now, tau);
+        assert!((avg - expected).abs() < 1e-3, "avg={} expected={}", avg, expected);
+
+        // 单一记录时平均年龄等于其自身年龄
+        let single = [HistoryRecord { timestamp: now - 3_600_000, amount_micros: 5_000_000 }];
+        assert!((calculate_avg_age_in_memory(&single, now, tau) - 3_600_000.0).abs() < 1e-6);
+
+        assert_eq!(calculate_avg_age_in_memory(&[], now, tau), 0.0);
+    }
+
+    #[test]
+    fn test_neff_multi_matches_individual_queries() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 500 * MS_PER_DAY_I64;
+        // 跨度 120 天的买卖交替记录：1 天窗口只覆盖末尾，30 天窗口覆盖大部分
+        let history: Vec<HistoryRecord> = (0..2_000)
+            .map(|i| HistoryRecord {
+                timestamp: now - 120 * MS_PER_DAY_I64 + i * 5_184_000,
+                amount_micros: if i % 3 == 0 { -2_500_000 } else { 1_750_000 + i },
+            })
+            .collect();
+
+        let taus = [1.0, 7.0, 30.0, 0.0];
+        let mut out = [f64::NAN; 4];
+        calculate_volume_multi(&history, now, &taus, &mut out);
+        for (tau, neff) in taus.iter().zip(out).take(3) {
+            let single = calculate_volume_in_memory(&history, now, *tau);
+            assert!((neff - single).abs() <= 1e-9 * single.abs().max(1.0), "tau={} multi={} single={}", tau, neff, single);
+        }
+        assert_eq!(out[3], 0.0);
+
+        let mut empty = [f64::NAN; 2];
+        calculate_volume_multi(&[], now, &[1.0, 7.0], &mut empty);
+        assert_eq!(empty, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_neff_confidence_reflects_samples_and_coverage() {
+        let now = 100 * MS_PER_DAY_I64;
+        let tau = 7.0;
+        let trade = |age_ms: i64, amount: i64| HistoryRecord { timestamp: now - age_ms, amount_micros: amount * 1_000_000 };
+
+        assert_eq!(calculate_neff_confidence(&[], now, tau), 0.0);
+
+        // 单笔交易恰好一个周期前：k = 1，覆盖完整
+        let single = [trade(7 * MS_PER_DAY_I64, 5)];
+        assert!((calculate_neff_confidence(&single, now, tau) - 1.0 / 11.0).abs() < 1e-12);
+
+        // 两个周期内均匀分布的 200 笔交易：样本充足且覆盖完整
+        let dense: Vec<HistoryRecord> = (0..200).rev().map(|i| trade(i * 14 * MS_PER_DAY_I64 / 200, 5)).collect();
+        let dense_conf = calculate_neff_confidence(&dense, now, tau);
+        assert!(dense_conf > 0.85, "dense={}", dense_conf);
+
+        // 同样 200 笔但全部集中在最近 1 小时：窗口前段无数据
+        let recent: Vec<HistoryRecord> = (0..200).rev().map(|i| trade(i * 18_000, 5)).collect();
+        assert!(calculate_neff_confidence(&recent, now, tau) < 0.01);
+
+        // 一笔巨额交易主导时有效样本数趋近 1
+        let mut whale = dense.clone();
+        whale.push(trade(0, 1_000_000));
+        assert!(calculate_neff_confidence(&whale, now, tau) < 0.1);
+    }
+
+    #[test]
+    fn test_weighted_median_ignores_single_whale() {
+        let now = 100 * MS_PER_DAY_I64;
+        let tau = 7.0;
+        // 九笔 1~9 的普通交易 (乱序规模) + 一笔远超其余总和的巨额抛售
+        let mut history: Vec<HistoryRecord> = [3, 7, 1, 9, 5, 2, 8, 4, 6]
+            .iter()
+            .enumerate()
+            .map(|(i, &amt)| HistoryRecord { timestamp: now - 1_000 * (10 - i as i64), amount_micros: amt * 1_000_000 })
+            .collect();
+        let baseline = calculate_weighted_median_in_memory(&history, now, tau);
+        assert_eq!(baseline, 5.0);
+
+        history.push(HistoryRecord { timestamp: now, amount_micros: 1_000_000 * 1_000_000 });
+        let with_whale = calculate_weighted_median_in_memory(&history, now, tau);
+        assert!((5.0..=6.0).contains(&with_whale), "median={}", with_whale);
+
+        // 对比：按规模加权的平均值被巨额交易主导
+        let mean = history.iter().map(|r| r.amount_micros as f64 / MICROS_SCALE).sum::<f64>() / history.len() as f64;
+        assert!(mean > 1_000.0 * with_whale);
+
+        // 买入 (负数) 按规模计入
+        let signed = [
+            HistoryRecord { timestamp: now, amount_micros: -4_000_000 },
+            HistoryRecord { timestamp: now, amount_micros: 1_000_000 },
+            HistoryRecord { timestamp: now, amount_micros: 9_000_000 },
+        ];
+        assert_eq!(calculate_weighted_median_in_memory(&signed, now, tau), 4.0);
+        assert_eq!(calculate_weighted_median_in_memory(&[], now, tau), 0.0);
+    }
+
+    #[test]
+    fn test_market_keys_are_isolated() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let now = 200 * MS_PER_DAY_I64;
+        assert!(append_trade_to_memory(now, 64.0, "isolation:diamond"));
+        assert!(append_trade_to_memory(now, 1.0, "isolation:wheat"));
+
+        // 钻石的抛售不得压低小麦的 neff；全局桶同时汇总两者
+        assert!((query_neff_internal(now, 7.0, "isolation:diamond") - 64.0).abs() < 1e-9);
+        assert!((query_neff_internal(now, 7.0, "isolation:wheat") - 1.0).abs() < 1e-9);
+        assert_eq!(query_neff_internal(now, 7.0, "isolation:unknown"), 0.0);
+        assert!(query_neff_global_internal(now, 7.0) >= 65.0 - 1e-9);
+    }
+
+    #[test]
+    fn test_ordered_insert_keeps_store_sorted() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 400 * MS_PER_DAY_I64;
+        let key = "ordered:item";
+
+        assert!(append_trade_to_memory(now, 1.0, key));
+        assert!(append_trade_to_memory(now + 2_000, 2.0, key));
+        // 较早时间戳的记录晚到
+        assert!(append_trade_to_memory(now + 1_000, 4.0, key));
+        assert!(append_trade_to_memory(now + 1_000, 8.0, key));
+
+        {
+            let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+            let history = &lock[key];
+            let order: Vec<(i64, i64)> = history.iter().map(|r| (r.timestamp - now, r.amount_micros / 1_000_000)).collect();
+            assert_eq!(order, vec![(0, 1), (1_000, 4), (1_000, 8), (2_000, 2)]);
+        }
+
+        // 查询时刻 now + 1000 之前的全部记录均被计入，未来记录受容忍窗口约束
+        set_window_params(0, DEFAULT_LOOKBACK_MULTIPLIER);
+        let tau = 7.0;
+        let expected = 1.0 * decay_weight(1_000, tau) + 4.0 + 8.0;
+        let neff = query_neff_internal(now + 1_000, tau, key);
+        assert!((neff - expected).abs() < 1e-6, "neff={} expected={}", neff, expected);
+        reset_state();
+    }
+
+    #[test]
+    fn test_late_record_does_not_break_neff_window() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 450 * MS_PER_DAY_I64;
+        let key = "late:item";
+        let tau = 1.0;
+
+        // 晚到记录早于回溯窗口 (10 天)：直接追加时二分定位会错切窗口
+        assert!(append_trade_to_memory(now - MS_PER_DAY_I64, 1.0, key));
+        assert!(append_trade_to_memory(now - 20 * MS_PER_DAY_I64, 100.0, key));
+        assert!(append_trade_to_memory(now, 1.0, key));
+
+        assert!(HOT_HISTORY_BY_KEY.read().unwrap()[key].is_sorted_by_key(|r| r.timestamp));
+        let expected = (-1.0f64).exp() + 1.0;
+        let neff = query_neff_internal(now, tau, key);
+        assert!((neff - expected).abs() < 1e-9, "neff={} expected={}", neff, expected);
+        assert!((query_neff_global_internal(now, tau) - expected).abs() < 1e-9);
+        reset_state();
+    }
+
+    #[test]
+    fn test_bulk_inject_merges_and_prunes() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 500 * MS_PER_DAY_I64;
+        let rec = |ts: i64, amount: i64| HistoryRecord { timestamp: ts, amount_micros: amount * 1_000_000 };
+
+        assert!(append_trade_to_memory(now + 1_000, 1.0, "bulk:item"));
+        // 乱序批次，且部分记录早于已有记录
+        assert_eq!(bulk_inject(&[rec(now + 3_000, 8), rec(now, 2), rec(now + 1_000, 4)]), 3);
+        assert_eq!(bulk_inject(&[]), 0);
+        {
+            let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+            let order: Vec<(i64, i64)> = lock[GLOBAL_MARKET_KEY].iter().map(|r| (r.timestamp - now, r.amount_micros / 1_000_000)).collect();
+            assert_eq!(order, vec![(0, 2), (1_000, 1), (1_000, 4), (3_000, 8)]);
+            // 仅写入全局热存储
+            assert_eq!(lock["bulk:item"].len(), 1);
+        }
+        let tau = 7.0;
+        let expected: f64 = [(0, 2.0), (1_000, 1.0), (1_000, 4.0), (3_000, 8.0)]
+            .iter()
+            .map(|&(dt, amount)| amount * decay_weight(3_000 - dt, tau))
+            .sum();
+        assert!((query_neff_global_internal(now + 3_000, tau) - expected).abs() < 1e-6);
+
+        // 超出容量后按裁剪策略收缩
+        let big: Vec<HistoryRecord> = (0..MAX_HISTORY_SIZE as i64).map(|i| rec(now + 10_000 + i, 1)).collect();
+        assert_eq!(bulk_inject(&big), MAX_HISTORY_SIZE);
+        {
+            let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+            let history = &lock[GLOBAL_MARKET_KEY];
+            assert!(history.len() <= PRUNE_TO_SIZE);
+            assert_eq!(history.last().unwrap().timestamp, now + 10_000 + MAX_HISTORY_SIZE as i64 - 1);
+        }
+        reset_state();
+    }
+
+    #[test]
+    fn test_merge_sorted_history_keeps_invariant() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 550 * MS_PER_DAY_I64;
+        let rec = |ts: i64, amount: i64| HistoryRecord { timestamp: now + ts, amount_micros: amount * 1_000_000 };
+        let global_order = || -> Vec<(i64, i64)> {
+            let lock = HOT_HISTORY_BY_KEY.read().unwrap();
+            lock[GLOBAL_MARKET_KEY].iter().map(|r| (r.timestamp - now, r.amount_micros / 1_000_000)).collect()
+        };
+
+        for (dt, amount) in [(1_000, 1), (4_000, 2), (9_000, 3)] {
+            assert!(append_trade_to_memory(now + dt, amount as f64, "peer:item"));
+        }
+        // 乱序的对端批次，与本地记录交错且含相同时间戳
+        assert_eq!(merge_sorted_history(&[rec(9_000, 30), rec(0, 10), rec(4_000, 20), rec(12_000, 40)]), 4);
+        assert_eq!(merge_sorted_history(&[]), 0);
+        assert_eq!(
+            global_order(),
+            vec![(0, 10), (1_000, 1), (4_000, 2), (4_000, 20), (9_000, 3), (9_000, 30), (12_000, 40)]
+        );
+
+        // 有序时二分窗口与逐条线性求和一致
+        let tau = 7.0;
+        let expected: f64 = global_order()
+            .iter()
+            .map(|&(dt, amount)| amount as f64 * decay_weight(12_000 - dt, tau))
+            .sum();
+        assert!((query_neff_global_internal(now + 12_000, tau) - expected).abs() < 1e-6);
+
+        // 归并后逐条追加的晚到记录同样插入正确位置
+        assert!(append_trade_to_memory(now + 2_000, 5.0, "peer:item"));
+        assert_eq!(merge_sorted_history(&[rec(3_000, 6)]), 1);
+        let order = global_order();
+        assert!(order.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert_eq!(order.len(), 9);
+        reset_state();
+    }
+
+    #[test]
+    fn test_remote_trades_decay_like_local_history() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 600 * MS_PER_DAY_I64;
+        let tau = 7.0;
+        assert_eq!(query_remote_neff(now, tau), 0.0);
+
+        // 乱序回放：3 天前的积压与刚成交的记录
+        append_remote_trade(now, 2_000_000);
+        append_remote_trade(now - 3 * MS_PER_DAY_I64, 10_000_000);
+        append_remote_trade(now - MS_PER_DAY_I64, -4_000_000);
+
+        let expected = 2.0 + 10.0 * decay_weight(3 * MS_PER_DAY_I64, tau) - 4.0 * decay_weight(MS_PER_DAY_I64, tau);
+        assert!((query_remote_neff(now, tau) - expected).abs() < 1e-6);
+        // 旧积压按年龄衰减，远小于视为 "当前" 的累加值
+        assert!(query_remote_neff(now, tau) < 2.0 + 10.0 - 4.0);
+        // 与本地热存储互不影响
+        assert_eq!(query_neff_global_internal(now, tau), 0.0);
+
+        // 超出容量后收缩
+        for i in 0..=REMOTE_HISTORY_CAP as i64 {
+            append_remote_trade(now + i, 1);
+        }
+        assert!(REMOTE_HISTORY.read().unwrap().len() <= REMOTE_HISTORY_CAP);
+        assert!(REMOTE_HISTORY.read().unwrap().windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+        reset_state();
+        assert_eq!(query_remote_neff(now, tau), 0.0);
+    }
+
+    #[test]
+    fn test_snapshot_recent_history() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 300 * MS_PER_DAY_I64;
+        for i in 0..5 {
+            assert!(append_trade_to_memory(now + i, (i + 1) as f64, "snapshot:item"));
+        }
+
+        // 容量不足时截断为最新的记录，保持时间顺序
+        let mut small = [HistoryRecord::default(); 3];
+        assert_eq!(snapshot_recent_history(&mut small), 3);
+        let stamps: Vec<i64> = small.iter().map(|r| r.timestamp).collect();
+        assert_eq!(stamps, vec![now + 2, now + 3, now + 4]);
+        assert_eq!(small[2].amount_micros, 5_000_000);
+
+        // 容量充足时全部写入，其余槽位不受影响
+        let mut large = [HistoryRecord::default(); 8];
+        assert_eq!(snapshot_recent_history(&mut large), 5);
+        assert_eq!(large[0].timestamp, now);
+        assert_eq!(large[5].timestamp, 0);
+
+        assert_eq!(snapshot_recent_history(&mut []), 0);
+        reset_state();
+        assert_eq!(snapshot_recent_history(&mut large), 0);
+    }
+
+    #[test]
+    fn test_window_params() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let now = 100 * MS_PER_DAY_I64;
+        let history = vec![
+            HistoryRecord { timestamp: now - 5 * MS_PER_DAY_I64, amount_micros: 1_000_000 },
+            HistoryRecord { timestamp: now, amount_micros: 1_000_000 },
+            HistoryRecord { timestamp: now + 30_000, amount_micros: 1_000_000 },
+        ];
+        let counted = |tau: f64| calculate_volume_with_mode(&history, now, tau, NeffMode::Absolute);
+
+        // 默认：回溯 10·tau 天、容忍 60s 未来偏差 => 三条全部计入
+        assert!(counted(1.0) > 2.0);
+
+        // 回溯缩至 2·tau 天且不容忍未来记录 => 仅剩当前记录
+        assert!(set_window_params(0, 2.0));
+        assert!((counted(1.0) - 1.0).abs() < 1e-9);
+
+        // 非法参数被拒绝且不影响当前配置
+        assert!(!set_window_params(-1, 2.0));
+        assert!(!set_window_params(0, 0.5));
+        assert!(!set_window_params(0, f64::INFINITY));
+        assert!((counted(1.0) - 1.0).abs() < 1e-9);
+
+        reset_state();
+        assert!(counted(1.0) > 2.0);
+    }
+
+    #[test]
+    fn test_market_correlation() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let hour = 3_600_000;
+        let from = 400 * MS_PER_DAY_I64;
+        // 两个完全反相的合成序列：A 在偶数小时活跃，B 在奇数小时活跃
+        for i in 0..24 {
+            let ts = from + i * hour + 1_000;
+            let (a, b) = if i % 2 == 0 { (10.0, 1.0) } else { (1.0, 10.0) };
+            assert!(append_trade_to_memory(ts, a, "corr:a"));
+            assert!(append_trade_to_memory(ts, -b, "corr:b"));
+        }
+        let to = from + 24 * hour;
+
+        let anti = query_market_correlation("corr:a", "corr:b", from, to, hour).unwrap();
+        assert!((anti + 1.0).abs() < 1e-9, "anti={}", anti);
+        let same = query_market_correlation("corr:a", "corr:a", from, to, hour).unwrap();
+        assert!((same - 1.0).abs() < 1e-9);
+
+        // 市场不存在、区间非法或桶数不足均无定义
+        assert_eq!(query_market_correlation("corr:a", "corr:missing", from, to, hour), None);
+        assert_eq!(query_market_correlation("corr:a", "corr:b", to, from, hour), None);
+        assert_eq!(query_market_correlation("corr:a", "corr:b", from, to, 24 * hour), None);
+    }
+
+    #[test]
+    fn test_decay_weight_matches_neff() {
+        let tau = 2.0;
+        assert_eq!(decay_weight(0, tau), 1.0);
+        let one_tau = (tau * MS_PER_DAY) as i64;
+        assert!((decay_weight(one_tau, tau) - (-1.0f64).exp()).abs() < 1e-15);
+
+        // 单条 1.0 记录的 neff 即为其衰减权重
+        let now = 100 * MS_PER_DAY_I64;
+        let age = 12 * 3_600_000;
+        let history = [HistoryRecord { timestamp: now - age, amount_micros: 1_000_000 }];
+        let neff = calculate_volume_in_memory(&history, now, tau);
+        assert!((neff - decay_weight(age, tau)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fit_tau_round_trip() {
+        let now = 100 * MS_PER_DAY_I64;
+        let tau = 3.5;
+        let history = [HistoryRecord { timestamp: now, amount_micros: 40_000_000 }];
+
+        // 无新成交时前后两次观测 neff，拟合结果应还原真实 tau
+        let dt_ms = 2 * MS_PER_DAY_I64 + 12_345;
+        let neff_t1 = calculate_volume_in_memory(&history, now, tau);
+        let neff_t2 = calculate_volume_in_memory(&history, now + dt_ms, tau);
+        let fitted = fit_tau(neff_t1, neff_t2, dt_ms).unwrap();
+        assert!((fitted - tau).abs() < 1e-9, "fitted={}", fitted);
+
+        // 未观测到衰减或参数非法
+        assert_eq!(fit_tau(10.0, 10.0, dt_ms), None);
+        assert_eq!(fit_tau(10.0, 12.0, dt_ms), None);
+        assert_eq!(fit_tau(10.0, -5.0, dt_ms), None);
+        assert_eq!(fit_tau(10.0, 5.0, 0), None);
+    }
+
+    #[test]
+    fn test_signed_vs_absolute_neff() {
+        let now = 100 * MS_PER_DAY_I64;
+        // 9 条记录 (覆盖 SIMD 4 路分块与余数)：卖出 +2.0 与买入 -2.0 交替，末尾多一笔卖出
+        let history: Vec<HistoryRecord> = (0..9)
+            .map(|i| HistoryRecord {
+                timestamp: now,
+                amount_micros: if i % 2 == 0 { 2_000_000 } else { -2_000_000 },
+            })
+            .collect();
+
+        let signed = calculate_volume_with_mode(&history, now, 7.0, NeffMode::Signed);
+        let absolute = calculate_volume_with_mode(&history, now, 7.0, NeffMode::Absolute);
+        assert!((signed - 2.0).abs() < 1e-9, "signed={}", signed);
+        assert!((absolute - 18.0).abs() < 1e-9, "absolute={}", absolute);
+        // 默认口径保持方向累积
+        assert_eq!(calculate_volume_in_memory(&history, now, 7.0), signed);
+    }
+
+    #[test]
+    fn test_compensated_sum_recovers_tiny_amount() {
+        // 病态数据：巨量买卖相互抵消，夹杂一笔远小于巨量 ULP (512 Micros) 的微小交易
+        let now = 100 * MS_PER_DAY_I64;
+        let large = 4_000_000_000_000_000_000i64;
+        let mut history = Vec::new();
+        for _ in 0..8 {
+            history.push(HistoryRecord { timestamp: now, amount_micros: large });
+            history.push(HistoryRecord { timestamp: now, amount_micros: -large });
+        }
+        history.insert(1, HistoryRecord { timestamp: now, amount_micros: 100 });
+
+        let values: Vec<f64> = history.iter().map(|r| r.amount_micros as f64).collect();
+        let naive: f64 = values.iter().sum();
+        assert_eq!(naive, 0.0, "朴素求和应丢失微小交易");
+        assert_eq!(neumaier_sum(values.iter().copied()), 100.0);
+
+        let precise = calculate_volume_compensated(&history, now, 7.0);
+        assert!((precise - 100.0 / MICROS_SCALE).abs() < 1e-12, "precise={}", precise);
+    }
+
+    #[test]
+    fn test_neff_error_reflects_naive_sum_loss() {
+        // 巨量买卖相互抵消时朴素累加丢失微小交易，误差估算应如实反映
+        let now = 100 * MS_PER_DAY_I64;
+        let large = 4_000_000_000_000_000_000i64;
+        let history = vec![
+            HistoryRecord { timestamp: now, amount_micros: large },
+            HistoryRecord { timestamp: now, amount_micros: 100 },
+            HistoryRecord { timestamp: now, amount_micros: -large },
+        ];
+        assert_eq!(calculate_volume_naive(&history, now, 7.0), 0.0);
+        let (_, error) = neff_error(&history, now, 7.0);
+        assert!((error - 100.0 / MICROS_SCALE).abs() < 1e-12, "error={}", error);
+
+        // 量级相近的数据集无可观误差
+        let calm: Vec<HistoryRecord> = (0..100)
+            .map(|i| HistoryRecord { timestamp: now - (99 - i) * 1_000, amount_micros: 5_000_000 })
+            .collect();
+        let (neff, error) = neff_error(&calm, now, 7.0);
+        assert!(neff > 0.0);
+        assert!(error < 1e-9, "error={}", error);
+    }
+
+    #[test]
+    fn test_scalar_path_neumaier_precision() {
+        // 1 笔巨量卖出 + 1000 笔微小交易 + 1 笔巨量买入，超过并行阈值以覆盖分块合并
+        let now = 100 * MS_PER_DAY_I64;
+        let large = 4_000_000_000_000_000_000i64;
+        let mut history = vec![HistoryRecord { timestamp: now, amount_micros: large }];
+        history.extend((0..1000).map(|_| HistoryRecord { timestamp: now, amount_micros: 100 }));
+        history.push(HistoryRecord { timestamp: now, amount_micros: -large });
+
+        let naive: f64 = history.iter().map(|r| r.amount_micros as f64).sum();
+        assert_eq!(naive, 0.0, "朴素求和应丢失全部微小交易");
+
+        let window = decay_window(&history, now, 7.0).unwrap();
+        let compensated = sum_partial_scalar(&window, NeffMode::Signed);
+        assert_eq!(compensated, 100_000.0);
+    }
+
+    #[test]
+    fn test_downsample_prune_preserves_volume() {
+        let hour = 3_600_000;
+        // 100 条旧记录 (超过 1 天) + 20 条近期记录，每条 1.0 单位
+        let mut history: Vec<HistoryRecord> = (0..100)
+            .map(|i| HistoryRecord { timestamp: i * hour, amount_micros: 1_000_000 })
+            .chain((0..20).map(|i| HistoryRecord { timestamp: 200 * hour + i * hour, amount_micros: 1_000_000 }))
+            .collect();
+        let total_before: i64 = history.iter().map(|r| r.amount_micros).sum();
+        let oldest_before = history[0].timestamp;
+
+        prune_history(&mut history, 80, PrunePolicy::Downsample, MS_PER_DAY_I64);
+
+        assert!(history.len() <= 80);
+        // 旧记录被合并而非丢弃：最早时间戳与总量均保留
+        assert_eq!(history[0].timestamp, oldest_before);
+        assert_eq!(history.iter().map(|r| r.amount_micros).sum::<i64>(), total_before);
+        assert_eq!(history[0].amount_micros, 2_000_000);
+        // 近期记录保持原分辨率
+        assert!(history[history.len() - 20..].iter().all(|r| r.amount_micros == 1_000_000));
+        assert!(history.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+        // 对照：DropOldest 丢弃最旧记录
+        let mut dropped: Vec<HistoryRecord> = (0..120)
+            .map(|i| HistoryRecord { timestamp: i * hour, amount_micros: 1_000_000 })
+            .collect();
+        prune_history(&mut dropped, 80, PrunePolicy::DropOldest, MS_PER_DAY_I64);
+        assert_eq!(dropped.len(), 80);
+        assert_eq!(dropped[0].timestamp, 40 * hour);
+    }
+}
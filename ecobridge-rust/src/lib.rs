@@ -3,11 +3,12 @@
 // ==================================================
 
 use libc::{c_char, c_double, c_int, c_longlong}; 
+use std::cell::Cell;
 use std::ffi::CStr;
 use std::panic::{self, AssertUnwindSafe};
 use std::collections::HashMap;
 use std::sync::RwLock;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::ptr;
 use lazy_static::lazy_static;
 
@@ -15,15 +16,12 @@ use lazy_static::lazy_static;
 // 模块声明
 // -----------------------------------------------------------------------------
 pub mod models;
-pub mod economy {
-    pub mod pricing;
-    pub mod summation;
-    pub mod environment;
-    pub mod control;
-    pub mod macro_eco;
-}
+pub mod api;
+pub mod economy;
 pub mod security;
 pub mod storage;
+#[cfg(feature = "async")]
+pub mod async_api;
 
 use crate::models::*;
 
@@ -39,6 +37,14 @@ pub enum EconStatus {
     InvalidLength = 2,
     InvalidValue = 3,
     NumericOverflow = 10,
+    Timeout = 11,
+    AlreadyInitialized = 12,
+    /// 当前构建未启用该功能 (如未开启 `testing` feature 时调用测试专用接口)
+    Unsupported = 13,
+    /// 全服经济冻结中，定价类接口拒绝演算 (见 `ecobridge_set_frozen`)
+    Frozen = 14,
+    /// 数据库写入线程未运行 (未初始化或已关闭)
+    NotRunning = 15,
     InternalError = 100,
     Panic = 101,
     Fatal = 255,
@@ -48,34 +54,24 @@ pub enum EconStatus {
 // 全局状态
 // -----------------------------------------------------------------------------
 static REMOTE_FLOW_ACCUMULATOR_MICROS: AtomicI64 = AtomicI64::new(0);
-const MICROS_SCALE: f64 = 1_000_000.0;
+/// 开发模式：FFI 屏障捕获 panic 后直接中止进程，而非返回 `EconStatus::Panic`
+static PANIC_ABORT: AtomicBool = AtomicBool::new(false);
 const MARKET_META_PREFIX: &str = "MARKET_TRADE:";
 
 lazy_static! {
     static ref REMOTE_FLOW_ACCUMULATOR_BY_KEY: RwLock<HashMap<String, i64>> = RwLock::new(HashMap::new());
 }
 
+/// 测试专用：串行化读写全局单例的测试用例
+#[cfg(test)]
+pub(crate) static GLOBAL_STATE_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
 #[inline]
 fn extract_market_key(meta: &str) -> Option<&str> {
     let key = meta.strip_prefix(MARKET_META_PREFIX)?;
     if key.is_empty() { None } else { Some(key) }
 }
 
-#[inline]
-fn to_micros_saturating(value: f64) -> i64 {
-    if !value.is_finite() {
-        return 0;
-    }
-    let scaled = value * MICROS_SCALE;
-    if scaled >= i64::MAX as f64 {
-        i64::MAX
-    } else if scaled <= i64::MIN as f64 {
-        i64::MIN
-    } else {
-        scaled.round() as i64
-    }
-}
-
 // -----------------------------------------------------------------------------
 // FFI 安全屏障 (The Firewall)
 // -----------------------------------------------------------------------------
@@ -94,6 +90,10 @@ macro_rules! ffi_guard {
                     "Unknown panic"
                 };
                 eprintln!("[EcoBridge-Native] PANIC INTERCEPTED: {}", msg);
+                if PANIC_ABORT.load(Ordering::Relaxed) {
+                    eprintln!("[EcoBridge-Native] Panic abort policy enabled, aborting process");
+                    std::process::abort();
+                }
                 EconStatus::Panic as c_int
             }
         }
@@ -106,7 +106,7 @@ macro_rules! ffi_guard {
 
 #[no_mangle]
 pub extern "C" fn ecobridge_abi_version() -> c_int {
-    0x0009_0000 
+    0x0009_000E
 }
 
 #[no_mangle]
@@ -115,6 +115,48 @@ pub extern "C" fn ecobridge_version() -> *const c_char {
     VERSION.as_ptr() as *const c_char
 }
 
+/// 导出运行时结构体布局描述，供 Java 侧启动时与自身 FFM 布局逐字段核对
+///
+/// 写入至多 `cap` 条 `LayoutEntry`，描述项总数写入 `out_count`；
+/// 可先以 `cap = 0` 查询总数再分配缓冲区。
+///
+/// # Safety
+///
+/// `out_ptr` 须可写入至少 `cap` 个 `LayoutEntry`；`out_count` 须指向可写的 `u64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_layout_descriptor(
+    out_ptr: *mut LayoutEntry,
+    cap: u64,
+    out_count: *mut u64,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_count.is_null() || (out_ptr.is_null() && cap > 0) {
+            return EconStatus::NullPointer;
+        }
+        let entries = models::layout_descriptor();
+        let n = entries.len().min(cap as usize);
+        if n > 0 {
+            std::slice::from_raw_parts_mut(out_ptr, n).copy_from_slice(&entries[..n]);
+        }
+        *out_count = entries.len() as u64;
+        EconStatus::Ok
+    })
+}
+
+/// 当前是否启用 panic 中止策略 (供内部自行捕获 panic 的路径遵循同一策略)
+pub(crate) fn panic_abort_enabled() -> bool {
+    PANIC_ABORT.load(Ordering::Relaxed)
+}
+
+/// 设置 panic 处理策略：非 0 = 中止进程 (开发调试)，0 = 捕获并返回 Panic (默认，生产环境)
+///
+/// 中止前 panic 位置已由默认 panic hook 输出；设置 `RUST_BACKTRACE=1` 可附带完整调用栈。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_panic_abort(enabled: c_int) -> c_int {
+    PANIC_ABORT.store(enabled != 0, Ordering::Relaxed);
+    EconStatus::Ok as c_int
+}
+
 #[no_mangle]
 pub extern "C" fn ecobridge_init_threading(num_threads: c_int) -> c_int {
     let config = rayon::ThreadPoolBuilder::new().num_threads(num_threads as usize);
@@ -128,21 +170,80 @@ pub extern "C" fn ecobridge_init_threading(num_threads: c_int) -> c_int {
 // 2. 存储与监控
 // -----------------------------------------------------------------------------
 
+/// 初始化数据库与写入线程
+///
+/// 每个进程只能绑定一次；重复调用返回 AlreadyInitialized，且本次传入的路径被忽略。
+///
+/// # Safety
+///
+/// `path_ptr` 须为以 NUL 结尾的有效 C 字符串。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_init_db(path_ptr: *const c_char) -> c_int {
+    ecobridge_init_db_ex(path_ptr, 0, 0, 0)
+}
+
+/// 数据库是否已初始化：1 = 已绑定 (后续 init 将返回 AlreadyInitialized)，0 = 未初始化
+#[no_mangle]
+pub extern "C" fn ecobridge_is_db_initialized() -> c_int {
+    storage::is_initialized() as c_int
+}
+
+/// 扩展初始化：可配置 DuckDB 内存上限 (MB)、引擎线程数与读连接池大小
+///
+/// 任一参数 <= 0 时使用默认值 (512MB / 4 线程 / 4 连接)；超出合理范围返回 InvalidValue。
+/// 重复初始化返回 AlreadyInitialized。
+///
+/// # Safety
+///
+/// `path_ptr` 须为以 NUL 结尾的有效 C 字符串。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_init_db_ex(
+    path_ptr: *const c_char,
+    mem_limit_mb: c_int,
+    db_threads: c_int,
+    pool_size: c_int,
+) -> c_int {
+    ecobridge_init_db_with_retry(path_ptr, mem_limit_mb, db_threads, pool_size, 0, 0)
+}
+
+/// 扩展初始化 (含写入重试)：在 `ecobridge_init_db_ex` 基础上配置写入线程的瞬时错误重试
+///
+/// 批次提交遇到锁冲突等瞬时错误时，按 `retry_backoff_ms` 起始的指数退避重试至多 `retry_attempts` 次，
+/// 耗尽或遇到致命错误才丢弃该批次。两者 <= 0 时使用默认值 (3 次 / 50 ms)；
+/// 超出范围 (1..=10 次 / 1..=5000 ms) 返回 InvalidValue。
+///
+/// # Safety
+///
+/// `path_ptr` 须为以 NUL 结尾的有效 C 字符串。
 #[no_mangle]
-pub extern "C" fn ecobridge_init_db(path_ptr: *const c_char) -> c_int {
+pub unsafe extern "C" fn ecobridge_init_db_with_retry(
+    path_ptr: *const c_char,
+    mem_limit_mb: c_int,
+    db_threads: c_int,
+    pool_size: c_int,
+    retry_attempts: c_int,
+    retry_backoff_ms: c_int,
+) -> c_int {
     ffi_guard!(|| {
         if path_ptr.is_null() {
             return EconStatus::NullPointer;
         }
-        let path_result = unsafe { CStr::from_ptr(path_ptr).to_str() };
-        
+        let config = match storage::DbConfig::from_raw(mem_limit_mb, db_threads, pool_size)
+            .and_then(|c| c.with_write_retry(retry_attempts, retry_backoff_ms))
+        {
+            Some(c) => c,
+            None => return EconStatus::InvalidValue,
+        };
+        let path_result = CStr::from_ptr(path_ptr).to_str();
+
         match path_result {
             Ok(path_str) => {
-                match storage::init_economy_db(path_str) {
+                match storage::init_economy_db(path_str, Some(config)) {
                     0 => {
                         economy::summation::hydrate_hot_store();
                         EconStatus::Ok
                     },
+                    storage::INIT_ALREADY_DONE => EconStatus::AlreadyInitialized,
                     _ => EconStatus::Fatal
                 }
             },
@@ -151,14 +252,100 @@ pub extern "C" fn ecobridge_init_db(path_ptr: *const c_char) -> c_int {
     })
 }
 
+/// 关闭数据库写入线程，阻塞等待最终批次落盘 (默认超时 5 秒)
 #[no_mangle]
 pub extern "C" fn ecobridge_shutdown_db() -> c_int {
+    ecobridge_shutdown_db_timeout(storage::DEFAULT_SHUTDOWN_TIMEOUT_MS as c_longlong)
+}
+
+/// 关闭数据库写入线程，最多等待 `timeout_ms` 毫秒让其排空通道并提交
+///
+/// 完整落盘返回 Ok；写入线程未运行 (未初始化或已关闭) 返回 NotRunning；
+/// 超时返回 Timeout，写入线程仍会在后台继续排空，再次调用将继续等待其退出。
+#[no_mangle]
+pub extern "C" fn ecobridge_shutdown_db_timeout(timeout_ms: c_longlong) -> c_int {
+    ffi_guard!(|| {
+        if timeout_ms < 0 {
+            return EconStatus::InvalidValue;
+        }
+        match storage::shutdown_db_internal(std::time::Duration::from_millis(timeout_ms as u64)) {
+            0 => EconStatus::Ok,
+            -8 => EconStatus::Timeout,
+            _ => EconStatus::NotRunning,
+        }
+    })
+}
+
+/// 重置全部全局状态 (插件重载)
+///
+/// 清空热存储与内存历史、归零远程流量累加器与本次会话的日志计数，并将所有可调参数 (含 panic 中止策略) 恢复默认值。
+/// 跨重启持久化的累计丢弃计数与写入线程心跳保留不变。
+/// 与 `ecobridge_shutdown_db` 不同，数据库连接与写入线程保持运行；
+/// 若写入线程正在刷盘，本调用会等待该批次提交完成。
+#[no_mangle]
+pub extern "C" fn ecobridge_reset_all_state() -> c_int {
     ffi_guard!(|| {
-        storage::shutdown_db_internal();
+        storage::reset_state();
+        economy::summation::reset_state();
+        economy::environment::reset_state();
+        economy::calendar::reset_state();
+        economy::pricing::reset_state();
+        economy::lambda_table::reset_state();
+        security::flow_tracker::reset_state();
+        security::ratelimit::reset_state();
+        security::regulator::reset_state();
+        security::freeze::reset_state();
+        REMOTE_FLOW_ACCUMULATOR_MICROS.store(0, Ordering::SeqCst);
+        REMOTE_FLOW_ACCUMULATOR_BY_KEY.write().unwrap_or_else(|e| e.into_inner()).clear();
+        PANIC_ABORT.store(false, Ordering::Relaxed);
         EconStatus::Ok
     })
 }
 
+/// 测试专用：在用例之间重置原生核心的全部全局状态
+///
+/// 在 `ecobridge_reset_all_state` 的基础上同时归零累计丢弃计数与写入线程心跳。
+/// 仅在启用 `testing` feature 的构建中生效；生产构建直接返回 Unsupported 且不修改任何状态。
+#[no_mangle]
+pub extern "C" fn ecobridge_reset_for_testing() -> c_int {
+    #[cfg(feature = "testing")]
+    {
+        let status = ecobridge_reset_all_state();
+        storage::reset_lifetime_counters();
+        status
+    }
+    #[cfg(not(feature = "testing"))]
+    {
+        EconStatus::Unsupported as c_int
+    }
+}
+
+/// 设置热存储超限裁剪策略 (0 = DropOldest, 1 = Downsample)
+///
+/// `downsample_age_ms` 为降采样年龄阈值，仅对早于 (最新记录 - 阈值) 的记录合并。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_prune_policy(policy: c_int, downsample_age_ms: c_longlong) -> c_int {
+    ffi_guard!(|| {
+        let policy = match economy::summation::PrunePolicy::from_raw(policy) {
+            Some(p) => p,
+            None => return EconStatus::InvalidValue,
+        };
+        if !economy::summation::set_prune_policy(policy, downsample_age_ms) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 兼容保留：热存储现始终按时间戳有序插入 (晚到记录插入正确位置)，参数被忽略
+#[no_mangle]
+pub extern "C" fn ecobridge_set_ordered_insert(_enabled: c_int) -> c_int {
+    EconStatus::Ok as c_int
+}
+
+/// # Safety
+///
+/// `uuid_ptr`、`meta_ptr` 须为以 NUL 结尾的有效 C 字符串。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_log_to_duckdb(
     ts: c_longlong,
@@ -174,30 +361,442 @@ pub unsafe extern "C" fn ecobridge_log_to_duckdb(
         let uuid = CStr::from_ptr(uuid_ptr).to_string_lossy().into_owned();
         let meta = CStr::from_ptr(meta_ptr).to_string_lossy().into_owned();
         
-        let amount_f64 = (trade_amount_micros as f64) / MICROS_SCALE;
-        let balance_f64 = (balance_micros as f64) / MICROS_SCALE;
+        let amount_f64 = from_micros(trade_amount_micros);
+        let balance_f64 = from_micros(balance_micros);
 
         if let Some(market_key) = extract_market_key(&meta) {
-            economy::summation::append_trade_to_memory(ts, amount_f64, market_key);
+            if !economy::summation::append_trade_to_memory(ts, amount_f64, market_key) {
+                return EconStatus::NumericOverflow;
+            }
+        }
+        if !storage::log_economy_event(ts, uuid, amount_f64, balance_f64, meta) {
+            return EconStatus::NumericOverflow;
         }
-        storage::log_economy_event(ts, uuid, amount_f64, balance_f64, meta);
         
         EconStatus::Ok
     })
 }
 
+/// 读取租户名：须为合法 UTF-8 且满足 `storage::tenant_table` 的命名规则 (非空)
+unsafe fn read_tenant_name<'a>(name_ptr: *const c_char) -> Option<&'a str> {
+    match CStr::from_ptr(name_ptr).to_str() {
+        Ok(v) if !v.is_empty() && storage::tenant_table(v).is_some() => Some(v),
+        _ => None,
+    }
+}
+
+/// 为租户在同一数据库文件中建立独立日志表 `economy_log_<name>` 与时间索引 (幂等)
+///
+/// 租户名仅允许 ASCII 字母、数字与下划线，长度 1..=32，否则返回 InvalidValue；
+/// 数据库未初始化或建表失败返回 InternalError。每次启动后须重新调用以登记租户。
+///
+/// # Safety
+///
+/// `name_ptr` 须为以 NUL 结尾的有效 C 字符串。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_init_tenant(name_ptr: *const c_char) -> c_int {
+    ffi_guard!(|| {
+        if name_ptr.is_null() { return EconStatus::NullPointer; }
+        let name = match read_tenant_name(name_ptr) {
+            Some(v) => v,
+            None => return EconStatus::InvalidValue,
+        };
+        match storage::init_tenant(name) {
+            Ok(()) => EconStatus::Ok,
+            Err(storage::TenantError::InvalidName) => EconStatus::InvalidValue,
+            Err(e) => {
+                eprintln!("[EcoBridge-Storage] Tenant '{}' init failed: {}", name, e);
+                EconStatus::InternalError
+            }
+        }
+    })
+}
+
+/// 记录经济事件到指定租户的日志表 (参数语义同 `ecobridge_log_to_duckdb`)
+///
+/// 命名租户相互隔离，不进入全局热存储与内存历史。租户名不合法或尚未 `ecobridge_init_tenant`
+/// 返回 InvalidValue；金额超出 i64 Micros 范围返回 NumericOverflow。
+///
+/// # Safety
+///
+/// `tenant_ptr`、`uuid_ptr`、`meta_ptr` 须为以 NUL 结尾的有效 C 字符串。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_log_to_duckdb_tenant(
+    tenant_ptr: *const c_char,
+    ts: c_longlong,
+    uuid_ptr: *const c_char,
+    trade_amount_micros: c_longlong,
+    balance_micros: c_longlong,
+    meta_ptr: *const c_char,
+) -> c_int {
+    ffi_guard!(|| {
+        if tenant_ptr.is_null() || uuid_ptr.is_null() || meta_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+        let tenant = match read_tenant_name(tenant_ptr) {
+            Some(v) if storage::is_tenant_registered(v) => v,
+            _ => return EconStatus::InvalidValue,
+        };
+        let uuid = CStr::from_ptr(uuid_ptr).to_string_lossy().into_owned();
+        let meta = CStr::from_ptr(meta_ptr).to_string_lossy().into_owned();
+
+        let amount_f64 = from_micros(trade_amount_micros);
+        let balance_f64 = from_micros(balance_micros);
+        if !storage::log_tenant_event(tenant, ts, uuid, amount_f64, balance_f64, meta) {
+            return EconStatus::NumericOverflow;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 从指定租户的日志表查询 neff (衰减加权，回溯 3·tau 天)
+///
+/// 租户名不合法或尚未 `ecobridge_init_tenant` 返回 InvalidValue；`tau` 须为正的有限值。
+///
+/// # Safety
+///
+/// `tenant_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_tenant_neff(
+    tenant_ptr: *const c_char,
+    current_ts: c_longlong,
+    tau: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if tenant_ptr.is_null() || out_result.is_null() { return EconStatus::NullPointer; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+        let tenant = match read_tenant_name(tenant_ptr) {
+            Some(v) if storage::is_tenant_registered(v) => v,
+            _ => return EconStatus::InvalidValue,
+        };
+        *out_result = storage::query_tenant_neff_from_db(tenant, current_ts, tau);
+        EconStatus::Ok
+    })
+}
+
+/// 设置启动预热失败策略 (0 = ContinueEmpty, 1 = RetryN, 2 = Fatal)，须在初始化数据库之前调用
+///
+/// `retry_attempts` 仅对 RetryN 生效 (默认 3 次，指数退避)；Fatal 策略下预热失败将令 init 返回 Fatal。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_preload_failure_policy(policy: c_int, retry_attempts: u32) -> c_int {
+    ffi_guard!(|| {
+        let policy = match storage::PreloadFailurePolicy::from_raw(policy) {
+            Some(p) => p,
+            None => return EconStatus::InvalidValue,
+        };
+        if storage::set_preload_failure_policy(policy, retry_attempts) {
+            EconStatus::Ok
+        } else {
+            EconStatus::InvalidValue
+        }
+    })
+}
+
+/// 设置启动预热窗口 (天，默认 30)，须在初始化数据库之前调用
+///
+/// 内存历史与分市场热存储共用此窗口。窗口越长启动越慢、常驻内存越多；`days <= 0` 返回 InvalidValue。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_preload_days(days: c_int) -> c_int {
+    ffi_guard!(|| {
+        if storage::set_preload_days(days as i64) {
+            EconStatus::Ok
+        } else {
+            EconStatus::InvalidValue
+        }
+    })
+}
+
+/// 设置预热窗口使用的 "当前时间" (ms)，用于游戏内时间与主机时钟不一致的服务器及集成测试
+///
+/// 非 0 时启动预热、按天数加载历史与 M1 估算缓存均以该值为 "现在"；0 恢复系统时钟，负值返回 InvalidValue。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_clock_override(now_ms: c_longlong) -> c_int {
+    ffi_guard!(|| {
+        if storage::set_now_override(now_ms) {
+            EconStatus::Ok
+        } else {
+            EconStatus::InvalidValue
+        }
+    })
+}
+
+/// 查询日志写入通道占用率 [0, 1]，数据库未初始化时为 0
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_log_queue_utilization(out_result: *mut c_double) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        *out_result = storage::writer_queue_utilization();
+        EconStatus::Ok
+    })
+}
+
+/// 设置关键事件阻塞发送：非 0 = 影响余额的事件在通道满时短暂阻塞等待 (最多 100ms)，0 = 满则丢弃 (默认)
+///
+/// 遥测事件 (delta == 0) 不受影响。开启后可避免丢账，但写入线程落后时调用线程会出现停顿。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_blocking_critical_logs(enabled: c_int) -> c_int {
+    ffi_guard!(|| {
+        storage::set_blocking_critical_send(enabled != 0);
+        EconStatus::Ok
+    })
+}
+
+/// 查询 M1 货币供应量估算值 (每位玩家最后一次记录的余额之和)
+///
+/// 基于日志中的最后可见余额估算，结果按缓存有效期复用；数据库未初始化时写入 0。
+///
+/// # Safety
+///
+/// `out_m1` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_m1(out_m1: *mut c_double) -> c_int {
+    ffi_guard!(|| {
+        if out_m1.is_null() { return EconStatus::NullPointer; }
+        *out_m1 = storage::latest_m1_estimate();
+        EconStatus::Ok
+    })
+}
+
+/// 基于历史日志建议风控 `velocity_threshold`
+///
+/// 统计 [from_ts, to_ts) 内每位玩家的事件速率 (次/分钟)，输出其分布在 `percentile` 处的取值
+/// (如 0.99 = 仅标记最活跃的 1%)。`percentile` 须位于 [0, 1]；区间内无日志时返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_suggest_velocity_threshold(
+    from_ts: c_longlong,
+    to_ts: c_longlong,
+    percentile: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if !(0.0..=1.0).contains(&percentile) || to_ts <= from_ts { return EconStatus::InvalidValue; }
+        match storage::player_rate_percentile(from_ts, to_ts, percentile) {
+            Some(v) => {
+                *out_result = v;
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
+        }
+    })
+}
+
+/// 设置 M1 估算缓存有效期 (ms)，0 表示不缓存
+#[no_mangle]
+pub extern "C" fn ecobridge_set_m1_cache_ttl(ttl_ms: u64) -> c_int {
+    ffi_guard!(|| {
+        storage::set_m1_cache_ttl_ms(ttl_ms);
+        EconStatus::Ok
+    })
+}
+
+/// 按 UTC 自然日汇总 [start_ts, end_ts) 的经济日志并写入调用方缓冲区
+///
+/// 按日期升序写入至多 `cap` 行 (单次上限 366 天)，实际行数写入 `out_written`。
+/// 区间为空或数据库未初始化时写入 0 行并返回 Ok；查询或行解码失败时返回 InternalError，不写入任何行。
+///
+/// # Safety
+///
+/// `out_ptr` 须可写入至少 `cap` 个 `DaySummary`；`out_written` 须指向可写的 `u64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_daily_summary(
+    start_ts: c_longlong,
+    end_ts: c_longlong,
+    out_ptr: *mut DaySummary,
+    cap: u64,
+    out_written: *mut u64,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_written.is_null() || (out_ptr.is_null() && cap > 0) {
+            return EconStatus::NullPointer;
+        }
+        let rows = if cap == 0 {
+            Vec::new()
+        } else {
+            match storage::daily_summary(start_ts, end_ts) {
+                Ok(rows) => rows,
+                Err(_) => return EconStatus::InternalError,
+            }
+        };
+        let n = rows.len().min(cap as usize);
+        if n > 0 {
+            std::slice::from_raw_parts_mut(out_ptr, n).copy_from_slice(&rows[..n]);
+        }
+        *out_written = n as u64;
+        EconStatus::Ok
+    })
+}
+
+/// 交易额排行榜：按 [start_ts, end_ts) 内资金变动绝对值之和降序写入至多 `cap` 名玩家 (单次上限 1,000)
+///
+/// 缓冲区约定：`out_uuids` 为 `cap × UUID_SLOT_LEN` 字节，第 i 名的标识写入第 i 个槽位，
+/// 以 NUL 结尾并以 0 填充剩余字节 (超过 36 字节的标识按字节截断)；`out_totals[i]` 为对应总额。
+/// 实际行数写入 `out_written`。区间为空或数据库未初始化时写入 0 行并返回 Ok；
+/// 查询或行解码失败时返回 InternalError，不写入任何行。
+///
+/// # Safety
+///
+/// - `out_uuids` 须可写入 `cap × UUID_SLOT_LEN` 字节；
+/// - `out_totals` 须可写入至少 `cap` 个 `c_double`；
+/// - `out_written` 须指向可写的 `u64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_top_traders(
+    start_ts: c_longlong,
+    end_ts: c_longlong,
+    out_uuids: *mut c_char,
+    out_totals: *mut c_double,
+    cap: u64,
+    out_written: *mut u64,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_written.is_null() || ((out_uuids.is_null() || out_totals.is_null()) && cap > 0) {
+            return EconStatus::NullPointer;
+        }
+        let rows = match storage::top_traders(start_ts, end_ts, cap as usize) {
+            Ok(rows) => rows,
+            Err(_) => return EconStatus::InternalError,
+        };
+        let n = rows.len().min(cap as usize);
+        if n > 0 {
+            let slots = std::slice::from_raw_parts_mut(out_uuids as *mut u8, n * storage::UUID_SLOT_LEN);
+            let totals = std::slice::from_raw_parts_mut(out_totals, n);
+            for ((slot, total), (uuid, sum)) in slots.chunks_exact_mut(storage::UUID_SLOT_LEN).zip(totals).zip(&rows) {
+                let bytes = &uuid.as_bytes()[..uuid.len().min(storage::UUID_SLOT_LEN - 1)];
+                slot.fill(0);
+                slot[..bytes.len()].copy_from_slice(bytes);
+                *total = *sum;
+            }
+        }
+        *out_written = n as u64;
+        EconStatus::Ok
+    })
+}
+
+/// 将 [start_ts, end_ts) 的经济日志导出为带表头的 CSV 文件，写入行数写入 `out_rows`
+///
+/// 目标目录不存在时自动创建。区间为空或路径非 UTF-8 返回 InvalidValue；
+/// 数据库未初始化、目录创建或 COPY 失败返回 InternalError。
+///
+/// # Safety
+///
+/// `path_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_rows` 须指向可写的 `u64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_export_csv(
+    path_ptr: *const c_char,
+    start_ts: c_longlong,
+    end_ts: c_longlong,
+    out_rows: *mut u64,
+) -> c_int {
+    ffi_guard!(|| {
+        if path_ptr.is_null() || out_rows.is_null() {
+            return EconStatus::NullPointer;
+        }
+        let path = match CStr::from_ptr(path_ptr).to_str() {
+            Ok(v) if !v.trim().is_empty() => v,
+            _ => return EconStatus::InvalidValue,
+        };
+        match storage::export_csv(path, start_ts, end_ts) {
+            Ok(rows) => {
+                *out_rows = rows;
+                EconStatus::Ok
+            }
+            Err(storage::ExportError::InvalidRange) => EconStatus::InvalidValue,
+            Err(e) => {
+                eprintln!("[EcoBridge-Storage] CSV export failed: {}", e);
+                EconStatus::InternalError
+            }
+        }
+    })
+}
+
+/// 从 Parquet 归档预热全局热存储 (需包含 `ts` 与 `delta` 列)，载入条数写入 `out_rows`
+///
+/// 记录与现有热存储合并并遵循裁剪上限。路径非 UTF-8 或缺少必需列返回 InvalidValue；
+/// 数据库未初始化或读取失败返回 InternalError。
+///
+/// # Safety
+///
+/// `path_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_rows` 须指向可写的 `u64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_hydrate_parquet(path_ptr: *const c_char, out_rows: *mut u64) -> c_int {
+    ffi_guard!(|| {
+        if path_ptr.is_null() || out_rows.is_null() {
+            return EconStatus::NullPointer;
+        }
+        let path = match CStr::from_ptr(path_ptr).to_str() {
+            Ok(v) if !v.trim().is_empty() => v,
+            _ => return EconStatus::InvalidValue,
+        };
+        match storage::hydrate_from_parquet(path) {
+            Ok(rows) => {
+                *out_rows = rows;
+                EconStatus::Ok
+            }
+            Err(e @ storage::ParquetError::MissingColumn(_)) => {
+                eprintln!("[EcoBridge-Storage] Parquet hydration rejected: {}", e);
+                EconStatus::InvalidValue
+            }
+            Err(e) => {
+                eprintln!("[EcoBridge-Storage] Parquet hydration failed: {}", e);
+                EconStatus::InternalError
+            }
+        }
+    })
+}
+
 // -----------------------------------------------------------------------------
 // 3. 核心计算
 // -----------------------------------------------------------------------------
 
+/// 累加远程成交量 (Micros)；累加器在 i64 两端饱和，长期运行的巨额流量不会回绕为反号
 #[no_mangle]
 pub extern "C" fn inject_remote_trade(amount_micros: c_longlong) -> c_int {
     ffi_guard!(|| {
-        REMOTE_FLOW_ACCUMULATOR_MICROS.fetch_add(amount_micros, Ordering::SeqCst);
+        let _ = REMOTE_FLOW_ACCUMULATOR_MICROS.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |acc| {
+            Some(acc.saturating_add(amount_micros))
+        });
+        EconStatus::Ok
+    })
+}
+
+/// 注入带成交时间戳的远程成交量 (Micros)，与本地历史一样按时间衰减计入 neff
+///
+/// 适用于联邦节点重连后回放积压：旧记录只按其年龄贡献供应量，避免 `inject_remote_trade`
+/// 将全部积压视为 "当前" 流量导致 neff 突增、价格崩塌。不经过累加器，查询时不被消费。
+#[no_mangle]
+pub extern "C" fn inject_remote_trade_smoothed(amount_micros: c_longlong, ts: c_longlong) -> c_int {
+    ffi_guard!(|| {
+        economy::summation::append_remote_trade(ts, amount_micros);
+        EconStatus::Ok
+    })
+}
+
+/// 查询全局远程流量累加器的待消费值 (Micros)，不消费该值
+///
+/// # Safety
+///
+/// `out_micros` 须指向可写的 `c_longlong`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_remote_accumulator_value(out_micros: *mut c_longlong) -> c_int {
+    ffi_guard!(|| {
+        if out_micros.is_null() { return EconStatus::NullPointer; }
+        *out_micros = REMOTE_FLOW_ACCUMULATOR_MICROS.load(Ordering::SeqCst);
         EconStatus::Ok
     })
 }
 
+/// # Safety
+///
+/// `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串。
 #[no_mangle]
 pub unsafe extern "C" fn inject_remote_trade_for_key(
     market_key_ptr: *const c_char,
@@ -220,6 +819,9 @@ pub unsafe extern "C" fn inject_remote_trade_for_key(
     })
 }
 
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_longlong`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_money_to_micros(
     value: c_double,
@@ -234,6 +836,9 @@ pub unsafe extern "C" fn ecobridge_money_to_micros(
     })
 }
 
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_micros_to_money(
     value_micros: c_longlong,
@@ -243,11 +848,14 @@ pub unsafe extern "C" fn ecobridge_micros_to_money(
         if out_result.is_null() {
             return EconStatus::NullPointer;
         }
-        *out_result = (value_micros as f64) / MICROS_SCALE;
+        *out_result = from_micros(value_micros);
         EconStatus::Ok
     })
 }
 
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_compute_volatility_from_stability(
     stability: c_double,
@@ -265,6 +873,9 @@ pub unsafe extern "C" fn ecobridge_compute_volatility_from_stability(
     })
 }
 
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_compute_velocity_decay(
     velocity: c_double,
@@ -289,6 +900,53 @@ pub unsafe extern "C" fn ecobridge_compute_velocity_decay(
     })
 }
 
+/// 税负归宿模拟：以 `cfg` 对 `count` 组 (余额, 转账金额) 批量计税，输出总税额与平均有效税率
+///
+/// 供运营在上线新税制前评估其对全体玩家的影响；不经过拦截判定，金额 <= 0 的样本被忽略。
+/// `count` 上限 1,000,000。
+///
+/// # Safety
+///
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `balances_ptr`、`amounts_ptr` 须指向至少 `count` 个连续的 `c_longlong`；
+/// - `out_total_tax` 须指向可写的 `c_longlong`；
+/// - `out_avg_rate` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_simulate_tax_incidence(
+    cfg_ptr: *const RegulatorConfig,
+    balances_ptr: *const c_longlong,
+    amounts_ptr: *const c_longlong,
+    count: u64,
+    out_total_tax: *mut c_longlong,
+    out_avg_rate: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if cfg_ptr.is_null() || out_total_tax.is_null() || out_avg_rate.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if count > 0 && (balances_ptr.is_null() || amounts_ptr.is_null()) {
+            return EconStatus::NullPointer;
+        }
+        if count > 1_000_000 { return EconStatus::InvalidLength; }
+
+        let (balances, amounts) = if count == 0 {
+            (&[][..], &[][..])
+        } else {
+            (
+                std::slice::from_raw_parts(balances_ptr, count as usize),
+                std::slice::from_raw_parts(amounts_ptr, count as usize),
+            )
+        };
+        let (total, avg_rate) = security::regulator::simulate_tax_incidence(&*cfg_ptr, balances, amounts);
+        *out_total_tax = total;
+        *out_avg_rate = avg_rate;
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_compute_fallback_tax(
     amount: c_double,
@@ -306,6 +964,9 @@ pub unsafe extern "C" fn ecobridge_compute_fallback_tax(
     })
 }
 
+/// # Safety
+///
+/// `out_tax`、`out_net` 须指向可写的 `c_double`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_compute_settlement(
     amount: c_double,
@@ -331,6 +992,11 @@ pub unsafe extern "C" fn ecobridge_compute_settlement(
     })
 }
 
+/// 全局有效供应量 = 本地 neff + 远程累加器 (读取后清零) + 带时间戳远程流量的衰减量
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
 pub unsafe extern "C" fn ecobridge_query_neff_vectorized(
     current_ts: c_longlong,
@@ -341,20 +1007,78 @@ pub unsafe extern "C" fn ecobridge_query_neff_vectorized(
         if out_result.is_null() { return EconStatus::NullPointer; }
         if tau <= 0.0 { return EconStatus::InvalidValue; }
 
-        let local_neff = economy::summation::query_neff_global_internal(current_ts, tau);
+        let neff = economy::summation::query_neff_with_remote_internal(current_ts, tau);
         let remote_micros = REMOTE_FLOW_ACCUMULATOR_MICROS.swap(0, Ordering::SeqCst);
-        let remote_neff = (remote_micros as f64) / MICROS_SCALE;
-        
-        *out_result = local_neff + remote_neff;
+        let remote_neff = from_micros(remote_micros);
+
+        *out_result = neff + remote_neff;
         EconStatus::Ok
     })
 }
 
+/// 查询全局有效供应量及其置信度 ∈ [0, 1]，供 UI 展示价格可靠度
+///
+/// 置信度 = 样本项 × 覆盖项：样本项为 `k / (k + NEFF_CONFIDENCE_HALF_SAMPLES)` (k 为 Kish 有效样本数)，
+/// 覆盖项为窗口内最早记录年龄 / tau 天 (钳位至 1)。neff 含带时间戳远程流量，但不消费远程流量累加器；
+/// 置信度只反映本地样本。
+///
+/// # Safety
+///
+/// `out_neff`、`out_confidence` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_query_neff_for_key(
+pub unsafe extern "C" fn ecobridge_query_neff_with_confidence(
     current_ts: c_longlong,
     tau: c_double,
-    market_key_ptr: *const c_char,
+    out_neff: *mut c_double,
+    out_confidence: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_neff.is_null() || out_confidence.is_null() { return EconStatus::NullPointer; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+
+        let (neff, confidence) = economy::summation::query_neff_with_confidence_internal(current_ts, tau);
+        *out_neff = neff;
+        *out_confidence = confidence;
+        EconStatus::Ok
+    })
+}
+
+/// 一次调用查询多个衰减常数下的全局有效供应量 (如 1 / 7 / 30 天)
+///
+/// 只遍历热存储一次，`out_ptr[i]` 对应 `taus_ptr[i]`。与 `ecobridge_query_neff_vectorized` 一致地计入
+/// 带时间戳远程流量，但不消费远程流量累加器。任一 tau 非正或非有限返回 InvalidValue；`count` 上限 1,024。
+///
+/// # Safety
+///
+/// `taus_ptr` 须指向至少 `count` 个连续的 `c_double`；`out_ptr` 须可写入至少 `count` 个 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_neff_multi(
+    current_ts: c_longlong,
+    taus_ptr: *const c_double,
+    count: u64,
+    out_ptr: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if count == 0 { return EconStatus::Ok; }
+        if taus_ptr.is_null() || out_ptr.is_null() { return EconStatus::NullPointer; }
+        if count > 1_024 { return EconStatus::InvalidLength; }
+
+        let taus = std::slice::from_raw_parts(taus_ptr, count as usize);
+        if taus.iter().any(|t| !t.is_finite() || *t <= 0.0) { return EconStatus::InvalidValue; }
+        let out = std::slice::from_raw_parts_mut(out_ptr, count as usize);
+        economy::summation::query_neff_multi_internal(current_ts, taus, out);
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_neff_for_key(
+    current_ts: c_longlong,
+    tau: c_double,
+    market_key_ptr: *const c_char,
     out_result: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
@@ -376,269 +1100,2412 @@ pub unsafe extern "C" fn ecobridge_query_neff_for_key(
         } else {
             0
         };
-        let remote_neff = (remote_micros as f64) / MICROS_SCALE;
+        let remote_neff = from_micros(remote_micros);
 
         *out_result = local_neff + remote_neff;
         EconStatus::Ok
     })
 }
 
+/// 设置 neff 衰减窗口：未来时钟偏差容忍 (ms, >= 0) 与回溯长度 (tau 的倍数, >= 1.0)
+///
+/// 默认值为 60,000 ms 与 10.0。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_batch_prices(
-    count: u64,
-    neff: f64,
-    ctx_ptr: *const TradeContext,
-    cfg_ptr: *const MarketConfig,
-    hist_avgs_ptr: *const f64,
-    lambdas_ptr: *const f64,
-    results_ptr: *mut f64,
-) -> c_int {
+pub extern "C" fn ecobridge_set_neff_window(future_tolerance_ms: c_longlong, lookback_multiplier: c_double) -> c_int {
     ffi_guard!(|| {
-        if ctx_ptr.is_null() || cfg_ptr.is_null() || hist_avgs_ptr.is_null() || 
-           lambdas_ptr.is_null() || results_ptr.is_null() {
-            return EconStatus::NullPointer;
+        if !economy::summation::set_window_params(future_tolerance_ms, lookback_multiplier) {
+            return EconStatus::InvalidValue;
         }
-        
-        if count == 0 { return EconStatus::Ok; }
-        if count > 1_000_000 { return EconStatus::InvalidLength; }
-
-        economy::pricing::compute_batch_prices_internal(
-            count as usize,
-            neff,
-            ctx_ptr,
-            cfg_ptr,
-            hist_avgs_ptr,
-            lambdas_ptr,
-            results_ptr
-        );
-        
         EconStatus::Ok
     })
 }
 
-// --- 单体价格计算函数 (Fix: 适配 i64 Micros 参数) ---
-
+/// 按累积模式查询全局有效供应量
+///
+/// `signed != 0`: 保留买卖方向 (与 `ecobridge_query_neff_vectorized` 的本地部分一致)；
+/// `signed == 0`: 取成交规模绝对值累积。不消费远程流量累加器。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_price_final(
-    base: c_double,
-    n_eff: c_double,
-    lambda: c_double,
-    epsilon: c_double,
+pub unsafe extern "C" fn ecobridge_query_neff_signed(
+    current_ts: c_longlong,
+    tau: c_double,
+    signed: c_int,
     out_result: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
         if out_result.is_null() { return EconStatus::NullPointer; }
-        // [Precision Fix]: 将 c_double base 转换为 i64 Micros
-        let base_micros = to_micros_saturating(base);
-        *out_result = economy::pricing::compute_price_final_internal(base_micros, n_eff, lambda, epsilon);
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+
+        *out_result = if signed != 0 {
+            economy::summation::query_neff_signed(current_ts, tau)
+        } else {
+            economy::summation::query_neff_with_mode(
+                current_ts, tau, economy::summation::GLOBAL_MARKET_KEY, economy::summation::NeffMode::Absolute
+            )
+        };
         EconStatus::Ok
     })
 }
 
+/// 估算全局 neff 的浮点累积误差
+///
+/// `out_neff` 为常规演算结果 (不含远程流量)，`out_error_estimate` 为朴素逐项累加与
+/// Neumaier 补偿求和参考值的绝对差，用于判断当前数据集是否需要关注精度。
+///
+/// # Safety
+///
+/// `out_neff`、`out_error_estimate` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_tier_price(
-    base: c_double,
-    qty: c_double,
-    is_sell: c_int,
-    out_result: *mut c_double,
+pub unsafe extern "C" fn ecobridge_estimate_neff_error(
+    current_ts: c_longlong,
+    tau: c_double,
+    out_neff: *mut c_double,
+    out_error_estimate: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
-        if out_result.is_null() { return EconStatus::NullPointer; }
-        *out_result = economy::pricing::compute_tier_price_internal(base, qty, is_sell != 0);
+        if out_neff.is_null() || out_error_estimate.is_null() { return EconStatus::NullPointer; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+
+        let (neff, error) = economy::summation::estimate_neff_error_internal(current_ts, tau);
+        *out_neff = neff;
+        *out_error_estimate = error;
         EconStatus::Ok
     })
 }
 
+/// # Safety
+///
+/// `out_age_ms` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_price_humane(
-    base: c_double,
-    n_eff: c_double,
-    trade_amount: c_double,
-    lambda: c_double,
-    epsilon: c_double,
-    out_result: *mut c_double,
+pub unsafe extern "C" fn ecobridge_query_avg_trade_age(
+    current_ts: c_longlong,
+    tau: c_double,
+    out_age_ms: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
-        if out_result.is_null() { return EconStatus::NullPointer; }
-        // [Precision Fix]: 将 base 和 trade_amount 转换为 i64 Micros
-        let base_micros = to_micros_saturating(base);
-        let amount_micros = to_micros_saturating(trade_amount);
-        *out_result = economy::pricing::compute_price_humane_internal(base_micros, n_eff, amount_micros, lambda, epsilon);
+        if out_age_ms.is_null() { return EconStatus::NullPointer; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+
+        *out_age_ms = economy::summation::query_avg_trade_age_internal(current_ts, tau);
         EconStatus::Ok
     })
 }
 
+/// 查询全局热存储的衰减加权中位交易规模 (标准单位，买卖均取绝对值)
+///
+/// 权重与 neff 衰减一致且不随规模放大，可作为不受巨额交易影响的 "典型交易" 指标。
+/// 窗口内无记录时写入 0。
+///
+/// # Safety
+///
+/// `out` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_price_bounded(
-    base: c_double,
-    n_eff: c_double,
-    amt: c_double,
-    lambda: c_double,
-    eps: c_double,
-    hist_avg: c_double,
-    out_result: *mut c_double,
+pub unsafe extern "C" fn ecobridge_query_weighted_median(
+    current_ts: c_longlong,
+    tau: c_double,
+    out: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
-        if out_result.is_null() { return EconStatus::NullPointer; }
-        // [Precision Fix]: 将 base 和 amt 转换为 i64 Micros
-        let base_micros = to_micros_saturating(base);
-        let amt_micros = to_micros_saturating(amt);
-        *out_result = economy::pricing::compute_price_bounded_internal(base_micros, n_eff, amt_micros, lambda, eps, hist_avg);
+        if out.is_null() { return EconStatus::NullPointer; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+
+        *out = economy::summation::query_weighted_median_internal(current_ts, tau);
         EconStatus::Ok
     })
 }
 
-// -----------------------------------------------------------------------------
-// 4. 宏观经济指标
-// -----------------------------------------------------------------------------
+/// 查询两个市场成交活跃度的相关系数 (正值: 互补品，负值: 替代品)
+///
+/// 将 [from_ts, to_ts) 按 `bucket_ms` 分桶后计算 Pearson 相关系数，桶数上限 100,000。
+/// 市场不存在于热存储或相关系数无定义 (任一序列无波动) 时返回 InvalidValue。
+///
+/// # Safety
+///
+/// `key_a_ptr`、`key_b_ptr` 须为以 NUL 结尾的有效 C 字符串；`out` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_query_market_neff_correlation(
+    key_a_ptr: *const c_char,
+    key_b_ptr: *const c_char,
+    from_ts: c_longlong,
+    to_ts: c_longlong,
+    bucket_ms: c_longlong,
+    out: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if key_a_ptr.is_null() || key_b_ptr.is_null() || out.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if bucket_ms <= 0 || to_ts <= from_ts {
+            return EconStatus::InvalidValue;
+        }
+        if (to_ts as i128 - from_ts as i128) / bucket_ms as i128 > 100_000 {
+            return EconStatus::InvalidLength;
+        }
+        let (key_a, key_b) = match (CStr::from_ptr(key_a_ptr).to_str(), CStr::from_ptr(key_b_ptr).to_str()) {
+            (Ok(a), Ok(b)) if !a.trim().is_empty() && !b.trim().is_empty() => (a.trim(), b.trim()),
+            _ => return EconStatus::InvalidValue,
+        };
+        match economy::summation::query_market_correlation(key_a, key_b, from_ts, to_ts, bucket_ms) {
+            Some(corr) => {
+                *out = corr;
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
+        }
+    })
+}
 
+/// 将 `MarketConfig` 的四项环境权重按比例归一化 (和为 1.0)
+///
+/// `out_adjusted` 写入 1 表示发生了调整、0 表示已归一化。权重为负/非有限或全为 0 时返回 InvalidValue 且不修改配置。
+///
+/// # Safety
+///
+/// `cfg_ptr` 须指向有效且可写的 `MarketConfig`；`out_adjusted` 须指向可写的 `c_int`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_calc_inflation(
-    current_heat: c_double,
-    m1: c_double,
-    out_result: *mut c_double,
+pub unsafe extern "C" fn ecobridge_normalize_market_weights(
+    cfg_ptr: *mut MarketConfig,
+    out_adjusted: *mut c_int,
 ) -> c_int {
     ffi_guard!(|| {
-        if out_result.is_null() { return EconStatus::NullPointer; }
-        if m1 <= 0.0 { return EconStatus::InvalidValue; }
-        *out_result = economy::macro_eco::calculate_inflation_rate(current_heat, m1);
+        if out_adjusted.is_null() { return EconStatus::NullPointer; }
+        let cfg = match cfg_ptr.as_mut() {
+            Some(c) => c,
+            None => return EconStatus::NullPointer,
+        };
+        match economy::environment::normalize_weights(cfg) {
+            Some(adjusted) => {
+                *out_adjusted = adjusted as c_int;
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
+        }
+    })
+}
+
+/// 按样本 (上下文, 期望 ε) 拟合四项环境权重，结果写入 `out_cfg`
+///
+/// `out_cfg` 为 `cfg_ptr` 的副本，仅四项权重被替换为拟合值 (非负且和为 1)。
+/// 无有效样本 (期望 ε 均非正或非有限) 时权重保持不变；`count` 上限 100,000。
+///
+/// # Safety
+///
+/// - `ctxs_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+/// - `targets_ptr` 须指向至少 `count` 个连续的 `c_double`；
+/// - `cfg_ptr` 须指向有效的 `MarketConfig`；
+/// - `out_cfg` 须指向可写的 `MarketConfig`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_solve_epsilon_weights(
+    ctxs_ptr: *const TradeContext,
+    targets_ptr: *const c_double,
+    count: u64,
+    cfg_ptr: *const MarketConfig,
+    out_cfg: *mut MarketConfig,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctxs_ptr.is_null() || targets_ptr.is_null() || cfg_ptr.is_null() || out_cfg.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if count == 0 || count > 100_000 { return EconStatus::InvalidLength; }
+
+        let ctxs = std::slice::from_raw_parts(ctxs_ptr, count as usize);
+        let targets = std::slice::from_raw_parts(targets_ptr, count as usize);
+        let samples: Vec<(TradeContext, f64)> = ctxs.iter().copied().zip(targets.iter().copied()).collect();
+        *out_cfg = economy::environment::solve_weights(&samples, &*cfg_ptr);
         EconStatus::Ok
     })
 }
 
+/// 计算单条记录在 `age_ms` 年龄下的衰减权重 `exp(-age_ms / (tau · 86_400_000))`
+///
+/// 与 neff 累积使用的权重公式一致。要求 `tau > 0` 且 `age_ms >= 0`。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_calc_stability(
-    last_ts: c_longlong,
-    curr_ts: c_longlong,
+pub unsafe extern "C" fn ecobridge_decay_weight(
+    age_ms: c_longlong,
+    tau: c_double,
     out_result: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
         if out_result.is_null() { return EconStatus::NullPointer; }
-        *out_result = economy::macro_eco::calculate_stability(last_ts, curr_ts, 900000.0);
+        if age_ms < 0 || !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+        *out_result = economy::summation::decay_weight(age_ms, tau);
         EconStatus::Ok
     })
 }
 
+/// 查询全局热存储在闭区间 [start_ts, end_ts] 内的原始 (不衰减) 成交量，单位为标准货币
+///
+/// 供报表使用，与衰减加权的 neff 互补；`start_ts > end_ts` 时返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_calc_decay(
-    heat: c_double,
-    rate: c_double,
+pub unsafe extern "C" fn ecobridge_volume_in_range(
+    start_ts: c_longlong,
+    end_ts: c_longlong,
     out_result: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
         if out_result.is_null() { return EconStatus::NullPointer; }
-        *out_result = economy::macro_eco::calculate_decay(heat, rate, 48.0);
+        if start_ts > end_ts { return EconStatus::InvalidValue; }
+        *out_result = economy::summation::volume_in_range(start_ts, end_ts);
         EconStatus::Ok
     })
 }
 
+/// 按分桶边界统计全局热存储的单笔成交量分布 (标准货币，取绝对值)，供看板绘制分布图
+///
+/// `edge_count` 个严格递增的边界划分 `edge_count - 1` 个桶，`out_counts` 须至少容纳同样数量；
+/// 最后一个桶包含右边界，超出边界范围的记录不计入。边界少于 2 个或超过 1,024 个返回 InvalidLength，
+/// 含非有限值或未严格递增返回 InvalidValue。
+///
+/// # Safety
+///
+/// `edges_ptr` 须指向至少 `edge_count` 个连续的 `c_double`；`out_counts` 须可写入至少 `edge_count - 1` 个 `u64`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_get_health_stats(
-    out_total: *mut u64, 
-    out_dropped: *mut u64,
+pub unsafe extern "C" fn ecobridge_amount_histogram(
+    edges_ptr: *const c_double,
+    edge_count: u64,
+    out_counts: *mut u64,
 ) -> c_int {
     ffi_guard!(|| {
-        if out_total.is_null() || out_dropped.is_null() {
-            return EconStatus::NullPointer;
+        if edges_ptr.is_null() || out_counts.is_null() { return EconStatus::NullPointer; }
+        if !(2..=1_024).contains(&edge_count) { return EconStatus::InvalidLength; }
+
+        let edges = std::slice::from_raw_parts(edges_ptr, edge_count as usize);
+        match economy::summation::amount_histogram(edges) {
+            Some(counts) => {
+                std::slice::from_raw_parts_mut(out_counts, counts.len()).copy_from_slice(&counts);
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
         }
-        *out_total = storage::get_total_logs() as u64; 
-        *out_dropped = storage::get_dropped_logs() as u64;
-        EconStatus::Ok
     })
 }
 
+/// 由两次 neff 观测 (期间无新成交) 反推衰减常数 tau (天)，用于按实测数据校准模型
+///
+/// 未观测到衰减 (`neff_t2 >= neff_t1` 或符号不一致) 或 `dt_ms <= 0` 时返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_tau` 须指向可写的 `c_double`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_calculate_epsilon(
-    ctx_ptr: *const TradeContext,
-    cfg_ptr: *const MarketConfig,
-    out_result: *mut c_double,
+pub unsafe extern "C" fn ecobridge_fit_tau(
+    neff_t1: c_double,
+    neff_t2: c_double,
+    dt_ms: c_longlong,
+    out_tau: *mut c_double,
 ) -> c_int {
     ffi_guard!(|| {
-        if ctx_ptr.is_null() || cfg_ptr.is_null() || out_result.is_null() {
-            return EconStatus::NullPointer;
+        if out_tau.is_null() { return EconStatus::NullPointer; }
+        match economy::summation::fit_tau(neff_t1, neff_t2, dt_ms) {
+            Some(tau) => {
+                *out_tau = tau;
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
         }
-        *out_result = economy::environment::calculate_epsilon_internal(&*ctx_ptr, &*cfg_ptr);
-        EconStatus::Ok
     })
 }
 
-// -----------------------------------------------------------------------------
-// 5. 安全审计与动态限额
-// -----------------------------------------------------------------------------
-
+/// 将全局热存储中最新的至多 `cap` 条记录复制到调用方缓冲区
+///
+/// 返回的数据为调用时刻的时间点快照，按时间先后排列；容量不足时仅保留最新部分。
+/// 实际写入条数写入 `out_written`。
+///
+/// # Safety
+///
+/// `out_ptr` 须可写入至少 `cap` 个 `HistoryRecord`；`out_written` 须指向可写的 `u64`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_transfer_check(
-    out_result: *mut TransferResult,
-    ctx_ptr: *const TransferContext,
-    cfg_ptr: *const RegulatorConfig,
+pub unsafe extern "C" fn ecobridge_snapshot_history(
+    out_ptr: *mut HistoryRecord,
+    cap: u64,
+    out_written: *mut u64,
 ) -> c_int {
     ffi_guard!(|| {
-        if out_result.is_null() || ctx_ptr.is_null() || cfg_ptr.is_null() {
+        if out_written.is_null() || (out_ptr.is_null() && cap > 0) {
             return EconStatus::NullPointer;
         }
-
-        let res = security::regulator::compute_transfer_check_internal(&*ctx_ptr, &*cfg_ptr);
-        ptr::write(out_result, res);
+        let written = if cap == 0 {
+            0
+        } else {
+            let out = std::slice::from_raw_parts_mut(out_ptr, cap as usize);
+            economy::summation::snapshot_recent_history(out)
+        };
+        *out_written = written as u64;
         EconStatus::Ok
     })
 }
 
+/// 批量注入历史交易到全局热存储 (回测重放)，不写入 DuckDB
+///
+/// 整批只加一次写锁；乱序记录自动按时间戳排序，超出容量时按当前裁剪策略处理。
+/// `count` 为 0 时直接返回 Ok，上限 1,000,000 条 (更大的数据集请分批注入)。
+///
+/// # Safety
+///
+/// `ptr` 须指向至少 `count` 个连续的 `HistoryRecord`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_get_dynamic_limit(
-    play_time_secs: c_longlong,
-    base: c_double,
-    rate: c_double,
-    max: c_double,
-    out_result: *mut c_double,
-) -> c_int {
+pub unsafe extern "C" fn ecobridge_bulk_inject(ptr: *const HistoryRecord, count: u64) -> c_int {
     ffi_guard!(|| {
-        if out_result.is_null() { return EconStatus::NullPointer; }
-        
-        let hours = (play_time_secs as f64) / 3600.0;
-        let calculated = base + (rate * hours.sqrt());
-        *out_result = calculated.min(max);
-        
+        if count == 0 { return EconStatus::Ok; }
+        if ptr.is_null() { return EconStatus::NullPointer; }
+        if count > 1_000_000 { return EconStatus::InvalidLength; }
+
+        economy::summation::bulk_inject(std::slice::from_raw_parts(ptr, count as usize));
         EconStatus::Ok
     })
 }
 
-// -----------------------------------------------------------------------------
-// 6. PID 控制
-// -----------------------------------------------------------------------------
+/// 将联邦节点的历史批次按时间戳归并进全局热存储 (乱序批次自动排序)，不写入 DuckDB
+///
+/// 与本地记录原地归并以维持热存储的有序不变量，同一时间戳下本地记录在前。
+/// `count` 为 0 时直接返回 Ok，上限 1,000,000 条。
+///
+/// # Safety
+///
+/// `ptr` 须指向至少 `count` 个连续的 `HistoryRecord`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_merge_peer_history(ptr: *const HistoryRecord, count: u64) -> c_int {
+    ffi_guard!(|| {
+        if count == 0 { return EconStatus::Ok; }
+        if ptr.is_null() { return EconStatus::NullPointer; }
+        if count > 1_000_000 { return EconStatus::InvalidLength; }
+
+        economy::summation::merge_sorted_history(std::slice::from_raw_parts(ptr, count as usize));
+        EconStatus::Ok
+    })
+}
 
+/// 批量定价 (全部物品共享同一 neff)
+///
+/// 单个元素演算 panic 时该元素写入哨兵价格 0.01，其余元素照常完成；
+/// 首个失败元素的下标可在同一线程经 `ecobridge_last_batch_error` 查询。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+/// - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+/// - `hist_avgs_ptr`、`lambdas_ptr` 须指向至少 `count` 个连续的 `f64`；
+/// - `results_ptr` 须可写入至少 `count` 个 `f64`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_compute_pid_adjustment(
-    pid_ptr: *mut PidState,
-    target: c_double,
-    current: c_double,
-    dt: c_double,
-    inflation: c_double,
-    market_heat: c_double,
-    out_result: *mut c_double,
+pub unsafe extern "C" fn ecobridge_compute_batch_prices(
+    count: u64,
+    neff: f64,
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    hist_avgs_ptr: *const f64,
+    lambdas_ptr: *const f64,
+    results_ptr: *mut f64,
 ) -> c_int {
+    set_last_batch_error(None);
     ffi_guard!(|| {
-        if pid_ptr.is_null() || out_result.is_null() { 
-            return EconStatus::NullPointer; 
-        }
-        if let Some(pid) = pid_ptr.as_mut() {
-            *out_result = economy::control::compute_pid_adjustment_internal(
-                pid, target, current, dt, inflation, market_heat
-            );
-            EconStatus::Ok
-        } else {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || hist_avgs_ptr.is_null() || 
+           lambdas_ptr.is_null() || results_ptr.is_null() {
             return EconStatus::NullPointer;
         }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        
+        if count == 0 { return EconStatus::Ok; }
+        if count > 1_000_000 { return EconStatus::InvalidLength; }
+
+        let failed = economy::pricing::compute_batch_prices_internal(
+            count as usize,
+            neff,
+            ctx_ptr,
+            cfg_ptr,
+            hist_avgs_ptr,
+            lambdas_ptr,
+            results_ptr
+        );
+        set_last_batch_error(failed);
+        
+        EconStatus::Ok
     })
 }
 
+/// 逐物品 neff 的批量定价：第 i 个物品使用 `neffs_ptr[i]`，其余参数与 `ecobridge_compute_batch_prices` 相同
+///
+/// 适用于按物品独立统计供应量的市场；全部物品共享同一 neff 时请使用原函数。
+/// `neffs_len` 为 `neffs_ptr` 数组的实际元素个数，不等于 `count` 时返回 InvalidLength 且不读写任何元素；
+/// 任一 neff 非有限值返回 InvalidValue 且不写入结果。
+///
+/// # Safety
+///
+/// - `neffs_ptr` 须指向至少 `neffs_len` 个连续的 `f64`；
+/// - `hist_avgs_ptr`、`lambdas_ptr` 须指向至少 `count` 个连续的 `f64`；
+/// - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+/// - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+/// - `results_ptr` 须可写入至少 `count` 个 `f64`。
 #[no_mangle]
-pub unsafe extern "C" fn ecobridge_reset_pid_state(pid_ptr: *mut PidState) -> c_int {
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ecobridge_compute_batch_prices_per_neff(
+    count: u64,
+    neffs_ptr: *const f64,
+    neffs_len: u64,
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    hist_avgs_ptr: *const f64,
+    lambdas_ptr: *const f64,
+    results_ptr: *mut f64,
+) -> c_int {
+    set_last_batch_error(None);
     ffi_guard!(|| {
-        if let Some(pid) = pid_ptr.as_mut() {
-            *pid = PidState::default();
-            EconStatus::Ok
-        } else {
-            return EconStatus::NullPointer
+        if neffs_ptr.is_null() || ctx_ptr.is_null() || cfg_ptr.is_null() || hist_avgs_ptr.is_null() ||
+           lambdas_ptr.is_null() || results_ptr.is_null() {
+            return EconStatus::NullPointer;
         }
-    })
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+
+        if neffs_len != count { return EconStatus::InvalidLength; }
+        if count == 0 { return EconStatus::Ok; }
+        if count > 1_000_000 { return EconStatus::InvalidLength; }
+        if std::slice::from_raw_parts(neffs_ptr, count as usize).iter().any(|n| !n.is_finite()) {
+            return EconStatus::InvalidValue;
+        }
+
+        let failed = economy::pricing::compute_batch_prices_per_neff_internal(
+            count as usize,
+            neffs_ptr,
+            ctx_ptr,
+            cfg_ptr,
+            hist_avgs_ptr,
+            lambdas_ptr,
+            results_ptr
+        );
+        set_last_batch_error(failed);
+
+        EconStatus::Ok
+    })
+}
+
+/// 按物品 ID 查表取 lambda 的批量定价：`item_ids_ptr[i]` 对应 `ecobridge_set_item_lambda` 登记的值
+///
+/// 未登记的物品取默认 lambda (0.01)；其余参数与 `ecobridge_compute_batch_prices` 相同。
+/// 查表在原生侧完成，调用方无需每次编组完整的 lambda 数组。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向至少 `count` 个连续的 `TradeContext`；
+/// - `cfg_ptr` 须指向至少 `count` 个连续的 `MarketConfig`；
+/// - `hist_avgs_ptr` 须指向至少 `count` 个连续的 `f64`；
+/// - `item_ids_ptr` 须指向至少 `count` 个连续的 `u32`；
+/// - `results_ptr` 须可写入至少 `count` 个 `f64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_batch_prices_by_item(
+    count: u64,
+    neff: f64,
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    hist_avgs_ptr: *const f64,
+    item_ids_ptr: *const u32,
+    results_ptr: *mut f64,
+) -> c_int {
+    set_last_batch_error(None);
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || hist_avgs_ptr.is_null() ||
+           item_ids_ptr.is_null() || results_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+
+        if count == 0 { return EconStatus::Ok; }
+        if count > 1_000_000 { return EconStatus::InvalidLength; }
+
+        let lambdas = economy::lambda_table::lookup_many(std::slice::from_raw_parts(item_ids_ptr, count as usize));
+        let failed = economy::pricing::compute_batch_prices_internal(
+            count as usize,
+            neff,
+            ctx_ptr,
+            cfg_ptr,
+            hist_avgs_ptr,
+            lambdas.as_ptr(),
+            results_ptr
+        );
+        set_last_batch_error(failed);
+
+        EconStatus::Ok
+    })
+}
+
+/// 登记物品的 lambda (价格敏感度)，供 `ecobridge_compute_batch_prices_by_item` 查表
+///
+/// `lambda` 须为正有限值，否则返回 InvalidValue。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_item_lambda(item_id: u32, lambda: c_double) -> c_int {
+    ffi_guard!(|| {
+        if !economy::lambda_table::set_lambda(item_id, lambda) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 查询物品的 lambda，未登记时写入默认值 0.01
+///
+/// # Safety
+///
+/// `out_lambda` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_get_item_lambda(item_id: u32, out_lambda: *mut c_double) -> c_int {
+    ffi_guard!(|| {
+        if out_lambda.is_null() { return EconStatus::NullPointer; }
+        *out_lambda = economy::lambda_table::get_lambda(item_id);
+        EconStatus::Ok
+    })
+}
+
+/// 带长度校验的批量定价
+///
+/// 跨 FFI 无法感知指针背后的真实数组长度，因此要求调用方为每个数组显式传入其元素个数：
+/// 输入数组 (`ctx`/`cfg`/`hist_avgs`/`lambdas`) 与输出数组 `results` 的长度均须等于 `count`，
+/// 任一不一致即返回 InvalidLength 且不读写任何元素，避免越界读取导致的未定义行为。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向至少 `ctx_len` 个连续的 `TradeContext`；
+/// - `cfg_ptr` 须指向至少 `cfg_len` 个连续的 `MarketConfig`；
+/// - `hist_avgs_ptr` 须指向至少 `hist_avgs_len` 个连续的 `f64`；
+/// - `lambdas_ptr` 须指向至少 `lambdas_len` 个连续的 `f64`；
+/// - `results_ptr` 须可写入至少 `results_len` 个 `f64`。
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ecobridge_compute_batch_prices_checked(
+    count: u64,
+    neff: f64,
+    ctx_ptr: *const TradeContext,
+    ctx_len: u64,
+    cfg_ptr: *const MarketConfig,
+    cfg_len: u64,
+    hist_avgs_ptr: *const f64,
+    hist_avgs_len: u64,
+    lambdas_ptr: *const f64,
+    lambdas_len: u64,
+    results_ptr: *mut f64,
+    results_len: u64,
+) -> c_int {
+    set_last_batch_error(None);
+    if [ctx_len, cfg_len, hist_avgs_len, lambdas_len, results_len].iter().any(|&len| len != count) {
+        return EconStatus::InvalidLength as c_int;
+    }
+    ecobridge_compute_batch_prices(count, neff, ctx_ptr, cfg_ptr, hist_avgs_ptr, lambdas_ptr, results_ptr)
+}
+
+thread_local! {
+    /// 本线程最近一次批量定价中首个 panic 元素的下标 (-1 = 无)；按线程记录，并发批次互不覆盖
+    static LAST_BATCH_FAILED_INDEX: Cell<c_longlong> = const { Cell::new(-1) };
+}
+
+/// 记录本线程批量定价的失败下标；各批量定价接口入口先清为 -1，任何提前返回都不会残留上一批次的结果
+fn set_last_batch_error(failed: Option<usize>) {
+    LAST_BATCH_FAILED_INDEX.with(|idx| idx.set(failed.map_or(-1, |i| i as c_longlong)));
+}
+
+/// 查询本线程最近一次批量定价中首个 panic 元素的下标，无失败时写入 -1
+///
+/// 批量定价对单个元素的 panic 做隔离：该元素输出哨兵价格 0.01，其余元素照常完成。
+/// 记录按调用线程区分，须在发起批量定价的同一线程查询；批量接口因参数校验提前返回时同样记为 -1。
+///
+/// # Safety
+///
+/// `out_index` 须指向可写的 `c_longlong`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_last_batch_error(out_index: *mut c_longlong) -> c_int {
+    ffi_guard!(|| {
+        if out_index.is_null() { return EconStatus::NullPointer; }
+        *out_index = LAST_BATCH_FAILED_INDEX.with(Cell::get);
+        EconStatus::Ok
+    })
+}
+
+/// 登记新市场的上线爬坡：`[launch_ts, launch_ts + ramp_ms)` 内价格从首发价线性过渡到演算价
+///
+/// `ramp_ms <= 0` 移除该市场的爬坡设置；启用爬坡时 `launch_price_micros` 须为正。
+///
+/// # Safety
+///
+/// `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_set_market_launch(
+    market_key_ptr: *const c_char,
+    launch_ts: c_longlong,
+    ramp_ms: c_longlong,
+    launch_price_micros: c_longlong,
+) -> c_int {
+    ffi_guard!(|| {
+        if market_key_ptr.is_null() { return EconStatus::NullPointer; }
+        let market_key = match CStr::from_ptr(market_key_ptr).to_str() {
+            Ok(v) if !v.trim().is_empty() => v.trim(),
+            _ => return EconStatus::InvalidValue,
+        };
+        let launch = economy::pricing::MarketLaunch { launch_ts, ramp_ms, launch_price_micros };
+        if economy::pricing::set_market_launch(market_key, launch) {
+            EconStatus::Ok
+        } else {
+            EconStatus::InvalidValue
+        }
+    })
+}
+
+/// 按市场成熟度混合价格：爬坡期内在首发价与 `computed_price` 之间按已上线时长插值
+///
+/// 未登记的市场或爬坡期结束后原样输出 `computed_price`。
+///
+/// # Safety
+///
+/// `market_key_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_apply_market_launch(
+    market_key_ptr: *const c_char,
+    now_ms: c_longlong,
+    computed_price: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if market_key_ptr.is_null() || out_result.is_null() { return EconStatus::NullPointer; }
+        let market_key = match CStr::from_ptr(market_key_ptr).to_str() {
+            Ok(v) if !v.trim().is_empty() => v.trim(),
+            _ => return EconStatus::InvalidValue,
+        };
+        *out_result = economy::pricing::apply_launch_ramp(market_key, now_ms, computed_price);
+        EconStatus::Ok
+    })
+}
+
+/// 设置冷启动最少样本数 (0 = 关闭，默认)
+///
+/// 定价时若对应市场热存储窗口内样本数低于该值，价格按 样本数/最少样本数 的置信度向基础价线性回归，
+/// 避免重置或新开服后 neff 过小导致价格剧烈跳变。单体定价按调用方传入的 tau 统计样本，
+/// 批量定价接口不携带 tau，按 `WARMUP_TAU_DAYS` (7 天) 统计。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_pricing_min_samples(n: u64) -> c_int {
+    ffi_guard!(|| {
+        economy::pricing::set_min_samples(n);
+        EconStatus::Ok
+    })
+}
+
+// --- 单体价格计算函数 (Fix: 适配 i64 Micros 参数) ---
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_final(
+    base: c_double,
+    n_eff: c_double,
+    lambda: c_double,
+    epsilon: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        // [Precision Fix]: 将 c_double base 转换为 i64 Micros (溢出返回 NumericOverflow)
+        let base_micros = match to_micros_checked(base) {
+            Some(v) => v,
+            None => return EconStatus::NumericOverflow,
+        };
+        *out_result = economy::pricing::compute_price_final_internal(base_micros, n_eff, lambda, epsilon);
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_tier_price(
+    base: c_double,
+    qty: c_double,
+    is_sell: c_int,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        *out_result = economy::pricing::compute_tier_price_internal(base, qty, is_sell != 0);
+        EconStatus::Ok
+    })
+}
+
+/// 预估一次性卖出 `quantity` 件物品的总收益 (Micros)
+///
+/// 逐件计入有效供应量冲击与 `tier_cfg_ptr` 指定的阶梯折扣，而非简单的 数量 × 单价。
+/// `quantity` 上限为 1,000,000 件；阶梯配置非法 (见 `TierConfig::is_valid`) 时返回 InvalidValue。
+///
+/// # Safety
+///
+/// - `tier_cfg_ptr` 须指向有效的 `TierConfig`；
+/// - `out_total_micros` 须指向可写的 `c_longlong`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_sell_all_value(
+    base_micros: c_longlong,
+    n_eff: c_double,
+    quantity: c_longlong,
+    lambda: c_double,
+    eps: c_double,
+    tier_cfg_ptr: *const TierConfig,
+    out_total_micros: *mut c_longlong,
+) -> c_int {
+    ffi_guard!(|| {
+        if tier_cfg_ptr.is_null() || out_total_micros.is_null() { return EconStatus::NullPointer; }
+        if quantity < 0 { return EconStatus::InvalidValue; }
+        if quantity > 1_000_000 { return EconStatus::InvalidLength; }
+        let tiers = &*tier_cfg_ptr;
+        if !tiers.is_valid() { return EconStatus::InvalidValue; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+
+        let total = economy::pricing::compute_sell_all_value_internal(
+            base_micros, n_eff, quantity as u64, lambda, eps, tiers
+        );
+        *out_total_micros = match to_micros_checked(total) {
+            Some(v) => v,
+            None => return EconStatus::NumericOverflow,
+        };
+        EconStatus::Ok
+    })
+}
+
+/// 计算参考物品在 [from_ts, to_ts] 间的购买力比值 (then 价格 / now 价格)
+///
+/// 两端价格使用全局热存储在各自时点的 neff，不消费远程流量累加器。
+/// 结果 < 1 表示单位货币现在能买到的更少。
+///
+/// # Safety
+///
+/// `out_ratio` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_purchasing_power(
+    base_micros: c_longlong,
+    from_ts: c_longlong,
+    to_ts: c_longlong,
+    lambda: c_double,
+    tau: c_double,
+    out_ratio: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_ratio.is_null() { return EconStatus::NullPointer; }
+        if base_micros <= 0 || from_ts > to_ts || !lambda.is_finite() || !tau.is_finite() || tau <= 0.0 {
+            return EconStatus::InvalidValue;
+        }
+
+        let ratio = economy::pricing::compute_purchasing_power_internal(base_micros, from_ts, to_ts, lambda, tau);
+        if !ratio.is_finite() { return EconStatus::NumericOverflow; }
+        *out_ratio = ratio;
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_humane(
+    base: c_double,
+    n_eff: c_double,
+    trade_amount: c_double,
+    lambda: c_double,
+    epsilon: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        // [Precision Fix]: 将 base 和 trade_amount 转换为 i64 Micros
+        let (base_micros, amount_micros) = match (to_micros_checked(base), to_micros_checked(trade_amount)) {
+            (Some(b), Some(a)) => (b, a),
+            _ => return EconStatus::NumericOverflow,
+        };
+        *out_result = economy::pricing::compute_price_humane_internal(base_micros, n_eff, amount_micros, lambda, epsilon);
+        EconStatus::Ok
+    })
+}
+
+/// 锚定价均值回归定价：在 `ecobridge_compute_price_humane` 的结果上按 `reversion_strength` 拉向 `anchor_micros`
+///
+/// `reversion_strength` 须位于 [0, 1] (0 时与 humane 结果完全一致)，`anchor_micros` 须为正，否则返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_anchored(
+    base: c_double,
+    n_eff: c_double,
+    trade_amount: c_double,
+    lambda: c_double,
+    epsilon: c_double,
+    anchor_micros: c_longlong,
+    reversion_strength: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        if !(0.0..=1.0).contains(&reversion_strength) || anchor_micros <= 0 {
+            return EconStatus::InvalidValue;
+        }
+        let (base_micros, amount_micros) = match (to_micros_checked(base), to_micros_checked(trade_amount)) {
+            (Some(b), Some(a)) => (b, a),
+            _ => return EconStatus::NumericOverflow,
+        };
+        *out_result = economy::pricing::compute_price_anchored_internal(
+            base_micros, n_eff, amount_micros, lambda, epsilon, anchor_micros, reversion_strength,
+        );
+        EconStatus::Ok
+    })
+}
+
+/// 带底价保护的价格演算：底价取 历史均价 × 20% 与物品硬底价 `min_price_micros` 的较大者
+///
+/// `min_price_micros <= 0` 时硬底价退回全局 0.01。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_bounded(
+    base: c_double,
+    n_eff: c_double,
+    amt: c_double,
+    lambda: c_double,
+    eps: c_double,
+    hist_avg: c_double,
+    min_price_micros: c_longlong,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        // [Precision Fix]: 将 base 和 amt 转换为 i64 Micros
+        let (base_micros, amt_micros) = match (to_micros_checked(base), to_micros_checked(amt)) {
+            (Some(b), Some(a)) => (b, a),
+            _ => return EconStatus::NumericOverflow,
+        };
+        *out_result = economy::pricing::compute_price_bounded_internal(
+            base_micros, n_eff, amt_micros, lambda, eps, hist_avg, min_price_micros
+        );
+        EconStatus::Ok
+    })
+}
+
+/// 一站式定价：由 `ctx` 与 `cfg` 直接得出带底价保护的价格
+///
+/// 内部以 `ctx.current_timestamp` 与 `tau` 查询全局 neff (含带时间戳远程流量，不消费远程流量累加器)，
+/// 由 `cfg` 计算环境因子，再按 `ctx.current_amount` 演算，硬底价取全局 0.01；
+/// 全局样本不足时按同一 `tau` 的预热置信度向基础价回归 (见 `ecobridge_set_pricing_min_samples`)；
+/// 卖出时按 `ctx.play_time_seconds` 叠加新手卖出加成 (见 `ecobridge_set_newbie_sell_bonus`)，不读取 `ctx.newbie_mask`。
+/// tau 非正或非有限返回 InvalidValue。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向有效的 `TradeContext`；
+/// - `cfg_ptr` 须指向有效的 `MarketConfig`；
+/// - `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_price_from_context(
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    tau: c_double,
+    lambda: c_double,
+    hist_avg: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || out_result.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+        let ctx = &*ctx_ptr;
+
+        let n_eff = economy::summation::query_neff_with_remote_internal(ctx.current_timestamp, tau);
+        *out_result = economy::pricing::compute_price_from_context_internal(
+            ctx, &*cfg_ptr, economy::summation::GLOBAL_MARKET_KEY, tau, n_eff, lambda, hist_avg,
+        );
+        EconStatus::Ok
+    })
+}
+
+/// 设置新手卖出加成：游戏时长低于 `threshold_secs` 的玩家经 `ecobridge_price_from_context` 卖出时，
+/// 价格乘以随时长线性衰减的倍率 (0 时长为 `factor`，达到阈值时为 1.0)
+///
+/// 新手身份与环境因子的新手保护一致，仅由 `play_time_seconds` 判定；`newbie_mask` 的 bit1 为节庆标记，不参与判定。
+/// `threshold_secs` 须非负 (0 关闭)，`factor` 须位于 [1.0, 2.0]，否则返回 InvalidValue。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_newbie_sell_bonus(threshold_secs: c_longlong, factor: c_double) -> c_int {
+    ffi_guard!(|| {
+        let bonus = economy::pricing::NewbieSellBonus { threshold_secs, factor };
+        if !economy::pricing::set_newbie_sell_bonus(bonus) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 同 `ecobridge_compute_price_humane`，卖出时 lambda 的缩放系数由调用方指定 (默认 0.6)
+///
+/// `sell_sensitivity_ratio` 须位于 (0, 2]，否则返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_humane_ex(
+    base: c_double,
+    n_eff: c_double,
+    trade_amount: c_double,
+    lambda: c_double,
+    epsilon: c_double,
+    sell_sensitivity_ratio: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        if !economy::pricing::validate_sell_sensitivity_ratio(sell_sensitivity_ratio) { return EconStatus::InvalidValue; }
+        let (base_micros, amount_micros) = match (to_micros_checked(base), to_micros_checked(trade_amount)) {
+            (Some(b), Some(a)) => (b, a),
+            _ => return EconStatus::NumericOverflow,
+        };
+        *out_result = economy::pricing::compute_price_humane_with_ratio_internal(
+            base_micros, n_eff, amount_micros, lambda, epsilon, sell_sensitivity_ratio
+        );
+        EconStatus::Ok
+    })
+}
+
+/// 同 `ecobridge_compute_price_bounded`，卖出时 lambda 的缩放系数由调用方指定 (默认 0.6)
+///
+/// `sell_sensitivity_ratio` 须位于 (0, 2]，否则返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ecobridge_compute_price_bounded_ex(
+    base: c_double,
+    n_eff: c_double,
+    amt: c_double,
+    lambda: c_double,
+    eps: c_double,
+    hist_avg: c_double,
+    min_price_micros: c_longlong,
+    sell_sensitivity_ratio: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        if !economy::pricing::validate_sell_sensitivity_ratio(sell_sensitivity_ratio) { return EconStatus::InvalidValue; }
+        let (base_micros, amt_micros) = match (to_micros_checked(base), to_micros_checked(amt)) {
+            (Some(b), Some(a)) => (b, a),
+            _ => return EconStatus::NumericOverflow,
+        };
+        *out_result = economy::pricing::compute_price_bounded_with_ratio_internal(
+            base_micros, n_eff, amt_micros, lambda, eps, hist_avg, min_price_micros, sell_sensitivity_ratio
+        );
+        EconStatus::Ok
+    })
+}
+
+/// 影子定价 A/B 对照：同一输入下分别按线上与候选 lambda 计算带底价保护的价格
+///
+/// 不影响实际价格，供运营记录 "live vs shadow" 偏差以决定是否采纳新配置。
+///
+/// # Safety
+///
+/// `out_live`、`out_shadow` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_ab(
+    base_micros: c_longlong,
+    n_eff: c_double,
+    amt_micros: c_longlong,
+    live_cfg_lambda: c_double,
+    shadow_cfg_lambda: c_double,
+    eps: c_double,
+    hist_avg: c_double,
+    out_live: *mut c_double,
+    out_shadow: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_live.is_null() || out_shadow.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        let (live, shadow) = economy::pricing::compute_price_ab_internal(
+            base_micros, n_eff, amt_micros, live_cfg_lambda, shadow_cfg_lambda, eps, hist_avg
+        );
+        *out_live = live;
+        *out_shadow = shadow;
+        EconStatus::Ok
+    })
+}
+
+/// 以数据库演算的权威 neff 定价 (不含本次交易冲击，带动态底价保护)
+///
+/// 供不维护热存储的只读副本 / 分析节点使用：neff 按 `tau` (天) 在数据库日志上衰减累积。
+/// 数据库未初始化返回 InternalError；`tau` 非正或非有限返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_price_db_neff(
+    base_micros: c_longlong,
+    current_ts: c_longlong,
+    tau: c_double,
+    lambda: c_double,
+    eps: c_double,
+    hist_avg: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+        if !storage::is_initialized() { return EconStatus::InternalError; }
+
+        *out_result = economy::pricing::compute_price_db_neff_internal(base_micros, current_ts, tau, lambda, eps, hist_avg);
+        EconStatus::Ok
+    })
+}
+
+/// 预估假想交易的市场冲击：成交后即时价格与价格恢复到交易前水平 (1% 以内) 所需时长 (ms)
+///
+/// `amount` 为物品件数 (正数为卖出)，`tau` 为 neff 衰减常数 (天)。
+/// 参数非有限值、`tau <= 0` 或 `lambda < 0` 时返回 InvalidValue；全服冻结时返回 Frozen 且不写入结果。
+///
+/// # Safety
+///
+/// `out_new_price` 须指向可写的 `c_double`；`out_recovery_ms` 须指向可写的 `c_longlong`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_project_trade_impact(
+    current_neff: c_double,
+    amount: c_double,
+    lambda: c_double,
+    tau: c_double,
+    out_new_price: *mut c_double,
+    out_recovery_ms: *mut c_longlong,
+    base_micros: c_longlong,
+    eps: c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_new_price.is_null() || out_recovery_ms.is_null() { return EconStatus::NullPointer; }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        match economy::pricing::project_trade_impact_internal(base_micros, current_neff, amount, lambda, tau, eps) {
+            Some((price, recovery_ms)) => {
+                *out_new_price = price;
+                *out_recovery_ms = recovery_ms;
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
+        }
+    })
+}
+
+// -----------------------------------------------------------------------------
+// 4. 宏观经济指标
+// -----------------------------------------------------------------------------
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calc_inflation(
+    current_heat: c_double,
+    m1: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if m1 <= 0.0 { return EconStatus::InvalidValue; }
+        *out_result = economy::macro_eco::calculate_inflation_rate(current_heat, m1);
+        EconStatus::Ok
+    })
+}
+
+/// 反解目标通胀率所需的 M1 供应量 (`m1 = heat / target_inflation`)
+///
+/// 目标须非 0 且位于 [-0.15, 0.45]；不存在有效 (> 1.0) 的 M1 时同样返回 InvalidValue。
+///
+/// # Safety
+///
+/// `out_m1` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_required_m1(
+    current_heat: c_double,
+    target_inflation: c_double,
+    out_m1: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_m1.is_null() { return EconStatus::NullPointer; }
+        match economy::macro_eco::required_m1_for_inflation(current_heat, target_inflation) {
+            Some(m1) => {
+                *out_m1 = m1;
+                EconStatus::Ok
+            }
+            None => EconStatus::InvalidValue,
+        }
+    })
+}
+
+/// 平滑通胀率：以 tanh 软饱和渐近逼近 [-0.15, 0.45]，`smoothing` 须为正的有限值
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calc_inflation_smooth(
+    current_heat: c_double,
+    m1: c_double,
+    smoothing: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if m1 <= 0.0 || !smoothing.is_finite() || smoothing <= 0.0 { return EconStatus::InvalidValue; }
+        *out_result = economy::macro_eco::calculate_inflation_rate_smooth(current_heat, m1, smoothing);
+        EconStatus::Ok
+    })
+}
+
+/// 市场稳定性因子，使用默认恢复窗口 `DEFAULT_RECOVERY_WINDOW_MS`
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calc_stability(
+    last_ts: c_longlong,
+    curr_ts: c_longlong,
+    out_result: *mut c_double,
+) -> c_int {
+    ecobridge_calc_stability_with_window(last_ts, curr_ts, economy::macro_eco::DEFAULT_RECOVERY_WINDOW_MS, out_result)
+}
+
+/// 市场稳定性因子，自定义恢复窗口 `recovery_window_ms` (须为正的有限值)
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calc_stability_with_window(
+    last_ts: c_longlong,
+    curr_ts: c_longlong,
+    recovery_window_ms: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        if !recovery_window_ms.is_finite() || recovery_window_ms <= 0.0 { return EconStatus::InvalidValue; }
+        *out_result = economy::macro_eco::calculate_stability(last_ts, curr_ts, recovery_window_ms);
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calc_decay(
+    heat: c_double,
+    rate: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        *out_result = economy::macro_eco::calculate_decay(heat, rate, 48.0);
+        EconStatus::Ok
+    })
+}
+
+/// 按模式计算热度衰减量 (0 = 线性, 1 = 指数复利)
+///
+/// `rate` 须位于 [0, 1]，`cycles_per_day` 须为正的有限值。
+///
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calc_decay_ex(
+    heat: c_double,
+    rate: c_double,
+    cycles_per_day: c_double,
+    mode: c_int,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        let mode = match economy::macro_eco::DecayMode::from_raw(mode) {
+            Some(m) => m,
+            None => return EconStatus::InvalidValue,
+        };
+        if !heat.is_finite() || !(0.0..=1.0).contains(&rate)
+            || !cycles_per_day.is_finite() || cycles_per_day <= 0.0 {
+            return EconStatus::InvalidValue;
+        }
+        *out_result = economy::macro_eco::calculate_decay_ex(heat, rate, cycles_per_day, mode);
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `out_total`、`out_dropped` 须指向可写的 `u64`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_get_health_stats(
+    out_total: *mut u64, 
+    out_dropped: *mut u64,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_total.is_null() || out_dropped.is_null() {
+            return EconStatus::NullPointer;
+        }
+        *out_total = storage::get_total_logs();
+        *out_dropped = storage::get_dropped_logs();
+        EconStatus::Ok
+    })
+}
+
+/// 写入线程心跳：最近一次批次刷盘完成的 Unix 时间戳 (ms)，尚未刷盘时返回 0
+///
+/// 写入线程仅在有日志时刷盘；若 `total_logs` 持续增长而该值长时间未推进，
+/// 说明写入线程已停滞或崩溃，队列将被填满并开始丢弃日志。
+#[no_mangle]
+pub extern "C" fn ecobridge_writer_last_flush_ms() -> c_longlong {
+    storage::get_last_flush_ts()
+}
+
+/// 监控采集：单次调用汇总日志健康度、全局 neff 与平均交易年龄
+///
+/// 只读快照，不消费远程流量累加器 (待消费量通过 `remote_pending_micros` 单独上报)。
+///
+/// # Safety
+///
+/// `out` 须指向可写的 `MonitoringSnapshot`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_monitoring_poll(
+    current_ts: c_longlong,
+    tau: c_double,
+    out: *mut MonitoringSnapshot,
+) -> c_int {
+    ffi_guard!(|| {
+        if out.is_null() { return EconStatus::NullPointer; }
+        if !tau.is_finite() || tau <= 0.0 { return EconStatus::InvalidValue; }
+
+        let snapshot = MonitoringSnapshot {
+            total_logs: storage::get_total_logs(),
+            dropped_logs: storage::get_dropped_logs(),
+            local_neff: economy::summation::query_neff_global_internal(current_ts, tau),
+            remote_pending_micros: REMOTE_FLOW_ACCUMULATOR_MICROS.load(Ordering::SeqCst),
+            avg_trade_age_ms: economy::summation::query_avg_trade_age_internal(current_ts, tau),
+            writer_last_flush_ms: storage::get_last_flush_ts(),
+        };
+        ptr::write(out, snapshot);
+        EconStatus::Ok
+    })
+}
+
+/// 设置按月季节振幅表 (12 个 f64，索引 0 = 一月)
+///
+/// 月份由 `TradeContext::current_timestamp` 结合时区偏移推算。
+/// 传入空指针清除该表，回退到 `MarketConfig::seasonal_amplitude` 常量。
+///
+/// # Safety
+///
+/// `ptr` 为空或指向 12 个连续的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_set_monthly_amplitude(ptr: *const c_double) -> c_int {
+    ffi_guard!(|| {
+        let schedule = if ptr.is_null() {
+            None
+        } else {
+            let mut values = [0.0; 12];
+            values.copy_from_slice(std::slice::from_raw_parts(ptr, 12));
+            Some(values)
+        };
+        if !economy::environment::set_monthly_amplitude(schedule) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 设置季节复合波的日/周/月周期 (秒) 与权重，适配自定义游戏内日历 (如 20 分钟一天)
+///
+/// 默认为现实世界的 86400 / 604800 / 2592000 秒与 0.6 / 0.3 / 0.1 权重；周末与月份判定不受影响。
+/// 任一周期非正或非有限、任一权重非有限返回 InvalidValue。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_seasonal_config(
+    day_secs: c_double,
+    week_secs: c_double,
+    month_secs: c_double,
+    day_w: c_double,
+    week_w: c_double,
+    month_w: c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        let config = economy::environment::SeasonalConfig { day_secs, week_secs, month_secs, day_w, week_w, month_w };
+        if !economy::environment::set_seasonal_config(config) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 添加节假日区间 [start_day, end_day] (含两端，单位为自 Unix 纪元起的天数)
+///
+/// 区间内的本地自然日按周末定价 (`MarketConfig::weekend_multiplier`)；重叠区间自动合并。
+/// `start_day > end_day` 返回 InvalidValue。
+#[no_mangle]
+pub extern "C" fn ecobridge_add_holiday(start_day: c_longlong, end_day: c_longlong) -> c_int {
+    ffi_guard!(|| {
+        if !economy::calendar::add_holiday(start_day, end_day) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 清空节假日表，周末因子回退到纯星期几判定
+#[no_mangle]
+pub extern "C" fn ecobridge_clear_holidays() -> c_int {
+    ffi_guard!(|| {
+        economy::calendar::clear_holidays();
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// - `ctx_ptr` 须指向有效的 `TradeContext`；
+/// - `cfg_ptr` 须指向有效的 `MarketConfig`；
+/// - `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_calculate_epsilon(
+    ctx_ptr: *const TradeContext,
+    cfg_ptr: *const MarketConfig,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || out_result.is_null() {
+            return EconStatus::NullPointer;
+        }
+        *out_result = economy::environment::calculate_epsilon_internal(&*ctx_ptr, &*cfg_ptr);
+        EconStatus::Ok
+    })
+}
+
+// -----------------------------------------------------------------------------
+// 5. 安全审计与动态限额
+// -----------------------------------------------------------------------------
+
+/// 全服经济冻结开关 (事故应急)：非 0 = 冻结，0 = 解除
+///
+/// 冻结期间交易审计、拦截预校验与购物车计税一律以 `CODE_FROZEN` 拦截，定价类接口返回 `EconStatus::Frozen`；
+/// 日志写入与只读查询照常进行，便于事后审计。
+#[no_mangle]
+pub extern "C" fn ecobridge_set_frozen(frozen: c_int) -> c_int {
+    ffi_guard!(|| {
+        security::freeze::set_frozen(frozen != 0);
+        EconStatus::Ok
+    })
+}
+
+/// 查询当前是否处于冻结状态：1 = 冻结，0 = 正常
+#[no_mangle]
+pub extern "C" fn ecobridge_is_frozen() -> c_int {
+    security::freeze::is_frozen() as c_int
+}
+
+/// # Safety
+///
+/// - `out_result` 须指向可写的 `TransferResult`；
+/// - `ctx_ptr` 须指向有效的 `TransferContext`；
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_transfer_check(
+    out_result: *mut TransferResult,
+    ctx_ptr: *const TransferContext,
+    cfg_ptr: *const RegulatorConfig,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() || ctx_ptr.is_null() || cfg_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+
+        let res = security::regulator::compute_transfer_check_internal(&*ctx_ptr, &*cfg_ptr);
+        ptr::write(out_result, res);
+        EconStatus::Ok
+    })
+}
+
+/// 购物车合并计税：`count` 件商品按总额统一计税，输出单一放行/拦截结论
+///
+/// 拦截与预警逐行判定 (同一物品须由调用方合并为一行)，任一物品被拦截则整单拦截；余额不足、违规注资与奢侈税、贫富调节税阈值作用于总额。
+/// 行为速率取各件最大值 (一次结账视为单次行为)，余额与游戏时长取自首件。`count` 上限 1,024，0 时放行且税额为 0。
+///
+/// # Safety
+///
+/// - `items_ptr` 须指向至少 `count` 个连续的 `TransferContext`；
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `out_result` 须指向可写的 `TransferResult`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_cart_tax(
+    items_ptr: *const TransferContext,
+    count: u64,
+    cfg_ptr: *const RegulatorConfig,
+    out_result: *mut TransferResult,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() || cfg_ptr.is_null() { return EconStatus::NullPointer; }
+        if count > 1_024 { return EconStatus::InvalidLength; }
+        let items = if count == 0 {
+            &[][..]
+        } else {
+            if items_ptr.is_null() { return EconStatus::NullPointer; }
+            std::slice::from_raw_parts(items_ptr, count as usize)
+        };
+        ptr::write(out_result, security::regulator::compute_cart_tax(items, &*cfg_ptr));
+        EconStatus::Ok
+    })
+}
+
+/// 税费预览：仅输出常规审计的 `final_tax_micros` (被拦截时为 0)
+///
+/// 不修改任何全局状态 (不记录流转、不更新计数器)，可供 UI 滑块等场景反复调用。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向有效的 `TransferContext`；
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `out_ptr` 须指向可写的 `c_longlong`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_preview_transfer_tax(
+    ctx_ptr: *const TransferContext,
+    cfg_ptr: *const RegulatorConfig,
+    out_ptr: *mut c_longlong,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || out_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+        *out_ptr = security::regulator::preview_transfer_tax(&*ctx_ptr, &*cfg_ptr);
+        EconStatus::Ok
+    })
+}
+
+/// 购买报价：一次调用完成定价与交易审计，减少结账流程的 FFI 往返
+///
+/// 单价由 `compute_price_bounded_internal` 演算 (交易数量取 `ctx.current_amount`，
+/// 环境因子由 `mcfg` 计算)，总价 = 单价 × |数量|。审计沿用 `tctx`，
+/// 但 `amount_micros` 以本次总价覆盖，调用方无需预先填写。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向有效的 `TradeContext`；
+/// - `tctx_ptr` 须指向有效的 `TransferContext`；
+/// - `mcfg_ptr` 须指向有效的 `MarketConfig`；
+/// - `rcfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `out_ptr` 须指向可写的 `PurchaseQuote`。
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ecobridge_quote_purchase(
+    ctx_ptr: *const TradeContext,
+    tctx_ptr: *const TransferContext,
+    mcfg_ptr: *const MarketConfig,
+    rcfg_ptr: *const RegulatorConfig,
+    neff: c_double,
+    lambda: c_double,
+    hist_avg: c_double,
+    out_ptr: *mut PurchaseQuote,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || tctx_ptr.is_null() || mcfg_ptr.is_null()
+            || rcfg_ptr.is_null() || out_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if security::freeze::is_frozen() { return EconStatus::Frozen; }
+        let ctx = &*ctx_ptr;
+
+        let epsilon = economy::environment::calculate_epsilon_internal(ctx, &*mcfg_ptr);
+        let unit_price = economy::pricing::compute_price_bounded_internal(
+            ctx.base_price_micros, neff, ctx.current_amount, lambda, epsilon, hist_avg, 0
+        );
+        let quantity = from_micros(ctx.current_amount).abs();
+        let (unit_price_micros, total_micros) = match (to_micros_checked(unit_price), to_micros_checked(unit_price * quantity)) {
+            (Some(u), Some(t)) => (u, t),
+            _ => return EconStatus::NumericOverflow,
+        };
+
+        let tctx = TransferContext { amount_micros: total_micros, ..*tctx_ptr };
+        let check = security::regulator::compute_transfer_check_internal(&tctx, &*rcfg_ptr);
+
+        ptr::write(out_ptr, PurchaseQuote {
+            unit_price_micros,
+            total_micros,
+            tax_micros: check.final_tax_micros,
+            is_blocked: check.is_blocked,
+            warning_code: check.warning_code,
+        });
+        EconStatus::Ok
+    })
+}
+
+/// 带账户身份的交易审计 (含逆向流转检测)
+///
+/// 在 `ecobridge_compute_transfer_check` 的基础上，拦截低活跃度账户在检测窗口内
+/// 向原发送者回转资金的行为 (`CODE_BLOCK_REVERSE_FLOW`)。放行的转账会被记录。
+///
+/// # Safety
+///
+/// - `out_result` 须指向可写的 `TransferResult`；
+/// - `ctx_ptr` 须指向有效的 `TransferContext`；
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `sender_uuid_ptr`、`receiver_uuid_ptr` 须为以 NUL 结尾的有效 C 字符串。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_transfer_check_tracked(
+    out_result: *mut TransferResult,
+    ctx_ptr: *const TransferContext,
+    cfg_ptr: *const RegulatorConfig,
+    sender_uuid_ptr: *const c_char,
+    receiver_uuid_ptr: *const c_char,
+    now_ms: c_longlong,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() || ctx_ptr.is_null() || cfg_ptr.is_null()
+            || sender_uuid_ptr.is_null() || receiver_uuid_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+        let sender = CStr::from_ptr(sender_uuid_ptr).to_string_lossy();
+        let receiver = CStr::from_ptr(receiver_uuid_ptr).to_string_lossy();
+
+        let res = security::regulator::compute_transfer_check_tracked(&*ctx_ptr, &*cfg_ptr, &sender, &receiver, now_ms);
+        ptr::write(out_result, res);
+        EconStatus::Ok
+    })
+}
+
+/// 设置行为惩罚税的指数输入上限 (`sender_velocity * 0.05` 的最大值，默认 2.0)
+#[no_mangle]
+pub extern "C" fn ecobridge_set_penalty_exponent_cap(cap: c_double) -> c_int {
+    ffi_guard!(|| {
+        if !security::regulator::set_penalty_exponent_cap(cap) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 设置累进税率表 (平行数组：起征点 Micros 与边际税率)，设置后以累进税替代单一阈值的奢侈税
+///
+/// 起征点须非负且严格递增，边际税率须位于 [0, 1]，否则返回 InvalidValue；
+/// `count` 为 0 时清空税率表 (恢复奢侈税阈值)，超过 `MAX_TAX_BRACKETS` 返回 InvalidLength。
+///
+/// # Safety
+///
+/// `thresholds_micros` 须指向至少 `count` 个连续的 `c_longlong`；`marginal_rates` 须指向至少 `count` 个连续的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_set_tax_brackets(
+    thresholds_micros: *const c_longlong,
+    marginal_rates: *const c_double,
+    count: u64,
+) -> c_int {
+    ffi_guard!(|| {
+        if count == 0 {
+            security::regulator::set_tax_brackets(&[]);
+            return EconStatus::Ok;
+        }
+        if thresholds_micros.is_null() || marginal_rates.is_null() {
+            return EconStatus::NullPointer;
+        }
+        if count > security::regulator::MAX_TAX_BRACKETS as u64 {
+            return EconStatus::InvalidLength;
+        }
+        let thresholds = std::slice::from_raw_parts(thresholds_micros, count as usize);
+        let rates = std::slice::from_raw_parts(marginal_rates, count as usize);
+        let brackets: Vec<(i64, f64)> = thresholds.iter().copied().zip(rates.iter().copied()).collect();
+        if !security::regulator::set_tax_brackets(&brackets) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 设置逆向流转检测窗口 (ms，须为正数，默认 300,000)
+#[no_mangle]
+pub extern "C" fn ecobridge_set_reverse_flow_window(window_ms: c_longlong) -> c_int {
+    ffi_guard!(|| {
+        if !security::flow_tracker::set_window_ms(window_ms) {
+            return EconStatus::InvalidValue;
+        }
+        EconStatus::Ok
+    })
+}
+
+/// 令牌桶限流：为玩家 `uuid_ptr` 获取 1 个转账令牌
+///
+/// 桶容量 `burst` (>= 1)，以 `rate_per_min` (>= 0) 个/分钟补充。`out_allowed` 写入 1 表示放行、
+/// 0 表示应拦截本次转账。参数非法或 uuid 为空返回 InvalidValue。
+///
+/// # Safety
+///
+/// `uuid_ptr` 须为以 NUL 结尾的有效 C 字符串；`out_allowed` 须指向可写的 `c_int`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_ratelimit_try_acquire(
+    uuid_ptr: *const c_char,
+    now_ms: c_longlong,
+    rate_per_min: c_double,
+    burst: c_double,
+    out_allowed: *mut c_int,
+) -> c_int {
+    ffi_guard!(|| {
+        if uuid_ptr.is_null() || out_allowed.is_null() { return EconStatus::NullPointer; }
+        if !security::ratelimit::params_valid(rate_per_min, burst) { return EconStatus::InvalidValue; }
+
+        let uuid = match CStr::from_ptr(uuid_ptr).to_str() {
+            Ok(v) if !v.trim().is_empty() => v.trim(),
+            _ => return EconStatus::InvalidValue,
+        };
+
+        *out_allowed = security::ratelimit::try_acquire(uuid, now_ms, rate_per_min, burst) as c_int;
+        EconStatus::Ok
+    })
+}
+
+/// 仅执行拦截判定的轻量预校验 (不计算税额)
+///
+/// `out_block_code` 写入首个命中的拦截码，放行时为 0；结果与 `ecobridge_compute_transfer_check` 一致。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向有效的 `TransferContext`；
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `out_block_code` 须指向可写的 `c_int`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_would_block(
+    ctx_ptr: *const TransferContext,
+    cfg_ptr: *const RegulatorConfig,
+    out_block_code: *mut c_int,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || out_block_code.is_null() {
+            return EconStatus::NullPointer;
+        }
+        *out_block_code = security::regulator::check_block_code(&*ctx_ptr, &*cfg_ptr);
+        EconStatus::Ok
+    })
+}
+
+/// 带账户身份的拦截预校验 (含逆向流转检测，不计算税额)
+///
+/// 结果与 `ecobridge_compute_transfer_check_tracked` 的拦截码一致，但不记录流转，可供 UI 反复调用。
+///
+/// # Safety
+///
+/// - `ctx_ptr` 须指向有效的 `TransferContext`；
+/// - `cfg_ptr` 须指向有效的 `RegulatorConfig`；
+/// - `sender_uuid_ptr`、`receiver_uuid_ptr` 须为以 NUL 结尾的有效 C 字符串；
+/// - `out_block_code` 须指向可写的 `c_int`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_would_block_tracked(
+    ctx_ptr: *const TransferContext,
+    cfg_ptr: *const RegulatorConfig,
+    sender_uuid_ptr: *const c_char,
+    receiver_uuid_ptr: *const c_char,
+    now_ms: c_longlong,
+    out_block_code: *mut c_int,
+) -> c_int {
+    ffi_guard!(|| {
+        if ctx_ptr.is_null() || cfg_ptr.is_null() || out_block_code.is_null()
+            || sender_uuid_ptr.is_null() || receiver_uuid_ptr.is_null() {
+            return EconStatus::NullPointer;
+        }
+        let sender = CStr::from_ptr(sender_uuid_ptr).to_string_lossy();
+        let receiver = CStr::from_ptr(receiver_uuid_ptr).to_string_lossy();
+        *out_block_code = security::regulator::check_block_code_tracked(&*ctx_ptr, &*cfg_ptr, &sender, &receiver, now_ms);
+        EconStatus::Ok
+    })
+}
+
+/// # Safety
+///
+/// `out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_get_dynamic_limit(
+    play_time_secs: c_longlong,
+    base: c_double,
+    rate: c_double,
+    max: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        *out_result = security::regulator::dynamic_quantity_limit(play_time_secs, base, rate, max);
+        EconStatus::Ok
+    })
+}
+
+// -----------------------------------------------------------------------------
+// 6. PID 控制
+// -----------------------------------------------------------------------------
+
+/// # Safety
+///
+/// `pid_ptr` 须指向有效且可写的 `PidState`；`out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_compute_pid_adjustment(
+    pid_ptr: *mut PidState,
+    target: c_double,
+    current: c_double,
+    dt: c_double,
+    inflation: c_double,
+    market_heat: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if pid_ptr.is_null() || out_result.is_null() { 
+            return EconStatus::NullPointer; 
+        }
+        if let Some(pid) = pid_ptr.as_mut() {
+            *out_result = economy::control::compute_pid_adjustment_internal(
+                pid, target, current, dt, inflation, market_heat
+            );
+            EconStatus::Ok
+        } else {
+            EconStatus::NullPointer
+        }
+    })
+}
+
+/// 带前馈补偿的 PID 调节：`feedforward` 在限幅前叠加到输出，用于提前抵消已知扰动
+///
+/// `feedforward = 0` 时与 `ecobridge_compute_pid_adjustment` 等价；非有限值时输出基准值 1.0。
+///
+/// # Safety
+///
+/// `pid_ptr` 须指向有效且可写的 `PidState`；`out_result` 须指向可写的 `c_double`。
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ecobridge_compute_pid_adjustment_ff(
+    pid_ptr: *mut PidState,
+    target: c_double,
+    current: c_double,
+    dt: c_double,
+    inflation: c_double,
+    market_heat: c_double,
+    feedforward: c_double,
+    out_result: *mut c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        if out_result.is_null() { return EconStatus::NullPointer; }
+        match pid_ptr.as_mut() {
+            Some(pid) => {
+                *out_result = economy::control::compute_pid_adjustment_ff_internal(
+                    pid, target, current, dt, inflation, market_heat, feedforward
+                );
+                EconStatus::Ok
+            }
+            None => EconStatus::NullPointer,
+        }
+    })
+}
+
+/// 设置 PID 恐慌抑制参数：微分超过 `threshold` 时 D 项放大 `damping` 倍 (0 表示使用默认值 50.0 / 1.8)
+///
+/// 任一参数为负或非有限值时返回 InvalidValue 且不修改状态。
+///
+/// # Safety
+///
+/// `pid_ptr` 须指向有效且可写的 `PidState`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_set_pid_panic_params(
+    pid_ptr: *mut PidState,
+    threshold: c_double,
+    damping: c_double,
+) -> c_int {
+    ffi_guard!(|| {
+        match pid_ptr.as_mut() {
+            Some(pid) => {
+                if economy::control::set_panic_params(pid, threshold, damping) {
+                    EconStatus::Ok
+                } else {
+                    EconStatus::InvalidValue
+                }
+            }
+            None => EconStatus::NullPointer,
+        }
+    })
+}
+
+/// 设置 PID 每个控制周期的积分保留系数 `decay` ∈ [0, 1] (0 表示使用默认值 0.99999)
+///
+/// 超出范围或非有限值时返回 InvalidValue 且不修改状态。
+///
+/// # Safety
+///
+/// `pid_ptr` 须指向有效且可写的 `PidState`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_set_pid_integral_decay(pid_ptr: *mut PidState, decay: c_double) -> c_int {
+    ffi_guard!(|| {
+        match pid_ptr.as_mut() {
+            Some(pid) => {
+                if economy::control::set_integral_decay(pid, decay) {
+                    EconStatus::Ok
+                } else {
+                    EconStatus::InvalidValue
+                }
+            }
+            None => EconStatus::NullPointer,
+        }
+    })
+}
+
+/// 设置 PID 输出倍率限幅区间 [min, max] (两端同为 0 表示使用默认值 [0.5, 5.0])
+///
+/// 区间非法 (非有限、为负或下限不小于上限) 时返回 InvalidValue 且不修改状态。
+///
+/// # Safety
+///
+/// `pid_ptr` 须指向有效且可写的 `PidState`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_set_pid_output_clamp(pid_ptr: *mut PidState, min: c_double, max: c_double) -> c_int {
+    ffi_guard!(|| {
+        match pid_ptr.as_mut() {
+            Some(pid) => {
+                if economy::control::set_output_clamp(pid, min, max) {
+                    EconStatus::Ok
+                } else {
+                    EconStatus::InvalidValue
+                }
+            }
+            None => EconStatus::NullPointer,
+        }
+    })
+}
+
+/// # Safety
+///
+/// `pid_ptr` 须指向有效且可写的 `PidState`。
+#[no_mangle]
+pub unsafe extern "C" fn ecobridge_reset_pid_state(pid_ptr: *mut PidState) -> c_int {
+    ffi_guard!(|| {
+        if let Some(pid) = pid_ptr.as_mut() {
+            *pid = PidState::default();
+            EconStatus::Ok
+        } else {
+            EconStatus::NullPointer
+        }
+    })
+}
+
+
+// -----------------------------------------------------------------------------
+// 集成测试 (FFI 层)
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_all_state_restores_defaults() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let now = 1_736_851_200_000;
+
+        // 1. 填充热存储、内存历史、计数器与远程累加器
+        assert!(economy::summation::append_trade_to_memory(now, 5.0, "reset_test"));
+        assert!(storage::log_economy_event(now, "uuid".into(), 5.0, 100.0, "MARKET_TRADE:reset_test".into()));
+        assert_eq!(inject_remote_trade(3_000_000), EconStatus::Ok as c_int);
+        let key = std::ffi::CString::new("reset_test").unwrap();
+        assert_eq!(unsafe { inject_remote_trade_for_key(key.as_ptr(), 2_000_000) }, EconStatus::Ok as c_int);
+
+        assert!(economy::summation::query_neff_internal(now, 7.0, "reset_test") > 0.0);
+        assert!(storage::get_total_logs() > 0);
+        let dropped = storage::get_dropped_logs();
+        assert_eq!(ecobridge_set_panic_abort(1), EconStatus::Ok as c_int);
+        assert!(panic_abort_enabled());
+
+        // 2. 重置
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+
+        // 3. 全部回到初始状态
+        assert_eq!(economy::summation::query_neff_internal(now, 7.0, "reset_test"), 0.0);
+        assert_eq!(economy::summation::query_neff_global_internal(now, 7.0), 0.0);
+        assert!(storage::get_history_read().is_empty());
+        assert_eq!(storage::get_total_logs(), 0);
+        assert_eq!(REMOTE_FLOW_ACCUMULATOR_MICROS.load(Ordering::SeqCst), 0);
+        assert!(REMOTE_FLOW_ACCUMULATOR_BY_KEY.read().unwrap().is_empty());
+        assert!(!panic_abort_enabled());
+        // 累计丢弃计数跨重载保留
+        assert_eq!(storage::get_dropped_logs(), dropped);
+    }
+
+    #[test]
+    fn test_reset_for_testing_is_gated_by_feature() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(economy::summation::append_trade_to_memory(1_000, 5.0, "reset_gate"));
+
+        let status = ecobridge_reset_for_testing();
+        let remaining = economy::summation::query_neff_internal(1_000, 7.0, "reset_gate");
+        if cfg!(feature = "testing") {
+            assert_eq!(status, EconStatus::Ok as c_int);
+            assert_eq!(remaining, 0.0);
+            assert_eq!(storage::get_dropped_logs(), 0);
+        } else {
+            assert_eq!(status, EconStatus::Unsupported as c_int);
+            assert!(remaining > 0.0);
+            ecobridge_reset_all_state();
+        }
+    }
+
+    #[test]
+    fn test_layout_descriptor_two_phase_query() {
+        let mut total = 0u64;
+        assert_eq!(unsafe { ecobridge_layout_descriptor(std::ptr::null_mut(), 0, &mut total) }, EconStatus::Ok as c_int);
+        assert_eq!(total as usize, models::layout_descriptor().len());
+
+        // 缓冲区不足时仅写入前 cap 项，总数照常返回
+        let mut partial = [LayoutEntry::default(); 2];
+        let mut count = 0u64;
+        assert_eq!(unsafe { ecobridge_layout_descriptor(partial.as_mut_ptr(), 2, &mut count) }, EconStatus::Ok as c_int);
+        assert_eq!(count, total);
+        assert_eq!(partial[..], models::layout_descriptor()[..2]);
+
+        assert_eq!(
+            unsafe { ecobridge_layout_descriptor(std::ptr::null_mut(), 1, &mut count) },
+            EconStatus::NullPointer as c_int
+        );
+    }
+
+    #[test]
+    fn test_monitoring_poll_matches_individual_queries() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        let now = 1_736_851_200_000;
+        assert!(economy::summation::append_trade_to_memory(now - 3_600_000, 4.0, "monitor_test"));
+        assert!(storage::log_economy_event(now, "uuid".into(), 4.0, 10.0, "MARKET_TRADE:monitor_test".into()));
+        assert_eq!(inject_remote_trade(2_500_000), EconStatus::Ok as c_int);
+
+        let mut snap = MonitoringSnapshot::default();
+        assert_eq!(unsafe { ecobridge_monitoring_poll(now, 7.0, &mut snap) }, EconStatus::Ok as c_int);
+
+        let (mut total, mut dropped) = (0u64, 0u64);
+        assert_eq!(unsafe { ecobridge_get_health_stats(&mut total, &mut dropped) }, EconStatus::Ok as c_int);
+        let mut age = 0.0;
+        assert_eq!(unsafe { ecobridge_query_avg_trade_age(now, 7.0, &mut age) }, EconStatus::Ok as c_int);
+
+        assert_eq!((snap.total_logs, snap.dropped_logs), (total, dropped));
+        assert_eq!(snap.avg_trade_age_ms, age);
+        assert_eq!(snap.remote_pending_micros, 2_500_000);
+        assert_eq!(snap.writer_last_flush_ms, ecobridge_writer_last_flush_ms());
+
+        // 采集不消费远程流量：随后的 neff 查询 = 本地 neff + 待消费远程流量
+        let mut neff = 0.0;
+        assert_eq!(unsafe { ecobridge_query_neff_vectorized(now, 7.0, &mut neff) }, EconStatus::Ok as c_int);
+        assert_eq!(neff, snap.local_neff + from_micros(snap.remote_pending_micros));
+
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+    }
+
+    #[test]
+    fn test_smoothed_remote_flow_decays_in_neff() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        let now = 1_736_851_200_000;
+        let tau = 7.0;
+        let day = 86_400_000;
+
+        // 同样 100 单位的积压：平滑注入按 5 天年龄衰减，直接累加则整体视为当前流量
+        assert_eq!(inject_remote_trade_smoothed(100_000_000, now - 5 * day), EconStatus::Ok as c_int);
+        let mut smoothed = 0.0;
+        assert_eq!(unsafe { ecobridge_query_neff_vectorized(now, tau, &mut smoothed) }, EconStatus::Ok as c_int);
+        assert!((smoothed - 100.0 * economy::summation::decay_weight(5 * day, tau)).abs() < 1e-6);
+        assert!(smoothed < 100.0);
+
+        // 平滑流量不经过累加器，重复查询仍然计入
+        let mut again = 0.0;
+        assert_eq!(unsafe { ecobridge_query_neff_vectorized(now, tau, &mut again) }, EconStatus::Ok as c_int);
+        assert_eq!(again, smoothed);
+
+        assert_eq!(inject_remote_trade(100_000_000), EconStatus::Ok as c_int);
+        let mut spiked = 0.0;
+        assert_eq!(unsafe { ecobridge_query_neff_vectorized(now, tau, &mut spiked) }, EconStatus::Ok as c_int);
+        assert!((spiked - smoothed - 100.0).abs() < 1e-6);
+
+        // 多 tau 查询、置信度查询与一站式定价使用同一口径的 neff
+        let taus = [tau, 1.0];
+        let mut multi = [0.0; 2];
+        assert_eq!(unsafe { ecobridge_query_neff_multi(now, taus.as_ptr(), 2, multi.as_mut_ptr()) }, EconStatus::Ok as c_int);
+        assert!((multi[0] - smoothed).abs() < 1e-9);
+        assert!((multi[1] - 100.0 * economy::summation::decay_weight(5 * day, 1.0)).abs() < 1e-6);
+        let (mut neff, mut confidence) = (0.0, 0.0);
+        assert_eq!(unsafe { ecobridge_query_neff_with_confidence(now, tau, &mut neff, &mut confidence) }, EconStatus::Ok as c_int);
+        assert!((neff - smoothed).abs() < 1e-9);
+
+        let ctx = TradeContext { base_price_micros: 10_000_000, current_timestamp: now, ..Default::default() };
+        let cfg = MarketConfig::default();
+        let mut price = 0.0;
+        assert_eq!(unsafe { ecobridge_price_from_context(&ctx, &cfg, tau, 0.01, 0.0, &mut price) }, EconStatus::Ok as c_int);
+        let epsilon = economy::environment::calculate_epsilon_internal(&ctx, &cfg);
+        assert_eq!(price, economy::pricing::compute_price_bounded_internal(ctx.base_price_micros, smoothed, 0, 0.01, epsilon, 0.0, 0));
+
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+    }
+
+    #[test]
+    fn test_remote_accumulator_saturates_instead_of_wrapping() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        let read = || {
+            let mut v = 0;
+            assert_eq!(unsafe { ecobridge_remote_accumulator_value(&mut v) }, EconStatus::Ok as c_int);
+            v
+        };
+
+        assert_eq!(inject_remote_trade(i64::MAX - 10), EconStatus::Ok as c_int);
+        assert_eq!(inject_remote_trade(100), EconStatus::Ok as c_int);
+        assert_eq!(read(), i64::MAX);
+        assert_eq!(inject_remote_trade(i64::MAX), EconStatus::Ok as c_int);
+        assert_eq!(read(), i64::MAX);
+
+        // 饱和后仍可正常回落；查询不消费累加器
+        assert_eq!(inject_remote_trade(-5), EconStatus::Ok as c_int);
+        assert_eq!(read(), i64::MAX - 5);
+        assert_eq!(read(), i64::MAX - 5);
+
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        assert_eq!(inject_remote_trade(i64::MIN + 1), EconStatus::Ok as c_int);
+        assert_eq!(inject_remote_trade(-100), EconStatus::Ok as c_int);
+        assert_eq!(read(), i64::MIN);
+
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+    }
+
+    #[test]
+    fn test_batch_prices_checked_rejects_length_mismatch() {
+        let ctx = [TradeContext { base_price_micros: 10_000_000, ..Default::default() }; 2];
+        let cfg = [MarketConfig::default(); 2];
+        let hist_avgs = [10.0; 2];
+        let lambdas = [0.01; 1]; // 调用方少传了一个元素
+        let mut results = [-1.0; 2];
+
+        let status = unsafe {
+            ecobridge_compute_batch_prices_checked(
+                2, 0.0, ctx.as_ptr(), 2, cfg.as_ptr(), 2,
+                hist_avgs.as_ptr(), 2, lambdas.as_ptr(), 1, results.as_mut_ptr(), 2,
+            )
+        };
+        assert_eq!(status, EconStatus::InvalidLength as c_int);
+        assert_eq!(results, [-1.0; 2]);
+
+        let status = unsafe {
+            ecobridge_compute_batch_prices_checked(
+                1, 0.0, ctx.as_ptr(), 1, cfg.as_ptr(), 1,
+                hist_avgs.as_ptr(), 1, lambdas.as_ptr(), 1, results.as_mut_ptr(), 1,
+            )
+        };
+        assert_eq!(status, EconStatus::Ok as c_int);
+        assert!(results[0] > 0.0);
+    }
+
+    #[test]
+    fn test_last_batch_error_is_per_thread_and_cleared_on_early_return() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let ctx = [TradeContext { base_price_micros: 10_000_000, ..Default::default() }; 4];
+        let cfg = [MarketConfig::default(); 4];
+        let (hist_avgs, lambdas) = ([10.0; 4], [0.01; 4]);
+        let mut results = [0.0; 4];
+        let last_error = || {
+            let mut idx: c_longlong = 0;
+            assert_eq!(unsafe { ecobridge_last_batch_error(&mut idx) }, EconStatus::Ok as c_int);
+            idx
+        };
+
+        economy::pricing::TEST_PANIC_AT_INDEX.store(2, Ordering::Relaxed);
+        let status = unsafe {
+            ecobridge_compute_batch_prices(4, 0.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), results.as_mut_ptr())
+        };
+        economy::pricing::TEST_PANIC_AT_INDEX.store(-1, Ordering::Relaxed);
+        assert_eq!(status, EconStatus::Ok as c_int);
+        assert_eq!(last_error(), 2);
+
+        // 其他线程看不到本线程的记录
+        assert_eq!(std::thread::spawn(last_error).join().unwrap(), -1);
+
+        // 长度校验失败提前返回，同样清除上一批次的记录
+        let status = unsafe {
+            ecobridge_compute_batch_prices_checked(
+                4, 0.0, ctx.as_ptr(), 4, cfg.as_ptr(), 4,
+                hist_avgs.as_ptr(), 4, lambdas.as_ptr(), 3, results.as_mut_ptr(), 4,
+            )
+        };
+        assert_eq!(status, EconStatus::InvalidLength as c_int);
+        assert_eq!(last_error(), -1);
+    }
+
+    #[test]
+    fn test_project_trade_impact_refuses_while_frozen() {
+        let project = |price: &mut c_double, recovery: &mut c_longlong| unsafe {
+            ecobridge_project_trade_impact(50.0, 100.0, 0.01, 7.0, price, recovery, 10_000_000, 1.0)
+        };
+        let (mut price, mut recovery) = (0.0, 0);
+        assert_eq!(project(&mut price, &mut recovery), EconStatus::Ok as c_int);
+        assert!(price > 0.0);
+
+        let (mut frozen_price, mut frozen_recovery) = (-1.0, -1);
+        security::freeze::set_frozen_for_current_thread(true);
+        let status = project(&mut frozen_price, &mut frozen_recovery);
+        security::freeze::set_frozen_for_current_thread(false);
+        assert_eq!(status, EconStatus::Frozen as c_int);
+        assert_eq!((frozen_price, frozen_recovery), (-1.0, -1));
+    }
+
+    #[test]
+    fn test_batch_prices_per_neff_rejects_short_neffs() {
+        let ctx = [TradeContext { base_price_micros: 10_000_000, ..Default::default() }; 2];
+        let cfg = [MarketConfig::default(); 2];
+        let hist_avgs = [10.0; 2];
+        let lambdas = [0.01; 2];
+        let neffs = [5.0; 1]; // 调用方少传了一个 neff
+        let mut results = [-1.0; 2];
+
+        let status = unsafe {
+            ecobridge_compute_batch_prices_per_neff(
+                2, neffs.as_ptr(), 1, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), results.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, EconStatus::InvalidLength as c_int);
+        assert_eq!(results, [-1.0; 2]);
+
+        let status = unsafe {
+            ecobridge_compute_batch_prices_per_neff(
+                1, neffs.as_ptr(), 1, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), results.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, EconStatus::Ok as c_int);
+        assert!(results[0] > 0.0);
+        assert_eq!(results[1], -1.0);
+    }
+
+    #[test]
+    fn test_batch_prices_by_item_matches_explicit_lambdas() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        assert_eq!(ecobridge_set_item_lambda(1, 0.05), EconStatus::Ok as c_int);
+        assert_eq!(ecobridge_set_item_lambda(2, 0.002), EconStatus::Ok as c_int);
+        assert_eq!(ecobridge_set_item_lambda(3, -1.0), EconStatus::InvalidValue as c_int);
+
+        let ctx = [TradeContext { base_price_micros: 10_000_000, current_amount: 1_000_000, ..Default::default() }; 3];
+        let cfg = [MarketConfig::default(); 3];
+        let hist_avgs = [10.0; 3];
+        let item_ids = [2u32, 1, 3]; // 3 未登记，取默认值
+        let lambdas = [0.002, 0.05, economy::DEFAULT_LAMBDA];
+
+        let (mut by_item, mut explicit) = ([0.0; 3], [0.0; 3]);
+        let status = unsafe {
+            ecobridge_compute_batch_prices_by_item(3, 50.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), item_ids.as_ptr(), by_item.as_mut_ptr())
+        };
+        assert_eq!(status, EconStatus::Ok as c_int);
+        let status = unsafe {
+            ecobridge_compute_batch_prices(3, 50.0, ctx.as_ptr(), cfg.as_ptr(), hist_avgs.as_ptr(), lambdas.as_ptr(), explicit.as_mut_ptr())
+        };
+        assert_eq!(status, EconStatus::Ok as c_int);
+        assert_eq!(by_item, explicit);
+
+        let mut lambda = 0.0;
+        assert_eq!(unsafe { ecobridge_get_item_lambda(3, &mut lambda) }, EconStatus::Ok as c_int);
+        assert_eq!(lambda, economy::DEFAULT_LAMBDA);
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+    }
+
+    #[test]
+    fn test_quote_purchase_matches_separate_calls() {
+        // 税率依赖可调的惩罚指数上限，需与修改该参数的测试串行
+        let _guard = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let ctx = TradeContext { base_price_micros: 10_000_000, current_amount: -3_000_000, ..Default::default() };
+        let mcfg = MarketConfig::default();
+        let rcfg = RegulatorConfig::default();
+        let tctx = TransferContext {
+            sender_balance: 1_000_000_000,
+            receiver_balance: 1_000_000_000,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        let (neff, lambda, hist_avg) = (20.0, 0.01, 10.0);
+
+        let mut quote = PurchaseQuote::default();
+        let status = unsafe { ecobridge_quote_purchase(&ctx, &tctx, &mcfg, &rcfg, neff, lambda, hist_avg, &mut quote) };
+        assert_eq!(status, EconStatus::Ok as c_int);
+
+        let epsilon = economy::environment::calculate_epsilon_internal(&ctx, &mcfg);
+        let unit = economy::pricing::compute_price_bounded_internal(ctx.base_price_micros, neff, ctx.current_amount, lambda, epsilon, hist_avg, 0);
+        assert_eq!(quote.unit_price_micros, to_micros_saturating(unit));
+        assert_eq!(quote.total_micros, to_micros_saturating(unit * 3.0));
+
+        let check = security::regulator::compute_transfer_check_internal(
+            &TransferContext { amount_micros: quote.total_micros, ..tctx }, &rcfg
+        );
+        assert_eq!(quote.tax_micros, check.final_tax_micros);
+        assert_eq!(quote.is_blocked, check.is_blocked);
+        assert_eq!(quote.warning_code, check.warning_code);
+    }
+
+    #[test]
+    fn test_price_from_context_matches_separate_calls() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        let now = 1_736_851_200_000;
+        assert!(economy::summation::append_trade_to_memory(now - 3_600_000, 50.0, "ctx_price_test"));
+        assert_eq!(inject_remote_trade(1_000_000), EconStatus::Ok as c_int);
+
+        let ctx = TradeContext {
+            base_price_micros: 10_000_000,
+            current_amount: 2_000_000,
+            current_timestamp: now,
+            ..Default::default()
+        };
+        let cfg = MarketConfig::default();
+        let (tau, lambda, hist_avg) = (7.0, 0.01, 10.0);
+
+        let mut price = 0.0;
+        assert_eq!(unsafe { ecobridge_price_from_context(&ctx, &cfg, tau, lambda, hist_avg, &mut price) }, EconStatus::Ok as c_int);
+
+        let n_eff = economy::summation::query_neff_with_remote_internal(now, tau);
+        assert!(n_eff > 0.0);
+        let epsilon = economy::environment::calculate_epsilon_internal(&ctx, &cfg);
+        let expected = economy::pricing::compute_price_bounded_internal(
+            ctx.base_price_micros, n_eff, ctx.current_amount, lambda, epsilon, hist_avg, 0
+        );
+        assert_eq!(price, expected);
+
+        // 新手卖出加成：零时长玩家卖出价按满额倍率放大
+        assert_eq!(ecobridge_set_newbie_sell_bonus(36_000, 1.25), EconStatus::Ok as c_int);
+        let mut boosted = 0.0;
+        assert_eq!(unsafe { ecobridge_price_from_context(&ctx, &cfg, tau, lambda, hist_avg, &mut boosted) }, EconStatus::Ok as c_int);
+        assert!((boosted - expected * 1.25).abs() < 1e-12);
+        assert_eq!(ecobridge_set_newbie_sell_bonus(36_000, 0.5), EconStatus::InvalidValue as c_int);
+        assert_eq!(ecobridge_set_newbie_sell_bonus(36_000, 2.5), EconStatus::InvalidValue as c_int);
+
+        // 不消费远程流量累加器
+        let mut pending = 0;
+        assert_eq!(unsafe { ecobridge_remote_accumulator_value(&mut pending) }, EconStatus::Ok as c_int);
+        assert_eq!(pending, 1_000_000);
+
+        for bad_tau in [0.0, -1.0, f64::NAN] {
+            assert_eq!(unsafe { ecobridge_price_from_context(&ctx, &cfg, bad_tau, lambda, hist_avg, &mut price) }, EconStatus::InvalidValue as c_int);
+        }
+        assert_eq!(unsafe { ecobridge_price_from_context(ptr::null(), &cfg, tau, lambda, hist_avg, &mut price) }, EconStatus::NullPointer as c_int);
+
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+    }
+
+    #[test]
+    fn test_market_keyed_price_applies_launch_ramp() {
+        let _lock = GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+        let now = 1_736_851_200_000;
+        assert!(economy::summation::append_trade_to_memory(now - 3_600_000, 50.0, "ramp_test"));
+        let key = std::ffi::CString::new("ramp_test").unwrap();
+
+        let ctx = TradeContext {
+            base_price_micros: 10_000_000,
+            current_amount: -1_000_000,
+            current_timestamp: now,
+            ..Default::default()
+        };
+        let cfg = MarketConfig::default();
+        let (tau, lambda, hist_avg) = (7.0, 0.01, 10.0);
+        let price_at = |ctx: &TradeContext| {
+            let n_eff = economy::summation::query_neff_internal(ctx.current_timestamp, tau, "ramp_test");
+            economy::pricing::compute_price_from_context_internal(ctx, &cfg, "ramp_test", tau, n_eff, lambda, hist_avg)
+        };
+
+        let computed = price_at(&ctx);
+        assert!((computed - 50.0).abs() > 1e-6, "测试前提: 演算价需偏离首发价");
+
+        // 上线 1/4 爬坡期：首发价权重 3/4
+        assert_eq!(unsafe { ecobridge_set_market_launch(key.as_ptr(), now - 2_500, 10_000, 50_000_000) }, EconStatus::Ok as c_int);
+        let ramped = price_at(&ctx);
+        assert!((ramped - (50.0 + (computed - 50.0) * 0.25)).abs() < 1e-9);
+        let mut via_apply = 0.0;
+        assert_eq!(unsafe { ecobridge_apply_market_launch(key.as_ptr(), now, computed, &mut via_apply) }, EconStatus::Ok as c_int);
+        assert_eq!(ramped, via_apply);
+
+        // 全局一站式定价不受单个市场的爬坡影响；爬坡期结束后恢复演算价
+        let mut global = 0.0;
+        assert_eq!(unsafe { ecobridge_price_from_context(&ctx, &cfg, tau, lambda, hist_avg, &mut global) }, EconStatus::Ok as c_int);
+        assert_eq!(global, computed);
+        assert_eq!(unsafe { ecobridge_set_market_launch(key.as_ptr(), now - 10_000, 10_000, 50_000_000) }, EconStatus::Ok as c_int);
+        assert_eq!(price_at(&ctx), computed);
+
+        assert_eq!(ecobridge_reset_all_state(), EconStatus::Ok as c_int);
+    }
+
+    #[test]
+    fn test_sell_all_value_uses_caller_tiers() {
+        let (base_micros, n_eff, lambda, eps) = (10_000_000, 50.0, 0.002, 1.0);
+        let tiers = TierConfig::default();
+        let value_with = |tiers: &TierConfig| {
+            let mut out = 0;
+            let status = unsafe { ecobridge_compute_sell_all_value(base_micros, n_eff, 2_500, lambda, eps, tiers, &mut out) };
+            (status, out)
+        };
+
+        let (status, discounted) = value_with(&tiers);
+        assert_eq!(status, EconStatus::Ok as c_int);
+        let (_, flat) = value_with(&TierConfig { tier2_rate: 1.0, tier3_rate: 1.0, ..tiers });
+        assert!(flat > discounted);
+
+        assert_eq!(value_with(&TierConfig { tier2_rate: 0.0, ..tiers }).0, EconStatus::InvalidValue as c_int);
+        let mut out = 0;
+        assert_eq!(unsafe { ecobridge_compute_sell_all_value(base_micros, n_eff, 10, lambda, eps, ptr::null(), &mut out) }, EconStatus::NullPointer as c_int);
+    }
+
+    #[test]
+    fn test_dynamic_limit_matches_regulator() {
+        let cfg = RegulatorConfig::default();
+        for (play_time, base, rate, max) in [(0, 64.0, 8.0, 512.0), (36_000, 64.0, 8.0, 512.0), (3_600_000, 64.0, 8.0, 512.0)] {
+            let mut ffi_limit = 0.0;
+            let status = unsafe { ecobridge_get_dynamic_limit(play_time, base, rate, max, &mut ffi_limit) };
+            assert_eq!(status, EconStatus::Ok as c_int);
+
+            // 数量为 0 时，审计结果的剩余额度即为风控使用的限额
+            let ctx = TransferContext {
+                sender_play_time: play_time,
+                item_base_limit: to_micros_saturating(base),
+                item_growth_rate: rate,
+                item_max_limit: to_micros_saturating(max),
+                sender_activity_score: 1.0,
+                ..Default::default()
+            };
+            let res = security::regulator::compute_transfer_check_internal(&ctx, &cfg);
+            assert_eq!(res.limit_headroom_micros, to_micros_saturating(ffi_limit));
+        }
+    }
+
+    #[test]
+    fn test_stability_window_is_configurable() {
+        let mut out = 0.0;
+        assert_eq!(unsafe { ecobridge_calc_stability(1_000, 451_000, &mut out) }, EconStatus::Ok as c_int);
+        assert_eq!(out, 0.5);
+
+        assert_eq!(unsafe { ecobridge_calc_stability_with_window(1_000, 451_000, 1_800_000.0, &mut out) }, EconStatus::Ok as c_int);
+        assert_eq!(out, 0.25);
+
+        for window in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let status = unsafe { ecobridge_calc_stability_with_window(1_000, 451_000, window, &mut out) };
+            assert_eq!(status, EconStatus::InvalidValue as c_int);
+        }
+    }
 }
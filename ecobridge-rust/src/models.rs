@@ -11,9 +11,61 @@
 
 use libc::{c_double, c_int, c_longlong};
 
+// ==================== 0. 定点数转换 (Micros) ====================
+
+/// 精度缩放常量 (1.0 = 1,000,000 Micros)
+pub const MICROS_SCALE: f64 = 1_000_000.0;
+
+/// f64 标准单位 -> i64 Micros (溢出检查)
+///
+/// 非有限值或缩放后超出 i64 可表示范围时返回 `None`，
+/// 调用方应据此向 FFI 返回 `EconStatus::NumericOverflow`。
+#[inline]
+pub fn to_micros_checked(value: f64) -> Option<i64> {
+    if !value.is_finite() {
+        return None;
+    }
+    let scaled = (value * MICROS_SCALE).round();
+    // i64::MAX as f64 == 2^63，本身已不可表示，因此上界为开区间
+    if scaled >= i64::MIN as f64 && scaled < i64::MAX as f64 {
+        Some(scaled as i64)
+    } else {
+        None
+    }
+}
+
+/// f64 标准单位 -> i64 Micros (饱和转换)
+///
+/// 超界时钳位到 i64::MIN / i64::MAX，非有限值返回 0。
+#[inline]
+pub fn to_micros_saturating(value: f64) -> i64 {
+    if !value.is_finite() {
+        return 0;
+    }
+    let scaled = value * MICROS_SCALE;
+    if scaled >= i64::MAX as f64 {
+        i64::MAX
+    } else if scaled <= i64::MIN as f64 {
+        i64::MIN
+    } else {
+        scaled.round() as i64
+    }
+}
+
+/// i64 Micros -> f64 标准单位
+#[inline]
+pub fn from_micros(value: i64) -> f64 {
+    (value as f64) / MICROS_SCALE
+}
+
 // ==================== 1. 物理控制器状态 (State) ====================
 
-/// 工业级 PID 控制器状态 (72 bytes)
+/// PID 微分项来源：对观测值求导 (默认，无设定值突变冲击)
+pub const PID_DERIVATIVE_ON_MEASUREMENT: c_int = 0;
+/// PID 微分项来源：对误差求导 (经典形式，设定值突变时产生冲击)
+pub const PID_DERIVATIVE_ON_ERROR: c_int = 1;
+
+/// 工业级 PID 控制器状态 (120 bytes, ABI 0x0009_000C 起)
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct PidState {
@@ -26,7 +78,13 @@ pub struct PidState {
     pub filtered_d: c_double,        // Offset 48
     pub integration_limit: c_double, // Offset 56
     pub is_saturated: c_int,         // Offset 64
-    pub _padding: c_int,             // Offset 68
+    pub derivative_mode: c_int,      // Offset 68: 微分项来源 (PID_DERIVATIVE_ON_*)
+    pub prev_error: c_double,        // Offset 72: 上一次误差 (误差求导模式使用；NaN = 尚无记录，首步不计微分)
+    pub panic_threshold: c_double,   // Offset 80: 触发恐慌抑制的微分阈值 (0 = 默认 50.0)
+    pub panic_damping: c_double,     // Offset 88: 恐慌状态下的微分项放大倍数 (0 = 默认 1.8)
+    pub integral_decay: c_double,    // Offset 96: 每个控制周期的积分保留系数 (0 = 默认 0.99999)
+    pub output_min: c_double,        // Offset 104: 输出倍率下限 (与 output_max 同为 0 时取默认 0.5)
+    pub output_max: c_double,        // Offset 112: 输出倍率上限 (与 output_min 同为 0 时取默认 5.0)
 }
 
 impl Default for PidState {
@@ -35,7 +93,13 @@ impl Default for PidState {
             kp: 0.5, ki: 0.1, kd: 0.05, lambda: 0.01,
             integral: 0.0, prev_pv: 0.0, filtered_d: 0.0,
             integration_limit: 30.0, is_saturated: 0,
-            _padding: 0,
+            derivative_mode: PID_DERIVATIVE_ON_MEASUREMENT,
+            prev_error: f64::NAN,
+            panic_threshold: 50.0,
+            panic_damping: 1.8,
+            integral_decay: 0.99999,
+            output_min: 0.5,
+            output_max: 5.0,
         }
     }
 }
@@ -89,7 +153,7 @@ pub struct TransferContext {
 
 // ==================== 4. 环境配置模型 (Configs) ====================
 
-/// 市场动态定价配置 (72 bytes)
+/// 市场动态定价配置 (80 bytes, ABI 0x0009_0003 起)
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct MarketConfig {
@@ -102,8 +166,12 @@ pub struct MarketConfig {
     pub weekend_weight: c_double,        // 48
     pub newbie_weight: c_double,         // 56
     pub inflation_weight: c_double,      // 64
+    pub ln_floor: c_double,              // 72: 环境因子取对数前的下限 (须为正，非正/非有限值回退 0.01)
 }
 
+/// `MarketConfig::ln_floor` 默认值
+pub const DEFAULT_LN_FLOOR: f64 = 0.01;
+
 impl Default for MarketConfig {
     fn default() -> Self {
         Self {
@@ -112,11 +180,12 @@ impl Default for MarketConfig {
             newbie_protection_rate: 0.2,
             seasonal_weight: 0.25, weekend_weight: 0.25,
             newbie_weight: 0.25, inflation_weight: 0.25,
+            ln_floor: DEFAULT_LN_FLOOR,
         }
     }
 }
 
-/// 审计监管与计税配置 (96 bytes)
+/// 审计监管与计税配置 (128 bytes, ABI 0x0009_000E 起)
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct RegulatorConfig {
@@ -126,12 +195,17 @@ pub struct RegulatorConfig {
     pub wealth_gap_tax_rate: c_double, // 24
     pub poor_threshold: c_longlong,    // 32: [Precision] 贫困判定线 Micros
     pub rich_threshold: c_longlong,    // 40: [Precision] 富裕判定线 Micros
-    pub _reserved: c_double,           // 48
+    pub tax_combination_mode: c_int,   // 48: 税种合成模式 (0=混合/1=累加/2=取最大/3=顺序)
+    pub exclusive_limit: c_int,        // 52: 为 0 时数量恰好等于限额仍放行 (>)，非 0 时等于限额即拦截 (>=)
     pub warning_ratio: c_double,       // 56
     pub warning_min_amount: c_longlong, // 64: [Precision] 触发警报金额 Micros
     pub newbie_hours: c_double,        // 72
     pub veteran_hours: c_double,       // 80
     pub velocity_threshold: c_double,  // 88
+    pub injection_balance_multiple: c_double, // 96: 老手向新手转账超过接收者余额的该倍数即判定为违规注资 (<= 0 关闭)
+    pub max_tax_ratio: c_double,       // 104: 税费占转账金额的上限比例 (<= 0 或非有限值时取 0.8，超过 1 按 1 处理)
+    pub velocity_penalty_coeff: c_double, // 112: 行为惩罚税指数系数，惩罚倍率 = e^(流速 × 系数) (<= 0 或非有限值时取 0.05)
+    pub injection_min_amount: c_longlong, // 120: [Precision] 违规注资判定的最低转账金额 Micros，低于该值不按余额倍数判定 (<= 0 时取 1,000)
 }
 
 impl Default for RegulatorConfig {
@@ -142,27 +216,185 @@ impl Default for RegulatorConfig {
             luxury_tax_rate: 0.10, wealth_gap_tax_rate: 0.20,
             poor_threshold: 10_000_000_000,    // 10k
             rich_threshold: 1_000_000_000_000, // 1M
-            _reserved: 0.0,
+            tax_combination_mode: 0,
+            exclusive_limit: 0,
             warning_ratio: 0.9,
             warning_min_amount: 50_000_000_000,
             newbie_hours: 10.0, veteran_hours: 100.0,
             velocity_threshold: 20.0,
+            injection_balance_multiple: 10.0,
+            max_tax_ratio: 0.8,
+            velocity_penalty_coeff: 0.05,
+            injection_min_amount: 1_000_000_000, // 1k
         }
     }
 }
+/// 卖出阶梯折扣配置 (32 bytes)：超出各档数量上限的部分按对应折扣率计价
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TierConfig {
+    pub tier1_limit: c_double, // 0: 原价档数量上限 (件)
+    pub tier2_limit: c_double, // 8: 第二档数量上限 (件)，须不低于 tier1_limit
+    pub tier2_rate: c_double,  // 16: 第二档折扣率 (0, 1]
+    pub tier3_rate: c_double,  // 24: 超出第二档部分的折扣率 (0, 1]
+}
+
+impl TierConfig {
+    /// 上限须为非负有限值且递增，折扣率须位于 (0, 1]
+    pub fn is_valid(&self) -> bool {
+        let valid_rate = |r: f64| r.is_finite() && r > 0.0 && r <= 1.0;
+        self.tier1_limit.is_finite()
+            && self.tier2_limit.is_finite()
+            && self.tier1_limit >= 0.0
+            && self.tier2_limit >= self.tier1_limit
+            && valid_rate(self.tier2_rate)
+            && valid_rate(self.tier3_rate)
+    }
+}
+
 
 // ==================== 5. 演算结果集 (Results) ====================
 
-/// 交易演算最终结果 (16 bytes)
+/// 交易演算最终结果 (32 bytes, ABI 0x0009_0002 起)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TransferResult {
     pub final_tax_micros: c_longlong, // 0: [Precision] 计算得出的税费 Micros
     pub is_blocked: c_int,           // 8: 0=通过, 1=拒绝
     pub warning_code: c_int,         // 12
+    pub applied_tax_flags: c_int,    // 16: 已征税种位标记 (基础/奢侈/贫富/行为惩罚)
+    pub _padding: c_int,             // 20
+    pub limit_headroom_micros: c_longlong, // 24: 放行时剩余数量限额 (限额 - 本次数量)；拦截时为 0，未设限额时为 i64::MAX
+}
+
+/// 购买报价 (32 bytes)：单次 FFI 调用合并定价与交易审计结果
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurchaseQuote {
+    pub unit_price_micros: c_longlong, // 0: [Precision] 单价 Micros (含本次数量冲击与底价保护)
+    pub total_micros: c_longlong,      // 8: [Precision] 总价 Micros (单价 × 数量)
+    pub tax_micros: c_longlong,        // 16: [Precision] 按总价计算的税费 Micros
+    pub is_blocked: c_int,             // 24: 0=通过, 1=拒绝
+    pub warning_code: c_int,           // 28
+}
+
+/// 监控采集快照 (48 bytes, ABI 0x0009_0007 起)：一次 FFI 调用汇总日志健康度与全局热存储指标
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MonitoringSnapshot {
+    pub total_logs: u64,                  // 0: 已入队日志总数
+    pub dropped_logs: u64,                // 8: 因队列满被丢弃的日志数
+    pub local_neff: c_double,             // 16: 全局热存储有效供应量 (不含远程流量)
+    pub remote_pending_micros: c_longlong, // 24: [Precision] 尚未被 neff 查询消费的远程流量 Micros
+    pub avg_trade_age_ms: c_double,       // 32: 衰减加权平均交易年龄
+    pub writer_last_flush_ms: c_longlong, // 40: 写入线程最近一次刷盘的 Unix 时间戳 (ms)，0 = 尚未刷盘
 }
 
-// ==================== 6. 静态布局一致性测试 ====================
+/// 单日经济汇总 (32 bytes)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DaySummary {
+    pub day_start_ms: c_longlong,           // 0: 当日 00:00 UTC 时间戳 (ms)
+    pub total_abs_delta_micros: c_longlong, // 8: [Precision] 当日资金变动绝对值之和 Micros
+    pub event_count: u64,                   // 16: 当日事件条数
+    pub distinct_players: u64,              // 24: 当日活跃玩家数
+}
+
+// ==================== 6. 运行时布局描述 (Layout Descriptor) ====================
+
+/// 结构体布局描述项 (16 bytes)：供 Java 侧启动时与自身 FFM 布局逐字段核对
+///
+/// `name_hash` 为 `"结构体名.字段名"` 的 FNV-1a 64 哈希；
+/// 每个结构体另有一条以 `"结构体名"` 哈希、offset 为 0、size 为结构体总大小的条目。
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayoutEntry {
+    pub name_hash: u64, // 0: FNV-1a 64 名称哈希
+    pub offset: u32,    // 8: 字段偏移 (字节)
+    pub size: u32,      // 12: 字段大小 (字节)
+}
+
+/// FNV-1a 64 名称哈希 (Java 侧须使用相同算法对 UTF-8 字节求值)
+pub const fn layout_name_hash(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// 通过字段投影推导字段类型大小
+const fn field_size<T, F>(_: fn(&T) -> &F) -> usize {
+    std::mem::size_of::<F>()
+}
+
+/// 生成单个结构体的描述项：首条为结构体总大小，其后按声明顺序列出字段
+macro_rules! layout_entries {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        [
+            LayoutEntry {
+                name_hash: layout_name_hash(stringify!($ty)),
+                offset: 0,
+                size: std::mem::size_of::<$ty>() as u32,
+            },
+            $(LayoutEntry {
+                name_hash: layout_name_hash(concat!(stringify!($ty), ".", stringify!($field))),
+                offset: std::mem::offset_of!($ty, $field) as u32,
+                size: field_size(|s: &$ty| &s.$field) as u32,
+            },)*
+        ]
+    };
+}
+
+/// 所有跨 FFI 边界且由 Java 侧手工布局的结构体的完整描述
+pub fn layout_descriptor() -> Vec<LayoutEntry> {
+    let mut entries = Vec::new();
+    entries.extend(layout_entries!(PidState {
+        kp, ki, kd, lambda, integral, prev_pv, filtered_d, integration_limit,
+        is_saturated, derivative_mode, prev_error, panic_threshold, panic_damping, integral_decay,
+        output_min, output_max,
+    }));
+    entries.extend(layout_entries!(TradeContext {
+        base_price_micros, current_amount, inflation_rate, current_timestamp,
+        play_time_seconds, timezone_offset, newbie_mask, market_heat, eco_saturation,
+    }));
+    entries.extend(layout_entries!(TransferContext {
+        amount_micros, sender_balance, receiver_balance, inflation_rate,
+        item_base_limit, item_growth_rate, item_max_limit,
+        sender_play_time, receiver_play_time, sender_activity_score, sender_velocity, _padding,
+    }));
+    entries.extend(layout_entries!(MarketConfig {
+        base_lambda, volatility_factor, seasonal_amplitude, weekend_multiplier,
+        newbie_protection_rate, seasonal_weight, weekend_weight, newbie_weight,
+        inflation_weight, ln_floor,
+    }));
+    entries.extend(layout_entries!(RegulatorConfig {
+        base_tax_rate, luxury_threshold, luxury_tax_rate, wealth_gap_tax_rate,
+        poor_threshold, rich_threshold, tax_combination_mode, exclusive_limit,
+        warning_ratio, warning_min_amount, newbie_hours, veteran_hours,
+        velocity_threshold, injection_balance_multiple, max_tax_ratio, velocity_penalty_coeff,
+        injection_min_amount,
+    }));
+    entries.extend(layout_entries!(TransferResult {
+        final_tax_micros, is_blocked, warning_code, applied_tax_flags, _padding, limit_headroom_micros,
+    }));
+    entries.extend(layout_entries!(PurchaseQuote {
+        unit_price_micros, total_micros, tax_micros, is_blocked, warning_code,
+    }));
+    entries.extend(layout_entries!(MonitoringSnapshot {
+        total_logs, dropped_logs, local_neff, remote_pending_micros, avg_trade_age_ms, writer_last_flush_ms,
+    }));
+    entries.extend(layout_entries!(DaySummary {
+        day_start_ms, total_abs_delta_micros, event_count, distinct_players,
+    }));
+    entries
+}
+
+// ==================== 7. 静态布局一致性测试 ====================
 
 #[cfg(test)]
 mod tests {
@@ -172,16 +404,88 @@ mod tests {
     #[test]
     fn verify_precision_alignment() {
         // 验证结构体总大小 (必须与 Java 侧配置绝对一致)
-        assert_eq!(mem::size_of::<PidState>(), 72);
+        assert_eq!(mem::size_of::<PidState>(), 120);
+        assert_eq!(mem::offset_of!(PidState, derivative_mode), 68);
+        assert_eq!(mem::offset_of!(PidState, prev_error), 72);
+        assert_eq!(mem::offset_of!(PidState, panic_threshold), 80);
+        assert_eq!(mem::offset_of!(PidState, panic_damping), 88);
+        assert_eq!(mem::offset_of!(PidState, integral_decay), 96);
+        assert_eq!(mem::offset_of!(PidState, output_min), 104);
+        assert_eq!(mem::offset_of!(PidState, output_max), 112);
         assert_eq!(mem::size_of::<TradeContext>(), 64);
         assert_eq!(mem::size_of::<TransferContext>(), 96);
-        assert_eq!(mem::size_of::<MarketConfig>(), 72); 
-        assert_eq!(mem::size_of::<RegulatorConfig>(), 96);
-        assert_eq!(mem::size_of::<TransferResult>(), 16);
+        assert_eq!(mem::size_of::<MarketConfig>(), 80);
+        assert_eq!(mem::size_of::<RegulatorConfig>(), 128);
+        assert_eq!(mem::size_of::<TransferResult>(), 32);
+        assert_eq!(mem::size_of::<MonitoringSnapshot>(), 48);
+        assert_eq!(mem::size_of::<DaySummary>(), 32);
+        assert_eq!(mem::size_of::<PurchaseQuote>(), 32);
+        assert_eq!(mem::size_of::<TierConfig>(), 32);
+        assert_eq!(mem::size_of::<LayoutEntry>(), 16);
         
         // 验证关键金额字段的偏移
         assert_eq!(mem::offset_of!(TransferContext, sender_balance), 8);
+        assert_eq!(mem::offset_of!(MarketConfig, ln_floor), 72);
         assert_eq!(mem::offset_of!(RegulatorConfig, rich_threshold), 40);
+        assert_eq!(mem::offset_of!(RegulatorConfig, tax_combination_mode), 48);
+        assert_eq!(mem::offset_of!(RegulatorConfig, exclusive_limit), 52);
+        assert_eq!(mem::offset_of!(RegulatorConfig, warning_ratio), 56);
+        assert_eq!(mem::offset_of!(RegulatorConfig, injection_balance_multiple), 96);
+        assert_eq!(mem::offset_of!(RegulatorConfig, max_tax_ratio), 104);
+        assert_eq!(mem::offset_of!(RegulatorConfig, velocity_penalty_coeff), 112);
+        assert_eq!(mem::offset_of!(RegulatorConfig, injection_min_amount), 120);
         assert_eq!(mem::offset_of!(TransferResult, final_tax_micros), 0);
+        assert_eq!(mem::offset_of!(TransferResult, applied_tax_flags), 16);
+        assert_eq!(mem::offset_of!(TransferResult, limit_headroom_micros), 24);
+    }
+
+    #[test]
+    fn test_layout_descriptor() {
+        // FNV-1a 64 标准测试向量
+        assert_eq!(layout_name_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(layout_name_hash("a"), 0xaf63_dc4c_8601_ec8c);
+
+        let entries = layout_descriptor();
+        let find = |name: &str| {
+            let hash = layout_name_hash(name);
+            *entries.iter().find(|e| e.name_hash == hash).expect(name)
+        };
+
+        assert_eq!(find("RegulatorConfig"), LayoutEntry { name_hash: layout_name_hash("RegulatorConfig"), offset: 0, size: 128 });
+        assert_eq!(find("PidState").size, 120);
+        assert_eq!(find("TransferContext.sender_balance").offset, 8);
+        assert_eq!(find("RegulatorConfig.exclusive_limit").offset, 52);
+        assert_eq!(find("RegulatorConfig.exclusive_limit").size, 4);
+        assert_eq!(find("RegulatorConfig.velocity_penalty_coeff").offset, 112);
+        assert_eq!(find("RegulatorConfig.injection_min_amount").offset, 120);
+        assert_eq!(find("TransferResult.limit_headroom_micros").offset, 24);
+        assert_eq!(find("PidState.derivative_mode").size, 4);
+
+        // 名称哈希不得冲突
+        let mut hashes: Vec<u64> = entries.iter().map(|e| e.name_hash).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), entries.len());
+    }
+
+    #[test]
+    fn test_micros_checked_conversion() {
+        assert_eq!(to_micros_checked(1.5), Some(1_500_000));
+        assert_eq!(to_micros_checked(-2.25), Some(-2_250_000));
+        assert_eq!(to_micros_checked(-9.2e12), Some(-9_200_000_000_000_000_000));
+
+        // 超出 i64 Micros 范围 (约 ±9.22e12 标准单位)
+        assert_eq!(to_micros_checked(1e30), None);
+        assert_eq!(to_micros_checked(-1e30), None);
+        assert_eq!(to_micros_checked(9.3e12), None);
+        assert_eq!(to_micros_checked(f64::NAN), None);
+        assert_eq!(to_micros_checked(f64::NEG_INFINITY), None);
+
+        // 饱和版本保持钳位语义
+        assert_eq!(to_micros_saturating(1e30), i64::MAX);
+        assert_eq!(to_micros_saturating(-1e30), i64::MIN);
+
+        assert_eq!(from_micros(-2_250_000), -2.25);
+        assert_eq!(from_micros(to_micros_checked(123.456789).unwrap()), 123.456789);
     }
 }
\ No newline at end of file
@@ -0,0 +1,115 @@
+// ==================================================
+// FILE: ecobridge-rust/src/security/flow_tracker.rs
+// ==================================================
+
+//! Transfer Flow Tracker (逆向流转检测)
+//!
+//! 记录最近的 (发送者, 接收者) 转账对，用于识别“乒乓洗钱”：
+//! 低活跃度账户在检测窗口内把刚收到的资金立即回转给原发送者。
+//!
+//! # 检测窗口
+//! 默认 `DEFAULT_REVERSE_FLOW_WINDOW_MS` (5 分钟)。仅当 B -> A 发生在 A -> B 之后的窗口内，
+//! 且 B 的活跃度评分低于 `LOW_ACTIVITY_THRESHOLD` 时判定为逆向流转；
+//! 活跃账户之间的正常双向交易不受影响。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use lazy_static::lazy_static;
+
+pub const DEFAULT_REVERSE_FLOW_WINDOW_MS: i64 = 5 * 60_000;
+
+/// 低活跃度判定阈值 (与傀儡账户识别的 0.1 保持一致)
+pub const LOW_ACTIVITY_THRESHOLD: f64 = 0.1;
+
+static REVERSE_FLOW_WINDOW_MS: AtomicI64 = AtomicI64::new(DEFAULT_REVERSE_FLOW_WINDOW_MS);
+
+/// 最近转账记录；过期记录只在检测时被忽略，按窗口周期批量清理
+#[derive(Default)]
+struct RecentTransfers {
+    /// (sender, receiver) -> 最近一次转账时间戳 (ms)
+    pairs: HashMap<(String, String), i64>,
+    /// 上次清理时的时间戳 (ms)
+    last_prune_ts: i64,
+}
+
+lazy_static! {
+    static ref RECENT_TRANSFERS: Mutex<RecentTransfers> = Mutex::new(RecentTransfers::default());
+}
+
+/// 设置逆向流转检测窗口 (ms)，须为正数
+pub fn set_window_ms(window_ms: i64) -> bool {
+    if window_ms <= 0 {
+        return false;
+    }
+    REVERSE_FLOW_WINDOW_MS.store(window_ms, Ordering::Relaxed);
+    true
+}
+
+/// 记录一次已放行的转账
+///
+/// 超出检测窗口的旧记录每经过一个窗口才整体清理一次，单次记录的均摊开销为 O(1)。
+pub fn record_transfer(sender: &str, receiver: &str, ts: i64) {
+    let window = REVERSE_FLOW_WINDOW_MS.load(Ordering::Relaxed);
+    let mut lock = RECENT_TRANSFERS.lock().unwrap_or_else(|e| e.into_inner());
+    if ts.saturating_sub(lock.last_prune_ts) >= window {
+        lock.pairs.retain(|_, last_ts| ts.saturating_sub(*last_ts) <= window);
+        lock.last_prune_ts = ts;
+    }
+    lock.pairs.insert((sender.to_string(), receiver.to_string()), ts);
+}
+
+/// 判断 sender -> receiver 是否构成逆向流转
+///
+/// 条件：receiver -> sender 的转账发生在 `ts` 之前的检测窗口内，且当前发送者活跃度低。
+pub fn is_reverse_flow(sender: &str, receiver: &str, ts: i64, sender_activity_score: f64) -> bool {
+    if sender_activity_score >= LOW_ACTIVITY_THRESHOLD {
+        return false;
+    }
+    let window = REVERSE_FLOW_WINDOW_MS.load(Ordering::Relaxed);
+    let lock = RECENT_TRANSFERS.lock().unwrap_or_else(|e| e.into_inner());
+    match lock.pairs.get(&(receiver.to_string(), sender.to_string())) {
+        Some(&prev_ts) => {
+            let elapsed = ts.saturating_sub(prev_ts);
+            (0..=window).contains(&elapsed)
+        }
+        None => false,
+    }
+}
+
+/// 清空转账记录并恢复默认检测窗口
+pub fn reset_state() {
+    *RECENT_TRANSFERS.lock().unwrap_or_else(|e| e.into_inner()) = RecentTransfers::default();
+    REVERSE_FLOW_WINDOW_MS.store(DEFAULT_REVERSE_FLOW_WINDOW_MS, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_pairs_pruned_once_per_window() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let window = DEFAULT_REVERSE_FLOW_WINDOW_MS;
+        let now = 1_736_851_200_000;
+        let tracked = |s: &str, r: &str| {
+            RECENT_TRANSFERS.lock().unwrap_or_else(|e| e.into_inner()).pairs.contains_key(&(s.to_string(), r.to_string()))
+        };
+
+        record_transfer("ft-a", "ft-b", now);
+        record_transfer("ft-c", "ft-d", now + window);
+
+        // 距上次清理不足一个窗口：过期的 a -> b 暂不清理，但已不参与检测
+        record_transfer("ft-e", "ft-f", now + window + window / 2);
+        assert!(tracked("ft-a", "ft-b"));
+        assert!(!is_reverse_flow("ft-b", "ft-a", now + window + window / 2, 0.0));
+
+        // 满一个窗口后整体清理，仍在窗口内的记录保留
+        record_transfer("ft-g", "ft-h", now + 2 * window);
+        assert!(!tracked("ft-a", "ft-b"));
+        assert!(tracked("ft-c", "ft-d"));
+        assert!(is_reverse_flow("ft-f", "ft-e", now + 2 * window, 0.0));
+        reset_state();
+    }
+}
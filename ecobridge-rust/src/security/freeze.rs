@@ -0,0 +1,43 @@
+// ==================================================
+// FILE: ecobridge-rust/src/security/freeze.rs
+// ==================================================
+
+//! Economy Kill-Switch (全服经济冻结)
+//!
+//! 漏洞事故期间一键冻结定价与转账：冻结时交易审计以 `CODE_FROZEN` 拦截，
+//! 定价类 FFI 返回 `EconStatus::Frozen`。日志写入不受影响，便于事后审计。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static FROZEN: AtomicBool = AtomicBool::new(false);
+
+#[cfg(test)]
+thread_local! {
+    /// 测试专用：仅对当前线程生效的冻结开关，避免影响并行执行的其他用例
+    static TEST_FROZEN: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// 设置冻结状态
+pub fn set_frozen(frozen: bool) {
+    FROZEN.store(frozen, Ordering::SeqCst);
+}
+
+/// 当前是否处于冻结状态
+pub fn is_frozen() -> bool {
+    #[cfg(test)]
+    if TEST_FROZEN.with(|f| f.get()) {
+        return true;
+    }
+    FROZEN.load(Ordering::SeqCst)
+}
+
+/// 测试专用：仅冻结当前线程
+#[cfg(test)]
+pub(crate) fn set_frozen_for_current_thread(frozen: bool) {
+    TEST_FROZEN.with(|f| f.set(frozen));
+}
+
+/// 解除冻结
+pub fn reset_state() {
+    set_frozen(false);
+}
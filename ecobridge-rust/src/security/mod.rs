@@ -14,6 +14,15 @@
 /// 风控核心逻辑实现 (包含动态数量限额演算、账户拆分防御与傀儡账户识别)
 pub mod regulator;
 
+/// 转账对追踪 (逆向流转 / 乒乓洗钱检测)
+pub mod flow_tracker;
+
+/// 玩家级转账硬限流 (令牌桶)
+pub mod ratelimit;
+
+/// 全服经济冻结开关 (事故应急)
+pub mod freeze;
+
 // ==================== 2. 跨模块重导出 ====================
 
 /// 重新导出配置结构体 (SSoT)
@@ -24,6 +33,10 @@ pub use crate::models::RegulatorConfig;
 pub use regulator::{
     // 核心审计函数 (已适配 v1.6.0 i64 定点数)
     compute_transfer_check_internal,
+    compute_transfer_check_tracked,
+    check_block_code,
+    check_block_code_tracked,
+    dynamic_quantity_limit,
     
     // 辅助判断函数
     is_high_risk_transfer,
@@ -38,4 +51,18 @@ pub use regulator::{
     
     // 拦截动态数量限额 (物品售出数量超过基于时长的演算上限)
     CODE_BLOCK_QUANTITY_LIMIT,     // 6: 触发平方根模型数量拦截
+    CODE_OVERFLOW,                 // 7: 金额或税额数值溢出
+    CODE_FROZEN,                   // 8: 全服经济冻结
+
+    // 税种合成模式 (RegulatorConfig.tax_combination_mode)
+    TAX_MODE_MIXED,                // 0: 奢侈税累加、贫富税取最大 (兼容默认)
+    TAX_MODE_ADDITIVE,             // 1: 全部累加
+    TAX_MODE_MAXIMUM,              // 2: 取最高单项
+    TAX_MODE_SEQUENTIAL,           // 3: 依次扣除后计税
+
+    // 已征税种位标记 (TransferResult.applied_tax_flags)
+    TAX_FLAG_BASE,                 // 1 << 0: 基础税
+    TAX_FLAG_LUXURY,               // 1 << 1: 奢侈税
+    TAX_FLAG_WEALTH_GAP,           // 1 << 2: 贫富调节税
+    TAX_FLAG_BEHAVIORAL,           // 1 << 3: 行为频率惩罚
 };
\ No newline at end of file
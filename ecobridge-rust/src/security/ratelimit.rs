@@ -0,0 +1,177 @@
+// ==================================================
+// FILE: ecobridge-rust/src/security/ratelimit.rs
+// ==================================================
+
+//! Per-Player Transfer Rate Limiter (令牌桶)
+//!
+//! 在交易频率启发式惩罚之外提供硬性限流：每个玩家一个令牌桶，容量为 `burst`，
+//! 以 `rate_per_min` 个/分钟的速率连续补充，每次转账消耗 1 个令牌，不足时拒绝。
+//!
+//! # 内存回收
+//! 空闲桶在补满后与新建桶等价，因此每隔 `GC_INTERVAL_MS` 顺带清除已补满的桶，
+//! 不改变任何限流结果。
+//!
+//! # 并发
+//! 令牌桶存放在分片哈希表中，不同玩家的转账通常落在不同分片，互不阻塞；
+//! 回收仅由抢到 `LAST_GC_TS` 的单个线程执行。
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+
+/// 空闲桶回收的最小间隔 (ms)
+pub const GC_INTERVAL_MS: i64 = 60_000;
+
+const MS_PER_MIN: f64 = 60_000.0;
+
+struct TokenBucket {
+    tokens: f64,
+    last_ts: i64,
+    /// 按当前速率补满的时间戳 (ms)，此后该桶可被回收
+    full_at: i64,
+}
+
+static LAST_GC_TS: AtomicI64 = AtomicI64::new(i64::MIN);
+
+lazy_static! {
+    /// uuid -> 令牌桶
+    static ref BUCKETS: DashMap<String, TokenBucket> = DashMap::new();
+}
+
+/// 限流参数是否合法：`rate_per_min` 为有限非负数，`burst` 为有限值且 >= 1
+pub fn params_valid(rate_per_min: f64, burst: f64) -> bool {
+    rate_per_min.is_finite() && rate_per_min >= 0.0 && burst.is_finite() && burst >= 1.0
+}
+
+/// 尝试为 `uuid` 获取 1 个令牌
+///
+/// 按距上次访问的时间补充令牌 (时钟回拨时不补充)，成功则扣减并返回 true。
+/// 参数非法时返回 false 且不创建桶。
+pub fn try_acquire(uuid: &str, now: i64, rate_per_min: f64, burst: f64) -> bool {
+    if !params_valid(rate_per_min, burst) {
+        return false;
+    }
+    let rate_per_ms = rate_per_min / MS_PER_MIN;
+
+    // 回收须在持有任何分片引用之前完成，否则 retain 会与本线程持有的分片锁死锁
+    let last_gc = LAST_GC_TS.load(Ordering::Relaxed);
+    if now.saturating_sub(last_gc) >= GC_INTERVAL_MS
+        && LAST_GC_TS.compare_exchange(last_gc, now, Ordering::Relaxed, Ordering::Relaxed).is_ok()
+    {
+        BUCKETS.retain(|_, bucket| now < bucket.full_at);
+    }
+
+    let mut bucket = BUCKETS.entry(uuid.to_string()).or_insert(TokenBucket { tokens: burst, last_ts: now, full_at: now });
+    let elapsed = now.saturating_sub(bucket.last_ts).max(0);
+    bucket.tokens = (bucket.tokens + elapsed as f64 * rate_per_ms).min(burst);
+    bucket.last_ts = bucket.last_ts.max(now);
+
+    let allowed = bucket.tokens >= 1.0;
+    if allowed {
+        bucket.tokens -= 1.0;
+    }
+    bucket.full_at = if rate_per_ms > 0.0 {
+        let refill_ms = ((burst - bucket.tokens) / rate_per_ms).ceil();
+        bucket.last_ts.saturating_add(refill_ms.min(i64::MAX as f64) as i64)
+    } else if bucket.tokens >= burst {
+        bucket.last_ts
+    } else {
+        i64::MAX
+    };
+    allowed
+}
+
+/// 当前驻留的令牌桶数量
+pub fn bucket_count() -> usize {
+    BUCKETS.len()
+}
+
+/// 清空全部令牌桶
+pub fn reset_state() {
+    BUCKETS.clear();
+    LAST_GC_TS.store(i64::MIN, Ordering::Relaxed);
+}
+
+// ==================== 单元测试 ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_exhaustion_and_refill() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 1_000_000;
+
+        // 容量 3：连续 3 次放行，第 4 次拒绝
+        for _ in 0..3 {
+            assert!(try_acquire("alice", now, 6.0, 3.0));
+        }
+        assert!(!try_acquire("alice", now, 6.0, 3.0));
+        // 其他玩家互不影响
+        assert!(try_acquire("bob", now, 6.0, 3.0));
+
+        // 6 个/分钟：20 秒补充 2 个令牌
+        let later = now + 20_000;
+        assert!(try_acquire("alice", later, 6.0, 3.0));
+        assert!(try_acquire("alice", later, 6.0, 3.0));
+        assert!(!try_acquire("alice", later, 6.0, 3.0));
+
+        // 时钟回拨不补充令牌
+        assert!(!try_acquire("alice", now, 6.0, 3.0));
+        // 速率为 0 时不再补充，且未补满的桶不会被回收
+        assert!(try_acquire("dave", now, 0.0, 1.0));
+        assert!(!try_acquire("dave", later + 600_000, 0.0, 1.0));
+
+        assert!(!try_acquire("carol", now, f64::NAN, 3.0));
+        assert!(!try_acquire("carol", now, 6.0, 0.5));
+        reset_state();
+    }
+
+    #[test]
+    fn test_idle_buckets_are_collected() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 1_000_000;
+
+        assert!(try_acquire("idle", now, 60.0, 5.0));
+        assert!(try_acquire("busy", now, 60.0, 5.0));
+        assert_eq!(bucket_count(), 2);
+
+        // 1 秒补 1 个令牌，"idle" 早已补满被回收；"busy" 刚消耗令牌得以保留
+        let later = now + GC_INTERVAL_MS;
+        for _ in 0..5 {
+            assert!(try_acquire("busy", later - 1, 60.0, 5.0));
+        }
+        assert!(!try_acquire("busy", later, 60.0, 5.0));
+        assert_eq!(bucket_count(), 1);
+
+        // 回收后重新访问等价于满桶
+        for _ in 0..5 {
+            assert!(try_acquire("idle", later, 60.0, 5.0));
+        }
+        assert!(!try_acquire("idle", later, 60.0, 5.0));
+        reset_state();
+    }
+
+    #[test]
+    fn test_concurrent_players_keep_independent_budgets() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let now = 1_000_000;
+
+        // 8 个线程各自争抢 4 名玩家的令牌：每名玩家恰好放行 burst 次
+        let allowed: usize = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|t| scope.spawn(move || {
+                    (0..40).filter(|i| try_acquire(&format!("p{}", (t + i) % 4), now, 0.0, 5.0)).count()
+                }))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(allowed, 4 * 5);
+        assert_eq!(bucket_count(), 4);
+        reset_state();
+    }
+}
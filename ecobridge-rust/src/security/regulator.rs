@@ -2,7 +2,10 @@
 // FILE: ecobridge-rust/src/security/regulator.rs
 // ==================================================
 
-use crate::models::{TransferContext, TransferResult, RegulatorConfig};
+use crate::models::{from_micros, to_micros_checked, to_micros_saturating, TransferContext, TransferResult, RegulatorConfig};
+use crate::security::{flow_tracker, freeze};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
 
 // 状态码常量
 pub const CODE_NORMAL: i32 = 0;
@@ -12,127 +15,1144 @@ pub const CODE_BLOCK_INJECTION: i32 = 3;
 pub const CODE_BLOCK_INSUFFICIENT_FUNDS: i32 = 4;
 pub const CODE_BLOCK_VELOCITY_LIMIT: i32 = 5; 
 pub const CODE_BLOCK_QUANTITY_LIMIT: i32 = 6;
+/// 金额或税额超出 i64 Micros 可表示范围，按拦截处理以免回绕成负税
+pub const CODE_OVERFLOW: i32 = 7;
+/// 全服经济冻结期间拦截一切转账
+pub const CODE_FROZEN: i32 = 8;
 
-/// 精度缩放常量 (1.0 = 1,000,000 Micros)
-const MICROS_SCALE: f64 = 1_000_000.0;
+// 税种合成模式 (RegulatorConfig.tax_combination_mode)
+/// 混合模式 (默认/兼容)：奢侈税与基础税累加，贫富调节税与该和取最大值
+pub const TAX_MODE_MIXED: i32 = 0;
+/// 累加模式：基础税 + 奢侈税 + 贫富调节税
+pub const TAX_MODE_ADDITIVE: i32 = 1;
+/// 取最大模式：仅征收三者中最高的一项
+pub const TAX_MODE_MAXIMUM: i32 = 2;
+/// 顺序模式：依次征收基础税、奢侈税、贫富调节税，后一项以扣除前项后的剩余金额为税基
+pub const TAX_MODE_SEQUENTIAL: i32 = 3;
 
-#[inline]
-fn to_micros_saturating(value: f64) -> i64 {
-    if !value.is_finite() {
-        return 0;
+// 已征税种位标记 (TransferResult.applied_tax_flags)
+pub const TAX_FLAG_BASE: i32 = 1 << 0;
+pub const TAX_FLAG_LUXURY: i32 = 1 << 1;
+pub const TAX_FLAG_WEALTH_GAP: i32 = 1 << 2;
+pub const TAX_FLAG_BEHAVIORAL: i32 = 1 << 3;
+
+/// 行为惩罚税指数输入上限默认值：惩罚倍率最高 e^2 ≈ 7.39
+pub const DEFAULT_PENALTY_EXPONENT_CAP: f64 = 2.0;
+
+/// 税费封顶比例默认值 (`RegulatorConfig::max_tax_ratio` 未设置时使用)
+pub const DEFAULT_MAX_TAX_RATIO: f64 = 0.8;
+
+/// 违规注资判定最低金额默认值 (`RegulatorConfig::injection_min_amount` 未设置时使用)：1,000
+pub const DEFAULT_INJECTION_MIN_AMOUNT: i64 = 1_000_000_000;
+
+/// 行为惩罚税指数系数默认值 (`RegulatorConfig::velocity_penalty_coeff` 未设置时使用)
+pub const DEFAULT_VELOCITY_PENALTY_COEFF: f64 = 0.05;
+
+// 行为惩罚指数上限 (以 f64 位模式存储)，防止异常流速使 exp() 溢出并直接顶到税率封顶
+static PENALTY_EXPONENT_CAP_BITS: AtomicU64 = AtomicU64::new(DEFAULT_PENALTY_EXPONENT_CAP.to_bits());
+
+/// 累进税率表最大档数
+pub const MAX_TAX_BRACKETS: usize = 32;
+
+// 累进税率表 (起征点 Micros, 边际税率)，按起征点升序；为空时沿用单一奢侈税阈值
+static TAX_BRACKETS: RwLock<Vec<(i64, f64)>> = RwLock::new(Vec::new());
+
+/// 设置行为惩罚税指数输入上限，须为非负有限值
+pub fn set_penalty_exponent_cap(cap: f64) -> bool {
+    if !cap.is_finite() || cap < 0.0 {
+        return false;
     }
-    let scaled = value * MICROS_SCALE;
-    if scaled >= i64::MAX as f64 {
-        i64::MAX
-    } else if scaled <= i64::MIN as f64 {
-        i64::MIN
-    } else {
-        scaled.round() as i64
+    PENALTY_EXPONENT_CAP_BITS.store(cap.to_bits(), Ordering::Relaxed);
+    true
+}
+
+/// 设置累进税率表，传入空表即恢复单一奢侈税阈值
+///
+/// 起征点须非负且严格递增，边际税率须位于 [0, 1]，档数不超过 `MAX_TAX_BRACKETS`；
+/// 非法税率表返回 false 且不修改当前配置。
+pub fn set_tax_brackets(brackets: &[(i64, f64)]) -> bool {
+    let ascending = brackets.windows(2).all(|w| w[0].0 < w[1].0);
+    let valid = brackets.len() <= MAX_TAX_BRACKETS
+        && ascending
+        && brackets.iter().all(|&(threshold, rate)| threshold >= 0 && (0.0..=1.0).contains(&rate));
+    if !valid {
+        return false;
+    }
+    *TAX_BRACKETS.write().unwrap_or_else(|e| e.into_inner()) = brackets.to_vec();
+    true
+}
+
+/// 累进税：金额落在第 i 档 [起征点_i, 起征点_{i+1}) 内的部分按该档边际税率计税
+///
+/// `brackets` 须按起征点升序；低于首档起征点的部分免税。返回税额 Micros (四舍五入，饱和至 i64)。
+pub fn progressive_tax(amount_micros: i64, brackets: &[(i64, f64)]) -> i64 {
+    let mut tax = 0.0;
+    for (i, &(threshold, rate)) in brackets.iter().enumerate() {
+        if amount_micros <= threshold {
+            break;
+        }
+        let upper = brackets.get(i + 1).map_or(amount_micros, |next| next.0.min(amount_micros));
+        tax += (upper - threshold) as f64 * rate;
     }
+    tax.round() as i64
+}
+
+/// 恢复默认参数
+pub fn reset_state() {
+    PENALTY_EXPONENT_CAP_BITS.store(DEFAULT_PENALTY_EXPONENT_CAP.to_bits(), Ordering::Relaxed);
+    TAX_BRACKETS.write().unwrap_or_else(|e| e.into_inner()).clear();
 }
 
 /// 增强型交易审计逻辑 (v1.6.0 - Precision Hardened)
 /// 
 /// 该版本已全面适配 i64 Micros 定点数协议，彻底解决 IEEE 754 累积误差。
+/// 被拦截的交易同样填充 `applied_tax_flags`，表示若放行将会征收的税种，便于审计日志记录。
+/// 金额或税额无法以 Micros 表示时以 `CODE_OVERFLOW` 拦截；全服冻结时以 `CODE_FROZEN` 拦截。
 pub fn compute_transfer_check_internal(
     ctx: &TransferContext,
     cfg: &RegulatorConfig,
 ) -> TransferResult {
-    // 1. 基础数据转换 (Micros i64 -> f64 用于数学运算)
-    let amount_f64 = (ctx.amount_micros as f64) / MICROS_SCALE;
-    let sender_bal_f64 = (ctx.sender_balance as f64) / MICROS_SCALE;
-    let receiver_bal_f64 = (ctx.receiver_balance as f64) / MICROS_SCALE;
-    
-    // ============================================================
-    // 1. 动态数量限额演算 (平方根递减模型)
-    // ============================================================
-    let play_hours = (ctx.sender_play_time as f64) / 3600.0;
-    
-    // 参数缩放：i64 Micros -> f64
-    let base_limit = (ctx.item_base_limit as f64) / MICROS_SCALE;
-    let max_limit = (ctx.item_max_limit as f64) / MICROS_SCALE;
-    let growth_rate = ctx.item_growth_rate; // 系数保持 f64
+    transfer_check(ctx, cfg, freeze::is_frozen())
+}
 
-    let calculated_limit = base_limit + (growth_rate * play_hours.sqrt());
-    let final_limit = calculated_limit.min(max_limit);
+/// 交易审计主体，冻结状态由调用方传入
+fn transfer_check(ctx: &TransferContext, cfg: &RegulatorConfig, frozen: bool) -> TransferResult {
+    // 税收演算不依赖拦截结果，预先计算以便拦截时仍可上报税种标记
+    let tax = compute_adaptive_tax(ctx, cfg);
 
-    // 拦截判定：比较原始 i64 Micros 以确保绝对精确
-    let final_limit_micros = to_micros_saturating(final_limit);
-    if ctx.amount_micros > final_limit_micros && final_limit_micros > 0 {
+    // 0. 全服冻结优先于其余拦截判定 (含数值溢出)，与 `block_code` 一致
+    if frozen {
+        return frozen_result(tax.map_or(0, |(_, flags)| flags));
+    }
+    let (tax_micros, tax_flags) = match tax {
+        Some(tax) => tax,
+        None => return overflow_result(),
+    };
+
+    // 1-2. 拦截判定 (数量限额、行为速率)
+    let assessment = assess_blocks(ctx, cfg);
+    if assessment.block_code != CODE_NORMAL {
         return TransferResult {
             final_tax_micros: 0,
             is_blocked: 1,
-            warning_code: CODE_BLOCK_QUANTITY_LIMIT,
+            warning_code: assessment.block_code,
+            applied_tax_flags: tax_flags,
+            _padding: 0,
+            limit_headroom_micros: 0,
         };
     }
 
     // ============================================================
-    // 2. 行为速率审计 (Behavioral Velocity Audit)
+    // 3. 动态风险评估 (Warning Code)
     // ============================================================
-    let puppet_factor = if ctx.sender_activity_score < 0.1 {
-        ctx.sender_velocity * 2.0 
+    let mut warning_code = CODE_NORMAL;
+    
+    if ctx.amount_micros > (assessment.final_limit_micros * 85 / 100)
+        || assessment.puppet_factor > (cfg.velocity_threshold * 0.7) {
+        warning_code = CODE_WARNING_HIGH_RISK;
+    }
+
+    // 剩余限额：未设限额 (<= 0) 时视为无上限
+    let limit_headroom_micros = if assessment.final_limit_micros > 0 {
+        assessment.final_limit_micros.saturating_sub(ctx.amount_micros)
     } else {
-        ctx.sender_velocity / ctx.sender_activity_score.max(0.1)
+        i64::MAX
     };
 
-    if puppet_factor > cfg.velocity_threshold {
+    TransferResult {
+        final_tax_micros: tax_micros,
+        is_blocked: 0,
+        warning_code,
+        applied_tax_flags: tax_flags,
+        _padding: 0,
+        limit_headroom_micros,
+    }
+}
+
+/// 税费预览：返回常规审计的 `final_tax_micros` (被拦截时为 0)
+///
+/// 纯函数，不读写流转记录等任何全局状态，可供 UI 滑块等场景反复调用。
+pub fn preview_transfer_tax(ctx: &TransferContext, cfg: &RegulatorConfig) -> i64 {
+    compute_transfer_check_internal(ctx, cfg).final_tax_micros
+}
+
+/// 购物车合并计税：同一玩家一次结账的多件商品按总额统一计税并给出单一放行/拦截结论
+///
+/// - 拦截与预警逐行判定 (各物品的数量限额不同)：上下文不携带物品标识，同一物品须由调用方合并为一行；
+///   任一物品被拦截则整单以其拦截码拦截，任一物品预警则整单预警，剩余限额取各物品最小值。
+///   余额不足与违规注资按整单合计金额判定，拆成多个小额行无法绕过。
+/// - 税额按合并上下文计算：金额为各件之和 (饱和加法)，奢侈税与贫富调节税阈值作用于总额；
+///   余额、游戏时长、活跃度与通胀率取自首件 (同一结账双方)；
+///   行为速率取各件最大值而非求和——一次结账是单次行为，按最激进的一件施加频率惩罚。
+/// - 空购物车放行且税额为 0 (全服冻结时仍以 `CODE_FROZEN` 拦截)；合并税额无法以 Micros 表示时以 `CODE_OVERFLOW` 拦截。
+pub fn compute_cart_tax(items: &[TransferContext], cfg: &RegulatorConfig) -> TransferResult {
+    cart_tax(items, cfg, freeze::is_frozen())
+}
+
+fn cart_tax(items: &[TransferContext], cfg: &RegulatorConfig, frozen: bool) -> TransferResult {
+    let first = match items.first() {
+        Some(f) => f,
+        None if frozen => return frozen_result(0),
+        None => {
+            return TransferResult { limit_headroom_micros: i64::MAX, ..Default::default() };
+        }
+    };
+
+    let mut combined = *first;
+    combined.amount_micros = items.iter().fold(0i64, |acc, it| acc.saturating_add(it.amount_micros));
+    combined.sender_velocity = items.iter().map(|it| it.sender_velocity).fold(f64::NEG_INFINITY, f64::max);
+    let tax = compute_adaptive_tax(&combined, cfg);
+    if frozen {
+        return frozen_result(tax.map_or(0, |(_, flags)| flags));
+    }
+    let (tax_micros, tax_flags) = match tax {
+        Some(tax) => tax,
+        None => return overflow_result(),
+    };
+
+    let mut warning_code = CODE_NORMAL;
+    let mut limit_headroom_micros = i64::MAX;
+    for line in items {
+        let res = transfer_check(line, cfg, frozen);
+        if res.is_blocked != 0 {
+            return TransferResult { applied_tax_flags: tax_flags, ..res };
+        }
+        warning_code = warning_code.max(res.warning_code);
+        limit_headroom_micros = limit_headroom_micros.min(res.limit_headroom_micros);
+    }
+    // 各行均放行时，仍需以合计金额核对余额与注资规则
+    let combined_block = if combined.amount_micros > combined.sender_balance {
+        CODE_BLOCK_INSUFFICIENT_FUNDS
+    } else if is_injection(&combined, cfg) {
+        CODE_BLOCK_INJECTION
+    } else {
+        CODE_NORMAL
+    };
+    if combined_block != CODE_NORMAL {
         return TransferResult {
             final_tax_micros: 0,
             is_blocked: 1,
-            warning_code: CODE_BLOCK_VELOCITY_LIMIT,
+            warning_code: combined_block,
+            applied_tax_flags: tax_flags,
+            _padding: 0,
+            limit_headroom_micros: 0,
         };
     }
 
+    TransferResult {
+        final_tax_micros: tax_micros,
+        is_blocked: 0,
+        warning_code,
+        applied_tax_flags: tax_flags,
+        _padding: 0,
+        limit_headroom_micros,
+    }
+}
+
+/// 带账户身份的交易审计：在常规审计之外执行逆向流转 (乒乓洗钱) 检测
+///
+/// 常规审计放行后，若低活跃度的 `sender` 在检测窗口内把资金回转给刚向其转账的 `receiver`，
+/// 则以 `CODE_BLOCK_REVERSE_FLOW` 拦截；最终放行的转账会被记录，供后续检测使用。
+pub fn compute_transfer_check_tracked(
+    ctx: &TransferContext,
+    cfg: &RegulatorConfig,
+    sender: &str,
+    receiver: &str,
+    now_ms: i64,
+) -> TransferResult {
+    let mut result = compute_transfer_check_internal(ctx, cfg);
+    if result.is_blocked != 0 {
+        return result;
+    }
+
+    if flow_tracker::is_reverse_flow(sender, receiver, now_ms, ctx.sender_activity_score) {
+        result.final_tax_micros = 0;
+        result.is_blocked = 1;
+        result.warning_code = CODE_BLOCK_REVERSE_FLOW;
+        result.limit_headroom_micros = 0;
+        return result;
+    }
+
+    flow_tracker::record_transfer(sender, receiver, now_ms);
+    result
+}
+
+/// 动态数量限额 (平方根模型)：`base + rate · sqrt(游戏小时数)`，以 `max` 封顶
+///
+/// 风控审计与 `ecobridge_get_dynamic_limit` 共用此公式，保证两者结果一致。
+pub fn dynamic_quantity_limit(play_time_secs: i64, base: f64, rate: f64, max: f64) -> f64 {
+    let play_hours = (play_time_secs as f64) / 3600.0;
+    (base + rate * play_hours.sqrt()).min(max)
+}
+
+/// 仅执行拦截判定，不进行税收演算 (供 UI 预校验使用)
+///
+/// 返回首个命中的拦截码 (`CODE_BLOCK_*`)，全服冻结时返回 `CODE_FROZEN`，金额溢出时返回 `CODE_OVERFLOW`，放行时返回 `CODE_NORMAL`；
+/// 与 `compute_transfer_check_internal` 的拦截结果保持一致。
+pub fn check_block_code(ctx: &TransferContext, cfg: &RegulatorConfig) -> i32 {
+    block_code(ctx, cfg, freeze::is_frozen())
+}
+
+/// 带账户身份的拦截预校验：在 `check_block_code` 的基础上执行逆向流转检测
+///
+/// 与 `compute_transfer_check_tracked` 的拦截结果保持一致，但不记录流转，可反复调用。
+pub fn check_block_code_tracked(
+    ctx: &TransferContext,
+    cfg: &RegulatorConfig,
+    sender: &str,
+    receiver: &str,
+    now_ms: i64,
+) -> i32 {
+    match check_block_code(ctx, cfg) {
+        CODE_NORMAL if flow_tracker::is_reverse_flow(sender, receiver, now_ms, ctx.sender_activity_score) => {
+            CODE_BLOCK_REVERSE_FLOW
+        }
+        code => code,
+    }
+}
+
+/// 拦截预校验主体，冻结状态由调用方传入
+fn block_code(ctx: &TransferContext, cfg: &RegulatorConfig, frozen: bool) -> i32 {
+    if frozen {
+        return CODE_FROZEN;
+    }
+    assess_blocks(ctx, cfg).block_code
+}
+
+/// 拦截判定的中间结果，供风险预警复用
+struct BlockAssessment {
+    final_limit_micros: i64,
+    puppet_factor: f64,
+    block_code: i32,
+}
+
+fn assess_blocks(ctx: &TransferContext, cfg: &RegulatorConfig) -> BlockAssessment {
+    // 0. 金额无法以 Micros 表示时优先拦截，预校验与完整审计给出相同结论
+    if !is_amount_representable(ctx) {
+        return BlockAssessment { final_limit_micros: 0, puppet_factor: 0.0, block_code: CODE_OVERFLOW };
+    }
+
     // ============================================================
-    // 3. 动态风险评估 (Warning Code)
+    // 1. 动态数量限额演算 (平方根递减模型)
     // ============================================================
-    let mut warning_code = CODE_NORMAL;
-    
-    if ctx.amount_micros > (final_limit_micros * 85 / 100) || puppet_factor > (cfg.velocity_threshold * 0.7) {
-        warning_code = CODE_WARNING_HIGH_RISK;
-    }
+    // 参数缩放：i64 Micros -> f64 (增长系数保持 f64)
+    let final_limit = dynamic_quantity_limit(
+        ctx.sender_play_time,
+        from_micros(ctx.item_base_limit),
+        ctx.item_growth_rate,
+        from_micros(ctx.item_max_limit),
+    );
 
     // ============================================================
-    // 4. 自适应税收计算 (Adaptive Behavioral Tax)
+    // 2. 行为速率审计 (Behavioral Velocity Audit)
     // ============================================================
+    let puppet_factor = if ctx.sender_activity_score < 0.1 {
+        ctx.sender_velocity * 2.0 
+    } else {
+        ctx.sender_velocity / ctx.sender_activity_score.max(0.1)
+    };
+
+    // 拦截判定：比较原始 i64 Micros 以确保绝对精确
+    let final_limit_micros = to_micros_saturating(final_limit);
+    let over_limit = if cfg.exclusive_limit != 0 {
+        ctx.amount_micros >= final_limit_micros
+    } else {
+        ctx.amount_micros > final_limit_micros
+    };
+    // 余额不足优先于其余拦截规则：转出金额超过发送者余额的交易必然无法完成
+    let block_code = if ctx.amount_micros > ctx.sender_balance {
+        CODE_BLOCK_INSUFFICIENT_FUNDS
+    } else if over_limit && final_limit_micros > 0 {
+        CODE_BLOCK_QUANTITY_LIMIT
+    } else if puppet_factor > cfg.velocity_threshold {
+        CODE_BLOCK_VELOCITY_LIMIT
+    } else if is_injection(ctx, cfg) {
+        CODE_BLOCK_INJECTION
+    } else {
+        CODE_NORMAL
+    };
+
+    BlockAssessment { final_limit_micros, puppet_factor, block_code }
+}
+
+/// 接近 i64 边界的金额转为 f64 后会舍入到 2^63 之外，其税额与限额比较同样不可信
+fn is_amount_representable(ctx: &TransferContext) -> bool {
+    to_micros_checked(from_micros(ctx.amount_micros)).is_some()
+}
+
+/// 违规注资判定 (RMT / 代练扶持特征)
+///
+/// 老手 (游戏时长 > `veteran_hours`) 向新手 (游戏时长 < `newbie_hours`) 转账，
+/// 金额不低于 `injection_min_amount` 且超过接收者余额的 `injection_balance_multiple` 倍时命中。
+/// 最低金额避免零余额新手收到任何小额赠予都被判定为注资。
+fn is_injection(ctx: &TransferContext, cfg: &RegulatorConfig) -> bool {
+    if !cfg.injection_balance_multiple.is_finite() || cfg.injection_balance_multiple <= 0.0 {
+        return false;
+    }
+    if ctx.amount_micros < effective_injection_min_amount(cfg) {
+        return false;
+    }
+    let sender_hours = (ctx.sender_play_time as f64) / 3600.0;
+    let receiver_hours = (ctx.receiver_play_time as f64) / 3600.0;
+    if sender_hours <= cfg.veteran_hours || receiver_hours >= cfg.newbie_hours {
+        return false;
+    }
+    let allowed = from_micros(ctx.receiver_balance.max(0)) * cfg.injection_balance_multiple;
+    from_micros(ctx.amount_micros) > allowed
+}
+
+/// 自适应税收计算 (Adaptive Behavioral Tax)
+///
+/// 返回 (税额 Micros, 实际生效的税种位标记 `TAX_FLAG_*`)。
+/// 在取最大/混合模式下，仅标记最终胜出并计入税额的税种。
+/// 金额经 f64 往返后或税额超出 i64 Micros 范围时返回 None。
+/// 已设置累进税率表时以累进税替代单一阈值的奢侈税 (仍以 `TAX_FLAG_LUXURY` 标记)。
+fn compute_adaptive_tax(ctx: &TransferContext, cfg: &RegulatorConfig) -> Option<(i64, i32)> {
+    let brackets = TAX_BRACKETS.read().unwrap_or_else(|e| e.into_inner());
+    adaptive_tax(ctx, cfg, &brackets)
+}
+
+/// 自适应税收计算主体，累进税率表由调用方传入 (空表 = 单一奢侈税阈值)
+fn adaptive_tax(ctx: &TransferContext, cfg: &RegulatorConfig, brackets: &[(i64, f64)]) -> Option<(i64, i32)> {
+    // 基础数据转换 (Micros i64 -> f64 用于数学运算)
+    if !is_amount_representable(ctx) {
+        return None;
+    }
+    let amount_f64 = from_micros(ctx.amount_micros);
+    let sender_bal_f64 = from_micros(ctx.sender_balance);
+    let receiver_bal_f64 = from_micros(ctx.receiver_balance);
+
     let inflation_adj = 1.0 + ctx.inflation_rate.max(0.0);
     
     // 基础税 + 通胀调节 (基于 f64 运算)
-    let mut tax_f64 = amount_f64 * cfg.base_tax_rate * inflation_adj;
+    let mut base_tax = amount_f64 * cfg.base_tax_rate * inflation_adj;
 
-    // 惩罚性频率税：指数增长惩罚
-    let behavioral_penalty = (ctx.sender_velocity * 0.05).exp(); 
-    tax_f64 *= behavioral_penalty;
+    // 惩罚性频率税：指数增长惩罚 (指数输入封顶，倍率至多 e^cap；NaN 经 min 后取上限)
+    let exponent_cap = f64::from_bits(PENALTY_EXPONENT_CAP_BITS.load(Ordering::Relaxed));
+    let behavioral_penalty = (ctx.sender_velocity * effective_velocity_penalty_coeff(cfg)).min(exponent_cap).exp();
+    base_tax *= behavioral_penalty;
 
-    // 奢侈税叠加 (i64 Micros -> f64 转换计算)
-    let luxury_threshold_f64 = (cfg.luxury_threshold as f64) / MICROS_SCALE;
-    if amount_f64 > luxury_threshold_f64 {
-        let excess = amount_f64 - luxury_threshold_f64;
-        tax_f64 = excess.mul_add(cfg.luxury_tax_rate, tax_f64);
+    let mut base_flags = 0;
+    if base_tax > 0.0 {
+        base_flags |= TAX_FLAG_BASE;
+        if behavioral_penalty > 1.0 {
+            base_flags |= TAX_FLAG_BEHAVIORAL;
+        }
     }
 
-    // 贫富调节税
-    let poor_threshold_f64 = (cfg.poor_threshold as f64) / MICROS_SCALE;
-    let rich_threshold_f64 = (cfg.rich_threshold as f64) / MICROS_SCALE;
-    if sender_bal_f64 < poor_threshold_f64 && receiver_bal_f64 > rich_threshold_f64 {
-        let gap_tax = amount_f64 * cfg.wealth_gap_tax_rate;
-        tax_f64 = tax_f64.max(gap_tax);
-    }
+    // 奢侈税与贫富调节税 (i64 Micros -> f64 转换计算)，按合成模式与基础税组合
+    let luxury_threshold_f64 = from_micros(cfg.luxury_threshold);
+    let poor_threshold_f64 = from_micros(cfg.poor_threshold);
+    let rich_threshold_f64 = from_micros(cfg.rich_threshold);
+    let gap_applies = sender_bal_f64 < poor_threshold_f64 && receiver_bal_f64 > rich_threshold_f64;
+
+    let luxury_tax = |taxable: f64| -> f64 {
+        if !brackets.is_empty() {
+            from_micros(progressive_tax(to_micros_saturating(taxable), brackets))
+        } else if taxable > luxury_threshold_f64 {
+            (taxable - luxury_threshold_f64) * cfg.luxury_tax_rate
+        } else {
+            0.0
+        }
+    };
+    let gap_tax = |taxable: f64| -> f64 {
+        if gap_applies { taxable * cfg.wealth_gap_tax_rate } else { 0.0 }
+    };
+    let flag_if = |value: f64, flag: i32| if value > 0.0 { flag } else { 0 };
+
+    let (tax_f64, flags) = match cfg.tax_combination_mode {
+        TAX_MODE_ADDITIVE => {
+            let (lux, gap) = (luxury_tax(amount_f64), gap_tax(amount_f64));
+            (base_tax + lux + gap, base_flags | flag_if(lux, TAX_FLAG_LUXURY) | flag_if(gap, TAX_FLAG_WEALTH_GAP))
+        }
+        TAX_MODE_MAXIMUM => {
+            let (lux, gap) = (luxury_tax(amount_f64), gap_tax(amount_f64));
+            if gap > base_tax && gap > lux {
+                (gap, TAX_FLAG_WEALTH_GAP)
+            } else if lux > base_tax {
+                (lux, TAX_FLAG_LUXURY)
+            } else {
+                (base_tax, base_flags)
+            }
+        }
+        TAX_MODE_SEQUENTIAL => {
+            let after_base = amount_f64 - base_tax;
+            let lux = luxury_tax(after_base);
+            let gap = gap_tax(after_base - lux);
+            (base_tax + lux + gap, base_flags | flag_if(lux, TAX_FLAG_LUXURY) | flag_if(gap, TAX_FLAG_WEALTH_GAP))
+        }
+        // TAX_MODE_MIXED 及未知取值：保持历史行为
+        _ => {
+            let lux = luxury_tax(amount_f64);
+            let mixed = base_tax + lux;
+            let gap = gap_tax(amount_f64);
+            if gap_applies && gap > mixed {
+                (gap, TAX_FLAG_WEALTH_GAP)
+            } else {
+                (mixed, base_flags | flag_if(lux, TAX_FLAG_LUXURY))
+            }
+        }
+    };
 
-    // 税收封顶修正 (80%)
-    let tax_clamped = tax_f64.min(amount_f64 * 0.8);
+    // 税收封顶修正 (默认 80%)
+    let tax_clamped = tax_f64.min(amount_f64 * effective_max_tax_ratio(cfg));
 
+    // 结果转换回 i64 Micros 传回 Java (检查溢出，避免饱和/回绕后的税额被当作正常结果)
+    Some((to_micros_checked(tax_clamped)?, flags))
+}
+
+/// 全服冻结时的拦截结果：保留税种标记以便审计日志记录 (税额不可表示时标记为 0)
+fn frozen_result(tax_flags: i32) -> TransferResult {
     TransferResult {
-        // 结果转换回 i64 Micros 传回 Java
-        final_tax_micros: to_micros_saturating(tax_clamped),
-        is_blocked: 0,
-        warning_code,
+        final_tax_micros: 0,
+        is_blocked: 1,
+        warning_code: CODE_FROZEN,
+        applied_tax_flags: tax_flags,
+        _padding: 0,
+        limit_headroom_micros: 0,
     }
 }
 
+/// 数值溢出时的拦截结果：金额或税额无法以 Micros 表示，不上报税种标记
+fn overflow_result() -> TransferResult {
+    TransferResult {
+        final_tax_micros: 0,
+        is_blocked: 1,
+        warning_code: CODE_OVERFLOW,
+        applied_tax_flags: 0,
+        _padding: 0,
+        limit_headroom_micros: 0,
+    }
+}
+
+/// 生效的税费封顶比例：未设置 (<= 0) 或非有限值时回退到默认值，超过 1 时按 100% 封顶
+fn effective_max_tax_ratio(cfg: &RegulatorConfig) -> f64 {
+    if cfg.max_tax_ratio.is_finite() && cfg.max_tax_ratio > 0.0 {
+        cfg.max_tax_ratio.min(1.0)
+    } else {
+        DEFAULT_MAX_TAX_RATIO
+    }
+}
+
+/// 生效的违规注资最低金额：未设置 (<= 0) 时回退到默认值
+fn effective_injection_min_amount(cfg: &RegulatorConfig) -> i64 {
+    if cfg.injection_min_amount > 0 {
+        cfg.injection_min_amount
+    } else {
+        DEFAULT_INJECTION_MIN_AMOUNT
+    }
+}
+
+/// 生效的行为惩罚指数系数：未设置 (<= 0) 或非有限值时回退到默认值
+fn effective_velocity_penalty_coeff(cfg: &RegulatorConfig) -> f64 {
+    if cfg.velocity_penalty_coeff.is_finite() && cfg.velocity_penalty_coeff > 0.0 {
+        cfg.velocity_penalty_coeff
+    } else {
+        DEFAULT_VELOCITY_PENALTY_COEFF
+    }
+}
+
+/// 税负归宿模拟：对一组 (发送方余额, 转账金额) 逐笔计算税额，返回 (总税额 Micros, 平均有效税率)
+///
+/// 每笔按仅含余额与金额的上下文计税 (无通胀、无行为惩罚、接收方余额为 0)，不经过拦截判定。
+/// 平均有效税率为各笔 `税额 / 金额` 的算术平均；金额 <= 0 或税额溢出的样本不计入。无有效样本时返回 (0, 0.0)。
+pub fn simulate_tax_incidence(cfg: &RegulatorConfig, balances: &[i64], amounts: &[i64]) -> (i64, f64) {
+    let mut total_tax: i64 = 0;
+    let mut rate_sum = 0.0;
+    let mut samples = 0usize;
+    for (&balance, &amount) in balances.iter().zip(amounts) {
+        if amount <= 0 {
+            continue;
+        }
+        let ctx = TransferContext {
+            amount_micros: amount,
+            sender_balance: balance,
+            ..Default::default()
+        };
+        let Some((tax, _)) = compute_adaptive_tax(&ctx, cfg) else {
+            continue;
+        };
+        total_tax = total_tax.saturating_add(tax);
+        rate_sum += tax as f64 / amount as f64;
+        samples += 1;
+    }
+    if samples == 0 {
+        return (0, 0.0);
+    }
+    (total_tax, rate_sum / samples as f64)
+}
+
 /// 判断演算结果是否属于高风险或拦截交易
 pub fn is_high_risk_transfer(result: &crate::models::TransferResult) -> bool {
     result.is_blocked == 1 
     || result.warning_code == CODE_WARNING_HIGH_RISK 
     || result.warning_code == CODE_BLOCK_QUANTITY_LIMIT
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MICROS: i64 = 1_000_000;
+    /// 足以支付各用例转账金额的发送者余额 (高于贫困线，不触发贫富调节税)
+    const BALANCE: i64 = 1_000_000 * MICROS;
+
+    #[test]
+    fn test_tax_combination_modes() {
+        // 同时触发奢侈税与贫富调节税：
+        // base = 200k * 5% = 10k, luxury = (200k - 100k) * 10% = 10k, gap = 200k * 8% = 16k
+        // 贫困线上调至 500k，使余额足以支付本笔转账的发送者仍适用贫富调节税
+        let ctx = TransferContext {
+            amount_micros: 200_000 * MICROS,
+            sender_balance: 300_000 * MICROS,
+            receiver_balance: 2_000_000 * MICROS,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        let tax_for = |mode: i32| -> i64 {
+            let cfg = RegulatorConfig {
+                wealth_gap_tax_rate: 0.08,
+                poor_threshold: 500_000 * MICROS,
+                tax_combination_mode: mode,
+                ..Default::default()
+            };
+            let res = compute_transfer_check_internal(&ctx, &cfg);
+            assert_eq!(res.is_blocked, 0);
+            res.final_tax_micros
+        };
+
+        assert_eq!(tax_for(TAX_MODE_MIXED), 20_000 * MICROS);
+        assert_eq!(tax_for(TAX_MODE_ADDITIVE), 36_000 * MICROS);
+        assert_eq!(tax_for(TAX_MODE_MAXIMUM), 16_000 * MICROS);
+        // 顺序: 10k + (190k - 100k) * 10% + (190k - 9k) * 8% = 33.48k
+        assert_eq!(tax_for(TAX_MODE_SEQUENTIAL), 33_480 * MICROS);
+    }
+
+    #[test]
+    fn test_progressive_tax_brackets() {
+        // 10k 以上 10%，50k 以上 20%，200k 以上 30%
+        let brackets = [(10_000 * MICROS, 0.1), (50_000 * MICROS, 0.2), (200_000 * MICROS, 0.3)];
+
+        assert_eq!(progressive_tax(0, &brackets), 0);
+        assert_eq!(progressive_tax(10_000 * MICROS, &brackets), 0);
+        assert_eq!(progressive_tax(50_000 * MICROS, &brackets), 4_000 * MICROS);
+        assert_eq!(progressive_tax(200_000 * MICROS, &brackets), 34_000 * MICROS);
+        assert_eq!(progressive_tax(300_000 * MICROS, &brackets), 64_000 * MICROS);
+        assert_eq!(progressive_tax(-5 * MICROS, &brackets), 0);
+        assert_eq!(progressive_tax(300_000 * MICROS, &[]), 0);
+
+        // 边界两侧的边际税率
+        assert_eq!(progressive_tax(10_000 * MICROS + 10, &brackets), 1);
+        assert_eq!(progressive_tax(50_000 * MICROS + 10, &brackets) - progressive_tax(50_000 * MICROS, &brackets), 2);
+        assert_eq!(progressive_tax(200_000 * MICROS + 10, &brackets) - progressive_tax(200_000 * MICROS, &brackets), 3);
+
+        // 设置税率表后累进税替代奢侈税：基础税 10k + 累进 34k
+        let ctx = TransferContext { amount_micros: 200_000 * MICROS, ..Default::default() };
+        let cfg = RegulatorConfig::default();
+        assert_eq!(adaptive_tax(&ctx, &cfg, &[]), Some((20_000 * MICROS, TAX_FLAG_BASE | TAX_FLAG_LUXURY)));
+        assert_eq!(adaptive_tax(&ctx, &cfg, &brackets), Some((44_000 * MICROS, TAX_FLAG_BASE | TAX_FLAG_LUXURY)));
+        let below = TransferContext { amount_micros: 10_000 * MICROS, ..ctx };
+        assert_eq!(adaptive_tax(&below, &cfg, &brackets), Some((500 * MICROS, TAX_FLAG_BASE)));
+
+        // 非法税率表被拒绝 (不修改全局配置，避免影响并行执行的其他审计用例)
+        assert!(!set_tax_brackets(&[(50 * MICROS, 0.1), (10 * MICROS, 0.2)]));
+        assert!(!set_tax_brackets(&[(10 * MICROS, 0.1), (10 * MICROS, 0.2)]));
+        assert!(!set_tax_brackets(&[(-1, 0.1)]));
+        assert!(!set_tax_brackets(&[(0, 1.5)]));
+        assert!(!set_tax_brackets(&[(0, f64::NAN)]));
+    }
+
+    #[test]
+    fn test_simulate_tax_incidence() {
+        let cfg = RegulatorConfig::default();
+        // 5% 基础税；超出 100k 部分另征 10% 奢侈税；金额 <= 0 的样本被忽略
+        let balances = [1_000 * MICROS, 1_000 * MICROS, 500_000 * MICROS, 0];
+        let amounts = [1_000 * MICROS, 200_000 * MICROS, 100 * MICROS, 0];
+
+        let (total, avg_rate) = simulate_tax_incidence(&cfg, &balances, &amounts);
+        // 50 + (10k + 10k) + 5
+        assert_eq!(total, 20_055 * MICROS);
+        // (5% + 10% + 5%) / 3
+        assert!((avg_rate - 0.2 / 3.0).abs() < 1e-12, "avg_rate={}", avg_rate);
+
+        assert_eq!(simulate_tax_incidence(&cfg, &[], &[]), (0, 0.0));
+    }
+
+    #[test]
+    fn test_cart_tax_applies_thresholds_to_total() {
+        let cfg = RegulatorConfig::default();
+        let item = TransferContext { amount_micros: 60_000 * MICROS, sender_balance: BALANCE, sender_activity_score: 1.0, ..Default::default() };
+        let cart = [item; 3];
+
+        // 逐件计税均低于奢侈税阈值：3 × 5% × 60k
+        let per_item: i64 = cart.iter().map(|c| compute_transfer_check_internal(c, &cfg).final_tax_micros).sum();
+        assert_eq!(per_item, 9_000 * MICROS);
+
+        // 合并后 180k 超出 100k 阈值：5% × 180k + 10% × 80k
+        let res = compute_cart_tax(&cart, &cfg);
+        assert_eq!(res.is_blocked, 0);
+        assert_eq!(res.final_tax_micros, 17_000 * MICROS);
+        assert_eq!(res.applied_tax_flags, TAX_FLAG_BASE | TAX_FLAG_LUXURY);
+
+        // 任一件触发拦截则整单拦截
+        let mut blocked = cart;
+        blocked[1].sender_velocity = 1e6;
+        let res = compute_cart_tax(&blocked, &cfg);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.final_tax_micros, 0);
+        assert_eq!(res.warning_code, CODE_BLOCK_VELOCITY_LIMIT);
+
+        let empty = compute_cart_tax(&[], &cfg);
+        assert_eq!((empty.is_blocked, empty.final_tax_micros), (0, 0));
+    }
+
+    #[test]
+    fn test_cart_checks_each_line_and_combined_amount() {
+        let cfg = RegulatorConfig::default();
+        // 限额参数相同的两行不再视为同一物品：逐行判定，各 60 均未超出限额 100
+        let line = TransferContext {
+            amount_micros: 60 * MICROS,
+            sender_balance: BALANCE,
+            item_base_limit: 100 * MICROS,
+            item_max_limit: 100 * MICROS,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(check_block_code(&line, &cfg), CODE_NORMAL);
+        let res = compute_cart_tax(&[line, line], &cfg);
+        assert_eq!(res.is_blocked, 0);
+        assert_eq!(res.limit_headroom_micros, 40 * MICROS);
+
+        // 任一行超限则整单拦截
+        let over = TransferContext { amount_micros: 120 * MICROS, ..line };
+        let res = compute_cart_tax(&[line, over], &cfg);
+        assert_eq!((res.is_blocked, res.warning_code), (1, CODE_BLOCK_QUANTITY_LIMIT));
+
+        // 老手向零余额新手拆成两笔 600：单笔低于注资最低金额，合计 1,200 拦截
+        let gift = TransferContext {
+            amount_micros: 600 * MICROS,
+            sender_balance: BALANCE,
+            receiver_balance: 0,
+            sender_play_time: 500 * 3600,
+            receiver_play_time: 2 * 3600,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(check_block_code(&gift, &cfg), CODE_NORMAL);
+        let other_item = TransferContext { item_base_limit: 1_000 * MICROS, item_max_limit: 1_000 * MICROS, ..gift };
+        let res = compute_cart_tax(&[gift, other_item], &cfg);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.warning_code, CODE_BLOCK_INJECTION);
+        assert_eq!(res.final_tax_micros, 0);
+        assert_ne!(res.applied_tax_flags, 0);
+    }
+
+    #[test]
+    fn test_insufficient_funds_blocks_transfer() {
+        let cfg = RegulatorConfig::default();
+        let exact = TransferContext {
+            amount_micros: 500 * MICROS,
+            sender_balance: 500 * MICROS,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        // 恰好等于余额放行
+        assert_eq!(check_block_code(&exact, &cfg), CODE_NORMAL);
+        assert_eq!(transfer_check(&exact, &cfg, false).is_blocked, 0);
+
+        // 超出余额 1 Micro 即拦截，预校验与完整审计一致
+        let short = TransferContext { amount_micros: 500 * MICROS + 1, ..exact };
+        let res = transfer_check(&short, &cfg, false);
+        assert_eq!((res.is_blocked, res.warning_code, res.final_tax_micros), (1, CODE_BLOCK_INSUFFICIENT_FUNDS, 0));
+        assert_eq!(block_code(&short, &cfg, false), CODE_BLOCK_INSUFFICIENT_FUNDS);
+        assert_eq!(check_block_code(&short, &cfg), CODE_BLOCK_INSUFFICIENT_FUNDS);
+
+        // 购物车两件各 300 均低于余额，合计 600 超出余额
+        let line = TransferContext { amount_micros: 300 * MICROS, ..exact };
+        let other = TransferContext { item_base_limit: 1_000 * MICROS, item_max_limit: 1_000 * MICROS, ..line };
+        let res = cart_tax(&[line, other], &cfg, false);
+        assert_eq!((res.is_blocked, res.warning_code), (1, CODE_BLOCK_INSUFFICIENT_FUNDS));
+    }
+
+    #[test]
+    fn test_max_tax_ratio_caps_tax() {
+        let ctx = TransferContext { amount_micros: 100 * MICROS, sender_balance: BALANCE, sender_activity_score: 1.0, ..Default::default() };
+        // 90% 基础税，必然触顶
+        let tax_with = |max_tax_ratio: f64| {
+            let cfg = RegulatorConfig { base_tax_rate: 0.9, max_tax_ratio, ..Default::default() };
+            compute_transfer_check_internal(&ctx, &cfg).final_tax_micros
+        };
+
+        assert_eq!(tax_with(0.5), 50 * MICROS);
+        assert_eq!(tax_with(0.8), 80 * MICROS);
+        // 未设置或非法值回退到 80%
+        assert_eq!(tax_with(0.0), 80 * MICROS);
+        assert_eq!(tax_with(f64::NAN), 80 * MICROS);
+        // 未触顶时不影响税额
+        assert_eq!(tax_with(0.95), 90 * MICROS);
+    }
+
+    #[test]
+    fn test_applied_tax_flags() {
+        let ctx = TransferContext {
+            amount_micros: 200_000 * MICROS,
+            sender_balance: 300_000 * MICROS,
+            receiver_balance: 2_000_000 * MICROS,
+            sender_activity_score: 1.0,
+            sender_velocity: 1.0,
+            ..Default::default()
+        };
+        let cfg = RegulatorConfig { poor_threshold: 500_000 * MICROS, ..Default::default() };
+        let additive = RegulatorConfig { tax_combination_mode: TAX_MODE_ADDITIVE, ..cfg };
+        let res = compute_transfer_check_internal(&ctx, &additive);
+        assert_eq!(
+            res.applied_tax_flags,
+            TAX_FLAG_BASE | TAX_FLAG_BEHAVIORAL | TAX_FLAG_LUXURY | TAX_FLAG_WEALTH_GAP
+        );
+
+        // 混合模式下贫富调节税 (40k) 高于基础 + 奢侈税，仅标记贫富调节税
+        let res = compute_transfer_check_internal(&ctx, &cfg);
+        assert_eq!(res.applied_tax_flags, TAX_FLAG_WEALTH_GAP);
+
+        // 被拦截的交易仍上报若放行将征收的税种
+        let blocked_ctx = TransferContext {
+            item_base_limit: 1_000 * MICROS,
+            item_max_limit: 1_000 * MICROS,
+            ..ctx
+        };
+        let res = compute_transfer_check_internal(&blocked_ctx, &additive);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.warning_code, CODE_BLOCK_QUANTITY_LIMIT);
+        assert_eq!(res.final_tax_micros, 0);
+        assert_ne!(res.applied_tax_flags & TAX_FLAG_LUXURY, 0);
+        assert_ne!(res.applied_tax_flags & TAX_FLAG_WEALTH_GAP, 0);
+    }
+
+    #[test]
+    fn test_check_block_code_matches_full_check() {
+        let cfg = RegulatorConfig::default();
+        let passing = TransferContext {
+            amount_micros: 100 * MICROS,
+            sender_balance: BALANCE,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(check_block_code(&passing, &cfg), CODE_NORMAL);
+        assert_eq!(compute_transfer_check_internal(&passing, &cfg).is_blocked, 0);
+
+        let over_limit = TransferContext {
+            item_base_limit: 10 * MICROS,
+            item_max_limit: 10 * MICROS,
+            ..passing
+        };
+        let too_fast = TransferContext {
+            sender_velocity: cfg.velocity_threshold * 10.0,
+            ..passing
+        };
+        for ctx in [over_limit, too_fast] {
+            let full = compute_transfer_check_internal(&ctx, &cfg);
+            assert_eq!(full.is_blocked, 1);
+            assert_eq!(check_block_code(&ctx, &cfg), full.warning_code);
+        }
+        assert_eq!(check_block_code(&over_limit, &cfg), CODE_BLOCK_QUANTITY_LIMIT);
+        assert_eq!(check_block_code(&too_fast, &cfg), CODE_BLOCK_VELOCITY_LIMIT);
+    }
+
+    #[test]
+    fn test_preview_matches_check_without_recording_flow() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        flow_tracker::reset_state();
+        let cfg = RegulatorConfig::default();
+        let now = 1_736_851_200_000;
+        let active = TransferContext { amount_micros: 200_000 * MICROS, sender_balance: BALANCE, sender_activity_score: 1.0, ..Default::default() };
+        let idle = TransferContext { amount_micros: 100 * MICROS, sender_balance: BALANCE, sender_activity_score: 0.01, ..Default::default() };
+
+        for _ in 0..3 {
+            assert_eq!(preview_transfer_tax(&active, &cfg), 20_000 * MICROS);
+        }
+        let blocked = TransferContext { sender_velocity: cfg.velocity_threshold * 10.0, ..active };
+        assert_eq!(preview_transfer_tax(&blocked, &cfg), compute_transfer_check_internal(&blocked, &cfg).final_tax_micros);
+        assert_eq!(preview_transfer_tax(&blocked, &cfg), 0);
+
+        // 预览不记录流转：随后的回转不被视为乒乓洗钱
+        assert_eq!(compute_transfer_check_tracked(&idle, &cfg, "pv-b", "pv-a", now).is_blocked, 0);
+        flow_tracker::reset_state();
+    }
+
+    #[test]
+    fn test_exclusive_limit_at_exact_boundary() {
+        let ctx = TransferContext {
+            amount_micros: 100 * MICROS,
+            sender_balance: BALANCE,
+            item_base_limit: 100 * MICROS,
+            item_max_limit: 100 * MICROS,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+
+        // 默认 (exclusive_limit = 0)：恰好等于限额放行，剩余额度为 0
+        let inclusive = RegulatorConfig::default();
+        let res = compute_transfer_check_internal(&ctx, &inclusive);
+        assert_eq!(res.is_blocked, 0);
+        assert_eq!(res.limit_headroom_micros, 0);
+
+        // exclusive_limit 非 0：等于限额即拦截，略低于限额仍放行
+        let exclusive = RegulatorConfig { exclusive_limit: 1, ..Default::default() };
+        let res = compute_transfer_check_internal(&ctx, &exclusive);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.warning_code, CODE_BLOCK_QUANTITY_LIMIT);
+        let below = TransferContext { amount_micros: 100 * MICROS - 1, ..ctx };
+        assert_eq!(check_block_code(&below, &exclusive), CODE_NORMAL);
+    }
+
+    #[test]
+    fn test_reverse_flow_ping_pong() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        flow_tracker::reset_state();
+        let cfg = RegulatorConfig::default();
+        let now = 1_736_851_200_000;
+        let active = TransferContext { amount_micros: 100 * MICROS, sender_balance: BALANCE, sender_activity_score: 1.0, ..Default::default() };
+        let idle = TransferContext { sender_activity_score: 0.01, ..active };
+
+        // A -> B 后，低活跃度的 B 在窗口内回转给 A：拦截
+        assert_eq!(compute_transfer_check_tracked(&active, &cfg, "rf-a", "rf-b", now).is_blocked, 0);
+        let res = compute_transfer_check_tracked(&idle, &cfg, "rf-b", "rf-a", now + 30_000);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.warning_code, CODE_BLOCK_REVERSE_FLOW);
+
+        // 带身份的预校验给出相同结论，且不记录流转
+        assert_eq!(check_block_code_tracked(&idle, &cfg, "rf-b", "rf-a", now + 30_000), CODE_BLOCK_REVERSE_FLOW);
+        assert_eq!(check_block_code(&idle, &cfg), CODE_NORMAL);
+        assert_eq!(check_block_code_tracked(&idle, &cfg, "rf-c", "rf-d", now + 30_000), CODE_NORMAL);
+        assert_eq!(check_block_code_tracked(&active, &cfg, "rf-d", "rf-c", now + 40_000), CODE_NORMAL);
+
+        // 超出检测窗口后不再视为逆向流转
+        let later = now + flow_tracker::DEFAULT_REVERSE_FLOW_WINDOW_MS + 1;
+        assert_eq!(check_block_code_tracked(&idle, &cfg, "rf-b", "rf-a", later), CODE_NORMAL);
+        assert_eq!(compute_transfer_check_tracked(&idle, &cfg, "rf-b", "rf-a", later).is_blocked, 0);
+        flow_tracker::reset_state();
+    }
+
+    #[test]
+    fn test_reverse_flow_allows_legitimate_two_way_trade() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        flow_tracker::reset_state();
+        let cfg = RegulatorConfig::default();
+        let now = 1_736_851_200_000;
+        let active = TransferContext {
+            amount_micros: 100 * MICROS,
+            sender_balance: BALANCE,
+            item_base_limit: 1_000 * MICROS,
+            item_max_limit: 1_000 * MICROS,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+
+        // 双方均为活跃账户：付款与找零往返均放行
+        assert_eq!(compute_transfer_check_tracked(&active, &cfg, "tw-a", "tw-b", now).is_blocked, 0);
+        let res = compute_transfer_check_tracked(&active, &cfg, "tw-b", "tw-a", now + 10_000);
+        assert_eq!(res.is_blocked, 0);
+        assert_eq!(res.warning_code, CODE_NORMAL);
+
+        // 与无关账户之间的转账不受影响
+        let idle = TransferContext { sender_activity_score: 0.01, ..active };
+        assert_eq!(compute_transfer_check_tracked(&idle, &cfg, "tw-c", "tw-a", now + 20_000).warning_code, CODE_NORMAL);
+        flow_tracker::reset_state();
+    }
+
+    #[test]
+    fn test_injection_rule() {
+        let cfg = RegulatorConfig::default(); // 老手 > 100h，新手 < 10h，倍数 10x
+        let gift = TransferContext {
+            amount_micros: 5_000 * MICROS,
+            sender_balance: BALANCE,
+            receiver_balance: 1_000 * MICROS,
+            sender_play_time: 500 * 3600,
+            receiver_play_time: 2 * 3600,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        // 5x 余额的正常赠予放行
+        assert_eq!(check_block_code(&gift, &cfg), CODE_NORMAL);
+
+        // 20x 余额的大额输送拦截
+        let dump = TransferContext { amount_micros: 20_000 * MICROS, ..gift };
+        let res = compute_transfer_check_internal(&dump, &cfg);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.warning_code, CODE_BLOCK_INJECTION);
+
+        // 接收者不再是新手、或关闭该规则时不拦截
+        let grown = TransferContext { receiver_play_time: 50 * 3600, ..dump };
+        assert_eq!(check_block_code(&grown, &cfg), CODE_NORMAL);
+        let disabled = RegulatorConfig { injection_balance_multiple: 0.0, ..cfg };
+        assert_eq!(check_block_code(&dump, &disabled), CODE_NORMAL);
+
+        // 零余额新手：低于最低金额 (默认 1,000) 的小额赠予放行，达到最低金额后按倍数规则拦截
+        let broke = TransferContext { amount_micros: 500 * MICROS, receiver_balance: 0, ..gift };
+        assert_eq!(check_block_code(&broke, &cfg), CODE_NORMAL);
+        let unset = RegulatorConfig { injection_min_amount: 0, ..cfg };
+        assert_eq!(check_block_code(&broke, &unset), CODE_NORMAL);
+        let at_floor = TransferContext { amount_micros: 1_000 * MICROS, ..broke };
+        assert_eq!(check_block_code(&at_floor, &cfg), CODE_BLOCK_INJECTION);
+        let strict = RegulatorConfig { injection_min_amount: 100 * MICROS, ..cfg };
+        assert_eq!(check_block_code(&broke, &strict), CODE_BLOCK_INJECTION);
+    }
+
+    #[test]
+    fn test_penalty_exponent_cap() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let cfg = RegulatorConfig::default();
+        let ctx = TransferContext {
+            amount_micros: 1_000 * MICROS,
+            sender_velocity: 1e300,
+            ..Default::default()
+        };
+
+        // 默认上限 2.0：税额 = 1000 * 5% * e^2 ≈ 369.5，远低于 80% 封顶 (800)
+        let (tax, _) = compute_adaptive_tax(&ctx, &cfg).unwrap();
+        let expected = to_micros_saturating(1_000.0 * 0.05 * 2.0f64.exp());
+        assert_eq!(tax, expected);
+        assert!(tax < 800 * MICROS);
+
+        // NaN 流速同样按上限处理
+        let nan_ctx = TransferContext { sender_velocity: f64::NAN, ..ctx };
+        assert_eq!(compute_adaptive_tax(&nan_ctx, &cfg).unwrap().0, expected);
+
+        // 调低上限后惩罚相应减轻；非法上限被拒绝
+        assert!(set_penalty_exponent_cap(0.01));
+        assert_eq!(compute_adaptive_tax(&ctx, &cfg).unwrap().0, to_micros_saturating(1_000.0 * 0.05 * 0.01f64.exp()));
+        assert!(!set_penalty_exponent_cap(-1.0));
+        assert!(!set_penalty_exponent_cap(f64::INFINITY));
+        reset_state();
+    }
+
+    #[test]
+    fn test_frozen_blocks_all_transfers() {
+        // 税额依赖可调的惩罚指数上限，需与修改该参数的测试串行
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let cfg = RegulatorConfig::default();
+        let ctx = TransferContext {
+            amount_micros: 1_000 * MICROS,
+            sender_balance: BALANCE,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(transfer_check(&ctx, &cfg, false).is_blocked, 0);
+
+        // 不修改全局开关，避免影响并行执行的其他审计用例
+        let res = transfer_check(&ctx, &cfg, true);
+        assert_eq!((res.is_blocked, res.warning_code, res.final_tax_micros), (1, CODE_FROZEN, 0));
+        assert_ne!(res.applied_tax_flags, 0);
+        assert_eq!(cart_tax(&[ctx, ctx], &cfg, true).warning_code, CODE_FROZEN);
+        assert_eq!(cart_tax(&[ctx, ctx], &cfg, false).is_blocked, 0);
+
+        // 预校验与空购物车同样受冻结约束
+        assert_eq!(block_code(&ctx, &cfg, true), CODE_FROZEN);
+        assert_eq!(block_code(&ctx, &cfg, false), CODE_NORMAL);
+        let empty = cart_tax(&[], &cfg, true);
+        assert_eq!((empty.is_blocked, empty.warning_code), (1, CODE_FROZEN));
+    }
+
+    #[test]
+    fn test_velocity_penalty_coeff() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let ctx = TransferContext {
+            amount_micros: 1_000 * MICROS,
+            sender_velocity: 10.0,
+            ..Default::default()
+        };
+        let tax_with = |coeff: f64| {
+            let cfg = RegulatorConfig { velocity_penalty_coeff: coeff, max_tax_ratio: 1.0, ..Default::default() };
+            compute_adaptive_tax(&ctx, &cfg).unwrap().0
+        };
+
+        // 默认系数 0.05：倍率 e^0.5；未设置或非法系数回退默认值
+        let default_tax = to_micros_saturating(1_000.0 * 0.05 * 0.5f64.exp());
+        assert_eq!(tax_with(DEFAULT_VELOCITY_PENALTY_COEFF), default_tax);
+        assert_eq!(tax_with(0.0), default_tax);
+        assert_eq!(tax_with(f64::NAN), default_tax);
+        assert_eq!(tax_with(0.1), to_micros_saturating(1_000.0 * 0.05 * 1.0f64.exp()));
+
+        // 极高流速或系数下倍率封顶于 e^2，封顶前税额约为金额的 37%，而非远超 80% 封顶
+        let capped = to_micros_saturating(1_000.0 * 0.05 * 2.0f64.exp());
+        assert_eq!(tax_with(1e6), capped);
+        let frantic = TransferContext { sender_velocity: 1e9, ..ctx };
+        let (tax, _) = compute_adaptive_tax(&frantic, &RegulatorConfig { max_tax_ratio: 1.0, ..Default::default() }).unwrap();
+        assert_eq!(tax, capped);
+        assert!(tax < 400 * MICROS);
+    }
+
+    #[test]
+    fn test_limit_headroom() {
+        let cfg = RegulatorConfig::default();
+        let ctx = TransferContext {
+            amount_micros: 30 * MICROS,
+            sender_balance: BALANCE,
+            item_base_limit: 100 * MICROS,
+            item_max_limit: 100 * MICROS,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+        let res = compute_transfer_check_internal(&ctx, &cfg);
+        assert_eq!(res.is_blocked, 0);
+        assert_eq!(res.limit_headroom_micros, 70 * MICROS);
+
+        let blocked = TransferContext { amount_micros: 150 * MICROS, ..ctx };
+        assert_eq!(compute_transfer_check_internal(&blocked, &cfg).limit_headroom_micros, 0);
+
+        let unlimited = TransferContext { item_base_limit: 0, item_max_limit: 0, ..ctx };
+        assert_eq!(compute_transfer_check_internal(&unlimited, &cfg).limit_headroom_micros, i64::MAX);
+    }
+
+    #[test]
+    fn test_frozen_takes_precedence_over_overflow() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let cfg = RegulatorConfig { max_tax_ratio: 1.0, ..Default::default() };
+        let ctx = TransferContext {
+            amount_micros: i64::MAX,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+
+        // 冻结时预校验与完整审计给出相同拦截码，未冻结时均为溢出
+        let res = transfer_check(&ctx, &cfg, true);
+        assert_eq!((res.is_blocked, res.warning_code, res.applied_tax_flags), (1, CODE_FROZEN, 0));
+        assert_eq!(block_code(&ctx, &cfg, true), CODE_FROZEN);
+        assert_eq!(cart_tax(&[ctx, ctx], &cfg, true).warning_code, CODE_FROZEN);
+
+        assert_eq!(transfer_check(&ctx, &cfg, false).warning_code, CODE_OVERFLOW);
+        assert_eq!(block_code(&ctx, &cfg, false), CODE_OVERFLOW);
+    }
+
+    #[test]
+    fn test_overflowing_amount_is_blocked() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let cfg = RegulatorConfig { max_tax_ratio: 1.0, ..Default::default() };
+        let ctx = TransferContext {
+            amount_micros: i64::MAX,
+            sender_balance: i64::MAX,
+            sender_activity_score: 1.0,
+            ..Default::default()
+        };
+
+        // i64::MAX 转为 f64 后舍入到 2^63，金额与税额均不可表示
+        let res = compute_transfer_check_internal(&ctx, &cfg);
+        assert_eq!(res.is_blocked, 1);
+        assert_eq!(res.warning_code, CODE_OVERFLOW);
+        assert_eq!(res.final_tax_micros, 0);
+        assert!(is_high_risk_transfer(&res));
+        assert_eq!(check_block_code(&ctx, &cfg), CODE_OVERFLOW);
+
+        // 购物车合并后饱和到 i64::MAX 同样拦截
+        let half = TransferContext { amount_micros: i64::MAX / 2 + 1, ..ctx };
+        assert_eq!(compute_cart_tax(&[half, half], &cfg).warning_code, CODE_OVERFLOW);
+        assert_eq!(simulate_tax_incidence(&cfg, &[0], &[i64::MAX]), (0, 0.0));
+
+        let normal = TransferContext { amount_micros: 1_000 * MICROS, ..ctx };
+        let res = compute_transfer_check_internal(&normal, &cfg);
+        assert_eq!(res.is_blocked, 0);
+        assert!(res.final_tax_micros > 0);
+    }
+}
@@ -1,14 +1,15 @@
-use crossbeam_channel::{bounded, Receiver, Sender};
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
 use duckdb::{params, Connection};
-use std::collections::HashMap;
-use std::ops::Deref;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Deref, RangeInclusive};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{OnceLock, RwLock};
-use std::thread;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use libc::c_int;
 use lazy_static::lazy_static;
-use crate::models::HistoryRecord;
+use crate::models::{to_micros_checked, to_micros_saturating, DaySummary, HistoryRecord};
 
 // -----------------------------------------------------------------------------
 // 静态状态管理
@@ -24,12 +25,99 @@ static LOG_SENDER: OnceLock<Sender<LogEvent>> = OnceLock::new();
 static READ_POOL: OnceLock<ConnectionPool> = OnceLock::new();
 static TOTAL_LOGS: AtomicU64 = AtomicU64::new(0);
 static DROPPED_LOGS: AtomicU64 = AtomicU64::new(0);
+// 写入线程心跳：最近一次批次刷盘完成的墙钟时间 (Unix ms)，0 表示尚未刷盘
+static LAST_FLUSH_TS: AtomicI64 = AtomicI64::new(0);
+
+// 写入线程刷盘期间持有，防止状态重置与批量提交交错
+static FLUSH_GUARD: Mutex<()> = Mutex::new(());
+// 串行化初始化，防止并发 init 竞争 OnceLock
+static INIT_GUARD: Mutex<()> = Mutex::new(());
+// 写入线程句柄，关机时用于等待最终批次落盘；仅在写入线程确认退出后移除
+static WRITER_HANDLE: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+// 关机信号是否已送达通道 (超时后重试关机时不再重复发送)
+static SHUTDOWN_SIGNALED: AtomicBool = AtomicBool::new(false);
+/// 已通过 `init_tenant` 建表的租户日志表名；事件共享其中的表名，写入线程据此判定目标表，无需逐次查询目录
+static TENANT_TABLES: RwLock<BTreeSet<Arc<str>>> = RwLock::new(BTreeSet::new());
+/// 默认租户表名的共享实例，避免每条事件分配表名
+static DEFAULT_TABLE_NAME: OnceLock<Arc<str>> = OnceLock::new();
+
+/// 默认 (未命名) 租户的日志表
+const DEFAULT_LOG_TABLE: &str = "economy_log";
+/// 租户名最大长度 (字节)
+pub const MAX_TENANT_NAME_LEN: usize = 32;
+
+/// 关机等待写入线程排空的默认超时
+pub const DEFAULT_SHUTDOWN_TIMEOUT_MS: u64 = 5_000;
+
+/// 启动预热查询失败时的处理策略
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PreloadFailurePolicy {
+    /// 保持空热存储继续启动 (neff 在新交易到达前读数为 0)
+    ContinueEmpty = 0,
+    /// 按指数退避重试 N 次，仍失败则以空热存储继续 (默认)
+    RetryN = 1,
+    /// 直接令初始化失败，交由运维介入
+    Fatal = 2,
+}
+
+impl PreloadFailurePolicy {
+    pub fn from_raw(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::ContinueEmpty),
+            1 => Some(Self::RetryN),
+            2 => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+pub const DEFAULT_PRELOAD_RETRY_ATTEMPTS: u32 = 3;
+/// 预热重试的首次退避间隔，之后每次翻倍
+const PRELOAD_RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+static PRELOAD_FAILURE_POLICY: AtomicI32 = AtomicI32::new(PreloadFailurePolicy::RetryN as i32);
+static PRELOAD_RETRY_ATTEMPTS: AtomicU32 = AtomicU32::new(DEFAULT_PRELOAD_RETRY_ATTEMPTS);
+
+/// 启动预热窗口的默认天数 (内存历史与分市场热存储共用)
+pub const DEFAULT_PRELOAD_DAYS: i64 = 30;
+static PRELOAD_DAYS: AtomicI64 = AtomicI64::new(DEFAULT_PRELOAD_DAYS);
+
+/// 预热与 M1 缓存使用的 "当前时间" 覆盖值 (ms)；0 表示未设置，使用系统时钟
+static NOW_OVERRIDE: AtomicI64 = AtomicI64::new(0);
+
+/// 写入通道容量
+const LOG_CHANNEL_CAPACITY: usize = 50_000;
+
+/// 阻塞发送模式下，关键事件等待通道空位的最长时间
+pub const CRITICAL_SEND_TIMEOUT: Duration = Duration::from_millis(100);
+
+// 关键事件 (delta != 0，影响余额) 是否在通道满时阻塞等待，而非直接丢弃
+static BLOCKING_CRITICAL_SEND: AtomicBool = AtomicBool::new(false);
+
+/// 持久化计数器表，用于跨重启累计运维指标
+const COUNTERS_DDL: &str = "CREATE TABLE IF NOT EXISTS counters (
+                                name VARCHAR PRIMARY KEY,
+                                value BIGINT
+                            );";
+const DROPPED_LOGS_COUNTER: &str = "dropped_logs";
+/// 写入线程将丢弃计数刷入 counters 表的最短间隔
+const COUNTER_PERSIST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// M1 估算结果的默认缓存有效期 (ms)
+pub const DEFAULT_M1_CACHE_TTL_MS: u64 = 1_000;
+
+static M1_CACHE_TTL_MS: AtomicU64 = AtomicU64::new(DEFAULT_M1_CACHE_TTL_MS);
+// 最近一次 M1 估算结果及其查询时刻 (`now_ms`，ms)
+static M1_CACHE: Mutex<Option<(i64, f64)>> = Mutex::new(None);
 
 // -----------------------------------------------------------------------------
 // 数据结构定义
 // -----------------------------------------------------------------------------
 
 struct LogEvent {
+    /// 目标日志表 (按租户区分)，与 `TENANT_TABLES` 共享同一分配
+    table: Arc<str>,
     ts: i64,
     uuid: String,
     delta: f64,    // 外部传入仍保持 f64 以兼容已有 API
@@ -62,35 +150,191 @@ impl Drop for DbConnectionGuard {
     }
 }
 
+/// DuckDB 运行参数 (内存上限 / 引擎线程数 / 读连接池大小 / 写入重试)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbConfig {
+    pub memory_limit_mb: u32,
+    pub threads: u32,
+    pub pool_size: usize,
+    pub write_retry: WriteRetry,
+}
+
+/// 写入线程遇到瞬时错误 (锁冲突等) 时的批次重试参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteRetry {
+    /// 首次失败后的最大重试次数，耗尽后丢弃本批次并计入丢弃计数
+    pub attempts: u32,
+    /// 首次重试前的退避间隔 (ms)，之后每次翻倍
+    pub backoff_ms: u32,
+}
+
+pub const DEFAULT_DB_MEMORY_LIMIT_MB: u32 = 512;
+pub const DEFAULT_DB_THREADS: u32 = 4;
+pub const DEFAULT_DB_POOL_SIZE: usize = 4;
+pub const DEFAULT_WRITE_RETRY_ATTEMPTS: u32 = 3;
+pub const DEFAULT_WRITE_RETRY_BACKOFF_MS: u32 = 50;
+
+const DB_MEMORY_LIMIT_RANGE_MB: RangeInclusive<c_int> = 64..=65_536;
+const DB_THREADS_RANGE: RangeInclusive<c_int> = 1..=256;
+const DB_POOL_SIZE_RANGE: RangeInclusive<c_int> = 1..=64;
+const WRITE_RETRY_ATTEMPTS_RANGE: RangeInclusive<c_int> = 1..=10;
+const WRITE_RETRY_BACKOFF_RANGE_MS: RangeInclusive<c_int> = 1..=5_000;
+
+impl Default for WriteRetry {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_WRITE_RETRY_ATTEMPTS,
+            backoff_ms: DEFAULT_WRITE_RETRY_BACKOFF_MS,
+        }
+    }
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit_mb: DEFAULT_DB_MEMORY_LIMIT_MB,
+            threads: DEFAULT_DB_THREADS,
+            pool_size: DEFAULT_DB_POOL_SIZE,
+            write_retry: WriteRetry::default(),
+        }
+    }
+}
+
+fn pick_raw(value: c_int, range: RangeInclusive<c_int>, default: c_int) -> Option<c_int> {
+    if value <= 0 {
+        Some(default)
+    } else if range.contains(&value) {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+impl DbConfig {
+    /// 从 FFI 原始参数构建配置
+    ///
+    /// 任一参数 <= 0 时回退为默认值；超出合理范围时返回 None。
+    pub fn from_raw(mem_limit_mb: c_int, db_threads: c_int, pool_size: c_int) -> Option<Self> {
+        Some(Self {
+            memory_limit_mb: pick_raw(mem_limit_mb, DB_MEMORY_LIMIT_RANGE_MB, DEFAULT_DB_MEMORY_LIMIT_MB as c_int)? as u32,
+            threads: pick_raw(db_threads, DB_THREADS_RANGE, DEFAULT_DB_THREADS as c_int)? as u32,
+            pool_size: pick_raw(pool_size, DB_POOL_SIZE_RANGE, DEFAULT_DB_POOL_SIZE as c_int)? as usize,
+            write_retry: WriteRetry::default(),
+        })
+    }
+
+    /// 设置写入重试参数 (重试次数 1..=10，首次退避 1..=5000 ms)
+    ///
+    /// 任一参数 <= 0 时回退为默认值 (3 次 / 50 ms)；超出范围时返回 None。
+    pub fn with_write_retry(self, attempts: c_int, backoff_ms: c_int) -> Option<Self> {
+        Some(Self {
+            write_retry: WriteRetry {
+                attempts: pick_raw(attempts, WRITE_RETRY_ATTEMPTS_RANGE, DEFAULT_WRITE_RETRY_ATTEMPTS as c_int)? as u32,
+                backoff_ms: pick_raw(backoff_ms, WRITE_RETRY_BACKOFF_RANGE_MS, DEFAULT_WRITE_RETRY_BACKOFF_MS as c_int)? as u32,
+            },
+            ..self
+        })
+    }
+}
+
 // -----------------------------------------------------------------------------
 // FFI 关机指令实现
 // -----------------------------------------------------------------------------
 
-pub fn shutdown_db_internal() -> c_int {
-    if let Some(sender) = LOG_SENDER.get() {
-        let res = sender.send(LogEvent {
+/// 发送关机信号并等待写入线程排空通道、提交最终批次
+///
+/// 返回值: 0 = 已完整落盘；-1 = 写入线程未运行 (未初始化或已关闭)；
+/// -8 = 在 `timeout` 内未能完成 (写入线程仍在后台继续排空，可再次调用以继续等待)。
+pub fn shutdown_db_internal(timeout: Duration) -> c_int {
+    shutdown_writer(&WRITER_HANDLE, &SHUTDOWN_SIGNALED, LOG_SENDER.get(), timeout)
+}
+
+fn shutdown_writer(
+    writer: &Mutex<Option<JoinHandle<()>>>,
+    signaled: &AtomicBool,
+    sender: Option<&Sender<LogEvent>>,
+    timeout: Duration,
+) -> c_int {
+    // 持锁等待：并发的关机调用串行化，超时返回时句柄仍留在原处供重试
+    let mut slot = writer.lock().unwrap_or_else(|e| e.into_inner());
+    let (handle, sender) = match (slot.as_ref(), sender) {
+        (Some(h), Some(s)) => (h, s),
+        _ => return -1,
+    };
+
+    // 通道为 FIFO，信号之前的全部事件都会先被写入线程处理
+    let deadline = Instant::now() + timeout;
+    if !signaled.load(Ordering::Acquire) {
+        let signal = LogEvent {
+            table: Arc::from(""),
             ts: -1, 
             uuid: String::new(),
             delta: 0.0,
             balance: 0.0,
             meta: String::from("SHUTDOWN_SIGNAL"),
-        });
-        
-        if res.is_ok() {
-            return 0;
+        };
+        if sender.send_deadline(signal, deadline).is_err() {
+            if !handle.is_finished() {
+                return -8;
+            }
+            // 写入线程已提前退出 (通道断开)
+            if let Some(h) = slot.take() {
+                let _ = h.join();
+            }
+            return -1;
+        }
+        signaled.store(true, Ordering::Release);
+    }
+
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            eprintln!("[EcoBridge-Storage] Writer drain timed out after {:?}", timeout);
+            return -8;
         }
+        thread::sleep(Duration::from_millis(5));
+    }
+    if let Some(h) = slot.take() {
+        let _ = h.join();
     }
-    -1
+    0
 }
 
 // -----------------------------------------------------------------------------
 // 核心初始化逻辑
 // -----------------------------------------------------------------------------
 
-pub fn init_economy_db(path_str: &str) -> c_int {
-    if LOG_SENDER.get().is_some() {
-        return 0;
+/// `init_economy_db` 的重复初始化返回码：数据库已绑定，本次传入的路径与配置被忽略
+pub const INIT_ALREADY_DONE: c_int = -3;
+
+/// 测试专用：以共享的内存库初始化全局读连接池 (不启动写入线程，`is_initialized` 仍为 false)，
+/// 返回同一内存库的连接供用例写入数据；调用方须持有 `GLOBAL_STATE_TEST_LOCK` 并在结束时清理写入的数据
+#[cfg(test)]
+pub(crate) fn test_read_pool_conn() -> Connection {
+    static TEST_DB: OnceLock<Mutex<Connection>> = OnceLock::new();
+    let db = TEST_DB.get_or_init(|| {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&log_table_ddl(DEFAULT_LOG_TABLE)).unwrap();
+        let (pool_tx, pool_rx) = bounded(2);
+        for _ in 0..2 {
+            pool_tx.send(conn.try_clone().unwrap()).unwrap();
+        }
+        assert!(READ_POOL.set(ConnectionPool { available: pool_rx, recycle: pool_tx }).is_ok());
+        Mutex::new(conn)
+    });
+    db.lock().unwrap_or_else(|e| e.into_inner()).try_clone().unwrap()
+}
+
+/// 数据库是否已完成初始化 (进程生命周期内只能绑定一次)
+pub fn is_initialized() -> bool {
+    LOG_SENDER.get().is_some()
+}
+
+pub fn init_economy_db(path_str: &str, config: Option<DbConfig>) -> c_int {
+    let _init = INIT_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    if is_initialized() {
+        return INIT_ALREADY_DONE;
     }
+    let config = config.unwrap_or_default();
 
     let mut db_path = PathBuf::from(path_str);
     db_path.push("ecobridge_vault.db");
@@ -103,29 +347,28 @@ pub fn init_economy_db(path_str: &str) -> c_int {
         }
     };
 
-    let ddl_res = write_conn.execute_batch(
-        "SET memory_limit='512MB';
-         SET threads=4;
-         CREATE TABLE IF NOT EXISTS economy_log (
-             ts BIGINT,
-             player_uuid VARCHAR,
-             delta DOUBLE,
-             balance DOUBLE,
-             metadata VARCHAR
-         );
-         CREATE INDEX IF NOT EXISTS idx_ts ON economy_log (ts);"
+    let ddl = format!(
+        "SET memory_limit='{}MB';
+         SET threads={};
+         {}
+         {}",
+        config.memory_limit_mb, config.threads, log_table_ddl(DEFAULT_LOG_TABLE), COUNTERS_DDL
     );
+    let ddl_res = write_conn.execute_batch(&ddl);
 
     if let Err(e) = ddl_res {
         eprintln!("[EcoBridge-Storage] DDL Error: {}", e);
         return -5;
     }
 
-    // 启动预热
-    load_recent_history_to_memory(&write_conn);
+    // 恢复跨重启累计的丢弃计数，并执行启动预热
+    restore_dropped_logs(&write_conn);
+    if load_recent_history_to_memory(&write_conn).is_err() {
+        return -9;
+    }
 
     // 初始化连接池
-    let pool_size = 4;
+    let pool_size = config.pool_size;
     let (pool_tx, pool_rx) = bounded(pool_size);
     for _ in 0..pool_size {
         if let Ok(c) = write_conn.try_clone() {
@@ -138,12 +381,13 @@ pub fn init_economy_db(path_str: &str) -> c_int {
         recycle: pool_tx,
     });
 
-    let (tx, rx) = bounded(50_000);
+    let (tx, rx) = bounded(LOG_CHANNEL_CAPACITY);
 
-    thread::Builder::new()
+    let handle = thread::Builder::new()
         .name("ecobridge-db-writer".into())
-        .spawn(move || writer_loop(write_conn, rx))
+        .spawn(move || writer_loop(write_conn, rx, config.write_retry))
         .expect("Failed to spawn DB writer thread");
+    *WRITER_HANDLE.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle);
 
     match LOG_SENDER.set(tx) {
         Ok(_) => 0,
@@ -151,53 +395,255 @@ pub fn init_economy_db(path_str: &str) -> c_int {
     }
 }
 
-/// [Fix] 适配 v1.6.0 定点数精度重构
-fn load_recent_history_to_memory(conn: &Connection) {
-    let now = chrono::Utc::now().timestamp_millis();
-    let cutoff = now - (90i64 * 86_400_000); 
-    
-    let mut stmt = match conn.prepare("SELECT ts, delta FROM economy_log WHERE ts > ? ORDER BY ts ASC") {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("[EcoBridge-Storage] Preload Error: {}", e);
-            return;
-        }
+/// 设置启动预热失败策略；`retry_attempts` 仅对 `RetryN` 生效，须至少为 1
+pub fn set_preload_failure_policy(policy: PreloadFailurePolicy, retry_attempts: u32) -> bool {
+    if policy == PreloadFailurePolicy::RetryN && retry_attempts == 0 {
+        return false;
+    }
+    PRELOAD_FAILURE_POLICY.store(policy as i32, Ordering::Relaxed);
+    if policy == PreloadFailurePolicy::RetryN {
+        PRELOAD_RETRY_ATTEMPTS.store(retry_attempts, Ordering::Relaxed);
+    }
+    true
+}
+
+/// 设置启动预热窗口 (天)，须为正数
+///
+/// 内存历史 (`load_recent_history_to_memory`) 与分市场热存储 (`hydrate_hot_store`) 均按此窗口加载。
+/// 窗口越长，启动时需扫描并逐条转换的日志越多，初始化耗时随之增加；加载的记录常驻内存
+/// (每条 16 字节，分市场热存储另存一份全局副本)，且在运行期才逐步被裁剪。
+/// 一般无需超过 neff 实际回溯范围 (`tau` × 回溯倍数)。
+pub fn set_preload_days(days: i64) -> bool {
+    if days <= 0 {
+        return false;
+    }
+    PRELOAD_DAYS.store(days, Ordering::Relaxed);
+    true
+}
+
+pub fn preload_days() -> i64 {
+    PRELOAD_DAYS.load(Ordering::Relaxed)
+}
+
+fn preload_failure_policy() -> PreloadFailurePolicy {
+    PreloadFailurePolicy::from_raw(PRELOAD_FAILURE_POLICY.load(Ordering::Relaxed))
+        .unwrap_or(PreloadFailurePolicy::RetryN)
+}
+
+/// 按预热失败策略执行 `op`
+///
+/// 返回 `Ok(Some)` = 成功；`Ok(None)` = 放弃预热并以空热存储继续；`Err` = 策略为 Fatal，初始化应失败。
+fn run_with_preload_policy<T, E: std::fmt::Display>(
+    policy: PreloadFailurePolicy,
+    mut op: impl FnMut() -> Result<T, E>,
+    backoff: Duration,
+) -> Result<Option<T>, E> {
+    let attempts = match policy {
+        PreloadFailurePolicy::RetryN => PRELOAD_RETRY_ATTEMPTS.load(Ordering::Relaxed).max(1),
+        _ => 1,
     };
+    let mut delay = backoff;
+    for attempt in 1..=attempts {
+        match op() {
+            Ok(v) => return Ok(Some(v)),
+            Err(e) => {
+                eprintln!("[EcoBridge-Storage] Preload Error (attempt {}/{}): {}", attempt, attempts, e);
+                if policy == PreloadFailurePolicy::Fatal {
+                    return Err(e);
+                }
+                if attempt < attempts {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                }
+            }
+        }
+    }
+    Ok(None)
+}
 
+fn query_recent_history(conn: &Connection, cutoff: i64) -> duckdb::Result<Vec<HistoryRecord>> {
+    let mut stmt = conn.prepare("SELECT ts, delta FROM economy_log WHERE ts > ? ORDER BY ts ASC")?;
     let records_iter = stmt.query_map(params![cutoff], |row| {
         // 先获取数据库中的浮点数原始值
         let amount_f64: f64 = row.get(1)?;
-        Ok(HistoryRecord {
-            timestamp: row.get(0)?,
-            // [Precision Fix]: 转换为 i64 Micros
-            amount_micros: (amount_f64 * 1_000_000.0) as i64,
-        })
-    });
+        let timestamp: i64 = row.get(0)?;
+        // [Precision Fix]: 转换为 i64 Micros，溢出记录直接跳过
+        Ok(to_micros_checked(amount_f64).map(|amount_micros| HistoryRecord { timestamp, amount_micros }))
+    })?;
+    Ok(records_iter.flatten().flatten().collect())
+}
 
-    if let Ok(iter) = records_iter {
-        if let Ok(mut hist) = GLOBAL_HISTORY.write() {
-            for rec in iter.flatten() {
-                hist.push(rec);
-            }
-            println!("[EcoBridge-Storage] 内存预热完成：同步加载了 {} 条高精度记录。", hist.len());
+/// 设置预热窗口使用的 "当前时间" (ms)，供测试与游戏内时间与主机时钟不一致的服务器使用
+///
+/// 非 0 时所有预热路径与 M1 缓存有效期以该值为 "现在"；传入 0 恢复系统时钟。负值返回 false。
+pub fn set_now_override(now_ms: i64) -> bool {
+    if now_ms < 0 {
+        return false;
+    }
+    NOW_OVERRIDE.store(now_ms, Ordering::Relaxed);
+    true
+}
+
+/// 预热路径与 M1 缓存的 "当前时间" (ms)：已设置覆盖值时返回覆盖值，否则为 `Utc::now`
+pub fn now_ms() -> i64 {
+    match NOW_OVERRIDE.load(Ordering::Relaxed) {
+        0 => chrono::Utc::now().timestamp_millis(),
+        v => v,
+    }
+}
+
+/// [Fix] 适配 v1.6.0 定点数精度重构
+/// 启动预热；仅当失败策略为 Fatal 且查询失败时返回 Err
+fn load_recent_history_to_memory(conn: &Connection) -> Result<(), duckdb::Error> {
+    let now = now_ms();
+    let cutoff = now - preload_days() * 86_400_000;
+
+    let records = match run_with_preload_policy(
+        preload_failure_policy(),
+        || query_recent_history(conn, cutoff),
+        PRELOAD_RETRY_BACKOFF,
+    )? {
+        Some(r) => r,
+        None => {
+            eprintln!("[EcoBridge-Storage] 内存预热放弃：热存储以空状态启动。");
+            return Ok(());
         }
+    };
+
+    if let Ok(mut hist) = GLOBAL_HISTORY.write() {
+        hist.extend(records);
+        println!("[EcoBridge-Storage] 内存预热完成：同步加载了 {} 条高精度记录。", hist.len());
     }
+    Ok(())
 }
 
 pub fn get_history_read() -> std::sync::RwLockReadGuard<'static, Vec<HistoryRecord>> {
     GLOBAL_HISTORY.read().unwrap()
 }
 
+/// 日志表建表语句 (幂等)；默认表沿用历史索引名 `idx_ts`，租户表使用 `idx_<table>_ts`
+fn log_table_ddl(table: &str) -> String {
+    let index = if table == DEFAULT_LOG_TABLE { "idx_ts".to_string() } else { format!("idx_{}_ts", table) };
+    format!(
+        "CREATE TABLE IF NOT EXISTS {table} (
+             ts BIGINT,
+             player_uuid VARCHAR,
+             delta DOUBLE,
+             balance DOUBLE,
+             metadata VARCHAR
+         );
+         CREATE INDEX IF NOT EXISTS {index} ON {table} (ts);"
+    )
+}
+
+/// 租户名到日志表名的映射：空名为默认租户 (`economy_log`)，否则为 `economy_log_<tenant>`
+///
+/// 租户名仅允许 ASCII 字母、数字与下划线且不超过 `MAX_TENANT_NAME_LEN` 字节，保证可安全拼接进 SQL；
+/// 不合法时返回 None。DuckDB 标识符不区分大小写，租户名统一转为小写，`Survival` 与 `survival` 为同一租户。
+pub fn tenant_table(tenant: &str) -> Option<String> {
+    if tenant.is_empty() {
+        return Some(DEFAULT_LOG_TABLE.to_string());
+    }
+    if tenant.len() > MAX_TENANT_NAME_LEN || !tenant.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        return None;
+    }
+    Some(format!("{}_{}", DEFAULT_LOG_TABLE, tenant.to_ascii_lowercase()))
+}
+
+/// 租户建表失败原因
+#[derive(Debug)]
+pub enum TenantError {
+    /// 数据库尚未初始化或读连接池已关闭
+    NotInitialized,
+    /// 租户名含非法字符或超长
+    InvalidName,
+    /// DuckDB 建表失败
+    Db(duckdb::Error),
+}
+
+impl std::fmt::Display for TenantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInitialized => write!(f, "database not initialized"),
+            Self::InvalidName => write!(f, "invalid tenant name"),
+            Self::Db(e) => write!(f, "duckdb error: {}", e),
+        }
+    }
+}
+
+/// 为租户在同一数据库文件中建立独立日志表与时间索引 (幂等)，须在数据库初始化之后调用
+///
+/// 建表后该租户的事件才会被接收；表在重启后保留，但每次启动仍需重新调用以登记租户。
+pub fn init_tenant(tenant: &str) -> Result<(), TenantError> {
+    let table = tenant_table(tenant).ok_or(TenantError::InvalidName)?;
+    let pool = READ_POOL.get().ok_or(TenantError::NotInitialized)?;
+    let raw_conn = pool.available.recv().map_err(|_| TenantError::NotInitialized)?;
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    conn_guard.execute_batch(&log_table_ddl(&table)).map_err(TenantError::Db)?;
+    TENANT_TABLES.write().unwrap_or_else(|e| e.into_inner()).insert(Arc::from(table));
+    Ok(())
+}
+
+/// 租户是否可接收事件：默认租户始终可用，命名租户须先经 `init_tenant` 建表
+pub fn is_tenant_registered(tenant: &str) -> bool {
+    match tenant_table(tenant) {
+        Some(table) if table == DEFAULT_LOG_TABLE => true,
+        Some(table) => TENANT_TABLES.read().unwrap_or_else(|e| e.into_inner()).contains(table.as_str()),
+        None => false,
+    }
+}
+
+fn default_log_table() -> Arc<str> {
+    DEFAULT_TABLE_NAME.get_or_init(|| Arc::from(DEFAULT_LOG_TABLE)).clone()
+}
+
+/// 已登记租户的共享表名；未登记时返回 None
+fn registered_table(table: &str) -> Option<Arc<str>> {
+    if table == DEFAULT_LOG_TABLE {
+        return Some(default_log_table());
+    }
+    TENANT_TABLES.read().unwrap_or_else(|e| e.into_inner()).get(table).cloned()
+}
+
+/// 记录经济事件到指定租户的日志表
+///
+/// 默认租户 (空名) 等同 `log_economy_event`；命名租户相互隔离，只写入各自的表，不进入全局内存历史。
+/// 租户未登记或金额超出 i64 Micros 可表示范围时返回 false，事件不会被记录。
+pub fn log_tenant_event(tenant: &str, ts: i64, uuid: String, delta: f64, balance: f64, meta: String) -> bool {
+    if tenant.is_empty() {
+        return log_economy_event(ts, uuid, delta, balance, meta);
+    }
+    let table = match tenant_table(tenant).and_then(|t| registered_table(&t)) {
+        Some(t) => t,
+        None => return false,
+    };
+    if to_micros_checked(delta).is_none() {
+        return false;
+    }
+    TOTAL_LOGS.fetch_add(1, Ordering::Relaxed);
+    enqueue_event(LogEvent { table, ts, uuid, delta, balance, meta });
+    true
+}
+
 /// [Fix] 记录经济事件：同步执行高精度内存注入
-pub fn log_economy_event(ts: i64, uuid: String, delta: f64, balance: f64, meta: String) {
+///
+/// 金额超出 i64 Micros 可表示范围时返回 false，事件不会被记录。
+pub fn log_economy_event(ts: i64, uuid: String, delta: f64, balance: f64, meta: String) -> bool {
+    // [Precision Fix]: 字段重命名并转换单位
+    let amount_micros = match to_micros_checked(delta) {
+        Some(v) => v,
+        None => return false,
+    };
     TOTAL_LOGS.fetch_add(1, Ordering::Relaxed);
     
     // 1. 同步更新内存历史记录 (SSoT)
     if let Ok(mut hist) = GLOBAL_HISTORY.write() {
-        // [Precision Fix]: 字段重命名并转换单位
         hist.push(HistoryRecord { 
             timestamp: ts, 
-            amount_micros: (delta * 1_000_000.0) as i64 
+            amount_micros,
         });
 
         // 维持滑动窗口
@@ -209,80 +655,234 @@ pub fn log_economy_event(ts: i64, uuid: String, delta: f64, balance: f64, meta:
     }
 
     // 2. 异步持久化
+    enqueue_event(LogEvent { table: default_log_table(), ts, uuid, delta, balance, meta });
+    true
+}
+
+/// 将事件投递给写入线程；通道已满 (阻塞模式下超时) 时计入丢弃
+fn enqueue_event(event: LogEvent) {
     if let Some(sender) = LOG_SENDER.get() {
-        if sender.try_send(LogEvent { ts, uuid, delta, balance, meta }).is_err() {
+        let sent = if event.delta != 0.0 && BLOCKING_CRITICAL_SEND.load(Ordering::Relaxed) {
+            sender.send_timeout(event, CRITICAL_SEND_TIMEOUT).is_ok()
+        } else {
+            sender.try_send(event).is_ok()
+        };
+        if !sent {
             DROPPED_LOGS.fetch_add(1, Ordering::Relaxed);
         }
     }
 }
 
+/// 设置关键事件的阻塞发送模式
+///
+/// 开启后，影响余额的事件 (delta != 0) 在通道满时最多阻塞调用线程 `CRITICAL_SEND_TIMEOUT`
+/// 等待写入线程腾出空位，仍超时才计入丢弃；纯遥测事件 (delta == 0) 始终非阻塞、满则丢弃。
+/// 代价是写入线程落后时调用方 (通常为主线程) 会出现停顿，适合宁可卡顿也不能丢账的场景。
+pub fn set_blocking_critical_send(enabled: bool) {
+    BLOCKING_CRITICAL_SEND.store(enabled, Ordering::Relaxed);
+}
+
+/// 写入通道占用率 (当前排队数 / 容量)，数据库未初始化时为 0
+///
+/// 接近 1.0 表示写入线程已跟不上，调用方应在丢弃发生前主动降速。
+pub fn writer_queue_utilization() -> f64 {
+    LOG_SENDER.get().map_or(0.0, channel_utilization)
+}
+
+fn channel_utilization<T>(sender: &Sender<T>) -> f64 {
+    match sender.capacity() {
+        Some(cap) if cap > 0 => sender.len() as f64 / cap as f64,
+        _ => 0.0,
+    }
+}
+
+/// 从 counters 表恢复生命周期丢弃计数 (初始化时调用，此时尚无写入可能产生丢弃)
+fn restore_dropped_logs(conn: &Connection) {
+    let restored: i64 = conn
+        .query_row("SELECT value FROM counters WHERE name = ?1", params![DROPPED_LOGS_COUNTER], |row| row.get(0))
+        .unwrap_or(0);
+    DROPPED_LOGS.store(restored.max(0) as u64, Ordering::Relaxed);
+}
+
+/// 将当前丢弃计数写入 counters 表，返回写入的值
+fn persist_dropped_logs(conn: &Connection) -> Option<u64> {
+    let dropped = DROPPED_LOGS.load(Ordering::Relaxed);
+    conn.execute(
+        "INSERT OR REPLACE INTO counters (name, value) VALUES (?1, ?2)",
+        params![DROPPED_LOGS_COUNTER, dropped.min(i64::MAX as u64) as i64],
+    ).ok().map(|_| dropped)
+}
+
 // [Fix] 增加 mut 关键字，允许传递可变引用
-fn writer_loop(mut conn: Connection, rx: Receiver<LogEvent>) {
+fn writer_loop(mut conn: Connection, rx: Receiver<LogEvent>, retry: WriteRetry) {
     let mut buffer = Vec::with_capacity(1024);
-    loop {
-        match rx.recv() {
+    let mut shutting_down = false;
+    let mut persisted_dropped = DROPPED_LOGS.load(Ordering::Relaxed);
+    let mut last_persist = Instant::now();
+    while !shutting_down {
+        // 丢弃计数有变化时按间隔落盘，空闲期间依靠接收超时唤醒
+        if last_persist.elapsed() >= COUNTER_PERSIST_INTERVAL {
+            if DROPPED_LOGS.load(Ordering::Relaxed) != persisted_dropped {
+                if let Some(v) = persist_dropped_logs(&conn) {
+                    persisted_dropped = v;
+                }
+            }
+            last_persist = Instant::now();
+        }
+        match rx.recv_timeout(COUNTER_PERSIST_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) => continue,
             Ok(msg) if msg.ts != -1 => {
                 buffer.push(msg);
                 while buffer.len() < 1024 {
                     match rx.try_recv() {
                         Ok(m) if m.ts != -1 => buffer.push(m),
-                        _ => break,
+                        // 批量收集途中收到关机信号：提交本批次后退出，避免信号被吞掉
+                        Ok(_) => {
+                            shutting_down = true;
+                            break;
+                        }
+                        Err(_) => break,
                     }
                 }
                 // [Fix] 传入 &mut conn
-                flush_buffer_to_db(&mut conn, &mut buffer);
+                let _flush = FLUSH_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+                flush_buffer_to_db(&mut conn, &mut buffer, retry);
             }
             _ => break, 
         }
     }
     if !buffer.is_empty() {
         // [Fix] 传入 &mut conn
-        flush_buffer_to_db(&mut conn, &mut buffer);
+        let _flush = FLUSH_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+        flush_buffer_to_db(&mut conn, &mut buffer, retry);
+    }
+    // 关机时无条件落盘，保证下次启动恢复到最新值
+    let _ = persist_dropped_logs(&conn);
+}
+
+/// 可重试的 DuckDB 异常类型 (事务冲突、文件锁争用等 IO 错误、查询中断)，其余类型视为致命，不再重试
+const TRANSIENT_ERROR_TYPES: [&str; 3] = ["TransactionContext", "IO", "Interrupt"];
+
+/// 解析 DuckDB 错误消息开头的异常类型 (`<Type> Error: ...`)
+///
+/// duckdb-rs 未透出 C API 的 `duckdb_error_type`，其错误码恒为 Unknown；
+/// 异常类型前缀由 DuckDB 统一生成，是可依赖的分类依据。无此前缀时返回 None。
+fn duckdb_error_type(msg: &str) -> Option<&str> {
+    let (kind, _) = msg.split_once(" Error: ")?;
+    (!kind.is_empty() && kind.bytes().all(|b| b.is_ascii_alphanumeric())).then_some(kind)
+}
+
+fn is_transient_error_message(msg: &str) -> bool {
+    duckdb_error_type(msg).is_some_and(|kind| TRANSIENT_ERROR_TYPES.contains(&kind))
+}
+
+/// 执行 `op`，瞬时错误按指数退避重试至多 `retry.attempts` 次；致命错误或重试耗尽时返回最后一次错误
+fn retry_transient<T, E: std::fmt::Display>(
+    retry: WriteRetry,
+    mut op: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut delay = Duration::from_millis(retry.backoff_ms as u64);
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Ok(v) => return Ok(v),
+            Err(e) => {
+                let msg = e.to_string();
+                if attempt >= retry.attempts || !is_transient_error_message(&msg) {
+                    return Err(e);
+                }
+                attempt += 1;
+                eprintln!("[EcoBridge-Storage] Transient write error (retry {}/{}): {}", attempt, retry.attempts, msg);
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+            }
+        }
     }
 }
 
 // [Fix] 签名修改：conn: &Connection -> conn: &mut Connection
-fn flush_buffer_to_db(conn: &mut Connection, buffer: &mut Vec<LogEvent>) {
+fn flush_buffer_to_db(conn: &mut Connection, buffer: &mut Vec<LogEvent>, retry: WriteRetry) {
     if buffer.is_empty() { return; }
 
-    // [Optimization] 使用事务批量提交，解决单条插入性能瓶颈 (1k -> 50k rows/sec)
-    
-    
-    // 1. 开启事务 (现在可以成功调用，因为有了 &mut self)
-    let tx = match conn.transaction() {
-        Ok(t) => t,
-        Err(_) => {
+    // 瞬时错误时整批重试；失败的事务已回滚，缓冲区保持完整，不会重复写入
+    match retry_transient(retry, || write_batch(conn, buffer)) {
+        Ok(0) => {}
+        Ok(skipped) => {
+            eprintln!("[EcoBridge-Storage] Dropping {} events for unregistered tenant tables", skipped);
+            DROPPED_LOGS.fetch_add(skipped as u64, Ordering::Relaxed);
+        }
+        // 瞬时错误重试耗尽：数据库整体不可写，逐行重写同样会失败
+        Err(e) if is_transient_error_message(&e.to_string()) => {
+            eprintln!("[EcoBridge-Storage] Dropping batch of {} events: {}", buffer.len(), e);
             DROPPED_LOGS.fetch_add(buffer.len() as u64, Ordering::Relaxed);
-            buffer.clear();
-            return;
         }
-    };
-
-    // 2. 在事务内执行批量插入
-    {
-        match tx.appender("economy_log") {
-            Ok(mut appender) => {
-                for ev in buffer.drain(..) {
-                    let _ = appender.append_row(params![ev.ts, ev.uuid, ev.delta, ev.balance, ev.meta]);
-                }
-                // Appender 在离开作用域时自动 flush 数据到 Transaction
-            },
-            Err(_) => {
-                DROPPED_LOGS.fetch_add(buffer.len() as u64, Ordering::Relaxed);
-                buffer.clear();
-                return;
+        // 致命错误多由个别坏行引起：逐行重写以隔离坏行，仅丢弃无法写入的事件
+        Err(e) => {
+            eprintln!("[EcoBridge-Storage] Batch write failed, retrying {} events row by row: {}", buffer.len(), e);
+            let dropped = write_rows_individually(conn, buffer);
+            if dropped > 0 {
+                eprintln!("[EcoBridge-Storage] Dropping {} unwritable events", dropped);
+                DROPPED_LOGS.fetch_add(dropped as u64, Ordering::Relaxed);
             }
         }
-    } 
+    }
+    buffer.clear();
+    // 无论提交成功与否，本批次已处理完毕，说明写入线程仍在消费队列
+    LAST_FLUSH_TS.store(unix_time_ms(), Ordering::Relaxed);
+}
+
+fn unix_time_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// 在单个事务内批量写入 (一次性 fsync)，成功提交前不修改缓冲区
+///
+/// [Optimization] 使用事务批量提交，解决单条插入性能瓶颈 (1k -> 50k rows/sec)
+/// 批次中的事件按目标表分组，各租户表共用同一事务；目标表未登记的事件被跳过而不影响其余租户，
+/// 返回跳过的事件数。已登记的表被外部删除时整批失败，由调用方逐行重写以隔离该租户。
+fn write_batch(conn: &mut Connection, buffer: &[LogEvent]) -> duckdb::Result<usize> {
+    let mut tables: Vec<&str> = buffer.iter().map(|ev| &*ev.table).collect();
+    tables.sort_unstable();
+    tables.dedup();
 
-    // 3. 提交事务 (一次性 fsync)
-    if let Err(_) = tx.commit() {
-        // 如果提交失败，由于 buffer 已被 drain，数据实际上丢失
-        // 在高频日志场景下，我们选择忽略极低概率的提交失败以保证不阻塞
+    let tx = conn.transaction()?;
+    let mut skipped = 0;
+    for table in tables {
+        if registered_table(table).is_none() {
+            skipped += buffer.iter().filter(|ev| &*ev.table == table).count();
+            continue;
+        }
+        let mut appender = tx.appender(table)?;
+        for ev in buffer.iter().filter(|ev| &*ev.table == table) {
+            appender.append_row(params![ev.ts, ev.uuid, ev.delta, ev.balance, ev.meta])?;
+        }
+        // Appender 在离开作用域时自动 flush 数据到 Transaction
     }
+    tx.commit()?;
+    Ok(skipped)
+}
+
+/// 逐条以独立事务写入，返回未能写入 (含目标表缺失) 的事件数
+fn write_rows_individually(conn: &mut Connection, buffer: &[LogEvent]) -> usize {
+    buffer
+        .iter()
+        .map(|ev| write_batch(conn, std::slice::from_ref(ev)).unwrap_or(1))
+        .sum()
 }
 
 pub fn query_neff_from_db(current_ts: i64, tau: f64) -> f64 {
+    query_tenant_neff_from_db("", current_ts, tau)
+}
+
+/// 从指定租户的日志表查询 neff；租户名不合法或未登记时返回 0
+pub fn query_tenant_neff_from_db(tenant: &str, current_ts: i64, tau: f64) -> f64 {
+    let table = match tenant_table(tenant) {
+        Some(t) if is_tenant_registered(tenant) => t,
+        _ => return 0.0,
+    };
     let pool = match READ_POOL.get() {
         Some(p) => p,
         None => return 0.0,
@@ -295,17 +895,358 @@ pub fn query_neff_from_db(current_ts: i64, tau: f64) -> f64 {
         conn: Some(raw_conn),
         pool_sender: pool.recycle.clone(),
     };
+    query_neff_on_conn(&conn_guard, &table, current_ts, tau)
+}
 
-    let query = "SELECT SUM(delta * EXP( -1.0 * (?1 - ts) / (?2 * 86400000.0) )) FROM economy_log WHERE ts > ?3";
+fn query_neff_on_conn(conn: &Connection, table: &str, current_ts: i64, tau: f64) -> f64 {
+    let query = format!("SELECT SUM(delta * EXP( -1.0 * (?1 - ts) / (?2 * 86400000.0) )) FROM {} WHERE ts > ?3", table);
     let ms_per_day = 86_400_000.0;
     let safe_lookback_ms = (tau * ms_per_day * 3.0) as i64;
     let min_ts = current_ts - safe_lookback_ms;
 
-    conn_guard.query_row(query, params![current_ts, tau, min_ts], |row| row.get(0)).unwrap_or(0.0)
+    conn.query_row(&query, params![current_ts, tau, min_ts], |row| row.get(0)).unwrap_or(0.0)
+}
+
+/// 单次日汇总查询最多返回的天数
+pub const MAX_SUMMARY_DAYS: usize = 366;
+
+/// 按 UTC 自然日聚合 [start_ts, end_ts) 内的经济日志：资金变动绝对值之和、事件数与活跃玩家数
+///
+/// 按日期升序返回，至多 `MAX_SUMMARY_DAYS` 天；区间为空或数据库未初始化时返回空列表。
+/// 查询或任一行解码失败时返回 Err，不会静默丢弃行而给出残缺的汇总。
+pub fn daily_summary(start_ts: i64, end_ts: i64) -> Result<Vec<DaySummary>, duckdb::Error> {
+    if end_ts <= start_ts {
+        return Ok(Vec::new());
+    }
+    let pool = match READ_POOL.get() {
+        Some(p) => p,
+        None => return Ok(Vec::new()),
+    };
+    let raw_conn = match pool.available.recv() {
+        Ok(c) => c,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    query_daily_summary(&conn_guard, start_ts, end_ts)
+}
+
+fn query_daily_summary(conn: &Connection, start_ts: i64, end_ts: i64) -> Result<Vec<DaySummary>, duckdb::Error> {
+    let query = "SELECT epoch_ms(date_trunc('day', epoch_ms(ts))) AS day,
+                        SUM(ABS(delta)), COUNT(*), COUNT(DISTINCT player_uuid)
+                 FROM economy_log
+                 WHERE ts >= ?1 AND ts < ?2
+                 GROUP BY day
+                 ORDER BY day ASC
+                 LIMIT ?3";
+    let mut stmt = conn.prepare(query)?;
+    let rows = stmt.query_map(params![start_ts, end_ts, MAX_SUMMARY_DAYS as i64], |row| {
+        let total_abs_delta: f64 = row.get(1)?;
+        let event_count: i64 = row.get(2)?;
+        let distinct_players: i64 = row.get(3)?;
+        Ok(DaySummary {
+            day_start_ms: row.get(0)?,
+            total_abs_delta_micros: to_micros_saturating(total_abs_delta),
+            event_count: event_count.max(0) as u64,
+            distinct_players: distinct_players.max(0) as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 统计 [start_ts, end_ts) 内每位玩家的事件速率 (次/分钟)，返回其分布在 `percentile` 处的取值
+///
+/// 分位数使用线性插值 (`quantile_cont`)，`percentile` 须位于 [0, 1]。
+/// 区间为空、区间内无日志或数据库未初始化时返回 `None`。
+pub fn player_rate_percentile(start_ts: i64, end_ts: i64, percentile: f64) -> Option<f64> {
+    if end_ts <= start_ts || !(0.0..=1.0).contains(&percentile) {
+        return None;
+    }
+    let pool = READ_POOL.get()?;
+    let raw_conn = pool.available.recv().ok()?;
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    query_player_rate_percentile(&conn_guard, start_ts, end_ts, percentile)
+}
+
+fn query_player_rate_percentile(conn: &Connection, start_ts: i64, end_ts: i64, percentile: f64) -> Option<f64> {
+    let window_minutes = (end_ts - start_ts) as f64 / 60_000.0;
+    let query = "SELECT quantile_cont(cnt / ?3, ?4) FROM (
+                     SELECT COUNT(*) AS cnt
+                     FROM economy_log
+                     WHERE ts >= ?1 AND ts < ?2
+                     GROUP BY player_uuid
+                 )";
+    conn.query_row(query, params![start_ts, end_ts, window_minutes, percentile], |row| row.get::<_, Option<f64>>(0))
+        .ok()
+        .flatten()
+}
+
+/// 单次排行榜查询最多返回的玩家数
+pub const MAX_TOP_TRADERS: usize = 1_000;
+
+/// 排行榜 FFI 中每个玩家标识占用的定宽槽位 (字节)：36 字符标准 UUID + 结尾 NUL
+pub const UUID_SLOT_LEN: usize = 37;
+
+/// 交易额排行榜：按 [start_ts, end_ts) 内资金变动绝对值之和降序返回 (玩家标识, 总额)
+///
+/// 总额相同时按玩家标识升序，至多 `limit` 条 (上限 `MAX_TOP_TRADERS`)；
+/// 区间为空、`limit` 为 0 或数据库未初始化时返回空列表；查询或任一行解码失败时返回 Err。
+pub fn top_traders(start_ts: i64, end_ts: i64, limit: usize) -> Result<Vec<(String, f64)>, duckdb::Error> {
+    if end_ts <= start_ts || limit == 0 {
+        return Ok(Vec::new());
+    }
+    let pool = match READ_POOL.get() {
+        Some(p) => p,
+        None => return Ok(Vec::new()),
+    };
+    let raw_conn = match pool.available.recv() {
+        Ok(c) => c,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    query_top_traders(&conn_guard, start_ts, end_ts, limit.min(MAX_TOP_TRADERS))
+}
+
+fn query_top_traders(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+    limit: usize,
+) -> Result<Vec<(String, f64)>, duckdb::Error> {
+    let query = "SELECT player_uuid, SUM(ABS(delta)) AS total
+                 FROM economy_log
+                 WHERE ts >= ?1 AND ts < ?2
+                 GROUP BY player_uuid
+                 ORDER BY total DESC, player_uuid ASC
+                 LIMIT ?3";
+    let mut stmt = conn.prepare(query)?;
+    let rows = stmt.query_map(params![start_ts, end_ts, limit as i64], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+    })?;
+    rows.collect()
+}
+
+/// 设置 M1 估算缓存有效期 (ms)，0 表示每次调用都重新查询
+pub fn set_m1_cache_ttl_ms(ttl_ms: u64) {
+    M1_CACHE_TTL_MS.store(ttl_ms, Ordering::Relaxed);
+    *M1_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 估算当前 M1 货币供应量：每位玩家最近一次日志记录的 `balance` 之和
+///
+/// 这是基于“最后可见余额”的估算值：未产生过日志的账户不计入，
+/// 日志之外的余额变动 (如离线发放) 在下一次记录前不会反映。
+/// 结果在 `set_m1_cache_ttl_ms` 设定的有效期内复用；数据库不可用时返回 0 且不缓存。
+pub fn latest_m1_estimate() -> f64 {
+    let ttl = M1_CACHE_TTL_MS.load(Ordering::Relaxed).min(i64::MAX as u64) as i64;
+    let now = now_ms();
+    let mut cache = M1_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, value)) = *cache {
+        // 时钟回拨 (elapsed < 0) 视为过期
+        if (0..ttl).contains(&now.saturating_sub(at)) {
+            return value;
+        }
+    }
+
+    let pool = match READ_POOL.get() {
+        Some(p) => p,
+        None => return 0.0,
+    };
+    let raw_conn = match pool.available.recv() {
+        Ok(c) => c,
+        Err(_) => return 0.0,
+    };
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    match query_m1_estimate(&conn_guard) {
+        Some(value) => {
+            *cache = Some((now, value));
+            value
+        }
+        None => 0.0,
+    }
+}
+
+fn query_m1_estimate(conn: &Connection) -> Option<f64> {
+    let query = "SELECT COALESCE(SUM(balance), 0.0) FROM (
+                     SELECT balance,
+                            ROW_NUMBER() OVER (PARTITION BY player_uuid ORDER BY ts DESC) AS rn
+                     FROM economy_log
+                 ) WHERE rn = 1";
+    conn.query_row(query, [], |row| row.get(0)).ok()
+}
+
+/// CSV 导出失败原因
+#[derive(Debug)]
+pub enum ExportError {
+    /// 数据库尚未初始化或读连接池已关闭
+    NotInitialized,
+    /// 时间区间为空 (end_ts <= start_ts)
+    InvalidRange,
+    /// 目标目录创建失败
+    Io(std::io::Error),
+    /// DuckDB 执行 COPY 失败 (如路径不可写)
+    Db(duckdb::Error),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInitialized => write!(f, "database not initialized"),
+            Self::InvalidRange => write!(f, "empty time range"),
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Db(e) => write!(f, "duckdb error: {}", e),
+        }
+    }
+}
+
+/// 将 [start_ts, end_ts) 内的经济日志按时间升序导出为带表头的 CSV 文件，返回写入行数
+///
+/// 在读连接池中的连接上执行 DuckDB `COPY ... TO`；目标目录不存在时自动创建，已有文件将被覆盖。
+pub fn export_csv(path: &str, start_ts: i64, end_ts: i64) -> Result<u64, ExportError> {
+    if end_ts <= start_ts {
+        return Err(ExportError::InvalidRange);
+    }
+    let pool = READ_POOL.get().ok_or(ExportError::NotInitialized)?;
+    let raw_conn = pool.available.recv().map_err(|_| ExportError::NotInitialized)?;
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    copy_range_to_csv(&conn_guard, path, start_ts, end_ts)
+}
+
+fn copy_range_to_csv(conn: &Connection, path: &str, start_ts: i64, end_ts: i64) -> Result<u64, ExportError> {
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir).map_err(ExportError::Io)?;
+        }
+    }
+    // COPY 的目标路径不支持参数绑定，按 SQL 字符串字面量转义单引号
+    let query = format!(
+        "COPY (SELECT ts, player_uuid, delta, balance, metadata
+               FROM economy_log
+               WHERE ts >= {} AND ts < {}
+               ORDER BY ts ASC)
+         TO '{}' (FORMAT CSV, HEADER)",
+        start_ts,
+        end_ts,
+        path.replace('\'', "''")
+    );
+    conn.execute(&query, []).map(|n| n as u64).map_err(ExportError::Db)
+}
+
+/// Parquet 归档预热失败原因
+#[derive(Debug)]
+pub enum ParquetError {
+    /// 数据库尚未初始化或读连接池已关闭
+    NotInitialized,
+    /// 文件缺少必需列 (`ts` 或 `delta`)
+    MissingColumn(&'static str),
+    /// DuckDB 读取失败 (如文件不存在或不是合法的 Parquet)
+    Db(duckdb::Error),
+}
+
+impl std::fmt::Display for ParquetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInitialized => write!(f, "database not initialized"),
+            Self::MissingColumn(name) => write!(f, "missing column '{}'", name),
+            Self::Db(e) => write!(f, "duckdb error: {}", e),
+        }
+    }
+}
+
+/// 从 Parquet 归档预热全局热存储，返回载入条数
+///
+/// 文件须包含 `ts` (毫秒时间戳) 与 `delta` (交易数量) 两列；含 NULL 或数量超出 i64 Micros 范围的行被跳过。
+/// 记录与现有热存储按时间戳合并，超出容量时按当前裁剪策略处理。
+pub fn hydrate_from_parquet(path: &str) -> Result<u64, ParquetError> {
+    let pool = READ_POOL.get().ok_or(ParquetError::NotInitialized)?;
+    let raw_conn = pool.available.recv().map_err(|_| ParquetError::NotInitialized)?;
+    let conn_guard = DbConnectionGuard {
+        conn: Some(raw_conn),
+        pool_sender: pool.recycle.clone(),
+    };
+    let records = read_parquet_history(&conn_guard, path)?;
+    let loaded = records.len() as u64;
+    crate::economy::summation::merge_global_history(records);
+    Ok(loaded)
+}
+
+fn read_parquet_history(conn: &Connection, path: &str) -> Result<Vec<HistoryRecord>, ParquetError> {
+    // 表函数参数按 SQL 字符串字面量转义单引号
+    let source = format!("read_parquet('{}')", path.replace('\'', "''"));
+
+    let mut describe = conn.prepare(&format!("DESCRIBE SELECT * FROM {}", source)).map_err(ParquetError::Db)?;
+    let columns: Vec<String> = describe
+        .query_map([], |row| row.get(0))
+        .map_err(ParquetError::Db)?
+        .collect::<Result<_, _>>()
+        .map_err(ParquetError::Db)?;
+    for required in ["ts", "delta"] {
+        if !columns.iter().any(|c| c == required) {
+            return Err(ParquetError::MissingColumn(required));
+        }
+    }
+
+    let query = format!(
+        "SELECT CAST(ts AS BIGINT), CAST(delta AS DOUBLE) FROM {} WHERE ts IS NOT NULL AND delta IS NOT NULL ORDER BY ts ASC",
+        source
+    );
+    let mut stmt = conn.prepare(&query).map_err(ParquetError::Db)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)))
+        .map_err(ParquetError::Db)?;
+
+    let mut records = Vec::new();
+    for row in rows {
+        let (timestamp, delta) = row.map_err(ParquetError::Db)?;
+        if let Some(amount_micros) = to_micros_checked(delta) {
+            records.push(HistoryRecord { timestamp, amount_micros });
+        }
+    }
+    Ok(records)
+}
+
+/// 清空内存历史与本次会话的日志计数 (不关闭数据库与写入线程)
+///
+/// 累计丢弃计数跨重启持久化于 counters 表，写入线程心跳反映仍在运行的写入线程，二者均保留；
+/// 需要归零时使用 `reset_lifetime_counters`。若写入线程正在刷盘，将阻塞等待本批次提交完成后再执行。
+pub fn reset_state() {
+    let _flush = FLUSH_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(mut hist) = GLOBAL_HISTORY.write() {
+        hist.clear();
+    }
+    TOTAL_LOGS.store(0, Ordering::Relaxed);
+    set_m1_cache_ttl_ms(DEFAULT_M1_CACHE_TTL_MS);
+    set_blocking_critical_send(false);
+    set_preload_failure_policy(PreloadFailurePolicy::RetryN, DEFAULT_PRELOAD_RETRY_ATTEMPTS);
+    PRELOAD_DAYS.store(DEFAULT_PRELOAD_DAYS, Ordering::Relaxed);
+    NOW_OVERRIDE.store(0, Ordering::Relaxed);
+}
+
+/// 归零累计丢弃计数与写入线程心跳 (仅供测试隔离；下次定期落盘时 counters 表随之归零)
+pub fn reset_lifetime_counters() {
+    let _flush = FLUSH_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    DROPPED_LOGS.store(0, Ordering::Relaxed);
+    LAST_FLUSH_TS.store(0, Ordering::Relaxed);
 }
 
 pub fn get_total_logs() -> u64 { TOTAL_LOGS.load(Ordering::Relaxed) }
 pub fn get_dropped_logs() -> u64 { DROPPED_LOGS.load(Ordering::Relaxed) }
+/// 写入线程最近一次刷盘的 Unix 时间戳 (ms)，尚未刷盘时为 0
+pub fn get_last_flush_ts() -> i64 { LAST_FLUSH_TS.load(Ordering::Relaxed) }
 
 /// [Fix] 这里的 HistoryRecord 构造也需要同步更新
 pub fn load_recent_history(days: i64) -> Vec<crate::models::HistoryRecord> {
@@ -319,21 +1260,19 @@ pub fn load_recent_history(days: i64) -> Vec<crate::models::HistoryRecord> {
     };
     
     let ms_lookback = days * 86_400_000;
-    let cutoff = chrono::Utc::now().timestamp_millis() - ms_lookback;
+    let cutoff = now_ms() - ms_lookback;
 
     let query = "SELECT ts, delta FROM economy_log WHERE ts > ? ORDER BY ts ASC";
     let mut stmt = raw_conn.prepare(query).unwrap();
     let record_iter = stmt.query_map(params![cutoff], |row| {
         let amt_f64: f64 = row.get(1)?;
-        Ok(crate::models::HistoryRecord {
-            timestamp: row.get(0)?,
-            // [Precision Fix]: 字段重命名并转换单位
-            amount_micros: (amt_f64 * 1_000_000.0) as i64,
-        })
+        let timestamp: i64 = row.get(0)?;
+        // [Precision Fix]: 字段重命名并转换单位
+        Ok(to_micros_checked(amt_f64).map(|amount_micros| crate::models::HistoryRecord { timestamp, amount_micros }))
     }).unwrap();
 
     let mut history = Vec::new();
-    for record in record_iter.flatten() {
+    for record in record_iter.flatten().flatten() {
         history.push(record);
     }
     
@@ -353,7 +1292,7 @@ pub fn load_recent_market_history_by_key(days: i64) -> HashMap<String, Vec<crate
     };
 
     let ms_lookback = days * 86_400_000;
-    let cutoff = chrono::Utc::now().timestamp_millis() - ms_lookback;
+    let cutoff = now_ms() - ms_lookback;
     let query = "SELECT ts, delta, metadata FROM economy_log WHERE ts > ? ORDER BY ts ASC";
     let mut stmt = match raw_conn.prepare(query) {
         Ok(s) => s,
@@ -382,10 +1321,14 @@ pub fn load_recent_market_history_by_key(days: i64) -> HashMap<String, Vec<crate
             if key.is_empty() {
                 continue;
             }
+            let amount_micros = match to_micros_checked(amt_f64) {
+                Some(v) => v,
+                None => continue,
+            };
             let bucket = result.entry(key.to_string()).or_default();
             bucket.push(crate::models::HistoryRecord {
                 timestamp,
-                amount_micros: (amt_f64 * 1_000_000.0) as i64,
+                amount_micros,
             });
         }
     }
@@ -393,3 +1336,594 @@ pub fn load_recent_market_history_by_key(days: i64) -> HashMap<String, Vec<crate
     let _ = pool.recycle.send(raw_conn);
     result
 }
+
+// -----------------------------------------------------------------------------
+// 单元测试
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400_000;
+
+    fn memory_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE economy_log (ts BIGINT, player_uuid VARCHAR, delta DOUBLE, balance DOUBLE, metadata VARCHAR);",
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_shutdown_retry_after_timeout_waits_for_writer() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        // 模拟排空缓慢的写入线程：收到关机信号后须等 release 放行才退出
+        let (tx, rx) = bounded::<LogEvent>(4);
+        let (release_tx, release_rx) = bounded::<()>(1);
+        let signals = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&signals);
+        let writer = Mutex::new(Some(thread::spawn(move || {
+            while let Ok(ev) = rx.recv() {
+                if ev.ts == -1 {
+                    seen.fetch_add(1, Ordering::SeqCst);
+                    let _ = release_rx.recv();
+                    return;
+                }
+            }
+        })));
+        let signaled = AtomicBool::new(false);
+
+        assert_eq!(shutdown_writer(&writer, &signaled, Some(&tx), Duration::from_millis(20)), -8);
+        assert!(writer.lock().unwrap().is_some(), "超时后句柄须保留");
+
+        // 重试仍在排空：继续超时，而非误报成功
+        assert_eq!(shutdown_writer(&writer, &signaled, Some(&tx), Duration::from_millis(20)), -8);
+
+        release_tx.send(()).unwrap();
+        assert_eq!(shutdown_writer(&writer, &signaled, Some(&tx), Duration::from_secs(5)), 0);
+        assert_eq!(signals.load(Ordering::SeqCst), 1, "关机信号只发送一次");
+
+        // 已关闭后再次调用报告未运行
+        assert_eq!(shutdown_writer(&writer, &signaled, Some(&tx), Duration::from_millis(20)), -1);
+    }
+
+    #[test]
+    fn test_db_config_falls_back_to_defaults() {
+        // 0 或负数回退默认值，与 `ecobridge_init_db` 使用的配置一致
+        assert_eq!(DbConfig::from_raw(0, 0, 0), Some(DbConfig::default()));
+        assert_eq!(DbConfig::from_raw(-1, -8, i32::MIN), Some(DbConfig::default()));
+        let with_retry = DbConfig::from_raw(0, 0, 0).and_then(|c| c.with_write_retry(0, -50));
+        assert_eq!(with_retry, Some(DbConfig::default()));
+
+        // 合法值原样采用，超出范围拒绝
+        let custom = DbConfig::from_raw(1_024, 2, 8).unwrap();
+        assert_eq!((custom.memory_limit_mb, custom.threads, custom.pool_size), (1_024, 2, 8));
+        assert_eq!(DbConfig::from_raw(32, 0, 0), None);
+        assert_eq!(DbConfig::from_raw(0, 0, 65), None);
+        assert_eq!(DbConfig::default().with_write_retry(11, 0), None);
+        assert_eq!(DbConfig::default().with_write_retry(0, 5_001), None);
+    }
+
+    #[test]
+    fn test_daily_summary_groups_by_utc_day() {
+        let conn = memory_db();
+        let day0 = 20_000 * DAY;
+        let rows: [(i64, &str, f64); 5] = [
+            (day0 + 1_000, "a", 10.0),
+            (day0 + 2_000, "a", -4.5),
+            (day0 + DAY - 1, "b", 1.0),
+            (day0 + DAY, "b", -2.0),
+            (day0 + 3 * DAY, "c", 7.0), // 超出查询区间
+        ];
+        for (ts, uuid, delta) in rows {
+            conn.execute(
+                "INSERT INTO economy_log VALUES (?, ?, ?, 0.0, '')",
+                params![ts, uuid, delta],
+            ).unwrap();
+        }
+
+        let summary = query_daily_summary(&conn, day0, day0 + 2 * DAY).unwrap();
+        assert_eq!(summary, vec![
+            DaySummary { day_start_ms: day0, total_abs_delta_micros: 15_500_000, event_count: 3, distinct_players: 2 },
+            DaySummary { day_start_ms: day0 + DAY, total_abs_delta_micros: 2_000_000, event_count: 1, distinct_players: 1 },
+        ]);
+
+        // 无数据区间返回空列表
+        assert!(query_daily_summary(&conn, day0 + 10 * DAY, day0 + 11 * DAY).unwrap().is_empty());
+        assert!(daily_summary(day0, day0).unwrap().is_empty());
+
+        // 某日资金变动全为 NULL 时该行无法解码：整体报错，而非悄悄漏掉这一天
+        conn.execute("INSERT INTO economy_log VALUES (?, 'd', NULL, 0.0, '')", params![day0 + 5 * DAY]).unwrap();
+        assert!(query_daily_summary(&conn, day0, day0 + 6 * DAY).is_err());
+    }
+
+    #[test]
+    fn test_dropped_logs_survive_restart() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let db_path = std::env::temp_dir().join(format!("ecobridge_counters_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+
+        // 首次运行：累计丢弃后关机落盘
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(COUNTERS_DDL).unwrap();
+            restore_dropped_logs(&conn);
+            assert_eq!(get_dropped_logs(), 0);
+            DROPPED_LOGS.fetch_add(42, Ordering::Relaxed);
+            assert_eq!(persist_dropped_logs(&conn), Some(42));
+        }
+
+        // 模拟进程重启：内存计数清零后从同一数据库恢复
+        DROPPED_LOGS.store(0, Ordering::Relaxed);
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(COUNTERS_DDL).unwrap();
+            restore_dropped_logs(&conn);
+            assert_eq!(get_dropped_logs(), 42);
+            DROPPED_LOGS.fetch_add(8, Ordering::Relaxed);
+            assert_eq!(persist_dropped_logs(&conn), Some(50));
+        }
+
+        DROPPED_LOGS.store(0, Ordering::Relaxed);
+        let _ = std::fs::remove_file(&db_path);
+        let _ = std::fs::remove_file(db_path.with_extension("db.wal"));
+    }
+
+    #[test]
+    fn test_player_rate_percentile() {
+        let conn = memory_db();
+        assert_eq!(query_player_rate_percentile(&conn, 0, 60_000, 0.99), None);
+
+        // 玩家 pK 在一分钟窗口内产生 K 条记录：速率分布为 1..=100 次/分钟
+        for k in 1..=100 {
+            conn.execute(
+                "INSERT INTO economy_log SELECT 1000 + i, ?, 1.0, 0.0, '' FROM range(?) t(i)",
+                params![format!("p{}", k), k as i64],
+            ).unwrap();
+        }
+        let p99 = query_player_rate_percentile(&conn, 0, 60_000, 0.99).unwrap();
+        assert!((p99 - 99.01).abs() < 1e-9, "p99={}", p99);
+        assert_eq!(query_player_rate_percentile(&conn, 0, 60_000, 0.0), Some(1.0));
+        assert_eq!(query_player_rate_percentile(&conn, 0, 60_000, 1.0), Some(100.0));
+
+        // 窗口加倍，速率减半
+        let median = query_player_rate_percentile(&conn, 0, 120_000, 0.5).unwrap();
+        assert!((median - 25.25).abs() < 1e-9, "median={}", median);
+    }
+
+    #[test]
+    fn test_top_traders_ranks_by_absolute_volume() {
+        let conn = memory_db();
+        assert!(query_top_traders(&conn, 0, 10_000, 10).unwrap().is_empty());
+
+        let rows: [(i64, &str, f64); 6] = [
+            (1_000, "alice", 10.0),
+            (2_000, "alice", -15.0),
+            (3_000, "bob", 30.0),
+            (4_000, "carol", -25.0),
+            (5_000, "dave", 25.0),
+            (20_000, "erin", 1_000.0), // 超出查询区间
+        ];
+        for (ts, uuid, delta) in rows {
+            conn.execute(
+                "INSERT INTO economy_log VALUES (?, ?, ?, 0.0, '')",
+                params![ts, uuid, delta],
+            ).unwrap();
+        }
+
+        let top = query_top_traders(&conn, 0, 10_000, 10).unwrap();
+        let expected = [("bob", 30.0), ("alice", 25.0), ("carol", 25.0), ("dave", 25.0)];
+        assert_eq!(top.len(), expected.len());
+        for ((uuid, total), (e_uuid, e_total)) in top.iter().zip(expected) {
+            assert_eq!((uuid.as_str(), *total), (e_uuid, e_total));
+        }
+        assert_eq!(query_top_traders(&conn, 0, 10_000, 2).unwrap().len(), 2);
+
+        // 玩家标识为 NULL 的行无法解码：整体报错，而非悄悄跳过该玩家
+        conn.execute("INSERT INTO economy_log VALUES (6000, NULL, 99.0, 0.0, '')", []).unwrap();
+        assert!(query_top_traders(&conn, 0, 10_000, 10).is_err());
+    }
+
+    #[test]
+    fn test_preload_retry_recovers_from_transient_failure() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let backoff = Duration::from_millis(1);
+        let flaky = |fail_times: u32| {
+            let mut calls = 0;
+            move || {
+                calls += 1;
+                if calls <= fail_times { Err(format!("transient #{}", calls)) } else { Ok(calls) }
+            }
+        };
+
+        // 默认 RetryN(3)：前两次失败，第三次成功
+        assert_eq!(run_with_preload_policy(PreloadFailurePolicy::RetryN, flaky(2), backoff), Ok(Some(3)));
+        // 重试耗尽后以空热存储继续
+        assert_eq!(run_with_preload_policy(PreloadFailurePolicy::RetryN, flaky(3), backoff), Ok(None));
+        // ContinueEmpty 不重试；Fatal 直接上报错误
+        assert_eq!(run_with_preload_policy(PreloadFailurePolicy::ContinueEmpty, flaky(1), backoff), Ok(None));
+        assert_eq!(
+            run_with_preload_policy(PreloadFailurePolicy::Fatal, flaky(1), backoff),
+            Err("transient #1".to_string())
+        );
+
+        assert!(!set_preload_failure_policy(PreloadFailurePolicy::RetryN, 0));
+        assert!(set_preload_failure_policy(PreloadFailurePolicy::RetryN, 5));
+        assert_eq!(run_with_preload_policy(PreloadFailurePolicy::RetryN, flaky(4), backoff), Ok(Some(5)));
+        reset_state();
+    }
+
+    #[test]
+    fn test_preload_respects_configured_window() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let conn = memory_db();
+        let now = chrono::Utc::now().timestamp_millis();
+        for age_days in [1, 5, 15, 40, 100] {
+            conn.execute(
+                "INSERT INTO economy_log VALUES (?, 'p', ?, 0.0, '')",
+                params![now - age_days * DAY, age_days as f64],
+            ).unwrap();
+        }
+        let loaded_ages = || -> Vec<i64> {
+            get_history_read().iter().map(|r| r.amount_micros / 1_000_000).collect()
+        };
+
+        // 默认 30 天
+        load_recent_history_to_memory(&conn).unwrap();
+        assert_eq!(loaded_ages(), vec![15, 5, 1]);
+
+        reset_state();
+        assert!(set_preload_days(10));
+        load_recent_history_to_memory(&conn).unwrap();
+        assert_eq!(loaded_ages(), vec![5, 1]);
+
+        reset_state();
+        assert!(set_preload_days(365));
+        load_recent_history_to_memory(&conn).unwrap();
+        assert_eq!(loaded_ages(), vec![100, 40, 15, 5, 1]);
+
+        assert!(!set_preload_days(0));
+        reset_state();
+        assert_eq!(preload_days(), DEFAULT_PRELOAD_DAYS);
+    }
+
+    #[test]
+    fn test_preload_uses_clock_override() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let conn = memory_db();
+        // 远早于系统时钟的游戏内时间：按系统时钟预热将一无所获
+        let game_now = 1_000 * DAY;
+        for age_days in [1, 20, 45] {
+            conn.execute(
+                "INSERT INTO economy_log VALUES (?, 'p', ?, 0.0, '')",
+                params![game_now - age_days * DAY, age_days as f64],
+            ).unwrap();
+        }
+
+        load_recent_history_to_memory(&conn).unwrap();
+        assert!(get_history_read().is_empty());
+
+        assert!(set_now_override(game_now));
+        assert_eq!(now_ms(), game_now);
+        load_recent_history_to_memory(&conn).unwrap();
+        let loaded: Vec<i64> = get_history_read().iter().map(|r| r.amount_micros / 1_000_000).collect();
+        assert_eq!(loaded, vec![20, 1]);
+
+        assert!(!set_now_override(-1));
+        reset_state();
+        assert!(now_ms() > game_now);
+    }
+
+    #[test]
+    fn test_price_reflects_db_neff() {
+        let conn = memory_db();
+        let now = 100 * DAY;
+        let tau = 7.0;
+        assert_eq!(query_neff_on_conn(&conn, DEFAULT_LOG_TABLE, now, tau), 0.0);
+
+        let rows: [(i64, f64); 3] = [(now, 40.0), (now - DAY, 30.0), (now - 30 * DAY, 500.0)]; // 最后一条超出 3·tau 回溯
+        for (ts, delta) in rows {
+            conn.execute("INSERT INTO economy_log VALUES (?, 'p', ?, 0.0, '')", params![ts, delta]).unwrap();
+        }
+        let neff = query_neff_on_conn(&conn, DEFAULT_LOG_TABLE, now, tau);
+        let expected = 40.0 + 30.0 * (-1.0f64 / tau).exp();
+        assert!((neff - expected).abs() < 1e-9, "neff={} expected={}", neff, expected);
+
+        use crate::economy::pricing::{compute_price_bounded_internal, price_against_neff};
+        let (base_micros, lambda, eps, hist_avg) = (10_000_000, 0.01, 1.0, 0.0);
+        let price = price_against_neff(base_micros, neff, lambda, eps, hist_avg);
+        assert_eq!(price, compute_price_bounded_internal(base_micros, neff, 0, lambda, eps, hist_avg, 0));
+        assert!(price < price_against_neff(base_micros, 0.0, lambda, eps, hist_avg));
+    }
+
+    #[test]
+    fn test_tenant_tables_are_isolated() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(tenant_table("").as_deref(), Some("economy_log"));
+        assert_eq!(tenant_table("creative_market").as_deref(), Some("economy_log_creative_market"));
+        assert_eq!(tenant_table("x; DROP TABLE economy_log"), None);
+        assert_eq!(tenant_table(&"a".repeat(MAX_TENANT_NAME_LEN + 1)), None);
+        assert_eq!(tenant_table("Creative_Market"), tenant_table("creative_market"));
+
+        let mut conn = memory_db();
+        let survival = tenant_table("survival").unwrap();
+        conn.execute_batch(&log_table_ddl(&survival)).unwrap();
+        TENANT_TABLES.write().unwrap().insert(Arc::from(survival.as_str()));
+        // 建表幂等
+        conn.execute_batch(&log_table_ddl(&survival)).unwrap();
+
+        let now = 100 * DAY;
+        let event = |table: &str, delta: f64| LogEvent {
+            table: table.into(),
+            ts: now,
+            uuid: "p".into(),
+            delta,
+            balance: 0.0,
+            meta: String::new(),
+        };
+        let buffer = vec![event(DEFAULT_LOG_TABLE, 3.0), event(&survival, 5.0), event(DEFAULT_LOG_TABLE, 4.0)];
+        assert_eq!(write_batch(&mut conn, &buffer).unwrap(), 0);
+
+        assert!((query_neff_on_conn(&conn, DEFAULT_LOG_TABLE, now, 7.0) - 7.0).abs() < 1e-9);
+        assert!((query_neff_on_conn(&conn, &survival, now, 7.0) - 5.0).abs() < 1e-9);
+
+        // 未登记租户的事件被跳过，同批次其余租户照常提交
+        let creative = tenant_table("creative").unwrap();
+        let buffer = vec![event(&creative, 9.0), event(&survival, 1.0), event(&creative, 9.0)];
+        assert_eq!(write_batch(&mut conn, &buffer).unwrap(), 2);
+        assert!((query_neff_on_conn(&conn, &survival, now, 7.0) - 6.0).abs() < 1e-9);
+
+        // 已登记的表被外部删除：整批失败后逐行重写，仅该租户的事件计入丢弃
+        conn.execute_batch(&format!("DROP TABLE {};", survival)).unwrap();
+        let dropped_before = get_dropped_logs();
+        let mut buffer = vec![event(&survival, 1.0), event(DEFAULT_LOG_TABLE, 2.0)];
+        flush_buffer_to_db(&mut conn, &mut buffer, WriteRetry { attempts: 0, backoff_ms: 1 });
+        assert_eq!(get_dropped_logs() - dropped_before, 1);
+        assert!((query_neff_on_conn(&conn, DEFAULT_LOG_TABLE, now, 7.0) - 9.0).abs() < 1e-9);
+        TENANT_TABLES.write().unwrap().remove(survival.as_str());
+    }
+
+    #[test]
+    fn test_transient_write_error_retries_without_data_loss() {
+        let mut conn = memory_db();
+        let buffer: Vec<LogEvent> = (0..5)
+            .map(|i| LogEvent {
+                table: DEFAULT_LOG_TABLE.into(),
+                ts: 1_000 + i,
+                uuid: format!("p{}", i),
+                delta: 1.0,
+                balance: 0.0,
+                meta: String::new(),
+            })
+            .collect();
+        let retry = WriteRetry { attempts: 3, backoff_ms: 1 };
+
+        // 首次尝试注入事务冲突，第二次正常写入
+        let mut calls = 0;
+        let result = retry_transient(retry, || {
+            calls += 1;
+            if calls == 1 {
+                return Err("TransactionContext Error: Conflict on tuple deletion".to_string());
+            }
+            write_batch(&mut conn, &buffer).map_err(|e| e.to_string())
+        });
+        assert_eq!(result, Ok(0));
+        assert_eq!(calls, 2);
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM economy_log", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 5);
+
+        // 致命错误不重试；瞬时错误重试耗尽后放弃
+        let mut calls = 0;
+        assert!(retry_transient(retry, || { calls += 1; Err::<(), _>("Catalog Error: table missing") }).is_err());
+        assert_eq!(calls, 1);
+        let mut calls = 0;
+        assert!(retry_transient(retry, || { calls += 1; Err::<(), _>("IO Error: Could not set lock on file") }).is_err());
+        assert_eq!(calls, 4);
+
+        // 按异常类型分类，消息正文中的 "lock" 等字样不影响判定
+        assert!(!is_transient_error_message("Catalog Error: Table with name economy_log_lock does not exist!"));
+        assert!(!is_transient_error_message("deadlock detected"));
+        assert!(is_transient_error_message("TransactionContext Error: Conflict on tuple deletion"));
+        assert_eq!(duckdb_error_type("Invalid Input Error: bad row"), None);
+        assert_eq!(duckdb_error_type("Conversion Error: bad row"), Some("Conversion"));
+
+        assert_eq!(DbConfig::default().with_write_retry(0, 0), Some(DbConfig::default()));
+        assert_eq!(DbConfig::default().with_write_retry(11, 0), None);
+    }
+
+    #[test]
+    fn test_bad_rows_isolated_from_batch() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        let mut conn = memory_db();
+        // 列数不符的租户表：写入该表的行必然失败
+        let broken = tenant_table("broken").unwrap();
+        conn.execute_batch(&format!("CREATE TABLE {} (ts BIGINT);", broken)).unwrap();
+        TENANT_TABLES.write().unwrap().insert(Arc::from(broken.as_str()));
+
+        let event = |table: &str, ts: i64| LogEvent {
+            table: table.into(),
+            ts,
+            uuid: "p".into(),
+            delta: 1.0,
+            balance: 0.0,
+            meta: String::new(),
+        };
+        let mut buffer = vec![event(DEFAULT_LOG_TABLE, 1), event(&broken, 2), event(DEFAULT_LOG_TABLE, 3)];
+        assert!(write_batch(&mut conn, &buffer).is_err());
+        let dropped_before = get_dropped_logs();
+
+        // 整批失败后逐行重写：正常行落盘，仅坏行计入丢弃
+        flush_buffer_to_db(&mut conn, &mut buffer, WriteRetry { attempts: 2, backoff_ms: 1 });
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM economy_log", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(get_dropped_logs() - dropped_before, 1);
+        assert!(buffer.is_empty());
+        TENANT_TABLES.write().unwrap().remove(broken.as_str());
+        reset_state();
+    }
+
+    #[test]
+    fn test_flush_updates_writer_heartbeat() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset_state();
+        reset_lifetime_counters();
+        let mut conn = memory_db();
+        let retry = WriteRetry::default();
+
+        // 空批次不算一次刷盘
+        flush_buffer_to_db(&mut conn, &mut Vec::new(), retry);
+        assert_eq!(get_last_flush_ts(), 0);
+
+        let before = unix_time_ms();
+        let mut buffer = vec![LogEvent { table: DEFAULT_LOG_TABLE.into(), ts: 1_000, uuid: "p".into(), delta: 1.0, balance: 0.0, meta: String::new() }];
+        flush_buffer_to_db(&mut conn, &mut buffer, retry);
+        let flushed_at = get_last_flush_ts();
+        assert!(flushed_at >= before);
+
+        // 插件重载不影响仍在运行的写入线程心跳与累计丢弃计数
+        DROPPED_LOGS.fetch_add(3, Ordering::Relaxed);
+        reset_state();
+        assert_eq!(get_last_flush_ts(), flushed_at);
+        assert_eq!(get_dropped_logs(), 3);
+        reset_lifetime_counters();
+        assert_eq!(get_last_flush_ts(), 0);
+        assert_eq!(get_dropped_logs(), 0);
+    }
+
+    #[test]
+    fn test_channel_utilization() {
+        let (tx, _rx) = bounded::<u32>(4);
+        assert_eq!(channel_utilization(&tx), 0.0);
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(channel_utilization(&tx), 0.5);
+        tx.send(3).unwrap();
+        tx.send(4).unwrap();
+        assert_eq!(channel_utilization(&tx), 1.0);
+    }
+
+    #[test]
+    fn test_m1_estimate_sums_latest_balance_per_player() {
+        let conn = memory_db();
+        assert_eq!(query_m1_estimate(&conn), Some(0.0));
+
+        let rows: [(i64, &str, f64); 5] = [
+            (1_000, "a", 100.0),
+            (3_000, "a", 80.0), // a 最新余额
+            (2_000, "b", 50.0),
+            (1_500, "b", 75.0),
+            (2_500, "c", 20.0),
+        ];
+        for (ts, uuid, balance) in rows {
+            conn.execute(
+                "INSERT INTO economy_log VALUES (?, ?, 0.0, ?, '')",
+                params![ts, uuid, balance],
+            ).unwrap();
+        }
+        assert_eq!(query_m1_estimate(&conn), Some(150.0));
+    }
+
+    #[test]
+    fn test_m1_cache_refreshes_after_ttl() {
+        let _guard = crate::GLOBAL_STATE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let conn = test_read_pool_conn();
+        conn.execute_batch("DELETE FROM economy_log").unwrap();
+        let insert = |ts: i64, uuid: &str, balance: f64| {
+            conn.execute("INSERT INTO economy_log VALUES (?, ?, 0.0, ?, '')", params![ts, uuid, balance]).unwrap();
+        };
+        let now = 1_736_851_200_000;
+        assert!(set_now_override(now));
+        set_m1_cache_ttl_ms(1_000);
+
+        insert(1_000, "a", 100.0);
+        assert_eq!(latest_m1_estimate(), 100.0);
+
+        // 有效期内复用缓存，新写入的余额暂不可见
+        insert(2_000, "b", 50.0);
+        assert!(set_now_override(now + 999));
+        assert_eq!(latest_m1_estimate(), 100.0);
+
+        // 超过有效期后重新查询
+        assert!(set_now_override(now + 1_000));
+        assert_eq!(latest_m1_estimate(), 150.0);
+
+        // TTL 为 0 时每次都重新查询
+        set_m1_cache_ttl_ms(0);
+        insert(3_000, "c", 25.0);
+        assert_eq!(latest_m1_estimate(), 175.0);
+
+        conn.execute_batch("DELETE FROM economy_log").unwrap();
+        set_m1_cache_ttl_ms(DEFAULT_M1_CACHE_TTL_MS);
+        assert!(set_now_override(0));
+    }
+
+    #[test]
+    fn test_read_parquet_history_validates_columns() {
+        let conn = memory_db();
+        let dir = std::env::temp_dir().join(format!("ecobridge_parquet_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("archive.parquet");
+        let bad = dir.join("no_delta.parquet");
+        conn.execute_batch(&format!(
+            "COPY (SELECT * FROM (VALUES (2000, -1.5, 'x'), (1000, 3.0, 'y'), (3000, NULL, 'z')) t(ts, delta, extra))
+               TO '{}' (FORMAT PARQUET);
+             COPY (SELECT 1000 AS ts, 3.0 AS amount) TO '{}' (FORMAT PARQUET);",
+            good.display(),
+            bad.display()
+        )).unwrap();
+
+        // 按时间戳升序载入，跳过 NULL 行，数量转换为 Micros
+        let records = read_parquet_history(&conn, good.to_str().unwrap()).unwrap();
+        let rows: Vec<(i64, i64)> = records.iter().map(|r| (r.timestamp, r.amount_micros)).collect();
+        assert_eq!(rows, vec![(1000, 3_000_000), (2000, -1_500_000)]);
+
+        assert!(matches!(
+            read_parquet_history(&conn, bad.to_str().unwrap()),
+            Err(ParquetError::MissingColumn("delta"))
+        ));
+        assert!(matches!(
+            read_parquet_history(&conn, dir.join("missing.parquet").to_str().unwrap()),
+            Err(ParquetError::Db(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_copy_range_to_csv_creates_missing_dir() {
+        let conn = memory_db();
+        let rows: [(i64, &str, f64); 3] = [
+            (2_000, "b", -1.5),
+            (1_000, "a", 3.0),
+            (5_000, "c", 9.0), // 超出导出区间
+        ];
+        for (ts, uuid, delta) in rows {
+            conn.execute(
+                "INSERT INTO economy_log VALUES (?, ?, ?, 0.0, 'm')",
+                params![ts, uuid, delta],
+            ).unwrap();
+        }
+
+        let dir = std::env::temp_dir().join(format!("ecobridge_export_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("nested").join("o'hist.csv");
+        let written = copy_range_to_csv(&conn, path.to_str().unwrap(), 0, 5_000).unwrap();
+        assert_eq!(written, 2);
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, vec![
+            "ts,player_uuid,delta,balance,metadata",
+            "1000,a,3.0,0.0,m",
+            "2000,b,-1.5,0.0,m",
+        ]);
+
+        assert!(matches!(export_csv(path.to_str().unwrap(), 10, 10), Err(ExportError::InvalidRange)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}